@@ -0,0 +1,70 @@
+//! Compares building and dropping many small trees with and without an arena.
+//!
+//! Run with `cargo bench --features arena --bench arena`.
+
+use dynamic_plan_tree::behaviour::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// Counts allocations to report alongside timings.
+struct CountingAlloc;
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+type BenchConfig = config::Default;
+
+fn build(arena: Option<&Arena<BenchConfig>>) -> Plan<BenchConfig> {
+    let new = |name: &str| match arena {
+        Some(arena) => Plan::new_in(arena, AllSuccessStatus.into(), name, 1, true),
+        None => Plan::new(AllSuccessStatus.into(), name, 1, true),
+    };
+    let mut root = new("root");
+    for i in 0..4 {
+        let plan = root.insert(new(&format!("unit{i}")));
+        plan.tags_mut().push("unit".into());
+        plan.transitions.push(transition!("idle" => "move"));
+        for name in ["idle", "move"] {
+            plan.insert(new(name));
+        }
+    }
+    root
+}
+
+fn main() {
+    const TREES: u32 = 10_000;
+    let arena = Arena::new();
+    for use_arena in [false, true] {
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        for _ in 0..TREES {
+            let mut plan = build(use_arena.then_some(&arena));
+            plan.run();
+            match use_arena {
+                true => arena.recycle(plan),
+                false => drop(plan),
+            }
+        }
+        let elapsed = start.elapsed();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+        println!(
+            "arena={:5} {} trees of 13 plans: {:?} and {} allocations per tree",
+            use_arena,
+            TREES,
+            elapsed / TREES,
+            allocations / TREES as usize
+        );
+    }
+}
@@ -0,0 +1,42 @@
+//! Compares ticking a plan with 1000 transitions with and without compiling them.
+//!
+//! Run with `cargo bench --bench compiled_transitions`.
+
+use dynamic_plan_tree::behaviour::*;
+use std::time::Instant;
+
+type BenchConfig = config::Default;
+
+fn build(compile: bool) -> Plan<BenchConfig> {
+    let mut root = Plan::<BenchConfig>::new_stub("root", true);
+    for i in 0..100 {
+        root.insert(Plan::new(AllSuccessStatus.into(), i.to_string(), 1, i == 0));
+    }
+    // 10 transitions out of each subplan that never fire, so the active subplan stays the same
+    for i in 0..1000 {
+        let (src, dst) = ((i / 10).to_string(), (i % 100).to_string());
+        root.transitions
+            .push(transition!(src => dst, predicate::False));
+    }
+    if compile {
+        root.compile();
+    }
+    root
+}
+
+fn main() {
+    const TICKS: u32 = 10_000;
+    for compile in [false, true] {
+        let mut plan = build(compile);
+        let start = Instant::now();
+        for _ in 0..TICKS {
+            plan.run();
+        }
+        let elapsed = start.elapsed();
+        println!(
+            "compile={:5} 100 plans, 1000 transitions: {:?} per tick",
+            compile,
+            elapsed / TICKS
+        );
+    }
+}
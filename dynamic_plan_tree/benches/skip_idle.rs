@@ -0,0 +1,34 @@
+//! Compares ticking a tree of infrequently running plans with and without `skip_idle`.
+//!
+//! Run with `cargo bench --bench skip_idle`.
+
+use dynamic_plan_tree::behaviour::*;
+use std::time::Instant;
+
+type BenchConfig = config::Default;
+
+fn build(skip_idle: bool) -> Plan<BenchConfig> {
+    let mut root = Plan::<BenchConfig>::new_stub("root", true);
+    root.skip_idle = skip_idle;
+    for i in 0..1000 {
+        root.insert(Plan::new(AllSuccessStatus.into(), i.to_string(), 100, true));
+    }
+    root
+}
+
+fn main() {
+    const TICKS: u32 = 10_000;
+    for skip_idle in [false, true] {
+        let mut plan = build(skip_idle);
+        let start = Instant::now();
+        for _ in 0..TICKS {
+            plan.run();
+        }
+        let elapsed = start.elapsed();
+        println!(
+            "skip_idle={:5} 1000 plans, interval 100: {:?} per tick",
+            skip_idle,
+            elapsed / TICKS
+        );
+    }
+}
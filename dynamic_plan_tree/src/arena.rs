@@ -0,0 +1,130 @@
+//! Reuse of plan allocations for workloads building and dropping many short-lived trees.
+//!
+//! An [Arena] keeps the storage of recycled plans, such as their subplan, transition, and tag vectors
+//! and the tree state shared by their root, then hands it out to plans built by [Plan::new_in].
+//! Recycled storage is emptied but keeps its capacity, so trees of a similar shape rebuilt from the
+//! arena avoid most allocator traffic once it has warmed up.
+//!
+//! Trade-offs of this approach:
+//! - Storage is only reused once a whole tree or subtree is passed to [Arena::recycle].
+//!   Plans dropped or removed otherwise free their storage as usual.
+//! - Names and boxed behaviours are still allocated per plan.
+//! - Deserialized plans allocate as usual, but may be recycled into an arena like any other.
+//! - Recycled storage is held until reused or the arena is dropped, sized by the largest tree recycled,
+//!   so the arena suits append-heavy workloads of similar trees rather than trees that shrink over time.
+
+use crate::*;
+use std::sync::Mutex;
+
+/// Pool of storage recycled from dropped plans. See the [module](self) documentation.
+pub struct Arena<C: Config> {
+    free: Mutex<Vec<PlanParts<C>>>,
+}
+
+impl<C: Config> Default for Arena<C> {
+    fn default() -> Self {
+        Self {
+            free: Mutex::default(),
+        }
+    }
+}
+
+impl<C: Config> Arena<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of recycled plans whose storage is available for reuse.
+    pub fn len(&self) -> usize {
+        self.free.lock().unwrap().len()
+    }
+
+    /// Whether no recycled storage is available.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all recycled storage.
+    pub fn clear(&self) {
+        self.free.lock().unwrap().clear();
+    }
+
+    /// Drop the plan and all subplans while keeping their storage for reuse.
+    ///
+    /// Behaviours of active plans trigger `on_exit()` in the same order as when dropped.
+    pub fn recycle(&self, mut plan: Plan<C>) {
+        fn recycle_into<C: Config>(plan: &mut Plan<C>, free: &mut Vec<PlanParts<C>>) {
+            let mut parts = plan.take_parts();
+            for plan in &mut parts.plans {
+                recycle_into(plan, free);
+            }
+            parts.plans.clear();
+            free.push(parts);
+        }
+        let mut free = self.free.lock().unwrap();
+        let start = free.len();
+        recycle_into(&mut plan, &mut free);
+        drop(plan);
+        // keep a single reference to the tree state shared by the subplans, now their plans are dropped
+        for parts in &mut free[start..] {
+            parts.release_shared_tree();
+        }
+    }
+
+    /// Storage of a recycled plan, if any.
+    pub(crate) fn take(&self) -> Option<PlanParts<C>> {
+        self.free.lock().unwrap().pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use behaviour::*;
+
+    type ArenaConfig = config::Default;
+
+    fn build(arena: &Arena<ArenaConfig>) -> Plan<ArenaConfig> {
+        let mut root = Plan::new_in(arena, AllSuccessStatus.into(), "root", 1, true);
+        for name in ["a", "b", "c"] {
+            let plan = root.insert(Plan::new_stub_in(arena, name, name == "a"));
+            plan.set_data("x", serde_value::Value::U32(1));
+        }
+        root.tags_mut().push("tag".into());
+        root.transitions.push(transition!("a" => "b"));
+        root
+    }
+
+    #[test]
+    fn recycle() {
+        let arena = Arena::new();
+        let mut root = build(&arena);
+        root.run();
+        assert!(root.get("b").unwrap().active());
+        arena.recycle(root);
+        assert_eq!(arena.len(), 4);
+
+        // plans built from recycled storage start out fresh
+        let root = Plan::<ArenaConfig>::new_stub_in(&arena, "root", true);
+        assert_eq!(arena.len(), 3);
+        assert!(root.plans.is_empty() && root.transitions.is_empty() && root.tags().is_empty());
+        assert!(root.data().is_empty() && !root.active());
+        assert!(root.plans.capacity() >= 3 && root.transitions.capacity() >= 1);
+        drop(root);
+        arena.clear();
+        assert!(arena.is_empty());
+
+        // rebuilt trees behave the same as the original
+        for _ in 0..3 {
+            let mut root = build(&arena);
+            assert!(arena.is_empty());
+            assert_eq!(root.plans.len(), 3);
+            root.run();
+            assert!(!root.get("a").unwrap().active());
+            assert!(root.get("b").unwrap().active());
+            assert_eq!(root.get("c").unwrap().data().len(), 1);
+            arena.recycle(root);
+            assert_eq!(arena.len(), 4);
+        }
+    }
+}
@@ -1,12 +1,17 @@
 pub use crate::*;
 
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+use tracing::{debug, warn};
+
 /// Macro to redefine `Behaviour` trait in external crates for remote enum_dispatch definition.
 #[macro_export]
 macro_rules! behaviour_trait {
     () => {
         /// An object that implements run-time behaviour logic of an active plan.
         #[enum_dispatch]
-        pub trait Behaviour<C: Config>: Sized + 'static {
+        pub trait Behaviour<C: Config>: 'static {
             /// State of the plan's objective. May be queried while inactive.
             ///
             /// **In Progress** := `None` **Success** := `Some(true)` **Failure** := `Some(false)`
@@ -15,6 +20,16 @@ macro_rules! behaviour_trait {
             fn utility(&self, _plan: &Plan<C>) -> f64 {
                 0.
             }
+            /// Named contributions to `utility()` for tuning, summing to it. Empty if not composed of parts.
+            fn utility_breakdown(&self, _plan: &Plan<C>) -> Vec<(String, f64)> {
+                Vec::new()
+            }
+            /// Status and utility expected if the plan were entered now, queried while inactive.
+            /// Only needed when `status()` and `utility()` are meaningful just while active.
+            /// See [Plan::hypothetical].
+            fn estimate(&self, _plan: &Plan<C>) -> Option<Hypothetical> {
+                None
+            }
             /// Triggers once upon becoming active.
             fn on_entry(&mut self, _plan: &mut Plan<C>) {}
             /// Triggers once upon becoming inactive.
@@ -23,6 +38,30 @@ macro_rules! behaviour_trait {
             fn on_prepare(&mut self, _plan: &mut Plan<C>) {}
             /// Triggers repeatedly while active. Executes after subplans if scheduled on the same tick.
             fn on_run(&mut self, _plan: &mut Plan<C>) {}
+            /// Triggers once while active when the tree begins a graceful shutdown.
+            fn on_shutdown(&mut self, _plan: &mut Plan<C>) {}
+            /// Reset any random number generation to a state determined by `seed`.
+            /// Triggers upon entry before `on_entry()` when the plan has a seed. See [Plan::seed].
+            fn reseed(&mut self, _seed: u64) {}
+            /// Subplans the behaviour expects, inserted by [Plan::new] and [Plan::set_behaviour]
+            /// unless a subplan with the same name already exists.
+            fn default_subplans(&self) -> Vec<Plan<C>> {
+                Vec::new()
+            }
+            /// Whether at most one subplan is expected to be active at a time, as with selectors and sequences.
+            /// Checked after each run when the `invariants` feature is enabled.
+            fn exclusive_subplans(&self) -> bool {
+                false
+            }
+            /// Name of the concrete behaviour type, without module path or generic parameters.
+            fn type_name(&self) -> &'static str {
+                predicate::base_type_name::<Self>()
+            }
+            /// Named internal state and parameters of the behaviour for generic inspection.
+            #[cfg(feature = "serde")]
+            fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+                Vec::new()
+            }
         }
     };
 }
@@ -35,14 +74,255 @@ behaviour_trait!();
 pub enum Behaviours<C: Config> {
     AllSuccessStatus,
     AnySuccessStatus,
+    ThresholdStatus,
+    VoteStatusBehaviour,
     EvaluateStatus(EvaluateStatus<C>),
+    PredicateStatusBehaviour(PredicateStatusBehaviour<C>),
     ModifyStatus(ModifyStatus<C>),
+    ConditionalInvertBehaviour(ConditionalInvertBehaviour<C>),
+    ModifyUtilityBehaviour(ModifyUtilityBehaviour<C>),
 
     MultiBehaviour(MultiBehaviour<C>),
     RepeatBehaviour(RepeatBehaviour<C>),
-    SequenceBehaviour,
-    FallbackBehaviour,
+    SmoothedUtilityBehaviour(SmoothedUtilityBehaviour<C>),
+    DiscountedUtilBehaviour(DiscountedUtilBehaviour<C>),
+    CatchPanicBehaviour(CatchPanicBehaviour<C>),
+    EventBroadcastBehaviour(EventBroadcastBehaviour<C>),
+    #[cfg(feature = "serde")]
+    StreakBehaviour(StreakBehaviour<C>),
+    #[cfg(feature = "serde")]
+    RewardAccumulatorBehaviour(RewardAccumulatorBehaviour<C>),
+    DelayedStatusBehaviour(DelayedStatusBehaviour<C>),
+    TimeoutBehaviour(TimeoutBehaviour<C>),
+    DelayBehaviour(DelayBehaviour<C>),
+    PreconditionBehaviour(PreconditionBehaviour<C>),
+    SemaphoreBehaviour(SemaphoreBehaviour<C>),
+    CooldownBehaviour(CooldownBehaviour<C>),
+    SequenceBehaviour(SequenceBehaviour<C>),
+    FallbackBehaviour(FallbackBehaviour<C>),
+    EscalateBehaviour(EscalateBehaviour<C>),
+    RoundRobinBehaviour,
+    #[cfg(feature = "serde")]
+    TimelineBehaviour(TimelineBehaviour<C>),
+    PacedSelectorBehaviour,
     MaxUtilBehaviour,
+    ContextUtilBehaviour,
+    NormalizedUtilBehaviour(NormalizedUtilBehaviour<C>),
+    #[cfg(feature = "serde")]
+    UtilityDistributionBehaviour,
+    #[cfg(feature = "serde")]
+    TableUtilBehaviour,
+}
+
+/// Macro to implement a `Behaviour` trait redefined by [behaviour_trait] for all built-in behaviours,
+/// so that they can be mixed with external behaviours in a remote enum_dispatch definition.
+///
+/// Each method delegates to the implementation of the built-in for this crate's [Behaviour] trait.
+/// The implementations are generic over the config, or specific to one config if given.
+/// If the calling crate has a `serde` feature, it must enable the `serde` feature of this crate,
+/// since the redefined trait only declares `inspect()` with it.
+///
+/// The remote enum still has to implement this crate's [Behaviour] trait to be used in a [Config],
+/// which [forward_behaviour] does by forwarding each method to the redefined trait.
+///
+/// ```
+/// use dynamic_plan_tree::behaviour::SequenceBehaviour;
+/// // named by the signatures copied into the enum_dispatch implementations
+/// use dynamic_plan_tree::{config, enum_dispatch, forward_behaviour, Config, Hypothetical, Plan};
+///
+/// mod remote {
+///     use dynamic_plan_tree::*;
+///     behaviour_trait!();
+///     impl_builtin_behaviours!(Behaviour);
+/// }
+/// use remote::Behaviour;
+///
+/// struct MyBehaviour;
+/// impl<C: Config> Behaviour<C> for MyBehaviour {
+///     fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+///         Some(true)
+///     }
+/// }
+///
+/// #[enum_dispatch(Behaviour<C>)]
+/// enum MyBehaviours<C: Config> {
+///     SequenceBehaviour(SequenceBehaviour<C>),
+///     MyBehaviour,
+/// }
+/// forward_behaviour!(Behaviour, MyBehaviours);
+///
+/// fn main() {
+///     let plan = Plan::<config::Default>::new_stub("plan", true);
+///     let behaviour = MyBehaviours::from(MyBehaviour);
+///     assert_eq!(dynamic_plan_tree::Behaviour::status(&behaviour, &plan), Some(true));
+/// }
+/// ```
+#[macro_export]
+macro_rules! impl_builtin_behaviours {
+    ($($behaviour:ident)::+) => {
+        $crate::impl_builtin_behaviours!(@all [C: $crate::Config] C; $($behaviour)::+);
+    };
+    ($($behaviour:ident)::+, $config:ty) => {
+        $crate::impl_builtin_behaviours!(@all [] $config; $($behaviour)::+);
+    };
+
+    (@all [$($generics:tt)*] $config:ty; $($behaviour:ident)::+) => {
+        $crate::impl_builtin_behaviours!(@impl [$($generics)*] $config; $($behaviour)::+;
+            $crate::behaviour::Behaviours<$config>,
+            $crate::behaviour::AllSuccessStatus,
+            $crate::behaviour::AnySuccessStatus,
+            $crate::behaviour::ThresholdStatus,
+            $crate::behaviour::VoteStatusBehaviour,
+            $crate::behaviour::EvaluateStatus<$config>,
+            $crate::behaviour::PredicateStatusBehaviour<$config>,
+            $crate::behaviour::ModifyStatus<$config>,
+            $crate::behaviour::ConditionalInvertBehaviour<$config>,
+            $crate::behaviour::ModifyUtilityBehaviour<$config>,
+            $crate::behaviour::MultiBehaviour<$config>,
+            $crate::behaviour::RepeatBehaviour<$config>,
+            $crate::behaviour::SmoothedUtilityBehaviour<$config>,
+            $crate::behaviour::DiscountedUtilBehaviour<$config>,
+            $crate::behaviour::CatchPanicBehaviour<$config>,
+            $crate::behaviour::EventBroadcastBehaviour<$config>,
+            $crate::behaviour::DelayedStatusBehaviour<$config>,
+            $crate::behaviour::TimeoutBehaviour<$config>,
+            $crate::behaviour::DelayBehaviour<$config>,
+            $crate::behaviour::PreconditionBehaviour<$config>,
+            $crate::behaviour::SemaphoreBehaviour<$config>,
+            $crate::behaviour::CooldownBehaviour<$config>,
+            $crate::behaviour::SequenceBehaviour<$config>,
+            $crate::behaviour::FallbackBehaviour<$config>,
+            $crate::behaviour::EscalateBehaviour<$config>,
+            $crate::behaviour::RoundRobinBehaviour,
+            $crate::behaviour::PacedSelectorBehaviour,
+            $crate::behaviour::MaxUtilBehaviour,
+            $crate::behaviour::ContextUtilBehaviour,
+            $crate::behaviour::NormalizedUtilBehaviour<$config>,
+        );
+        $crate::__if_serde! {
+            $crate::impl_builtin_behaviours!(@impl [$($generics)*] $config; $($behaviour)::+;
+                $crate::behaviour::StreakBehaviour<$config>,
+                $crate::behaviour::RewardAccumulatorBehaviour<$config>,
+                $crate::behaviour::TimelineBehaviour<$config>,
+                $crate::behaviour::UtilityDistributionBehaviour,
+                $crate::behaviour::TableUtilBehaviour,
+            );
+        }
+    };
+
+    (@impl [$($generics:tt)*] $config:ty; $($behaviour:ident)::+;) => {};
+    (@impl [$($generics:tt)*] $config:ty; $($behaviour:ident)::+; $ty:ty, $($rest:ty,)*) => {
+        impl<$($generics)*> $($behaviour)::+<$config> for $ty {
+            fn status(&self, plan: &$crate::Plan<$config>) -> Option<bool> {
+                <Self as $crate::Behaviour<$config>>::status(self, plan)
+            }
+            fn utility(&self, plan: &$crate::Plan<$config>) -> f64 {
+                <Self as $crate::Behaviour<$config>>::utility(self, plan)
+            }
+            fn utility_breakdown(&self, plan: &$crate::Plan<$config>) -> Vec<(String, f64)> {
+                <Self as $crate::Behaviour<$config>>::utility_breakdown(self, plan)
+            }
+            fn estimate(&self, plan: &$crate::Plan<$config>) -> Option<$crate::Hypothetical> {
+                <Self as $crate::Behaviour<$config>>::estimate(self, plan)
+            }
+            fn on_entry(&mut self, plan: &mut $crate::Plan<$config>) {
+                <Self as $crate::Behaviour<$config>>::on_entry(self, plan)
+            }
+            fn on_exit(&mut self, plan: &mut $crate::Plan<$config>) {
+                <Self as $crate::Behaviour<$config>>::on_exit(self, plan)
+            }
+            fn on_prepare(&mut self, plan: &mut $crate::Plan<$config>) {
+                <Self as $crate::Behaviour<$config>>::on_prepare(self, plan)
+            }
+            fn on_run(&mut self, plan: &mut $crate::Plan<$config>) {
+                <Self as $crate::Behaviour<$config>>::on_run(self, plan)
+            }
+            fn on_shutdown(&mut self, plan: &mut $crate::Plan<$config>) {
+                <Self as $crate::Behaviour<$config>>::on_shutdown(self, plan)
+            }
+            fn reseed(&mut self, seed: u64) {
+                <Self as $crate::Behaviour<$config>>::reseed(self, seed)
+            }
+            fn default_subplans(&self) -> Vec<$crate::Plan<$config>> {
+                <Self as $crate::Behaviour<$config>>::default_subplans(self)
+            }
+            fn exclusive_subplans(&self) -> bool {
+                <Self as $crate::Behaviour<$config>>::exclusive_subplans(self)
+            }
+            fn type_name(&self) -> &'static str {
+                <Self as $crate::Behaviour<$config>>::type_name(self)
+            }
+            #[cfg(feature = "serde")]
+            fn inspect(&self) -> Vec<(&'static str, $crate::serde_value::Value)> {
+                <Self as $crate::Behaviour<$config>>::inspect(self)
+            }
+        }
+        $crate::impl_builtin_behaviours!(@impl [$($generics)*] $config; $($behaviour)::+; $($rest,)*);
+    };
+}
+
+/// Macro to implement this crate's [Behaviour] trait for a remote enum_dispatch enum,
+/// forwarding each method to the `Behaviour` trait redefined by [behaviour_trait], so it can be used in a [Config].
+///
+/// The implementation is generic over the config for an enum taking it as its only generic parameter,
+/// or specific to one config if given. As with [impl_builtin_behaviours], `inspect()` is forwarded
+/// only if the calling crate has a `serde` feature. See [impl_builtin_behaviours] for an example.
+#[macro_export]
+macro_rules! forward_behaviour {
+    ($($behaviour:ident)::+, $enum:ident) => {
+        $crate::forward_behaviour!(@impl [C: $crate::Config] C; $($behaviour)::+; $enum<C>);
+    };
+    ($($behaviour:ident)::+, $enum:ty, $config:ty) => {
+        $crate::forward_behaviour!(@impl [] $config; $($behaviour)::+; $enum);
+    };
+
+    (@impl [$($generics:tt)*] $config:ty; $($behaviour:ident)::+; $ty:ty) => {
+        impl<$($generics)*> $crate::Behaviour<$config> for $ty {
+            fn status(&self, plan: &$crate::Plan<$config>) -> Option<bool> {
+                <Self as $($behaviour)::+<$config>>::status(self, plan)
+            }
+            fn utility(&self, plan: &$crate::Plan<$config>) -> f64 {
+                <Self as $($behaviour)::+<$config>>::utility(self, plan)
+            }
+            fn utility_breakdown(&self, plan: &$crate::Plan<$config>) -> Vec<(String, f64)> {
+                <Self as $($behaviour)::+<$config>>::utility_breakdown(self, plan)
+            }
+            fn estimate(&self, plan: &$crate::Plan<$config>) -> Option<$crate::Hypothetical> {
+                <Self as $($behaviour)::+<$config>>::estimate(self, plan)
+            }
+            fn on_entry(&mut self, plan: &mut $crate::Plan<$config>) {
+                <Self as $($behaviour)::+<$config>>::on_entry(self, plan)
+            }
+            fn on_exit(&mut self, plan: &mut $crate::Plan<$config>) {
+                <Self as $($behaviour)::+<$config>>::on_exit(self, plan)
+            }
+            fn on_prepare(&mut self, plan: &mut $crate::Plan<$config>) {
+                <Self as $($behaviour)::+<$config>>::on_prepare(self, plan)
+            }
+            fn on_run(&mut self, plan: &mut $crate::Plan<$config>) {
+                <Self as $($behaviour)::+<$config>>::on_run(self, plan)
+            }
+            fn on_shutdown(&mut self, plan: &mut $crate::Plan<$config>) {
+                <Self as $($behaviour)::+<$config>>::on_shutdown(self, plan)
+            }
+            fn reseed(&mut self, seed: u64) {
+                <Self as $($behaviour)::+<$config>>::reseed(self, seed)
+            }
+            fn default_subplans(&self) -> Vec<$crate::Plan<$config>> {
+                <Self as $($behaviour)::+<$config>>::default_subplans(self)
+            }
+            fn exclusive_subplans(&self) -> bool {
+                <Self as $($behaviour)::+<$config>>::exclusive_subplans(self)
+            }
+            fn type_name(&self) -> &'static str {
+                <Self as $($behaviour)::+<$config>>::type_name(self)
+            }
+            #[cfg(feature = "serde")]
+            fn inspect(&self) -> Vec<(&'static str, $crate::serde_value::Value)> {
+                <Self as $($behaviour)::+<$config>>::inspect(self)
+            }
+        }
+    };
 }
 
 /// Returns `false` if `f.evaluate()`, `true` if `t.evaluate()`, otherwise `None`.
@@ -61,6 +341,22 @@ pub fn evaluate_status<C: Config, T: Predicate, F: Predicate>(
 }
 
 /// Behaviour with status that invokes `evaluate_status(&self.0, &self.1)`.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// use dynamic_plan_tree::serde_value::Value;
+///
+/// let done = DataValue { key: "done".into() };
+/// let failed = DataValue { key: "failed".into() };
+/// let status = EvaluateStatus(done.into(), failed.into());
+/// let mut plan = Plan::<config::Default>::new(status.into(), "root", 1, true);
+/// assert_eq!(plan.status(), None);
+/// plan.set_data("done", Value::Bool(true));
+/// assert_eq!(plan.status(), Some(true));
+/// // failure takes precedence
+/// plan.set_data("failed", Value::Bool(true));
+/// assert_eq!(plan.status(), Some(false));
+/// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EvaluateStatus<C: Config>(pub C::Predicate, pub C::Predicate);
 impl<C: Config> Behaviour<C> for EvaluateStatus<C> {
@@ -69,7 +365,42 @@ impl<C: Config> Behaviour<C> for EvaluateStatus<C> {
     }
 }
 
+/// Behaviour with status that always reflects the result of `self.0.evaluate()`.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// use dynamic_plan_tree::serde_value::Value;
+///
+/// let blocked = DataValue { key: "blocked".into() };
+/// let status = PredicateStatusBehaviour(Not(Box::new(blocked.into())).into());
+/// let mut plan = Plan::<config::Default>::new(status.into(), "root", 1, true);
+/// assert_eq!(plan.status(), Some(true));
+/// plan.set_data("blocked", Value::Bool(true));
+/// assert_eq!(plan.status(), Some(false));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PredicateStatusBehaviour<C: Config>(pub C::Predicate);
+impl<C: Config> Behaviour<C> for PredicateStatusBehaviour<C> {
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        Some(self.0.evaluate(plan, &[]))
+    }
+}
+
 /// Behaviour with status `true` if `AllSuccess`, `false` if `AnyFailure`, otherwise `None`.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let mut plan = Plan::<config::Default>::new(AllSuccessStatus.into(), "root", 1, true);
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+/// plan.insert(Plan::new_stub("b", true));
+/// plan.run();
+/// assert_eq!(plan.status(), None);
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "b", 1, true));
+/// assert_eq!(plan.status(), Some(true));
+/// plan.insert(Plan::new(AnySuccessStatus.into(), "c", 1, true));
+/// assert_eq!(plan.status(), Some(false));
+/// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AllSuccessStatus;
 impl<C: Config> Behaviour<C> for AllSuccessStatus {
@@ -79,6 +410,20 @@ impl<C: Config> Behaviour<C> for AllSuccessStatus {
 }
 
 /// Behaviour with status `true` if `AnySuccess`, `false` if `AllFailure`, otherwise `None`.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let mut plan = Plan::<config::Default>::new(AnySuccessStatus.into(), "root", 1, true);
+/// plan.insert(Plan::new(AnySuccessStatus.into(), "a", 1, true));
+/// plan.insert(Plan::new_stub("b", true));
+/// plan.run();
+/// assert_eq!(plan.status(), None);
+/// plan.insert(Plan::new(AnySuccessStatus.into(), "b", 1, true));
+/// assert_eq!(plan.status(), Some(false));
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "c", 1, true));
+/// assert_eq!(plan.status(), Some(true));
+/// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AnySuccessStatus;
 impl<C: Config> Behaviour<C> for AnySuccessStatus {
@@ -87,9 +432,112 @@ impl<C: Config> Behaviour<C> for AnySuccessStatus {
     }
 }
 
+/// Behaviour with status `true` once at least `self.0` subplans succeed,
+/// `false` once too few are left in progress to get there, otherwise `None`.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let mut plan = Plan::<config::Default>::new(ThresholdStatus(2).into(), "root", 1, true);
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+/// plan.insert(Plan::new_stub("b", true));
+/// plan.insert(Plan::new_stub("c", true));
+/// plan.run();
+/// assert_eq!(plan.status(), None);
+/// plan.insert(Plan::new(AnySuccessStatus.into(), "b", 1, true));
+/// assert_eq!(plan.status(), None);
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "c", 1, true));
+/// assert_eq!(plan.status(), Some(true));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ThresholdStatus(pub usize);
+impl<C: Config> Behaviour<C> for ThresholdStatus {
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        let (success, pending) =
+            plan.plans
+                .iter()
+                .fold((0, 0), |(success, pending), p| match p.status() {
+                    Some(true) => (success + 1, pending),
+                    Some(false) => (success, pending),
+                    None => (success, pending + 1),
+                });
+        if success >= self.0 {
+            Some(true)
+        } else if success + pending < self.0 {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+/// Behaviour with status of the majority vote among subplans that have settled.
+///
+/// Subplans with status `None` abstain. Returns `self.tie` when the votes are even,
+/// and `None` when no subplan has settled.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let vote = VoteStatusBehaviour { tie: Some(false) };
+/// let mut plan = Plan::<config::Default>::new(vote.into(), "root", 1, true);
+/// plan.insert(Plan::new_stub("a", true));
+/// plan.run();
+/// assert_eq!(plan.status(), None);
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "b", 1, true));
+/// plan.insert(Plan::new(AnySuccessStatus.into(), "c", 1, true));
+/// assert_eq!(plan.status(), Some(false));
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "d", 1, true));
+/// assert_eq!(plan.status(), Some(true));
+/// ```
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VoteStatusBehaviour {
+    pub tie: Option<bool>,
+}
+impl<C: Config> Behaviour<C> for VoteStatusBehaviour {
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        let (yes, no) = plan
+            .plans
+            .iter()
+            .fold((0, 0), |(yes, no), p| match p.status() {
+                Some(true) => (yes + 1, no),
+                Some(false) => (yes, no + 1),
+                None => (yes, no),
+            });
+        match (yes + no, yes.cmp(&no)) {
+            (0, _) => None,
+            (_, Ordering::Greater) => Some(true),
+            (_, Ordering::Less) => Some(false),
+            (_, Ordering::Equal) => self.tie,
+        }
+    }
+}
+
 /// Wraps inner behaviour. If inner status exists, invert when `self.1` is `None` otherwise use `self.1`.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let invert = ModifyStatus(Box::new(AllSuccessStatus.into()), None);
+/// let mut plan = Plan::<config::Default>::new(invert.into(), "root", 1, true);
+/// plan.insert(Plan::new(AnySuccessStatus.into(), "a", 1, true));
+/// plan.run();
+/// assert_eq!(plan.status(), Some(true));
+/// // status in progress is left as is
+/// plan.insert(Plan::new_stub("a", true));
+/// assert_eq!(plan.status(), None);
+///
+/// let succeed = ModifyStatus(Box::new(AllSuccessStatus.into()), Some(true));
+/// plan.set_behaviour(succeed.into());
+/// plan.insert(Plan::new(AnySuccessStatus.into(), "a", 1, true));
+/// assert_eq!(plan.status(), Some(true));
+/// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct ModifyStatus<C: Config>(pub Box<C::Behaviour>, pub Option<bool>);
+pub struct ModifyStatus<C: Config>(
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))] pub Box<C::Behaviour>,
+    pub Option<bool>,
+);
 impl<C: Config> Behaviour<C> for ModifyStatus<C> {
     fn status(&self, plan: &Plan<C>) -> Option<bool> {
         self.0.status(plan).map(|x| self.1.unwrap_or(!x))
@@ -97,6 +545,14 @@ impl<C: Config> Behaviour<C> for ModifyStatus<C> {
     fn utility(&self, plan: &Plan<C>) -> f64 {
         self.0.utility(plan)
     }
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        self.0.utility_breakdown(plan)
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        let estimate = self.0.estimate(plan)?;
+        let status = estimate.status.map(|x| self.1.unwrap_or(!x));
+        Some(Hypothetical { status, ..estimate })
+    }
     fn on_entry(&mut self, plan: &mut Plan<C>) {
         self.0.on_entry(plan);
     }
@@ -109,11 +565,222 @@ impl<C: Config> Behaviour<C> for ModifyStatus<C> {
     fn on_run(&mut self, plan: &mut Plan<C>) {
         self.0.on_run(plan);
     }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        self.0.on_shutdown(plan);
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.0.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.0.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.0.exclusive_subplans()
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        self.0.inspect()
+    }
+}
+
+/// Wraps inner behaviour. Inverts inner status only while `invert_when` holds, otherwise passes it through.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// use dynamic_plan_tree::serde_value::Value;
+///
+/// let behaviour = ConditionalInvertBehaviour {
+///     behaviour: Box::new(AllSuccessStatus.into()),
+///     invert_when: DataValue { key: "invert".into() }.into(),
+/// };
+/// let mut plan = Plan::<config::Default>::new(behaviour.into(), "root", 1, true);
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+/// plan.run();
+/// assert_eq!(plan.status(), Some(true));
+/// plan.set_data("invert", Value::Bool(true));
+/// assert_eq!(plan.status(), Some(false));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConditionalInvertBehaviour<C: Config> {
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
+    pub behaviour: Box<C::Behaviour>,
+    /// Evaluated against the plan each time status is queried.
+    pub invert_when: C::Predicate,
+}
+impl<C: Config> Behaviour<C> for ConditionalInvertBehaviour<C> {
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        let status = self.behaviour.status(plan)?;
+        Some(status != self.invert_when.evaluate(plan, &[]))
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        self.behaviour.utility(plan)
+    }
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        self.behaviour.utility_breakdown(plan)
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        let estimate = self.behaviour.estimate(plan)?;
+        let invert = self.invert_when.evaluate(plan, &[]);
+        let status = estimate.status.map(|x| x != invert);
+        Some(Hypothetical { status, ..estimate })
+    }
+    fn on_entry(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_entry(plan);
+    }
+    fn on_exit(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_exit(plan);
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_prepare(plan);
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_run(plan);
+    }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_shutdown(plan);
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        self.behaviour.inspect()
+    }
+}
+
+/// Wraps inner behaviour, reporting its utility as `utility * scale + offset`.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// // utility of 0 raised to 2, then scaled by 3 and raised to 7
+/// let inner = ModifyUtilityBehaviour::new(AllSuccessStatus.into(), 1., 2.);
+/// let outer = ModifyUtilityBehaviour::new(inner.into(), 3., 1.);
+/// let mut plan = Plan::<config::Default>::new(outer.into(), "root", 1, true);
+/// plan.run();
+/// assert_eq!(plan.utility(), 7.);
+/// assert_eq!(plan.status(), Some(true));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModifyUtilityBehaviour<C: Config> {
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
+    pub behaviour: Box<C::Behaviour>,
+    pub scale: f64,
+    pub offset: f64,
+}
+impl<C: Config> ModifyUtilityBehaviour<C> {
+    pub fn new(behaviour: C::Behaviour, scale: f64, offset: f64) -> Self {
+        Self {
+            behaviour: Box::new(behaviour),
+            scale,
+            offset,
+        }
+    }
+}
+impl<C: Config> Behaviour<C> for ModifyUtilityBehaviour<C> {
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        self.behaviour.status(plan)
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        self.behaviour.utility(plan) * self.scale + self.offset
+    }
+    /// Inner contributions under `inner`, plus the `scale` and `offset` adjustments.
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        let mut parts = labelled_breakdown(&*self.behaviour, plan, "inner");
+        let inner = self.behaviour.utility(plan);
+        parts.push(("scale".into(), inner * (self.scale - 1.)));
+        parts.push(("offset".into(), self.offset));
+        parts
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        let estimate = self.behaviour.estimate(plan)?;
+        let utility = estimate.utility * self.scale + self.offset;
+        Some(Hypothetical {
+            utility,
+            ..estimate
+        })
+    }
+    fn on_entry(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_entry(plan);
+    }
+    fn on_exit(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_exit(plan);
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_prepare(plan);
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_run(plan);
+    }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_shutdown(plan);
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        self.behaviour.inspect()
+    }
+}
+
+/// Utility breakdown of `behaviour` with each part prefixed by `label`,
+/// or its whole utility as a single part labelled `label` if it has no breakdown.
+pub fn labelled_breakdown<C: Config>(
+    behaviour: &C::Behaviour,
+    plan: &Plan<C>,
+    label: &str,
+) -> Vec<(String, f64)> {
+    let parts = behaviour.utility_breakdown(plan);
+    if parts.is_empty() {
+        return vec![(label.into(), behaviour.utility(plan))];
+    }
+    let parts = parts.into_iter();
+    parts
+        .map(|(part, x)| (format!("{label}.{part}"), x))
+        .collect()
 }
 
 /// Vector of behaviours sharing the same plan. Status takes aggregate AND. Utility takes aggregate sum.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// use dynamic_plan_tree::serde_value::Value;
+///
+/// let ready = PredicateStatusBehaviour(DataValue { key: "ready".into() }.into());
+/// let valued = ModifyUtilityBehaviour::new(AllSuccessStatus.into(), 0., 0.5);
+/// let multi = MultiBehaviour(vec![AllSuccessStatus.into(), ready.into(), valued.into()]);
+/// let mut plan = Plan::<config::Default>::new(multi.into(), "root", 1, true);
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+/// plan.run();
+/// assert_eq!(plan.status(), Some(false));
+/// plan.set_data("ready", Value::Bool(true));
+/// assert_eq!(plan.status(), Some(true));
+/// assert_eq!(plan.utility(), 0.5);
+/// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct MultiBehaviour<C: Config>(pub Vec<C::Behaviour>);
+pub struct MultiBehaviour<C: Config>(
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "TaggedBehaviour::<C>::serialize_vec",
+            deserialize_with = "TaggedBehaviour::<C>::deserialize_vec"
+        )
+    )]
+    pub Vec<C::Behaviour>,
+);
 impl<C: Config> Behaviour<C> for MultiBehaviour<C> {
     fn status(&self, plan: &Plan<C>) -> Option<bool> {
         let mut status = Some(true);
@@ -129,6 +796,38 @@ impl<C: Config> Behaviour<C> for MultiBehaviour<C> {
     fn utility(&self, plan: &Plan<C>) -> f64 {
         self.0.iter().map(|behaviour| behaviour.utility(plan)).sum()
     }
+    /// Contributions of each behaviour labelled by index.
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        let terms = self.0.iter().enumerate();
+        terms
+            .flat_map(|(i, behaviour)| labelled_breakdown(behaviour, plan, &i.to_string()))
+            .collect()
+    }
+    /// Estimates combined like status and utility, if any of the behaviours estimates.
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        let estimates = self.0.iter().map(|behaviour| behaviour.estimate(plan));
+        let estimates = estimates.collect::<Vec<_>>();
+        if estimates.iter().all(Option::is_none) {
+            return None;
+        }
+        let mut combined = Hypothetical {
+            status: Some(true),
+            utility: 0.,
+        };
+        for (behaviour, estimate) in self.0.iter().zip(estimates) {
+            let estimate = estimate.unwrap_or_else(|| Hypothetical {
+                status: behaviour.status(plan),
+                utility: behaviour.utility(plan),
+            });
+            combined.utility += estimate.utility;
+            combined.status = match (combined.status, estimate.status) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            };
+        }
+        Some(combined)
+    }
     fn on_entry(&mut self, plan: &mut Plan<C>) {
         for behaviour in &mut self.0 {
             behaviour.on_entry(plan);
@@ -149,12 +848,50 @@ impl<C: Config> Behaviour<C> for MultiBehaviour<C> {
             behaviour.on_run(plan);
         }
     }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        for behaviour in &mut self.0 {
+            behaviour.on_shutdown(plan);
+        }
+    }
+    fn reseed(&mut self, seed: u64) {
+        for (i, behaviour) in self.0.iter_mut().enumerate() {
+            behaviour.reseed(derive_seed(seed, &i));
+        }
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.0.iter().flat_map(|b| b.default_subplans()).collect()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.0.iter().any(|b| b.exclusive_subplans())
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        self.0
+            .iter()
+            .flat_map(|behaviour| behaviour.inspect())
+            .collect()
+    }
 }
 
 /// Repeats inner behaviour for specified iterations until failure encountered while condition holds.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let mut repeat = RepeatBehaviour::new(AllSuccessStatus.into());
+/// repeat.iterations = 3;
+/// let mut plan = Plan::<config::Default>::new(repeat.into(), "root", 1, true);
+/// for _ in 0..3 {
+///     plan.run();
+///     assert_eq!(plan.status(), None);
+/// }
+/// plan.run();
+/// assert_eq!(plan.status(), Some(true));
+/// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RepeatBehaviour<C: Config> {
     /// Behaviour that expects some status on completion to mark each iteration.
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
     pub behaviour: Box<C::Behaviour>,
     /// Stop running behaviour once condition no longer holds.
     pub condition: Option<C::Predicate>,
@@ -188,6 +925,17 @@ impl<C: Config> Behaviour<C> for RepeatBehaviour<C> {
     fn utility(&self, plan: &Plan<C>) -> f64 {
         self.behaviour.utility(plan)
     }
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        self.behaviour.utility_breakdown(plan)
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        let estimate = self.behaviour.estimate(plan)?;
+        // iterations start over upon entry
+        Some(Hypothetical {
+            status: None,
+            ..estimate
+        })
+    }
     fn on_entry(&mut self, plan: &mut Plan<C>) {
         self.status = None;
         self.count_down = self.iterations;
@@ -233,279 +981,4393 @@ impl<C: Config> Behaviour<C> for RepeatBehaviour<C> {
             }
         }
     }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_shutdown(plan);
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        use serde_value::Value;
+        vec![
+            ("count_down", Value::U64(self.count_down as u64)),
+            ("iterations", Value::U64(self.iterations as u64)),
+            ("stop_value", Value::Bool(self.stop_value)),
+            (
+                "status",
+                Value::Option(self.status.map(|x| Box::new(Value::Bool(x)))),
+            ),
+        ]
+    }
 }
 
-/// Behaviour that sequentially transitions through child plans until first failure.
+/// Wraps inner behaviour and reports an exponential moving average of its utility.
 ///
-/// # Transitions
-/// Plan is expected to contain transitions that form a linear sequence of success predicates,
-/// with only one child plan active at a time. Behaviour is undefined otherwise.
+/// The average is primed with the inner utility upon entry and updated after each run.
+/// While inactive, the last averaged value is reported.
 ///
-/// If the status of any previously visited child plan changes from success,
-/// the sequence will transition back to that point.
-
-#[derive(Default)]
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let inner = ModifyUtilityBehaviour::new(AllSuccessStatus.into(), 0., 1.);
+/// let smoothed = SmoothedUtilityBehaviour::new(inner.into(), 0.5);
+/// let mut plan = Plan::<config::Default>::new(smoothed.into(), "root", 1, true);
+/// plan.run();
+/// assert_eq!(plan.utility(), 1.);
+/// // raise the inner utility to 3
+/// let smoothed = plan.cast_mut::<SmoothedUtilityBehaviour<config::Default>>().unwrap();
+/// let inner = smoothed.behaviour.cast_mut::<ModifyUtilityBehaviour<config::Default>>();
+/// inner.unwrap().offset = 3.;
+/// plan.run();
+/// assert_eq!(plan.utility(), 2.);
+/// plan.run();
+/// assert_eq!(plan.utility(), 2.5);
+/// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct SequenceBehaviour(Vec<String>);
-impl<C: Config> Behaviour<C> for SequenceBehaviour {
-    /// - Success when all child plans succeed.
-    /// - Failure when any child plan fails.
-    /// - None while otherwise in-progress.
+pub struct SmoothedUtilityBehaviour<C: Config> {
+    /// Behaviour whose utility is smoothed.
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
+    pub behaviour: Box<C::Behaviour>,
+    /// Weight of the newest sample, clamped within `(0, 1]`.
+    pub alpha: f64,
+
+    smoothed: Option<f64>,
+}
+
+impl<C: Config> SmoothedUtilityBehaviour<C> {
+    pub fn new(behaviour: C::Behaviour, alpha: f64) -> Self {
+        Self {
+            behaviour: Box::new(behaviour),
+            alpha,
+            smoothed: None,
+        }
+    }
+}
+
+impl<C: Config> Behaviour<C> for SmoothedUtilityBehaviour<C> {
     fn status(&self, plan: &Plan<C>) -> Option<bool> {
-        AllSuccessStatus.status(plan)
+        self.behaviour.status(plan)
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        self.smoothed
+            .unwrap_or_else(|| self.behaviour.utility(plan))
+    }
+    /// Inner contributions under `raw`, plus the lag of the average behind the inner utility.
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        let mut parts = labelled_breakdown(&*self.behaviour, plan, "raw");
+        if let Some(smoothed) = self.smoothed {
+            parts.push(("smoothing".into(), smoothed - self.behaviour.utility(plan)));
+        }
+        parts
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        // the average starts from the inner utility upon entry
+        self.behaviour.estimate(plan)
+    }
+    fn on_entry(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_entry(plan);
+        self.smoothed = Some(self.behaviour.utility(plan));
+    }
+    fn on_exit(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_exit(plan);
     }
     fn on_prepare(&mut self, plan: &mut Plan<C>) {
-        check_visited_status_and_jump(plan, &mut self.0, false);
+        self.behaviour.on_prepare(plan);
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_run(plan);
+        let alpha = self.alpha.clamp(f64::EPSILON, 1.);
+        let utility = self.behaviour.utility(plan);
+        self.smoothed = Some(match self.smoothed {
+            Some(smoothed) => alpha * utility + (1. - alpha) * smoothed,
+            None => utility,
+        });
+    }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_shutdown(plan);
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        self.behaviour.inspect()
     }
 }
 
-/// Behaviour that sequentially transitions through child plans until first success.
+/// Wraps inner behaviour, adding the utility of child plans discounted by `gamma`.
 ///
-/// # Transitions
-/// Plan is expected to contain transitions that form a linear sequence of failure predicates,
-/// with only one child plan active at a time. Behaviour is undefined otherwise.
+/// Children using this behaviour roll up their own subtrees, so utility compounds discounts per level.
 ///
-/// If the status of any previously visited child plan changes from failure,
-/// the sequence will transition back to that point.
-
-#[derive(Default)]
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// # use dynamic_plan_tree::config::fixtures::scored;
+///
+/// let inner = ModifyUtilityBehaviour::new(AllSuccessStatus.into(), 0., 1.);
+/// let discounted = DiscountedUtilBehaviour::new(inner.into(), 0.5);
+/// let mut plan = Plan::<config::Default>::new(discounted.into(), "root", 1, true);
+/// plan.insert(scored("a", true, 2.));
+/// plan.insert(scored("b", false, 4.));
+/// plan.run();
+/// assert_eq!(plan.utility(), 1. + 0.5 * (2. + 4.));
+/// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct FallbackBehaviour(Vec<String>);
-impl<C: Config> Behaviour<C> for FallbackBehaviour {
-    /// - Success when any child plans succeeds.
-    /// - Failure when all child plan fail.
-    /// - None while otherwise in-progress.
+pub struct DiscountedUtilBehaviour<C: Config> {
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
+    pub behaviour: Box<C::Behaviour>,
+    /// Discount applied to the sum of child utilities, clamped within `[0, 1]`.
+    pub gamma: f64,
+}
+impl<C: Config> DiscountedUtilBehaviour<C> {
+    pub fn new(behaviour: C::Behaviour, gamma: f64) -> Self {
+        Self {
+            behaviour: Box::new(behaviour),
+            gamma,
+        }
+    }
+
+    fn discounted(&self, plan: &Plan<C>) -> f64 {
+        let children = plan.plans.iter().map(Plan::utility).sum::<f64>();
+        self.gamma.clamp(0., 1.) * children
+    }
+}
+impl<C: Config> Behaviour<C> for DiscountedUtilBehaviour<C> {
     fn status(&self, plan: &Plan<C>) -> Option<bool> {
-        AnySuccessStatus.status(plan)
+        self.behaviour.status(plan)
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        self.behaviour.utility(plan) + self.discounted(plan)
+    }
+    /// Inner contributions under `inner`, plus the discounted sum of `children`.
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        let mut parts = labelled_breakdown(&*self.behaviour, plan, "inner");
+        parts.push(("children".into(), self.discounted(plan)));
+        parts
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        let estimate = self.behaviour.estimate(plan)?;
+        let utility = estimate.utility + self.discounted(plan);
+        Some(Hypothetical {
+            utility,
+            ..estimate
+        })
+    }
+    fn on_entry(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_entry(plan);
+    }
+    fn on_exit(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_exit(plan);
     }
     fn on_prepare(&mut self, plan: &mut Plan<C>) {
-        check_visited_status_and_jump(plan, &mut self.0, true);
+        self.behaviour.on_prepare(plan);
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_run(plan);
+    }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_shutdown(plan);
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        self.behaviour.inspect()
     }
 }
 
-fn check_visited_status_and_jump<C: Config>(
-    plan: &mut Plan<C>,
-    visited: &mut Vec<String>,
-    jump_val: bool,
-) {
-    // find first inactive visited plans that has status none
-    let pos = visited.iter().position(|x| match plan.get(x) {
-        Some(x) => !x.active() && x.status().map(|x| x == jump_val).unwrap_or(true),
-        None => false,
-    });
-    // jump back to that plan
-    if let Some(pos) = pos {
-        plan.exit(true);
-        plan.enter_plan(&visited[pos]);
-        visited.truncate(pos);
+/// Contains panics raised by the inner behaviour, failing the plan instead of aborting the tick.
+///
+/// Once the inner behaviour panics it may be left in an inconsistent state, as may any `data` it partially wrote,
+/// so it is no longer called until the plan is entered again. Status is failure and utility is zero meanwhile.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*};
+/// use std::sync::Arc;
+///
+/// struct Unready;
+/// impl ScoreContext for Unready {
+///     fn score(&self, name: &String) -> Option<f64> {
+///         panic!("no score for {name}")
+///     }
+/// }
+///
+/// let select = CatchPanicBehaviour::new(MaxUtilBehaviour::default().into());
+/// let mut plan = Plan::<config::Default>::new(select.into(), "root", 1, true);
+/// plan.insert(Plan::new(ContextUtilBehaviour::default().into(), "a", 1, false));
+/// // behaves as the inner behaviour until it panics
+/// plan.run();
+/// assert_eq!(plan.status(), None);
+/// // the panic while selecting fails the plan instead of aborting the tick
+/// plan.set_context(Some(Arc::new(Unready)));
+/// plan.run();
+/// assert_eq!(plan.status(), Some(false));
+/// let catch = plan.cast::<CatchPanicBehaviour<config::Default>>().unwrap();
+/// assert_eq!(catch.panic_message(), Some("no score for a"));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CatchPanicBehaviour<C: Config> {
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
+    pub behaviour: Box<C::Behaviour>,
+    panic: Option<String>,
+}
+
+impl<C: Config> CatchPanicBehaviour<C> {
+    pub fn new(behaviour: C::Behaviour) -> Self {
+        Self {
+            behaviour: Box::new(behaviour),
+            panic: None,
+        }
     }
-    // find currently active plan
-    let active = match plan.plans.iter().find(|x| x.active()) {
-        Some(x) => x.name(),
-        None => return,
-    };
-    // add active plan to visited if not already
-    if let Some(last) = visited.last() {
-        if last == active {
+
+    /// Message of the contained panic since last entry.
+    pub fn panic_message(&self) -> Option<&str> {
+        self.panic.as_deref()
+    }
+
+    fn catch(
+        &mut self,
+        plan: &mut Plan<C>,
+        hook: &str,
+        f: impl FnOnce(&mut C::Behaviour, &mut Plan<C>),
+    ) {
+        if self.panic.is_some() {
             return;
         }
+        // asserting unwind safety is sound since inner state is never observed again after a panic
+        let behaviour = &mut self.behaviour;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(behaviour, plan)));
+        if let Err(payload) = result {
+            let message = PlanPanic::message_of(&*payload);
+            tracing::error!(plan=?plan.name(), hook, %message, "behaviour panicked");
+            self.panic = Some(message);
+        }
     }
-    visited.push(active.clone());
 }
 
-/// Behaviour that monitors and transitions to the child plan with highest utility.
+impl<C: Config> Behaviour<C> for CatchPanicBehaviour<C> {
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        if self.panic.is_some() {
+            return Some(false);
+        }
+        let status =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| self.behaviour.status(plan)));
+        status.unwrap_or(Some(false))
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        if self.panic.is_some() {
+            return 0.;
+        }
+        let utility = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.behaviour.utility(plan)
+        }));
+        utility.unwrap_or(0.)
+    }
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        if self.panic.is_some() {
+            return Vec::new();
+        }
+        let parts = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.behaviour.utility_breakdown(plan)
+        }));
+        parts.unwrap_or_default()
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        // a previous panic is cleared upon entry
+        let estimate = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.behaviour.estimate(plan)
+        }));
+        estimate.unwrap_or(Some(Hypothetical {
+            status: Some(false),
+            utility: 0.,
+        }))
+    }
+    fn on_entry(&mut self, plan: &mut Plan<C>) {
+        self.panic = None;
+        self.catch(plan, "entry", |b, plan| b.on_entry(plan));
+    }
+    fn on_exit(&mut self, plan: &mut Plan<C>) {
+        self.catch(plan, "exit", |b, plan| b.on_exit(plan));
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        self.catch(plan, "prepare", |b, plan| b.on_prepare(plan));
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        self.catch(plan, "run", |b, plan| b.on_run(plan));
+    }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        self.catch(plan, "shutdown", |b, plan| b.on_shutdown(plan));
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        let mut fields = self.behaviour.inspect();
+        fields.push(("panic", serde_value::to_value(&self.panic).unwrap()));
+        fields
+    }
+}
+
+/// Event posted by [EventBroadcastBehaviour], also serving as a matcher for events of the same name.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Broadcast(pub String);
+impl predicate::EventMatcher for Broadcast {
+    fn matches(&self, event: &impl EnumCast) -> bool {
+        event.cast::<Broadcast>() == Some(self)
+    }
+}
+
+/// Wraps inner behaviour and broadcasts an event once its status settles on success or failure.
 ///
-/// Plan is expected to contain no transitions, with only one child active at a time. Behaviour is undefined otherwise.
+/// The [Broadcast] event is posted to all active descendants of the plan, becoming visible on their next run.
+/// Nothing is posted if `C::Event` has no variant for [Broadcast], warning with code `W204` instead.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let broadcast = EventBroadcastBehaviour::new(AllSuccessStatus.into(), Some("done".into()), None);
+/// let mut plan = Plan::<config::Default>::new(broadcast.into(), "root", 1, true);
+/// plan.insert(Plan::new_stub("task", true));
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "listener", 1, true));
+/// plan.run();
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "task", 1, true));
+/// plan.run();
+/// // visible to descendants on their next run
+/// plan.run();
+/// let listener = plan.get("listener").unwrap();
+/// assert!(EventMatches(Broadcast("done".into())).evaluate(listener, &[]));
+/// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct MaxUtilBehaviour;
-impl<C: Config> Behaviour<C> for MaxUtilBehaviour {
-    /// Returns status of currently active child plan.
+pub struct EventBroadcastBehaviour<C: Config> {
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
+    pub behaviour: Box<C::Behaviour>,
+    /// Name of event to broadcast upon success.
+    pub on_success: Option<String>,
+    /// Name of event to broadcast upon failure.
+    pub on_failure: Option<String>,
+    settled: Option<bool>,
+}
+
+impl<C: Config> EventBroadcastBehaviour<C> {
+    pub fn new(
+        behaviour: C::Behaviour,
+        on_success: Option<String>,
+        on_failure: Option<String>,
+    ) -> Self {
+        Self {
+            behaviour: Box::new(behaviour),
+            on_success,
+            on_failure,
+            settled: None,
+        }
+    }
+}
+
+impl<C: Config> Behaviour<C> for EventBroadcastBehaviour<C> {
     fn status(&self, plan: &Plan<C>) -> Option<bool> {
-        plan.plans.iter().find(|p| p.active())?.status()
+        self.behaviour.status(plan)
     }
-    /// Returns max utility of all child plans.
     fn utility(&self, plan: &Plan<C>) -> f64 {
-        match max_utility(&plan.plans) {
-            Some((_, util)) => util,
-            None => 0.,
+        self.behaviour.utility(plan)
+    }
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        self.behaviour.utility_breakdown(plan)
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        self.behaviour.estimate(plan)
+    }
+    fn on_entry(&mut self, plan: &mut Plan<C>) {
+        self.settled = None;
+        self.behaviour.on_entry(plan);
+    }
+    fn on_exit(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_exit(plan);
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_prepare(plan);
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_run(plan);
+        // broadcast only when status changes into success or failure
+        let status = self.behaviour.status(plan);
+        if status == self.settled {
+            return;
+        }
+        self.settled = status;
+        let name = match status {
+            Some(true) => &self.on_success,
+            Some(false) => &self.on_failure,
+            None => &None,
+        };
+        let Some(name) = name else {
+            return;
+        };
+        if C::Event::from_any(Broadcast(name.clone())).is_none() {
+            if let Some(suppressed) = plan.throttle_warning("W204") {
+                warn!(path=%plan.path(), event=%name, suppressed, "broadcast dropped");
+            }
+            return;
+        }
+        fn post<C: Config>(plan: &mut Plan<C>, name: &str) {
+            for plan in plan.plans.iter_mut().filter(|x| x.active()) {
+                if let Some(event) = C::Event::from_any(Broadcast(name.into())) {
+                    plan.post_event(event);
+                }
+                post(plan, name);
+            }
+        }
+        post(plan, name);
+    }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_shutdown(plan);
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        self.behaviour.inspect()
+    }
+}
+
+/// Wraps inner behaviour and counts its consecutive successes and failures in `data`.
+///
+/// Each time the inner status settles on success or failure, the matching counter is incremented
+/// and the other is reset to zero. Counters are stored as `u64` values.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// use dynamic_plan_tree::serde_value::Value;
+///
+/// let streak = StreakBehaviour::new(AllSuccessStatus.into(), "wins".into(), "losses".into());
+/// let mut plan = Plan::<config::Default>::new(streak.into(), "root", 1, true);
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+/// plan.run();
+/// assert_eq!(plan.data().get("wins"), Some(&Value::U64(1)));
+/// plan.insert(Plan::new(AnySuccessStatus.into(), "a", 1, true));
+/// plan.run();
+/// assert_eq!(plan.data().get("wins"), Some(&Value::U64(0)));
+/// assert_eq!(plan.data().get("losses"), Some(&Value::U64(1)));
+/// ```
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+pub struct StreakBehaviour<C: Config> {
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
+    pub behaviour: Box<C::Behaviour>,
+    /// Data key of the consecutive success counter.
+    pub success_key: String,
+    /// Data key of the consecutive failure counter.
+    pub failure_key: String,
+    settled: Option<bool>,
+}
+
+#[cfg(feature = "serde")]
+impl<C: Config> StreakBehaviour<C> {
+    pub fn new(behaviour: C::Behaviour, success_key: String, failure_key: String) -> Self {
+        Self {
+            behaviour: Box::new(behaviour),
+            success_key,
+            failure_key,
+            settled: None,
         }
     }
+}
+
+#[cfg(feature = "serde")]
+impl<C: Config> Behaviour<C> for StreakBehaviour<C> {
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        self.behaviour.status(plan)
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        self.behaviour.utility(plan)
+    }
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        self.behaviour.utility_breakdown(plan)
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        self.behaviour.estimate(plan)
+    }
+    fn on_entry(&mut self, plan: &mut Plan<C>) {
+        self.settled = None;
+        self.behaviour.on_entry(plan);
+    }
+    fn on_exit(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_exit(plan);
+    }
     fn on_prepare(&mut self, plan: &mut Plan<C>) {
-        // get highest utility plan
-        let best = match max_utility(&plan.plans) {
-            Some((plan, _)) => plan.name().clone(),
+        self.behaviour.on_prepare(plan);
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        use serde_value::Value;
+        self.behaviour.on_run(plan);
+        // count only when status changes into success or failure
+        let status = self.behaviour.status(plan);
+        if status == self.settled {
+            return;
+        }
+        self.settled = status;
+        let (increment, reset) = match status {
+            Some(true) => (&self.success_key, &self.failure_key),
+            Some(false) => (&self.failure_key, &self.success_key),
             None => return,
         };
-        // get active plan
-        if let Some(active_plan) = plan.plans.iter().find(|plan| plan.active()) {
-            // current plan is already best
-            if *active_plan.name() == best {
-                return;
-            }
-            // exit active plan
-            let active = active_plan.name().clone();
-            plan.exit_plan(&active);
+        let count = match plan.data().get(increment) {
+            Some(Value::U64(count)) => *count,
+            _ => 0,
+        };
+        plan.set_data(increment.clone(), Value::U64(count + 1));
+        plan.set_data(reset.clone(), Value::U64(0));
+    }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_shutdown(plan);
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        self.behaviour.inspect()
+    }
+}
+
+/// Wraps inner behaviour and adds its utility after each run to a running reward in `data`.
+///
+/// The reward is stored as an `f64` value under `reward_key` and persists across activations,
+/// so that external code reads the return of an episode and removes the entry to start the next.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// use dynamic_plan_tree::serde_value::Value;
+///
+/// let inner = ModifyUtilityBehaviour::new(AllSuccessStatus.into(), 1., 0.5);
+/// let reward = RewardAccumulatorBehaviour::new(inner.into(), "return".into());
+/// let mut plan = Plan::<config::Default>::new(reward.into(), "root", 1, true);
+/// plan.run();
+/// plan.run();
+/// assert_eq!(plan.data().get("return"), Some(&Value::F64(1.)));
+/// assert_eq!(plan.utility(), 0.5);
+/// ```
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+pub struct RewardAccumulatorBehaviour<C: Config> {
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
+    pub behaviour: Box<C::Behaviour>,
+    /// Data key of the accumulated reward.
+    pub reward_key: String,
+}
+
+#[cfg(feature = "serde")]
+impl<C: Config> RewardAccumulatorBehaviour<C> {
+    pub fn new(behaviour: C::Behaviour, reward_key: String) -> Self {
+        Self {
+            behaviour: Box::new(behaviour),
+            reward_key,
         }
-        // enter new plan
-        plan.enter_plan(&best);
     }
-}
+}
+
+#[cfg(feature = "serde")]
+impl<C: Config> Behaviour<C> for RewardAccumulatorBehaviour<C> {
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        self.behaviour.status(plan)
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        self.behaviour.utility(plan)
+    }
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        self.behaviour.utility_breakdown(plan)
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        self.behaviour.estimate(plan)
+    }
+    fn on_entry(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_entry(plan);
+    }
+    fn on_exit(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_exit(plan);
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_prepare(plan);
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        use serde_value::Value;
+        self.behaviour.on_run(plan);
+        let reward = match plan.data().get(&self.reward_key) {
+            Some(Value::F64(reward)) => *reward,
+            _ => 0.,
+        };
+        let utility = self.behaviour.utility(plan);
+        plan.set_data(self.reward_key.clone(), Value::F64(reward + utility));
+    }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_shutdown(plan);
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        self.behaviour.inspect()
+    }
+}
+
+/// Wraps inner behaviour and reports its status as of the previous run, delaying it by one tick.
+///
+/// Lets subtrees observe each other's prior-tick status regardless of the order they run in.
+/// Status is `None` until the inner behaviour has run once since entry.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let delayed = DelayedStatusBehaviour::new(AllSuccessStatus.into());
+/// let mut plan = Plan::<config::Default>::new(delayed.into(), "root", 1, true);
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+/// assert_eq!(plan.status(), None);
+/// plan.run();
+/// assert_eq!(plan.status(), Some(true));
+/// plan.insert(Plan::new(AnySuccessStatus.into(), "a", 1, true));
+/// assert_eq!(plan.status(), Some(true));
+/// plan.run();
+/// assert_eq!(plan.status(), Some(false));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DelayedStatusBehaviour<C: Config> {
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
+    pub behaviour: Box<C::Behaviour>,
+    status: Option<bool>,
+}
+
+impl<C: Config> DelayedStatusBehaviour<C> {
+    pub fn new(behaviour: C::Behaviour) -> Self {
+        Self {
+            behaviour: Box::new(behaviour),
+            status: None,
+        }
+    }
+}
+
+impl<C: Config> Behaviour<C> for DelayedStatusBehaviour<C> {
+    fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+        self.status
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        self.behaviour.utility(plan)
+    }
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        self.behaviour.utility_breakdown(plan)
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        let estimate = self.behaviour.estimate(plan)?;
+        // the status is delayed from upon entry
+        Some(Hypothetical {
+            status: None,
+            ..estimate
+        })
+    }
+    fn on_entry(&mut self, plan: &mut Plan<C>) {
+        self.status = None;
+        self.behaviour.on_entry(plan);
+    }
+    fn on_exit(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_exit(plan);
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_prepare(plan);
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_run(plan);
+        self.status = self.behaviour.status(plan);
+    }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_shutdown(plan);
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        self.behaviour.inspect()
+    }
+}
+
+/// Wraps inner behaviour and postpones its entry until `delay` runs have passed since entry.
+///
+/// Status is `None` while waiting, and the inner behaviour receives no hooks other than `reseed()`.
+/// It is entered at the end of the last run of the delay, and runs from the next run on.
+/// The delay restarts on each entry.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let delay = DelayBehaviour::new(AllSuccessStatus.into(), 2);
+/// let mut plan = Plan::<config::Default>::new(delay.into(), "root", 1, true);
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+/// for _ in 0..2 {
+///     assert_eq!(plan.status(), None);
+///     plan.run();
+/// }
+/// assert_eq!(plan.status(), Some(true));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DelayBehaviour<C: Config> {
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
+    pub behaviour: Box<C::Behaviour>,
+    /// Number of runs to wait before entering the inner behaviour.
+    pub delay: u32,
+
+    count_down: u32,
+    entered: bool,
+}
+
+impl<C: Config> DelayBehaviour<C> {
+    pub fn new(behaviour: C::Behaviour, delay: u32) -> Self {
+        Self {
+            behaviour: Box::new(behaviour),
+            delay,
+            count_down: 0,
+            entered: false,
+        }
+    }
+
+    /// Number of runs left before the inner behaviour is entered.
+    pub fn count_down(&self) -> u32 {
+        self.count_down
+    }
+
+    /// Whether the delay has elapsed and the inner behaviour was entered.
+    pub fn entered(&self) -> bool {
+        self.entered
+    }
+}
+
+impl<C: Config> Behaviour<C> for DelayBehaviour<C> {
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        if !self.entered {
+            return None;
+        }
+        self.behaviour.status(plan)
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        self.behaviour.utility(plan)
+    }
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        self.behaviour.utility_breakdown(plan)
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        let estimate = self.behaviour.estimate(plan)?;
+        if self.delay > 0 {
+            return Some(Hypothetical {
+                status: None,
+                ..estimate
+            });
+        }
+        Some(estimate)
+    }
+    fn on_entry(&mut self, plan: &mut Plan<C>) {
+        self.count_down = self.delay;
+        self.entered = self.delay == 0;
+        if self.entered {
+            self.behaviour.on_entry(plan);
+        }
+    }
+    fn on_exit(&mut self, plan: &mut Plan<C>) {
+        if self.entered {
+            self.entered = false;
+            self.behaviour.on_exit(plan);
+        }
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        if self.entered {
+            self.behaviour.on_prepare(plan);
+        }
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        if self.entered {
+            self.behaviour.on_run(plan);
+            return;
+        }
+        self.count_down -= 1;
+        if self.count_down == 0 {
+            self.entered = true;
+            self.behaviour.on_entry(plan);
+        }
+    }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        if self.entered {
+            self.behaviour.on_shutdown(plan);
+        }
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        use serde_value::Value;
+        vec![
+            ("count_down", Value::U32(self.count_down)),
+            ("delay", Value::U32(self.delay)),
+            ("entered", Value::Bool(self.entered)),
+        ]
+    }
+}
+
+/// Wraps inner behaviour and fails it if still in progress after `ticks` runs since entry.
+///
+/// The deadline is checked once, on the last run of the budget. Status is `Some(false)` from then on
+/// only if the inner status was `None` at the deadline, otherwise the inner status is reported as is.
+/// The countdown restarts on each entry. A budget of zero ticks is checked upon entry instead,
+/// and grants the inner behaviour no runs, so neither `on_prepare()` nor `on_run()` are forwarded.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let timeout = TimeoutBehaviour::new(AllSuccessStatus.into(), 2);
+/// let mut plan = Plan::<config::Default>::new(timeout.into(), "root", 1, true);
+/// plan.insert(Plan::new_stub("a", true));
+/// plan.run();
+/// assert_eq!(plan.status(), None);
+/// plan.run();
+/// assert_eq!(plan.status(), Some(false));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimeoutBehaviour<C: Config> {
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
+    pub behaviour: Box<C::Behaviour>,
+    /// Number of runs the inner behaviour has to settle on a status.
+    pub ticks: u32,
+
+    count_down: Option<u32>,
+    timed_out: bool,
+}
+
+impl<C: Config> TimeoutBehaviour<C> {
+    pub fn new(behaviour: C::Behaviour, ticks: u32) -> Self {
+        Self {
+            behaviour: Box::new(behaviour),
+            ticks,
+            count_down: None,
+            timed_out: false,
+        }
+    }
+
+    /// Whether the deadline passed while the inner behaviour was still in progress.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+}
+
+impl<C: Config> Behaviour<C> for TimeoutBehaviour<C> {
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        if self.timed_out {
+            return Some(false);
+        }
+        self.behaviour.status(plan)
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        self.behaviour.utility(plan)
+    }
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        self.behaviour.utility_breakdown(plan)
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        let estimate = self.behaviour.estimate(plan)?;
+        if self.ticks == 0 && estimate.status.is_none() {
+            return Some(Hypothetical {
+                status: Some(false),
+                ..estimate
+            });
+        }
+        Some(estimate)
+    }
+    fn on_entry(&mut self, plan: &mut Plan<C>) {
+        self.count_down = (self.ticks > 0).then_some(self.ticks);
+        self.timed_out = false;
+        self.behaviour.on_entry(plan);
+        if self.ticks == 0 {
+            self.timed_out = self.behaviour.status(plan).is_none();
+        }
+    }
+    fn on_exit(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_exit(plan);
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        if self.ticks > 0 {
+            self.behaviour.on_prepare(plan);
+        }
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        if self.ticks == 0 {
+            return;
+        }
+        self.behaviour.on_run(plan);
+        // check the deadline once, leaving any settled status untouched
+        self.count_down = match self.count_down {
+            Some(count_down) if count_down > 1 => Some(count_down - 1),
+            Some(_) => {
+                self.timed_out = self.behaviour.status(plan).is_none();
+                None
+            }
+            None => None,
+        };
+    }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        self.behaviour.on_shutdown(plan);
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        use serde_value::Value;
+        vec![
+            ("count_down", Value::U32(self.count_down.unwrap_or(0))),
+            ("ticks", Value::U32(self.ticks)),
+            ("timed_out", Value::Bool(self.timed_out)),
+        ]
+    }
+}
+
+/// Wraps inner behaviour and only enters it if `condition` holds on entry.
+///
+/// Otherwise status is `Some(false)` and the inner behaviour receives no hooks other than `reseed()`
+/// until the next entry. Unlike the `condition` of [RepeatBehaviour],
+/// the condition is only checked once per entry.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// use dynamic_plan_tree::serde_value::Value;
+///
+/// let ready = DataValue { key: "ready".into() };
+/// let precondition = PreconditionBehaviour::new(ready.into(), AllSuccessStatus.into());
+/// let mut plan = Plan::<config::Default>::new(precondition.into(), "root", 1, false);
+/// plan.insert(Plan::new_stub("a", true));
+/// plan.enter(None);
+/// assert_eq!(plan.status(), Some(false));
+/// // checked again on the next entry
+/// plan.exit(false);
+/// plan.set_data("ready", Value::Bool(true));
+/// plan.enter(None);
+/// assert_eq!(plan.status(), None);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PreconditionBehaviour<C: Config> {
+    /// Predicate that has to hold on entry for the inner behaviour to be entered.
+    pub condition: C::Predicate,
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
+    pub behaviour: Box<C::Behaviour>,
+
+    failed: bool,
+}
+
+impl<C: Config> PreconditionBehaviour<C> {
+    pub fn new(condition: C::Predicate, behaviour: C::Behaviour) -> Self {
+        Self {
+            condition,
+            behaviour: Box::new(behaviour),
+            failed: false,
+        }
+    }
+
+    /// Whether the condition did not hold on the latest entry.
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+}
+
+impl<C: Config> Behaviour<C> for PreconditionBehaviour<C> {
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        if self.failed {
+            return Some(false);
+        }
+        self.behaviour.status(plan)
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        self.behaviour.utility(plan)
+    }
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        self.behaviour.utility_breakdown(plan)
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        let estimate = self.behaviour.estimate(plan)?;
+        if !self.condition.evaluate(plan, &[]) {
+            return Some(Hypothetical {
+                status: Some(false),
+                ..estimate
+            });
+        }
+        Some(estimate)
+    }
+    fn on_entry(&mut self, plan: &mut Plan<C>) {
+        self.failed = !self.condition.evaluate(plan, &[]);
+        if !self.failed {
+            self.behaviour.on_entry(plan);
+        }
+    }
+    fn on_exit(&mut self, plan: &mut Plan<C>) {
+        if !self.failed {
+            self.behaviour.on_exit(plan);
+        }
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        if !self.failed {
+            self.behaviour.on_prepare(plan);
+        }
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        if !self.failed {
+            self.behaviour.on_run(plan);
+        }
+    }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        if !self.failed {
+            self.behaviour.on_shutdown(plan);
+        }
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        vec![("failed", serde_value::Value::Bool(self.failed))]
+    }
+}
+
+/// Wraps inner behaviour and only enters it once a slot of a counter shared across the tree is acquired,
+/// limiting how many plans may run it at once, such as the number of agents attacking simultaneously.
+///
+/// # Acquire and release
+/// A slot of the counter `counter_key` is requested upon entry, then on every run until one is free,
+/// see [Plan::acquire_slot]. Status is `None` while waiting, and the inner behaviour receives no hooks
+/// other than `reseed()` until the slot is acquired, whereupon it is entered. The slot is released upon exit,
+/// after the inner behaviour exits, or when the plan is abandoned after a panic contained by [Plan::isolate].
+/// Plans sharing a counter must agree on its `limit`, and belong to the same tree.
+///
+/// The counter is kept in the data of the root under `counter_key` as the number of slots taken,
+/// written at the end of each tick it changes in. Like other data, it can be read by predicates
+/// and is recorded by the journal. Slots held are saved along with the tree, so loaded plans keep them.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let mut root = Plan::<config::Default>::new_stub("root", true);
+/// for name in ["a", "b", "c"] {
+///     let semaphore = SemaphoreBehaviour::new("attack", 2, AllSuccessStatus.into());
+///     root.insert(Plan::new(semaphore.into(), name, 1, true));
+/// }
+/// root.run();
+/// assert_eq!(root.data()["attack"], serde_value::Value::U64(2));
+/// // the plan of lowest priority waits for a slot
+/// assert_eq!(root.get("c").unwrap().status(), None);
+/// root.exit_plan("a");
+/// root.run();
+/// assert_eq!(root.get("c").unwrap().status(), Some(true));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SemaphoreBehaviour<C: Config> {
+    /// Key of the counter shared by plans of the tree.
+    pub counter_key: String,
+    /// Maximum number of slots of the counter taken at once.
+    pub limit: usize,
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
+    pub behaviour: Box<C::Behaviour>,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    acquired: bool,
+}
+
+impl<C: Config> SemaphoreBehaviour<C> {
+    pub fn new(counter_key: impl Into<String>, limit: usize, behaviour: C::Behaviour) -> Self {
+        Self {
+            counter_key: counter_key.into(),
+            limit,
+            behaviour: Box::new(behaviour),
+            acquired: false,
+        }
+    }
+
+    /// Whether a slot is held and the inner behaviour was entered.
+    pub fn acquired(&self) -> bool {
+        self.acquired
+    }
+
+    fn try_acquire(&mut self, plan: &mut Plan<C>) {
+        self.acquired = plan.acquire_slot(&self.counter_key, self.limit);
+        if self.acquired {
+            self.behaviour.on_entry(plan);
+        }
+    }
+}
+
+impl<C: Config> Behaviour<C> for SemaphoreBehaviour<C> {
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        if !self.acquired {
+            return None;
+        }
+        self.behaviour.status(plan)
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        self.behaviour.utility(plan)
+    }
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        self.behaviour.utility_breakdown(plan)
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        let estimate = self.behaviour.estimate(plan)?;
+        if !self.acquired && plan.slots_taken(&self.counter_key) >= self.limit {
+            return Some(Hypothetical {
+                status: None,
+                ..estimate
+            });
+        }
+        Some(estimate)
+    }
+    fn on_entry(&mut self, plan: &mut Plan<C>) {
+        self.try_acquire(plan);
+    }
+    fn on_exit(&mut self, plan: &mut Plan<C>) {
+        if self.acquired {
+            self.behaviour.on_exit(plan);
+            self.acquired = false;
+            plan.release_slot(&self.counter_key);
+        }
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        if self.acquired {
+            self.behaviour.on_prepare(plan);
+        }
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        if self.acquired {
+            self.behaviour.on_run(plan);
+        } else {
+            self.try_acquire(plan);
+        }
+    }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        if self.acquired {
+            self.behaviour.on_shutdown(plan);
+        }
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        vec![
+            ("acquired", serde_value::Value::Bool(self.acquired)),
+            ("limit", serde_value::Value::U64(self.limit as u64)),
+        ]
+    }
+}
+
+/// Wraps inner behaviour and keeps it from being entered during the `cooldown` ticks following the one
+/// where it exits with a definite status, such as an action that cannot be repeated right after completing.
+///
+/// # Cooldown
+/// The cooldown is measured in ticks of the tree, see [Plan::tick], so it also elapses while the plan is inactive.
+/// Status is `Some(false)` during the cooldown, whether or not the plan is active.
+/// If the plan is entered during the cooldown, the inner behaviour receives no hooks other than `reseed()`
+/// until the first run after the cooldown ends, where it is entered before `on_prepare()`.
+///
+/// The ticks left in the cooldown are serialized, as counted by the last check of the cooldown.
+/// Plans deserialized from a saved tree, or rolled back by `Plan::run_transactional`,
+/// resume cooling down from the tick where they are next checked.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let mut root = Plan::<config::Default>::new_stub("root", true);
+/// let cooldown = CooldownBehaviour::new(AllSuccessStatus.into(), 2);
+/// root.insert(Plan::new(cooldown.into(), "attack", 1, true));
+/// root.run();
+/// // exiting on tick 1 blocks ticks 2 and 3
+/// root.exit_plan("attack");
+/// while root.tick() < 3 {
+///     root.run();
+///     assert_eq!(root.get("attack").unwrap().status(), Some(false));
+/// }
+/// root.run();
+/// assert_eq!(root.get("attack").unwrap().status(), Some(true));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CooldownBehaviour<C: Config> {
+    #[cfg_attr(feature = "serde", serde(with = "TaggedBehaviour::<C>"))]
+    pub behaviour: Box<C::Behaviour>,
+    /// Number of ticks after exiting with a definite status during which the inner behaviour is not entered.
+    pub cooldown: u32,
+
+    /// Ticks left in the cooldown as of `counted_tick`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    remaining_ticks: AtomicU64,
+    /// Tick of the tree plus one as of which `remaining_ticks` was counted, or 0 if not counted since deserialization.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    counted_tick: AtomicU64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    entered: bool,
+}
+
+impl<C: Config> CooldownBehaviour<C> {
+    pub fn new(behaviour: C::Behaviour, cooldown: u32) -> Self {
+        Self {
+            behaviour: Box::new(behaviour),
+            cooldown,
+            remaining_ticks: AtomicU64::new(0),
+            counted_tick: AtomicU64::new(0),
+            entered: false,
+        }
+    }
+
+    /// Number of ticks left in the cooldown as of the current tick of the plan.
+    pub fn remaining(&self, plan: &Plan<C>) -> u64 {
+        let tick = plan.tick();
+        // count down the ticks elapsed since the last count, if any
+        if let Some(counted) = self.counted_tick.swap(tick + 1, Relaxed).checked_sub(1) {
+            let elapsed = tick.saturating_sub(counted);
+            let remaining = self.remaining_ticks.load(Relaxed).saturating_sub(elapsed);
+            self.remaining_ticks.store(remaining, Relaxed);
+        }
+        self.remaining_ticks.load(Relaxed)
+    }
+
+    /// Whether the inner behaviour was entered during the current entry.
+    pub fn entered(&self) -> bool {
+        self.entered
+    }
+
+    fn try_enter(&mut self, plan: &mut Plan<C>) {
+        if !self.entered && self.remaining(plan) == 0 {
+            self.entered = true;
+            self.behaviour.on_entry(plan);
+        }
+    }
+}
+
+impl<C: Config> Behaviour<C> for CooldownBehaviour<C> {
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        if self.remaining(plan) > 0 || (plan.active() && !self.entered) {
+            return Some(false);
+        }
+        self.behaviour.status(plan)
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        self.behaviour.utility(plan)
+    }
+    fn utility_breakdown(&self, plan: &Plan<C>) -> Vec<(String, f64)> {
+        self.behaviour.utility_breakdown(plan)
+    }
+    fn estimate(&self, plan: &Plan<C>) -> Option<Hypothetical> {
+        let estimate = self.behaviour.estimate(plan)?;
+        if self.remaining(plan) > 0 {
+            return Some(Hypothetical {
+                status: Some(false),
+                ..estimate
+            });
+        }
+        Some(estimate)
+    }
+    fn on_entry(&mut self, plan: &mut Plan<C>) {
+        self.try_enter(plan);
+    }
+    fn on_exit(&mut self, plan: &mut Plan<C>) {
+        if self.entered {
+            self.entered = false;
+            if self.behaviour.status(plan).is_some() {
+                // cooling down for the ticks following this one
+                self.remaining_ticks
+                    .store(self.cooldown as u64 + 1, Relaxed);
+                self.counted_tick.store(plan.tick() + 1, Relaxed);
+            }
+            self.behaviour.on_exit(plan);
+        }
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        self.try_enter(plan);
+        if self.entered {
+            self.behaviour.on_prepare(plan);
+        }
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        if self.entered {
+            self.behaviour.on_run(plan);
+        }
+    }
+    fn on_shutdown(&mut self, plan: &mut Plan<C>) {
+        if self.entered {
+            self.behaviour.on_shutdown(plan);
+        }
+    }
+    fn reseed(&mut self, seed: u64) {
+        self.behaviour.reseed(seed);
+    }
+    fn default_subplans(&self) -> Vec<Plan<C>> {
+        self.behaviour.default_subplans()
+    }
+    fn exclusive_subplans(&self) -> bool {
+        self.behaviour.exclusive_subplans()
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        use serde_value::Value;
+        vec![
+            (
+                "remaining_ticks",
+                Value::U64(self.remaining_ticks.load(Relaxed)),
+            ),
+            ("entered", Value::Bool(self.entered)),
+        ]
+    }
+}
+
+/// Behaviour that sequentially transitions through child plans until first failure.
+///
+/// # Transitions
+/// Plan is expected to contain transitions that form a linear sequence of success predicates,
+/// with only one child plan active at a time. Behaviour is undefined otherwise.
+///
+/// If the status of any previously visited child plan changes from success,
+/// the sequence will transition back to that point.
+/// Visited children are inactive, so their status is re-evaluated against subplans they have exited.
+/// Set [Plan::report_cached_when_inactive] on children whose status is only meaningful while active.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// # use dynamic_plan_tree::config::fixtures::active_names as active;
+///
+/// let sequence = SequenceBehaviour::<config::Default>::new();
+/// let transitions = sequence.generate_transitions(&["a".into(), "b".into(), "c".into()]);
+/// let mut plan = Plan::<config::Default>::new(sequence.into(), "root", 1, true);
+/// plan.transitions = transitions;
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "b", 1, false));
+/// plan.insert(Plan::new_stub("c", false));
+/// for expected in ["b", "c"] {
+///     plan.run();
+///     assert_eq!(active(&plan), [expected]);
+///     assert_eq!(plan.status(), None);
+/// }
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "c", 1, true));
+/// assert_eq!(plan.status(), Some(true));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SequenceBehaviour<C: Config>(Vec<C::Name>);
+impl<C: Config> Default for SequenceBehaviour<C> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+impl<C: Config> SequenceBehaviour<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generate the linear chain of success transitions expected between the ordered child plans.
+    pub fn generate_transitions(
+        &self,
+        children: &[C::Name],
+    ) -> Vec<Transition<C::Predicate, C::Name>>
+    where
+        C::Predicate: From<predicate::AllSuccess>,
+    {
+        chain_transitions(children, || predicate::AllSuccess.into())
+    }
+}
+impl<C: Config> Behaviour<C> for SequenceBehaviour<C> {
+    /// - Success when all child plans succeed.
+    /// - Failure when any child plan fails.
+    /// - None while otherwise in-progress.
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        AllSuccessStatus.status(plan)
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        check_visited_status_and_jump(plan, &mut self.0, false);
+    }
+    fn exclusive_subplans(&self) -> bool {
+        true
+    }
+}
+
+/// Behaviour that sequentially transitions through child plans until first success.
+///
+/// # Transitions
+/// Plan is expected to contain transitions that form a linear sequence of failure predicates,
+/// with only one child plan active at a time. Behaviour is undefined otherwise.
+///
+/// If the status of any previously visited child plan changes from failure,
+/// the sequence will transition back to that point.
+/// See [SequenceBehaviour] regarding the status of visited children.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// # use dynamic_plan_tree::config::fixtures::active_names as active;
+///
+/// let fallback = FallbackBehaviour::<config::Default>::new();
+/// let transitions = fallback.generate_transitions(&["a".into(), "b".into(), "c".into()]);
+/// let mut plan = Plan::<config::Default>::new(fallback.into(), "root", 1, true);
+/// plan.transitions = transitions;
+/// plan.insert(Plan::new(AnySuccessStatus.into(), "a", 1, true));
+/// plan.insert(Plan::new(AnySuccessStatus.into(), "b", 1, false));
+/// plan.insert(Plan::new_stub("c", false));
+/// for expected in ["b", "c"] {
+///     plan.run();
+///     assert_eq!(active(&plan), [expected]);
+///     assert_eq!(plan.status(), None);
+/// }
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "c", 1, true));
+/// assert_eq!(plan.status(), Some(true));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FallbackBehaviour<C: Config>(Vec<C::Name>);
+impl<C: Config> Default for FallbackBehaviour<C> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+impl<C: Config> FallbackBehaviour<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generate the linear chain of failure transitions expected between the ordered child plans.
+    pub fn generate_transitions(
+        &self,
+        children: &[C::Name],
+    ) -> Vec<Transition<C::Predicate, C::Name>>
+    where
+        C::Predicate: From<predicate::AllFailure>,
+    {
+        chain_transitions(children, || predicate::AllFailure.into())
+    }
+}
+impl<C: Config> Behaviour<C> for FallbackBehaviour<C> {
+    /// - Success when any child plans succeeds.
+    /// - Failure when all child plan fail.
+    /// - None while otherwise in-progress.
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        AnySuccessStatus.status(plan)
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        check_visited_status_and_jump(plan, &mut self.0, true);
+    }
+    fn exclusive_subplans(&self) -> bool {
+        true
+    }
+}
+
+/// Behaviour that escalates through child plans in `order`, moving on once a child fails `attempts_per_level` times.
+///
+/// The child of the current level is re-entered after each failure until it runs out of attempts,
+/// then the child of the next level is entered, never returning to earlier levels until the plan is entered again.
+/// Unlike [FallbackBehaviour], which advances upon a single failure, each level is retried.
+/// Children missing from the plan are skipped.
+/// The plan should have no transitions of its own, as the behaviour enters and exits its children.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// # use dynamic_plan_tree::config::fixtures::active_names as active;
+///
+/// let escalate = EscalateBehaviour::new(2, vec!["ask".into(), "insist".into()]);
+/// let mut plan = Plan::<config::Default>::new(escalate.into(), "root", 1, true);
+/// plan.insert(Plan::new(AnySuccessStatus.into(), "ask", 1, false));
+/// plan.insert(Plan::new_stub("insist", false));
+/// for expected in ["ask", "ask", "insist", "insist"] {
+///     plan.run();
+///     assert_eq!(active(&plan), [expected]);
+/// }
+/// assert_eq!(plan.status(), None);
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "insist", 1, true));
+/// assert_eq!(plan.status(), Some(true));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EscalateBehaviour<C: Config> {
+    /// Number of failures of each child before escalating to the next, at least 1.
+    pub attempts_per_level: u32,
+    /// Names of child plans in order of escalation.
+    pub order: Vec<C::Name>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    level: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    failures: u32,
+}
+
+impl<C: Config> EscalateBehaviour<C> {
+    pub fn new(attempts_per_level: u32, order: Vec<C::Name>) -> Self {
+        Self {
+            attempts_per_level,
+            order,
+            level: 0,
+            failures: 0,
+        }
+    }
+
+    /// Index of the current level, equal to the number of levels once all have failed.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Number of failures of the current level.
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+}
+
+impl<C: Config> Behaviour<C> for EscalateBehaviour<C> {
+    /// - Status of the child of the current level while active.
+    /// - Failure once all levels have failed, including immediately for an empty order.
+    /// - None while otherwise in-progress.
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        match self.order.get(self.level) {
+            Some(name) => plan.get(name).filter(|x| x.active())?.status(),
+            None => Some(false),
+        }
+    }
+    fn on_entry(&mut self, _plan: &mut Plan<C>) {
+        self.level = 0;
+        self.failures = 0;
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        while let Some(name) = self.order.get(self.level) {
+            match plan.get(name) {
+                Some(child) if !child.active() => {
+                    plan.enter_plan(name);
+                    return;
+                }
+                Some(child) if child.status() != Some(false) => return,
+                // retry upon failure until the level runs out of attempts
+                Some(_) => {
+                    plan.exit_plan(name);
+                    self.failures += 1;
+                    if self.failures < self.attempts_per_level.max(1) {
+                        plan.enter_plan(name);
+                        return;
+                    }
+                }
+                None => {}
+            }
+            self.level += 1;
+            self.failures = 0;
+        }
+    }
+    #[cfg(feature = "serde")]
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        use serde_value::Value;
+        vec![
+            ("level", Value::U64(self.level as u64)),
+            ("failures", Value::U32(self.failures)),
+        ]
+    }
+    fn exclusive_subplans(&self) -> bool {
+        true
+    }
+}
+
+/// Behaviour that cycles through child plans in priority order, advancing whenever the active child settles.
+///
+/// Holds the index of the current child, wrapping around after the last child.
+/// Only the current child is kept active, so the plan should have no transitions of its own.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// # use dynamic_plan_tree::config::fixtures::active_names as active;
+///
+/// let mut plan = Plan::<config::Default>::new(RoundRobinBehaviour(0).into(), "root", 1, true);
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, false));
+/// plan.insert(Plan::new(AnySuccessStatus.into(), "b", 1, false));
+/// for expected in ["a", "b", "a"] {
+///     plan.run();
+///     assert_eq!(active(&plan), [expected]);
+/// }
+/// assert_eq!(plan.status(), Some(true));
+/// ```
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RoundRobinBehaviour(pub usize);
+impl<C: Config> Behaviour<C> for RoundRobinBehaviour {
+    /// Returns status of currently active child plan.
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        plan.plans.iter().find(|p| p.active())?.status()
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        if plan.plans.is_empty() {
+            return;
+        }
+        // advance past the active child once it settles
+        if let Some(pos) = plan.plans.iter().position(|p| p.active()) {
+            self.0 = pos;
+            if plan.plans[pos].status().is_none() {
+                return;
+            }
+            let active = plan.plans[pos].name().clone();
+            plan.exit_plan(&active);
+            self.0 += 1;
+        }
+        self.0 %= plan.plans.len();
+        let next = plan.plans[self.0].name().clone();
+        plan.enter_plan(&next);
+    }
+    fn exclusive_subplans(&self) -> bool {
+        true
+    }
+}
+
+/// Behaviour that activates child plans one at a time for a fixed number of runs each, regardless of their status.
+///
+/// Each step keeps its child active for `duration` runs of this plan before exiting it and entering the next.
+/// Steps with a duration of 0 are skipped. Plan is expected to contain no transitions. Behaviour is undefined otherwise.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// # use dynamic_plan_tree::config::fixtures::active_names as active;
+///
+/// let timeline = TimelineBehaviour::new(vec![(2, "a".into()), (1, "b".into())]);
+/// let mut plan = Plan::<config::Default>::new(timeline.into(), "root", 1, true);
+/// plan.insert(Plan::new_stub("a", false));
+/// plan.insert(Plan::new_stub("b", false));
+/// for expected in ["a", "a", "b"] {
+///     plan.run();
+///     assert_eq!(active(&plan), [expected]);
+///     assert_eq!(plan.status(), None);
+/// }
+/// plan.run();
+/// assert!(active(&plan).is_empty());
+/// assert_eq!(plan.status(), Some(true));
+/// ```
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+pub struct TimelineBehaviour<C: Config> {
+    /// Pairs of duration in runs and child plan name, in order of activation.
+    pub steps: Vec<(u32, C::Name)>,
+    step: usize,
+    elapsed: u32,
+}
+
+#[cfg(feature = "serde")]
+impl<C: Config> TimelineBehaviour<C> {
+    pub fn new(steps: Vec<(u32, C::Name)>) -> Self {
+        Self {
+            steps,
+            step: 0,
+            elapsed: 0,
+        }
+    }
+
+    /// Index of the current step, equal to the number of steps once complete.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<C: Config> Behaviour<C> for TimelineBehaviour<C> {
+    /// - Success once all steps have elapsed, including immediately for an empty timeline.
+    /// - None while otherwise in-progress.
+    fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+        match self.step < self.steps.len() {
+            true => None,
+            false => Some(true),
+        }
+    }
+    fn on_entry(&mut self, _plan: &mut Plan<C>) {
+        self.step = 0;
+        self.elapsed = 0;
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        while let Some((duration, name)) = self.steps.get(self.step) {
+            if self.elapsed < *duration {
+                if !plan.get(name).is_some_and(|x| x.active()) {
+                    plan.enter_plan(name);
+                }
+                self.elapsed += 1;
+                return;
+            }
+            plan.exit_plan(name);
+            self.step += 1;
+            self.elapsed = 0;
+        }
+    }
+    fn inspect(&self) -> Vec<(&'static str, serde_value::Value)> {
+        use serde_value::Value;
+        vec![
+            ("step", Value::U64(self.step as u64)),
+            ("elapsed", Value::U32(self.elapsed)),
+        ]
+    }
+    fn exclusive_subplans(&self) -> bool {
+        true
+    }
+}
+
+fn chain_transitions<P, N: Clone>(
+    children: &[N],
+    predicate: impl Fn() -> P,
+) -> Vec<Transition<P, N>> {
+    children
+        .windows(2)
+        .map(|pair| Transition::new(vec![pair[0].clone()], vec![pair[1].clone()], predicate()))
+        .collect()
+}
+
+fn check_visited_status_and_jump<C: Config>(
+    plan: &mut Plan<C>,
+    visited: &mut Vec<C::Name>,
+    jump_val: bool,
+) {
+    // find first inactive visited plans that has status none
+    let pos = visited.iter().position(|x| match plan.get(x) {
+        Some(x) => !x.active() && x.status().map(|x| x == jump_val).unwrap_or(true),
+        None => false,
+    });
+    // jump back to that plan
+    if let Some(pos) = pos {
+        plan.exit(true);
+        plan.enter_plan(&visited[pos]);
+        visited.truncate(pos);
+    }
+    // find currently active plan
+    let active = match plan.plans.iter().find(|x| x.active()) {
+        Some(x) => x.name(),
+        None => return,
+    };
+    // add active plan to visited if not already
+    if let Some(last) = visited.last() {
+        if last == active {
+            return;
+        }
+    }
+    visited.push(active.clone());
+}
+
+/// Behaviour that monitors and transitions to the child plan with highest utility.
+///
+/// Plan is expected to contain no transitions, with only one child active at a time. Behaviour is undefined otherwise.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// # use dynamic_plan_tree::config::fixtures::{active_names as active, scored};
+///
+/// let mut plan = Plan::<config::Default>::new(MaxUtilBehaviour::default().into(), "root", 1, true);
+/// plan.insert(scored("a", false, 1.));
+/// plan.insert(scored("b", false, 2.));
+/// plan.run();
+/// assert_eq!(active(&plan), ["b"]);
+/// assert_eq!(plan.utility(), 2.);
+/// plan.insert(scored("a", false, 3.));
+/// plan.run();
+/// assert_eq!(active(&plan), ["a"]);
+/// // nothing is worth doing below the floor
+/// plan.cast_mut::<MaxUtilBehaviour>().unwrap().min_utility = Some(5.);
+/// plan.run();
+/// assert!(active(&plan).is_empty());
+/// ```
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct MaxUtilBehaviour {
+    /// Select no child while the highest utility is below this floor.
+    pub min_utility: Option<f64>,
+    /// Compare inactive child plans by their [Plan::hypothetical] utility instead of raw utility.
+    pub use_estimates: bool,
+}
+
+/// Also accepts the unit struct that [MaxUtilBehaviour] was serialized as before it had fields.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for MaxUtilBehaviour {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "MaxUtilBehaviour")]
+        struct Fields {
+            #[serde(default)]
+            min_utility: Option<f64>,
+            #[serde(default)]
+            use_estimates: bool,
+        }
+        // the unit form is only told apart from the fields by self-describing formats
+        let fields = match deserializer.is_human_readable() {
+            true => Option::<Fields>::deserialize(deserializer)?,
+            false => Some(Fields::deserialize(deserializer)?),
+        };
+        Ok(fields.map_or_else(Self::default, |x| Self {
+            min_utility: x.min_utility,
+            use_estimates: x.use_estimates,
+        }))
+    }
+}
+impl<C: Config> Behaviour<C> for MaxUtilBehaviour {
+    /// Returns status of currently active child plan.
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        plan.plans.iter().find(|p| p.active())?.status()
+    }
+    /// Returns max utility of all child plans.
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        match max_utility_by(&plan.plans, self.use_estimates) {
+            Some((_, util)) => util,
+            None => 0.,
+        }
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        // get highest utility plan
+        let (best, utility) = match max_utility_by(&plan.plans, self.use_estimates) {
+            Some((plan, utility)) => (plan.name().clone(), utility),
+            None => return,
+        };
+        // exit all plans when nothing is worth doing
+        if let Some(floor) = self.min_utility {
+            if utility < floor {
+                let active = plan.plans.iter().filter(|plan| plan.active());
+                let active = active.map(|plan| plan.name().clone()).collect::<Vec<_>>();
+                active.iter().for_each(|name| {
+                    plan.exit_plan(name);
+                });
+                return;
+            }
+        }
+        // get active plan
+        if let Some(active_plan) = plan.plans.iter().find(|plan| plan.active()) {
+            // current plan is already best
+            if *active_plan.name() == best {
+                return;
+            }
+            // exit active plan
+            let active = active_plan.name().clone();
+            plan.exit_plan(&active);
+        }
+        // enter new plan
+        if tracing::enabled!(tracing::Level::DEBUG) {
+            let candidates = plan.plans.iter().map(|p| {
+                let mut parts = p.utility_breakdown();
+                parts.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+                parts.truncate(3);
+                (p.name(), p.utility(), parts)
+            });
+            let candidates = candidates.collect::<Vec<_>>();
+            debug!(plan = ?plan.name(), selected = ?best, ?candidates, "max utility selection");
+        }
+        plan.enter_plan(&best);
+    }
+    fn exclusive_subplans(&self) -> bool {
+        true
+    }
+}
+
+/// Normalization of child utilities by the statistics of their recent samples. See [NormalizedUtilBehaviour].
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Normalization {
+    /// Scaled between the minimum and maximum sample to `[0, 1]`.
+    #[default]
+    MinMax,
+    /// Number of standard deviations from the mean sample.
+    ZScore,
+}
+
+impl Normalization {
+    /// Normalize the latest of the samples, or 0 if they are empty or all equal.
+    pub fn normalize(self, samples: &VecDeque<f64>) -> f64 {
+        let Some(&latest) = samples.back() else {
+            return 0.;
+        };
+        let score = match self {
+            Normalization::MinMax => {
+                let min = samples.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = samples.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                (latest - min) / (max - min)
+            }
+            Normalization::ZScore => {
+                let n = samples.len() as f64;
+                let mean = samples.iter().sum::<f64>() / n;
+                let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+                (latest - mean) / variance.sqrt()
+            }
+        };
+        if score.is_finite() {
+            score
+        } else {
+            0.
+        }
+    }
+}
+
+/// Behaviour that transitions to the child plan with highest utility, normalized by its own recent utilities.
+///
+/// Each run samples the utility of every child over a sliding `window` of runs,
+/// so that children reporting on different scales are compared by how good their current utility is for them.
+/// Raw utilities are compared until every child has `warmup` samples.
+/// Selects among children as [MaxUtilBehaviour] does, with the same expectations of the plan.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// # use dynamic_plan_tree::config::fixtures::{active_names as active, scored};
+///
+/// let normalized = NormalizedUtilBehaviour::new(Normalization::MinMax, 10, 2);
+/// let mut plan = Plan::<config::Default>::new(normalized.into(), "root", 1, true);
+/// // a reports utility on a scale of 1, b on a scale of 1000
+/// plan.insert(scored("a", false, 0.));
+/// plan.insert(scored("b", false, 1000.));
+/// plan.run();
+/// assert_eq!(active(&plan), ["b"]);
+/// // a is at the top of its range while b is at the bottom of its own
+/// plan.insert(scored("a", false, 1.));
+/// plan.insert(scored("b", false, 500.));
+/// plan.run();
+/// assert_eq!(active(&plan), ["a"]);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NormalizedUtilBehaviour<C: Config> {
+    pub normalization: Normalization,
+    /// Number of most recent runs sampled per child.
+    pub window: usize,
+    /// Number of samples required of every child before comparing normalized utilities.
+    pub warmup: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    samples: BTreeMap<C::Name, VecDeque<f64>>,
+}
+impl<C: Config> NormalizedUtilBehaviour<C> {
+    pub fn new(normalization: Normalization, window: usize, warmup: usize) -> Self {
+        Self {
+            normalization,
+            window,
+            warmup,
+            samples: BTreeMap::new(),
+        }
+    }
+
+    /// Recent utilities of a child plan, oldest first.
+    pub fn samples(&self, name: &C::Name) -> Option<&VecDeque<f64>> {
+        self.samples.get(name)
+    }
+
+    /// Scores of the child plans compared for selection, normalized once all are warmed up.
+    pub fn scores(&self, plan: &Plan<C>) -> Vec<f64> {
+        let warmup = self.warmup.clamp(1, self.window.max(1));
+        let samples = plan.plans.iter().map(|x| self.samples.get(x.name()));
+        let samples = samples.collect::<Option<Vec<_>>>();
+        match samples {
+            Some(samples) if samples.iter().all(|x| x.len() >= warmup) => samples
+                .into_iter()
+                .map(|x| self.normalization.normalize(x))
+                .collect(),
+            _ => plan.plans.iter().map(|x| x.utility()).collect(),
+        }
+    }
+}
+impl<C: Config> Behaviour<C> for NormalizedUtilBehaviour<C> {
+    /// Returns status of currently active child plan.
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        plan.plans.iter().find(|p| p.active())?.status()
+    }
+    /// Returns max utility of all child plans.
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        match max_utility(&plan.plans) {
+            Some((_, util)) => util,
+            None => 0.,
+        }
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        // sample utilities of the current child plans
+        let window = self.window.max(1);
+        self.samples.retain(|name, _| plan.get(name).is_some());
+        for child in &plan.plans {
+            let samples = self.samples.entry(child.name().clone()).or_default();
+            samples.push_back(child.utility());
+            if samples.len() > window {
+                samples.pop_front();
+            }
+        }
+        // get highest scoring plan
+        let scores = self.scores(plan);
+        let best = scores
+            .into_iter()
+            .enumerate()
+            .fold((0, f64::NAN), |max, x| if max.1 > x.1 { max } else { x });
+        let best = match plan.plans.get(best.0) {
+            Some(best) => best.name().clone(),
+            None => return,
+        };
+        // switch active plan
+        if let Some(active_plan) = plan.plans.iter().find(|plan| plan.active()) {
+            if *active_plan.name() == best {
+                return;
+            }
+            let active = active_plan.name().clone();
+            plan.exit_plan(&active);
+        }
+        plan.enter_plan(&best);
+    }
+    fn exclusive_subplans(&self) -> bool {
+        true
+    }
+}
+
+/// Behaviour with utility scored by the [ScoreContext] of the tree by name of the plan, for selection by an external scorer.
+///
+/// Utility is `default` while the tree has no context or the context has no score for the plan. Status is always `None`.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// use std::sync::Arc;
+/// # use dynamic_plan_tree::config::fixtures::active_names as active;
+///
+/// struct Preferred(&'static str);
+/// impl ScoreContext for Preferred {
+///     fn score(&self, name: &String) -> Option<f64> {
+///         Some((name == self.0) as u8 as f64)
+///     }
+/// }
+///
+/// let mut plan = Plan::<config::Default>::new(MaxUtilBehaviour::default().into(), "root", 1, true);
+/// for name in ["a", "b"] {
+///     plan.insert(Plan::new(ContextUtilBehaviour::default().into(), name, 1, false));
+/// }
+/// plan.set_context(Some(Arc::new(Preferred("b"))));
+/// plan.run();
+/// assert_eq!(active(&plan), ["b"]);
+/// plan.set_context(Some(Arc::new(Preferred("a"))));
+/// plan.run();
+/// assert_eq!(active(&plan), ["a"]);
+/// ```
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ContextUtilBehaviour {
+    /// Utility in the absence of a score from the context.
+    pub default: f64,
+}
+impl<C: Config> Behaviour<C> for ContextUtilBehaviour {
+    fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+        None
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        let score = plan.context().and_then(|x| x.score(plan.name()));
+        score.unwrap_or(self.default)
+    }
+}
+
+/// Rule for choosing which child plan to activate. See [PacedSelectorBehaviour].
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SelectMode {
+    /// Child plan with highest utility.
+    #[default]
+    Util,
+    /// Child plan with highest priority that is not failing.
+    Priority,
+}
+
+/// Behaviour that selects a child plan to activate, then waits `cooldown` runs before activating any other.
+///
+/// Re-selecting the active child does not restart the cooldown.
+/// As with [MaxUtilBehaviour], the plan should have no transitions and a single active child.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// # use dynamic_plan_tree::config::fixtures::{active_names as active, scored};
+///
+/// let paced = PacedSelectorBehaviour::new(2, SelectMode::Util);
+/// let mut plan = Plan::<config::Default>::new(paced.into(), "root", 1, true);
+/// plan.insert(scored("a", false, 1.));
+/// plan.insert(scored("b", false, 2.));
+/// plan.run();
+/// assert_eq!(active(&plan), ["b"]);
+/// // switching waits out the cooldown
+/// plan.insert(scored("a", false, 3.));
+/// for expected in ["b", "b", "a"] {
+///     plan.run();
+///     assert_eq!(active(&plan), [expected]);
+/// }
+/// ```
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PacedSelectorBehaviour {
+    /// Number of runs after each activation during which no child is activated.
+    pub cooldown: u32,
+    pub select: SelectMode,
+    /// Judge inactive child plans by their [Plan::hypothetical] status and utility instead of raw ones.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub use_estimates: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    countdown: u32,
+}
+impl PacedSelectorBehaviour {
+    pub fn new(cooldown: u32, select: SelectMode) -> Self {
+        Self {
+            cooldown,
+            select,
+            use_estimates: false,
+            countdown: 0,
+        }
+    }
+
+    /// Number of runs left before a child may be activated.
+    pub fn countdown(&self) -> u32 {
+        self.countdown
+    }
+}
+impl<C: Config> Behaviour<C> for PacedSelectorBehaviour {
+    /// Returns status of currently active child plan.
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        plan.plans.iter().find(|p| p.active())?.status()
+    }
+    fn on_entry(&mut self, _plan: &mut Plan<C>) {
+        self.countdown = 0;
+    }
+    fn on_prepare(&mut self, plan: &mut Plan<C>) {
+        if self.countdown > 0 {
+            self.countdown -= 1;
+            return;
+        }
+        let selected = match self.select {
+            SelectMode::Util => {
+                max_utility_by(&plan.plans, self.use_estimates).map(|(plan, _)| plan)
+            }
+            SelectMode::Priority => plan.plans.iter().find(|p| {
+                let status = if self.use_estimates {
+                    p.hypothetical().status
+                } else {
+                    p.status()
+                };
+                status != Some(false)
+            }),
+        };
+        let Some(selected) = selected.map(|p| p.name().clone()) else {
+            return;
+        };
+        if let Some(active) = plan.plans.iter().find(|p| p.active()) {
+            if *active.name() == selected {
+                return;
+            }
+            let active = active.name().clone();
+            plan.exit_plan(&active);
+        }
+        plan.enter_plan(&selected);
+        self.countdown = self.cooldown;
+    }
+    fn exclusive_subplans(&self) -> bool {
+        true
+    }
+}
+
+/// Behaviour that writes the softmax distribution over child utilities into `data` each run.
+///
+/// The distribution is stored under `out_key` as a sequence of `(name, probability)` pairs.
+/// Storing the names requires them to be serializable, so the behaviour is only available
+/// with the `serde` feature, like the other behaviours that write into `data`.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// # use dynamic_plan_tree::config::fixtures::scored;
+///
+/// let distribution = UtilityDistributionBehaviour {
+///     temperature: 1.,
+///     out_key: "p".into(),
+/// };
+/// let mut plan = Plan::<config::Default>::new(distribution.into(), "root", 1, true);
+/// plan.insert(scored("a", true, 0.));
+/// plan.insert(scored("b", true, 0.));
+/// plan.run();
+/// let p = plan.data()["p"].clone().deserialize_into::<Vec<(String, f64)>>();
+/// assert_eq!(p.unwrap(), [("a".into(), 0.5), ("b".into(), 0.5)]);
+/// ```
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+pub struct UtilityDistributionBehaviour {
+    /// Higher temperature flattens the distribution, clamped to be positive.
+    pub temperature: f64,
+    /// Data key to store the distribution under.
+    pub out_key: String,
+}
+#[cfg(feature = "serde")]
+impl<C: Config> Behaviour<C> for UtilityDistributionBehaviour {
+    fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+        None
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        use serde_value::Value;
+        let distribution = softmax_utility(&plan.plans, self.temperature)
+            .into_iter()
+            .map(|(plan, p)| {
+                Value::Seq(vec![
+                    serde_value::to_value(plan.name()).unwrap(),
+                    Value::F64(p),
+                ])
+            })
+            .collect();
+        plan.set_data(self.out_key.clone(), Value::Seq(distribution));
+    }
+}
+
+/// Behaviour with utility looked up from a piecewise linear table in `data`.
+///
+/// Reads a number under `input_key` and a table of `(input, utility)` points sorted by input under `table_key`,
+/// interpolating linearly between points. Inputs outside the table are clamped to its ends.
+/// Utility is 0 while either entry is missing or malformed.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// use dynamic_plan_tree::serde_value::{to_value, Value};
+///
+/// let table = TableUtilBehaviour {
+///     input_key: "distance".into(),
+///     table_key: "curve".into(),
+/// };
+/// let mut plan = Plan::<config::Default>::new(table.into(), "root", 1, true);
+/// plan.set_data("curve", to_value(vec![(0., 1.), (10., 0.5)]).unwrap());
+/// plan.set_data("distance", Value::F64(5.));
+/// assert_eq!(plan.utility(), 0.75);
+/// // clamped to the ends of the table
+/// plan.set_data("distance", Value::F64(20.));
+/// assert_eq!(plan.utility(), 0.5);
+/// ```
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+pub struct TableUtilBehaviour {
+    pub input_key: String,
+    pub table_key: String,
+}
+#[cfg(feature = "serde")]
+impl<C: Config> Behaviour<C> for TableUtilBehaviour {
+    fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+        None
+    }
+    fn utility(&self, plan: &Plan<C>) -> f64 {
+        let input = plan.data().get(&self.input_key).cloned();
+        let table = plan.data().get(&self.table_key).cloned();
+        let input = input.and_then(|x| x.deserialize_into::<f64>().ok());
+        let table = table.and_then(|x| x.deserialize_into::<Vec<(f64, f64)>>().ok());
+        match (input, table) {
+            (Some(input), Some(table)) => interpolate_table(&table, input).unwrap_or(0.),
+            _ => 0.,
+        }
+    }
+}
+
+/// Linearly interpolate a table of `(x, y)` points sorted by `x`, clamping `x` to the ends of the table.
+/// Returns `None` if the table is empty, `x` is not finite, or `x` can't be placed between two points.
+pub fn interpolate_table(table: &[(f64, f64)], x: f64) -> Option<f64> {
+    if !x.is_finite() {
+        return None;
+    }
+    let (first, last) = (table.first()?, table.last()?);
+    if x <= first.0 {
+        return Some(first.1);
+    }
+    if x >= last.0 {
+        return Some(last.1);
+    }
+    let i = table.partition_point(|point| point.0 <= x);
+    let ((x0, y0), (x1, y1)) = (*table.get(i.checked_sub(1)?)?, *table.get(i)?);
+    Some(y0 + (y1 - y0) * (x - x0) / (x1 - x0))
+}
+
+/// Compute the softmax probability of each plan given their utilities.
+pub fn softmax_utility<C: Config>(plans: &[Plan<C>], temperature: f64) -> Vec<(&Plan<C>, f64)> {
+    let temperature = temperature.max(f64::EPSILON);
+    let utilities = plans.iter().map(|plan| plan.utility()).collect::<Vec<_>>();
+    // shift by max utility for numerical stability
+    let max = utilities.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let weights = utilities
+        .iter()
+        .map(|x| ((x - max) / temperature).exp())
+        .collect::<Vec<_>>();
+    let sum = weights.iter().sum::<f64>();
+    plans
+        .iter()
+        .zip(weights)
+        .map(|(plan, w)| (plan, w / sum))
+        .collect()
+}
+
+/// Find and return the plan with highest utility.
+pub fn max_utility<C: Config>(plans: &[Plan<C>]) -> Option<(&Plan<C>, f64)> {
+    max_utility_by(plans, false)
+}
+
+/// Like [max_utility], comparing by [Plan::hypothetical] utility if `use_estimates`.
+fn max_utility_by<C: Config>(plans: &[Plan<C>], use_estimates: bool) -> Option<(&Plan<C>, f64)> {
+    if plans.is_empty() {
+        None
+    } else {
+        let (pos, utility) = plans
+            .iter()
+            .map(|plan| {
+                if use_estimates {
+                    plan.hypothetical().utility
+                } else {
+                    plan.utility()
+                }
+            })
+            .enumerate()
+            .fold((0, f64::NAN), |max, x| if max.1 > x.1 { max } else { x });
+        Some((&plans[pos], utility))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type DC = config::Default;
+
+    #[test]
+    fn evaluate_status() {
+        let make_plan = |t: bool, f: bool| {
+            Plan::<DC>::new(
+                EvaluateStatus(
+                    if t {
+                        predicate::True.into()
+                    } else {
+                        predicate::False.into()
+                    },
+                    if f {
+                        predicate::True.into()
+                    } else {
+                        predicate::False.into()
+                    },
+                )
+                .into(),
+                "root",
+                1,
+                true,
+            )
+        };
+
+        let plan = make_plan(false, false);
+        assert_eq!(plan.status(), None);
+
+        let plan = make_plan(false, true);
+        assert_eq!(plan.status(), Some(false));
+
+        let plan = make_plan(true, false);
+        assert_eq!(plan.status(), Some(true));
+
+        let plan = make_plan(true, true);
+        assert_eq!(plan.status(), Some(false));
+    }
+
+    #[test]
+    fn predicate_status_behaviour() {
+        let plan = Plan::<DC>::new(
+            PredicateStatusBehaviour(predicate::True.into()).into(),
+            "root",
+            1,
+            true,
+        );
+        assert_eq!(plan.status(), Some(true));
+
+        let plan = Plan::<DC>::new(
+            PredicateStatusBehaviour(predicate::False.into()).into(),
+            "root",
+            1,
+            true,
+        );
+        assert_eq!(plan.status(), Some(false));
+    }
+
+    #[test]
+    fn threshold_status_behaviour() {
+        let child = |name: &str, status: Option<bool>| {
+            let behaviour = match status {
+                Some(true) => PredicateStatusBehaviour(predicate::True.into()).into(),
+                Some(false) => PredicateStatusBehaviour(predicate::False.into()).into(),
+                None => EvaluateStatus(predicate::False.into(), predicate::False.into()).into(),
+            };
+            Plan::<DC>::new(behaviour, name, 1, true)
+        };
+        let status = |threshold, statuses: &[Option<bool>]| {
+            let mut plan = Plan::<DC>::new(ThresholdStatus(threshold).into(), "root", 1, true);
+            for (i, status) in statuses.iter().enumerate() {
+                plan.insert(child(&i.to_string(), *status));
+            }
+            plan.status()
+        };
+
+        // succeeds once enough children succeed, regardless of the rest
+        assert_eq!(
+            status(2, &[Some(true), Some(false), Some(true), None]),
+            Some(true)
+        );
+        assert_eq!(status(2, &[Some(true), None, None]), None);
+        // fails once the remaining children can no longer reach the threshold
+        assert_eq!(
+            status(2, &[Some(true), Some(false), Some(false), None]),
+            None
+        );
+        assert_eq!(
+            status(2, &[Some(true), Some(false), Some(false)]),
+            Some(false)
+        );
+        assert_eq!(status(3, &[Some(true), None]), Some(false));
+        // zero threshold is always met
+        assert_eq!(status(0, &[]), Some(true));
+        assert_eq!(status(1, &[]), Some(false));
+    }
+
+    #[test]
+    fn vote_status_behaviour() {
+        let voter = |name: &str, vote: Option<bool>| {
+            let behaviour = match vote {
+                Some(true) => PredicateStatusBehaviour(predicate::True.into()).into(),
+                Some(false) => PredicateStatusBehaviour(predicate::False.into()).into(),
+                None => EvaluateStatus(predicate::False.into(), predicate::False.into()).into(),
+            };
+            Plan::<DC>::new(behaviour, name, 1, true)
+        };
+        let make_plan = |tie: Option<bool>, votes: &[Option<bool>]| {
+            let mut plan = Plan::<DC>::new(VoteStatusBehaviour { tie }.into(), "root", 1, true);
+            for (i, vote) in votes.iter().enumerate() {
+                plan.insert(voter(&i.to_string(), *vote));
+            }
+            plan
+        };
+
+        // majority wins and abstentions are ignored
+        let plan = make_plan(None, &[Some(true), Some(true), Some(false), None, None]);
+        assert_eq!(plan.status(), Some(true));
+        let plan = make_plan(None, &[Some(false), Some(false), Some(true), None]);
+        assert_eq!(plan.status(), Some(false));
+
+        // tie is broken by `tie`
+        let votes = [Some(true), Some(false), None];
+        assert_eq!(make_plan(None, &votes).status(), None);
+        assert_eq!(make_plan(Some(true), &votes).status(), Some(true));
+        assert_eq!(make_plan(Some(false), &votes).status(), Some(false));
+
+        // no settled votes
+        assert_eq!(make_plan(Some(true), &[None, None]).status(), None);
+        assert_eq!(make_plan(Some(true), &[]).status(), None);
+    }
+
+    #[test]
+    fn subtree_status() {
+        let status_plan = |name: &str, status: Option<bool>, autostart: bool| {
+            let behaviour = match status {
+                Some(true) => PredicateStatusBehaviour(predicate::True.into()).into(),
+                Some(false) => PredicateStatusBehaviour(predicate::False.into()).into(),
+                None => EvaluateStatus(predicate::False.into(), predicate::False.into()).into(),
+            };
+            Plan::<DC>::new(behaviour, name, 1, autostart)
+        };
+        let mut root_plan = status_plan("root", Some(true), true);
+        let mut a = status_plan("A", Some(true), true);
+        a.insert(status_plan("x", Some(true), true));
+        a.insert(status_plan("y", Some(false), false));
+        root_plan.insert(a);
+        root_plan.insert(status_plan("B", None, false));
+        root_plan.run();
+
+        // inactive failures and unknowns are ignored
+        assert_eq!(root_plan.subtree_status(), Some(true));
+
+        // an active unknown anywhere in the branch withholds success
+        root_plan.enter_plan("B");
+        assert_eq!(root_plan.subtree_status(), None);
+        root_plan.exit_plan("B");
+
+        // an active failure at depth overrides everything else
+        root_plan.get_mut("A").unwrap().enter_plan("y");
+        assert_eq!(root_plan.subtree_status(), Some(false));
+        assert_eq!(root_plan.get("A").unwrap().status(), Some(true));
+        root_plan.get_mut("A").unwrap().exit_plan("y");
+        assert_eq!(root_plan.subtree_status(), Some(true));
+
+        // the root's own status counts even when inactive
+        let mut root_plan = status_plan("root", Some(false), false);
+        root_plan.insert(status_plan("A", Some(true), true));
+        assert_eq!(root_plan.subtree_status(), Some(false));
+    }
+
+    #[test]
+    #[allow(clippy::replace_box)]
+    fn repeat_behaviour() {
+        //use tracing::info;
+        //let _ = tracing_subscriber::fmt::try_init();
+        let mut repeat = RepeatBehaviour::new(AllSuccessStatus.into());
+        repeat.iterations = 5;
+        let mut plan = Plan::<DC>::new(repeat.into(), "root", 1, true);
+        // test iteration limit
+        for _ in 0..5 {
+            plan.run();
+            assert_eq!(plan.status(), None);
+        }
+        plan.run();
+        assert_eq!(plan.status(), Some(true));
+
+        // test reset
+        plan.exit(false);
+        for _ in 0..5 {
+            plan.run();
+            assert_eq!(plan.status(), None);
+        }
+        plan.run();
+        assert_eq!(plan.status(), Some(true));
+
+        // test stop on failure
+        plan.exit(false);
+        for _ in 0..3 {
+            plan.run();
+            assert_eq!(plan.status(), None);
+        }
+        plan.cast_mut::<RepeatBehaviour<DC>>().unwrap().behaviour =
+            Box::new(AnySuccessStatus.into());
+        plan.run();
+        assert_eq!(plan.status(), Some(false));
+
+        // test retry bool
+        plan.exit(false);
+        plan.cast_mut::<RepeatBehaviour<DC>>().unwrap().stop_value = true;
+        for _ in 0..3 {
+            plan.run();
+            assert_eq!(plan.status(), None);
+        }
+        plan.cast_mut::<RepeatBehaviour<DC>>().unwrap().behaviour =
+            Box::new(AllSuccessStatus.into());
+        plan.run();
+        assert_eq!(plan.status(), Some(true));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn inspect_behaviour() {
+        use serde_value::Value;
+        let mut repeat = RepeatBehaviour::new(AllSuccessStatus.into());
+        repeat.iterations = 3;
+        let mut plan = Plan::<DC>::new(repeat.into(), "root", 1, true);
+        let field = |plan: &Plan<DC>, name: &str| {
+            plan.inspect_behaviour()
+                .into_iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value)
+        };
+        plan.run();
+        assert_eq!(field(&plan, "count_down"), Some(Value::U64(2)));
+        assert_eq!(field(&plan, "iterations"), Some(Value::U64(3)));
+        assert_eq!(field(&plan, "stop_value"), Some(Value::Bool(false)));
+        assert_eq!(field(&plan, "status"), Some(Value::Option(None)));
+        plan.run();
+        plan.run();
+        plan.run();
+        assert_eq!(field(&plan, "count_down"), Some(Value::U64(0)));
+        assert_eq!(
+            field(&plan, "status"),
+            Some(Value::Option(Some(Box::new(Value::Bool(true)))))
+        );
+        // stateless behaviours expose nothing
+        let plan = Plan::<DC>::new(AllSuccessStatus.into(), "root", 1, true);
+        assert!(plan.inspect_behaviour().is_empty());
+        assert!(Plan::<DC>::new_stub("root", true)
+            .inspect_behaviour()
+            .is_empty());
+    }
+
+    #[test]
+    fn timeout_behaviour() {
+        let timeout = TimeoutBehaviour::new(AllSuccessStatus.into(), 3);
+        let mut plan = Plan::<DC>::new(timeout.into(), "root", 1, true);
+        plan.insert(Plan::new_stub("a", true));
+        let timed_out = |plan: &Plan<DC>| plan.cast::<TimeoutBehaviour<DC>>().unwrap().timed_out();
+        // in progress until the budget runs out
+        for _ in 0..2 {
+            plan.run();
+            assert_eq!(plan.status(), None);
+        }
+        plan.run();
+        assert_eq!(plan.status(), Some(false));
+        assert!(timed_out(&plan));
+        // stays failed even if the inner behaviour later settles
+        plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+        plan.run();
+        assert_eq!(plan.status(), Some(false));
+
+        // countdown restarts on re-entry
+        plan.exit(false);
+        plan.insert(Plan::new_stub("a", true));
+        plan.run();
+        assert_eq!(plan.status(), None);
+        assert!(!timed_out(&plan));
+        // success before the deadline is not overridden
+        plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+        for _ in 0..3 {
+            plan.run();
+            assert_eq!(plan.status(), Some(true));
+        }
+        assert!(!timed_out(&plan));
+    }
+
+    #[test]
+    fn timeout_behaviour_zero_ticks() {
+        let delay = DelayBehaviour::new(AllSuccessStatus.into(), 3);
+        let timeout = TimeoutBehaviour::new(delay.into(), 0);
+        let mut plan = Plan::<DC>::new(timeout.into(), "root", 1, true);
+        let state = |plan: &Plan<DC>| {
+            let timeout = plan.cast::<TimeoutBehaviour<DC>>().unwrap();
+            let delay = timeout.behaviour.cast::<DelayBehaviour<DC>>().unwrap();
+            (timeout.timed_out(), delay.count_down())
+        };
+        // fails upon entry without running the inner behaviour
+        plan.enter(None);
+        assert_eq!(plan.status(), Some(false));
+        assert_eq!(state(&plan), (true, 3));
+        plan.run();
+        assert_eq!(plan.status(), Some(false));
+        assert_eq!(state(&plan), (true, 3));
+
+        // a status settled upon entry is kept
+        plan.exit(false);
+        plan.set_behaviour(TimeoutBehaviour::new(AllSuccessStatus.into(), 0).into());
+        plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+        plan.run();
+        assert_eq!(plan.status(), Some(true));
+        assert!(!plan.cast::<TimeoutBehaviour<DC>>().unwrap().timed_out());
+    }
+
+    #[test]
+    fn timeout_behaviour_settled() {
+        let timeout = TimeoutBehaviour::new(AllSuccessStatus.into(), 2);
+        let mut plan = Plan::<DC>::new(timeout.into(), "root", 1, true);
+        plan.insert(Plan::new_stub("a", true));
+        plan.run();
+        assert_eq!(plan.status(), None);
+        // success before the deadline is not overridden by it or by later runs
+        plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+        for _ in 0..3 {
+            plan.run();
+            assert_eq!(plan.status(), Some(true));
+        }
+        assert!(!plan.cast::<TimeoutBehaviour<DC>>().unwrap().timed_out());
+    }
+
+    #[test]
+    fn delay_behaviour() {
+        let delay = DelayBehaviour::new(RepeatBehaviour::new(AllSuccessStatus.into()).into(), 3);
+        let mut plan = Plan::<DC>::new(delay.into(), "root", 1, true);
+        plan.insert(Plan::new_stub("a", true));
+        let delay = |plan: &Plan<DC>| {
+            let delay = plan.cast::<DelayBehaviour<DC>>().unwrap();
+            let repeat = delay.behaviour.cast::<RepeatBehaviour<DC>>().unwrap();
+            (delay.count_down(), delay.entered(), repeat.count_down)
+        };
+        // inner behaviour is not entered while waiting
+        plan.run();
+        assert_eq!(delay(&plan), (2, false, 0));
+        plan.run();
+        assert_eq!(delay(&plan), (1, false, 0));
+        assert_eq!(plan.status(), None);
+        // entered once the delay expires
+        plan.run();
+        assert_eq!(delay(&plan), (0, true, usize::MAX));
+        plan.run();
+        assert_eq!(plan.status(), None);
+
+        // re-entry restarts the delay
+        plan.exit(false);
+        assert_eq!(delay(&plan), (0, false, usize::MAX));
+        plan.cast_mut::<DelayBehaviour<DC>>().unwrap().delay = 1;
+        plan.run();
+        assert_eq!(delay(&plan), (0, true, usize::MAX));
+
+        // no delay enters immediately
+        plan.exit(false);
+        plan.cast_mut::<DelayBehaviour<DC>>().unwrap().delay = 0;
+        plan.enter(None);
+        assert_eq!(delay(&plan), (0, true, usize::MAX));
+    }
+
+    #[test]
+    fn precondition_behaviour() {
+        let ready = predicate::DataValue {
+            key: "ready".into(),
+        };
+        let delay = DelayBehaviour::new(AllSuccessStatus.into(), 2);
+        let precondition = PreconditionBehaviour::new(ready.into(), delay.into());
+        let mut plan = Plan::<DC>::new(precondition.into(), "root", 1, false);
+        let state = |plan: &Plan<DC>| {
+            let precondition = plan.cast::<PreconditionBehaviour<DC>>().unwrap();
+            let delay = precondition.behaviour.cast::<DelayBehaviour<DC>>().unwrap();
+            (precondition.failed(), delay.count_down())
+        };
+        // inner behaviour is neither entered nor run when the condition fails
+        plan.enter(None);
+        plan.run();
+        plan.run();
+        assert_eq!(state(&plan), (true, 0));
+        assert_eq!(plan.status(), Some(false));
+
+        // otherwise delegates as usual
+        plan.exit(false);
+        plan.set_data("ready", serde_value::Value::Bool(true));
+        plan.enter(None);
+        assert_eq!(state(&plan), (false, 2));
+        plan.run();
+        assert_eq!(state(&plan), (false, 1));
+        assert_eq!(plan.status(), None);
+
+        // not checked again while active
+        plan.set_data("ready", serde_value::Value::Bool(false));
+        plan.run();
+        assert_eq!(state(&plan), (false, 0));
+        assert_eq!(plan.status(), Some(true));
+    }
+
+    #[test]
+    fn semaphore_behaviour() {
+        let mut root = Plan::<DC>::new_stub("root", true);
+        for i in 0..5 {
+            let delay = DelayBehaviour::new(AllSuccessStatus.into(), 3);
+            let semaphore = SemaphoreBehaviour::new("n", 2, delay.into());
+            root.insert(Plan::new(semaphore.into(), i.to_string(), 1, true));
+        }
+        let acquired = |root: &Plan<DC>| {
+            let plans = root.plans.iter().filter(|x| x.active());
+            let acquired = plans.filter(|x| x.cast::<SemaphoreBehaviour<DC>>().unwrap().acquired());
+            acquired.map(|x| x.name().clone()).collect::<Vec<_>>()
+        };
+        let delay = |root: &Plan<DC>, name| {
+            let semaphore = root.get_cast::<SemaphoreBehaviour<DC>, _>(name).unwrap();
+            semaphore
+                .behaviour
+                .cast::<DelayBehaviour<DC>>()
+                .unwrap()
+                .count_down()
+        };
+        // which plans acquire a slot is up to the order they run in, which may be parallel
+        root.run();
+        let first = acquired(&root);
+        assert_eq!(first.len(), 2);
+        let names = root.plans.iter().map(|x| x.name().clone());
+        let waiting = names.clone().find(|x| !first.contains(x)).unwrap();
+        // inner behaviours only run once acquired
+        assert_eq!((delay(&root, &first[0]), delay(&root, &waiting)), (2, 0));
+        assert_eq!(root.get(&waiting).unwrap().status(), None);
+
+        // released slots are taken by the next run of waiting plans
+        for exited in 1..=3 {
+            let released = acquired(&root)[0].clone();
+            root.exit_plan(&released);
+            assert_eq!(root.slots_taken("n"), 1);
+            root.run();
+            let taken = acquired(&root);
+            assert!(taken.len() == 2 && !taken.contains(&released));
+            assert_eq!(root.slots_taken("n"), 2);
+            assert_eq!(root.plans.iter().filter(|x| x.active()).count(), 5 - exited);
+        }
+        // re-entered plans wait for a slot again
+        root.enter_plan(&first[0]);
+        let semaphore = root.get_cast::<SemaphoreBehaviour<DC>, _>(&first[0]);
+        assert!(!semaphore.unwrap().acquired());
+        root.exit(true);
+        assert_eq!(root.slots_taken("n"), 0);
+    }
+
+    #[test]
+    fn semaphore_behaviour_data() {
+        let mut root = Plan::<DC>::new_stub("root", true);
+        for i in 0..3 {
+            let semaphore = SemaphoreBehaviour::new("n", 2, AllSuccessStatus.into());
+            root.insert(Plan::new(semaphore.into(), i.to_string(), 1, true));
+        }
+        root.run();
+        assert_eq!(root.data()["n"], serde_value::Value::U64(2));
+        // the counter is written back once slots are given back
+        root.exit_plan("0");
+        assert_eq!(root.data()["n"], serde_value::Value::U64(2));
+        root.run();
+        assert_eq!(root.data()["n"], serde_value::Value::U64(2));
+        assert!(root.plans.iter().all(|x| x.active() == x.holds_slot("n")));
+        root.exit_plan("1");
+        root.run();
+        assert_eq!(root.data()["n"], serde_value::Value::U64(1));
+
+        // slots taken outside the tree are set in the data of the root
+        root.set_data("n", serde_value::Value::U64(2));
+        root.run();
+        assert_eq!(root.slots_taken("n"), 2);
+        root.enter_plan("0");
+        root.run();
+        assert!(!root.get("0").unwrap().holds_slot("n"));
+        assert_eq!(root.get("0").unwrap().status(), None);
+        root.set_data("n", serde_value::Value::U64(1));
+        root.run();
+        assert!(root.get("0").unwrap().holds_slot("n"));
+        assert_eq!(root.data()["n"], serde_value::Value::U64(2));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn semaphore_behaviour_serde() {
+        let mut root = Plan::<DC>::new_stub("root", true);
+        for i in 0..3 {
+            let semaphore = SemaphoreBehaviour::new("n", 2, AllSuccessStatus.into());
+            root.insert(Plan::new(semaphore.into(), i.to_string(), 1, true));
+        }
+        root.run();
+        let holders = |root: &Plan<DC>| {
+            let plans = root.plans.iter().filter(|x| x.holds_slot("n"));
+            plans.map(|x| x.name().clone()).collect::<Vec<_>>()
+        };
+        let held = holders(&root);
+        let json = serde_json::to_value(&root).unwrap();
+
+        // loaded plans keep their slots, so the remaining one still waits
+        let mut root = serde_json::from_value::<Plan<DC>>(json).unwrap();
+        assert_eq!(root.slots_taken("n"), 2);
+        assert_eq!(holders(&root), held);
+        root.run();
+        assert_eq!(holders(&root), held);
+        let waiting = root
+            .plans
+            .iter()
+            .find(|x| !held.contains(x.name()))
+            .unwrap();
+        assert_eq!(waiting.status(), None);
+        for plan in &root.plans {
+            let acquired = held.contains(plan.name());
+            let fields = plan.cast::<SemaphoreBehaviour<DC>>().unwrap().inspect();
+            assert_eq!(fields[0], ("acquired", serde_value::Value::Bool(acquired)));
+            assert_eq!(fields[1], ("limit", serde_value::Value::U64(2)));
+        }
+        root.exit(true);
+        assert_eq!(root.slots_taken("n"), 0);
+        assert_eq!(root.data()["n"], serde_value::Value::U64(2));
+        root.run();
+        assert_eq!(root.data()["n"], serde_value::Value::U64(0));
+    }
+
+    #[test]
+    fn cooldown_behaviour() {
+        let cooldown = |behaviour: Behaviours<DC>| CooldownBehaviour::new(behaviour, 3).into();
+        let pending = EvaluateStatus(predicate::False.into(), predicate::False.into()).into();
+        let mut root = Plan::<DC>::new_stub("root", true);
+        root.insert(Plan::new(
+            cooldown(AllSuccessStatus.into()),
+            "attack",
+            1,
+            true,
+        ));
+        root.insert(Plan::new(cooldown(pending), "wait", 1, true));
+        let behaviour = |root: &Plan<DC>, name| {
+            let plan = root.get(name).unwrap();
+            let cooldown = plan.cast::<CooldownBehaviour<DC>>().unwrap();
+            (cooldown.entered(), cooldown.remaining(plan), plan.status())
+        };
+
+        root.run();
+        assert_eq!(behaviour(&root, "attack"), (true, 0, Some(true)));
+        // cools down for the 3 ticks following the exit
+        root.exit_plan("attack");
+        assert_eq!(behaviour(&root, "attack"), (false, 4, Some(false)));
+        root.run();
+        // entry is blocked, failing until the cooldown is over
+        root.enter_plan("attack");
+        assert_eq!(behaviour(&root, "attack"), (false, 3, Some(false)));
+        while root.tick() < 4 {
+            root.run();
+            assert!(!behaviour(&root, "attack").0);
+        }
+        // inner behaviour is entered on the first run after
+        root.run();
+        assert_eq!(behaviour(&root, "attack"), (true, 0, Some(true)));
+
+        // exits without a definite status start no cooldown
+        root.exit_plan("wait");
+        root.enter_plan("wait");
+        assert_eq!(behaviour(&root, "wait"), (true, 0, None));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn cooldown_behaviour_serde() {
+        let cooldown = CooldownBehaviour::<DC>::new(AllSuccessStatus.into(), 3);
+        let mut root = Plan::<DC>::new_stub("root", true);
+        root.insert(Plan::new(cooldown.into(), "attack", 1, true));
+        for _ in 0..5 {
+            root.run();
+        }
+        root.exit_plan("attack");
+        let remaining = |root: &Plan<DC>| {
+            let plan = root.get("attack").unwrap();
+            let cooldown = plan.cast::<CooldownBehaviour<DC>>().unwrap();
+            (cooldown.remaining(plan), plan.status())
+        };
+        assert_eq!(remaining(&root), (4, Some(false)));
+
+        root.run();
+        assert_eq!(remaining(&root), (3, Some(false)));
+
+        // the tick of a loaded tree restarts, while the cooldown resumes from where it was counted
+        let json = serde_json::to_value(&root).unwrap();
+        let mut root = serde_json::from_value::<Plan<DC>>(json).unwrap();
+        assert_eq!(root.tick(), 0);
+        assert_eq!(remaining(&root), (3, Some(false)));
+        root.enter_plan("attack");
+        for left in (1..3).rev() {
+            root.run();
+            assert_eq!(remaining(&root), (left, Some(false)));
+        }
+        root.run();
+        assert_eq!(remaining(&root), (0, Some(true)));
+        assert!(root
+            .get_cast::<CooldownBehaviour<DC>, _>("attack")
+            .unwrap()
+            .entered());
+    }
+
+    #[test]
+    fn sequence_behaviour() {
+        //use tracing::info;
+        //let _ = tracing_subscriber::fmt::try_init();
+        let mut plan = Plan::<DC>::new(SequenceBehaviour::default().into(), "root", 1, true);
+        // the first 5 child plans return success
+        for i in 0..5 {
+            plan.insert(Plan::new(AllSuccessStatus.into(), i.to_string(), 0, i == 0));
+            plan.transitions.push(Transition::new(
+                vec![i.to_string()],
+                vec![(i + 1).to_string()],
+                predicate::True.into(),
+            ));
+        }
+        // the last child plan returns None
+        plan.insert(Plan::new_stub("5", false));
+        // check that child plans sequentually transition as long current child status succeeds
+        for i in 0..5 {
+            plan.run();
+            let active = plan.plans.iter().find(|x| x.active()).unwrap().name();
+            assert_eq!(active, &(i + 1).to_string());
+            assert_eq!(plan.status(), None);
+        }
+        // check that child plans stop transitioning when current child status is None
+        for _ in 0..5 {
+            plan.run();
+            let active = plan.plans.iter().find(|x| x.active()).unwrap().name();
+            assert_eq!(active, "5");
+            assert_eq!(plan.status(), None);
+        }
+        // change the last child plan to success as well
+        plan.insert(Plan::new(AllSuccessStatus.into(), "5", 0, false));
+        // expect sequence behaviour to return success when all children are successful
+        plan.run();
+        assert_eq!(plan.status(), Some(true));
+        // expect that sequence will jump back to previusly successful child if status changes
+        plan.insert(Plan::new_stub("3", false));
+        plan.run();
+        assert_eq!(plan.plans.iter().find(|x| x.active()).unwrap().name(), "3");
+        assert_eq!(plan.status(), None);
+        // same test above with failure status instead
+        plan.insert(Plan::new(AnySuccessStatus.into(), "1", 0, false));
+        plan.run();
+        assert_eq!(plan.plans.iter().find(|x| x.active()).unwrap().name(), "1");
+        assert_eq!(plan.status(), Some(false));
+    }
+
+    #[test]
+    fn escalate_behaviour() {
+        use predicate::{False, True};
+        let status = |status: Option<bool>| -> Behaviours<DC> {
+            match status {
+                Some(true) => EvaluateStatus(True.into(), False.into()).into(),
+                Some(false) => EvaluateStatus(False.into(), True.into()).into(),
+                None => EvaluateStatus(False.into(), False.into()).into(),
+            }
+        };
+        let set_status = |plan: &mut Plan<DC>, name: &str, value| {
+            plan.get_mut(name).unwrap().set_behaviour(status(value));
+        };
+        let active = |plan: &Plan<DC>| {
+            let active = plan.plans.iter().filter(|x| x.active());
+            active.map(|x| x.name().clone()).collect::<Vec<_>>()
+        };
+        let state = |plan: &Plan<DC>| {
+            let behaviour = plan.cast::<EscalateBehaviour<DC>>().unwrap();
+            (behaviour.level(), behaviour.failures())
+        };
+        let order = ["a", "b", "c"].map(String::from).to_vec();
+        let mut plan = Plan::<DC>::new(EscalateBehaviour::new(3, order).into(), "root", 1, true);
+        for name in ["a", "b", "c"] {
+            plan.insert(Plan::new(status(Some(false)), name, 1, false));
+        }
+
+        // each failure of a level is counted and retried until out of attempts
+        plan.run();
+        assert_eq!(active(&plan), ["a"]);
+        assert_eq!(state(&plan), (0, 0));
+        for failures in 1..3 {
+            plan.run();
+            assert_eq!(active(&plan), ["a"]);
+            assert_eq!(state(&plan), (0, failures));
+            assert_eq!(plan.status(), Some(false));
+        }
+        plan.run();
+        assert_eq!(active(&plan), ["b"]);
+        assert_eq!(state(&plan), (1, 0));
+
+        // in progress and success do not count as attempts
+        set_status(&mut plan, "b", None);
+        plan.run();
+        assert_eq!(plan.status(), None);
+        set_status(&mut plan, "b", Some(true));
+        for _ in 0..3 {
+            plan.run();
+            assert_eq!(active(&plan), ["b"]);
+            assert_eq!(state(&plan), (1, 0));
+            assert_eq!(plan.status(), Some(true));
+        }
+
+        // never returns to earlier levels, even once they would succeed
+        set_status(&mut plan, "a", Some(true));
+        set_status(&mut plan, "b", Some(false));
+        for _ in 0..3 {
+            plan.run();
+        }
+        assert_eq!(active(&plan), ["c"]);
+        assert_eq!(state(&plan), (2, 0));
+
+        // fails out after the last level
+        for _ in 0..3 {
+            assert_eq!(plan.status(), Some(false));
+            plan.run();
+        }
+        assert!(active(&plan).is_empty());
+        assert_eq!(state(&plan), (3, 0));
+        assert_eq!(plan.status(), Some(false));
+
+        // restarts upon entry, skipping missing children
+        plan.exit(false);
+        plan.remove("a");
+        plan.run();
+        assert_eq!(active(&plan), ["b"]);
+        assert_eq!(state(&plan), (1, 0));
+
+        // no attempts are treated as a single attempt
+        let order = vec!["a".to_string()];
+        let mut plan = Plan::<DC>::new(EscalateBehaviour::new(0, order).into(), "root", 1, true);
+        plan.insert(Plan::new(status(Some(false)), "a", 1, false));
+        plan.run();
+        plan.run();
+        assert_eq!(state(&plan), (1, 0));
+        assert_eq!(plan.status(), Some(false));
+    }
+
+    #[test]
+    fn round_robin_behaviour() {
+        let active = |plan: &Plan<DC>| {
+            plan.plans
+                .iter()
+                .find(|x| x.active())
+                .unwrap()
+                .name()
+                .clone()
+        };
+        let settling = |name: &str| {
+            let behaviour = DelayedStatusBehaviour::new(AllSuccessStatus.into());
+            Plan::<DC>::new(behaviour.into(), name, 1, false)
+        };
+
+        // no children to cycle through
+        let mut plan = Plan::<DC>::new(RoundRobinBehaviour::default().into(), "root", 1, true);
+        plan.run();
+        assert_eq!(plan.status(), None);
+        assert_eq!(plan.cast::<RoundRobinBehaviour>().unwrap().0, 0);
+
+        // each child settles on its first run, so the next one is entered on the following tick
+        for name in ["a", "b", "c"] {
+            plan.insert(settling(name));
+        }
+        for expected in ["a", "b", "c", "a", "b"] {
+            plan.run();
+            assert_eq!(active(&plan), expected);
+            assert_eq!(plan.plans.iter().filter(|x| x.active()).count(), 1);
+            assert_eq!(plan.status(), Some(true));
+        }
+        assert_eq!(plan.cast::<RoundRobinBehaviour>().unwrap().0, 1);
+
+        // stay on a child until it settles
+        plan.insert(Plan::new_stub("c", false));
+        for _ in 0..3 {
+            plan.run();
+            assert_eq!(active(&plan), "c");
+            assert_eq!(plan.status(), None);
+        }
+        plan.insert(settling("c"));
+        plan.run();
+        plan.run();
+        assert_eq!(active(&plan), "a");
+    }
+
+    #[test]
+    fn cached_status() {
+        let make_plan = |cached| {
+            let mut plan = Plan::<DC>::new(SequenceBehaviour::default().into(), "root", 1, true);
+            // status follows the selected subplan, which only exists while active
+            let mut first = Plan::new(MaxUtilBehaviour::default().into(), "1", 1, true);
+            first.insert(Plan::new(AllSuccessStatus.into(), "x", 0, false));
+            first.report_cached_when_inactive = cached;
+            plan.insert(first);
+            plan.insert(Plan::new_stub("2", false));
+            plan.transitions =
+                SequenceBehaviour::<DC>::default().generate_transitions(&["1".into(), "2".into()]);
+            plan
+        };
+        let active = |plan: &Plan<DC>| {
+            plan.plans
+                .iter()
+                .find(|x| x.active())
+                .unwrap()
+                .name()
+                .clone()
+        };
+        // stale status of the exited child makes the sequence bounce back to it
+        let mut plan = make_plan(false);
+        plan.run();
+        assert_eq!(plan.get("1").unwrap().status(), Some(true));
+        for _ in 0..4 {
+            plan.run();
+            assert_eq!(active(&plan), "1");
+        }
+        let first = plan.get("1").unwrap();
+        assert_eq!(first.last_active_status(), Some(true));
+        // cached status keeps the outcome observed while active
+        let mut plan = make_plan(true);
+        for _ in 0..4 {
+            plan.run();
+        }
+        assert_eq!(active(&plan), "2");
+        let first = plan.get("1").unwrap();
+        assert!(!first.active());
+        assert_eq!(first.status(), Some(true));
+        assert_eq!(first.last_active_status(), Some(true));
+        assert!(!first.get("x").unwrap().active());
+    }
+
+    #[test]
+    fn generated_transitions() {
+        let names: Vec<_> = (0..4).map(|i| i.to_string()).collect();
+        let sequence = SequenceBehaviour::default();
+        let transitions = sequence.generate_transitions(&names);
+        let mut plan = Plan::<DC>::new(sequence.into(), "root", 1, true);
+        plan.transitions = transitions;
+        for name in &names {
+            plan.insert(Plan::new(AllSuccessStatus.into(), name, 0, name == "0"));
+        }
+        assert_eq!(plan.transitions.len(), 3);
+        // generated success chain drives the sequence like manually defined transitions
+        for i in 0..3 {
+            plan.run();
+            let active = plan.plans.iter().find(|x| x.active()).unwrap().name();
+            assert_eq!(active, &(i + 1).to_string());
+        }
+        assert_eq!(plan.status(), Some(true));
+        // fallback chain advances through failing children
+        let fallback = FallbackBehaviour::default();
+        let transitions = fallback.generate_transitions(&names);
+        let mut plan = Plan::<DC>::new(fallback.into(), "root", 1, true);
+        plan.transitions = transitions;
+        for name in &names {
+            plan.insert(Plan::new(AnySuccessStatus.into(), name, 0, name == "0"));
+        }
+        for _ in 0..3 {
+            plan.run();
+        }
+        assert_eq!(plan.plans.iter().find(|x| x.active()).unwrap().name(), "3");
+        assert_eq!(plan.status(), Some(false));
+    }
+
+    #[test]
+    fn transition_labels() {
+        let transition =
+            |src: &str, dst: &str, predicate: predicate::Predicates, label: &str| Transition {
+                label: Some(label.into()),
+                ..Transition::new(vec![src.into()], vec![dst.into()], predicate)
+            };
+        // status follows the labeled transition exactly on the ticks it fires
+        let fired = predicate::TransitionFired { label: "ab".into() };
+        let status = EvaluateStatus(fired.into(), predicate::False.into());
+        let mut plan = Plan::<DC>::new(status.into(), "root", 1, true);
+        plan.insert(Plan::new_stub("A", true));
+        plan.insert(Plan::new_stub("B", false));
+        plan.transitions = vec![
+            transition("A", "B", predicate::True.into(), "ab"),
+            transition("B", "A", predicate::True.into(), "ba"),
+        ];
+        for i in 0..6 {
+            plan.run();
+            let ab = i % 2 == 0;
+            assert_eq!(plan.status(), if ab { Some(true) } else { None });
+            let label = if ab { "ab" } else { "ba" };
+            assert_eq!(plan.fired_transitions(), [label.to_string()]);
+        }
+        plan.exit(false);
+        assert!(plan.fired_transitions().is_empty());
+
+        // condition of labeled transition is reused without duplication
+        let would_fire = |label: &str| predicate::TransitionWouldFire {
+            label: label.into(),
+        };
+        let mut plan = Plan::<DC>::new_stub("root", false);
+        plan.insert(Plan::new_stub("X", false));
+        plan.transitions = vec![transition("X", "Y", predicate::AllSuccess.into(), "go")];
+        assert!(!would_fire("go").evaluate(&plan, &[]));
+        plan.insert(Plan::new(AllSuccessStatus.into(), "X", 0, false));
+        assert!(would_fire("go").evaluate(&plan, &[]));
+        assert!(!would_fire("missing").evaluate(&plan, &[]));
+    }
+
+    #[test]
+    fn hypothetical_estimates() {
+        /// Fails and is worthless until entered, but estimates its worth when entered.
+        #[derive(Default)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct Charger(bool);
+        impl<C: Config> Behaviour<C> for Charger {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                (!self.0).then_some(false)
+            }
+            fn utility(&self, _plan: &Plan<C>) -> f64 {
+                if self.0 {
+                    5.
+                } else {
+                    0.
+                }
+            }
+            fn estimate(&self, _plan: &Plan<C>) -> Option<Hypothetical> {
+                Some(Hypothetical {
+                    status: None,
+                    utility: 5.,
+                })
+            }
+            fn on_entry(&mut self, _plan: &mut Plan<C>) {
+                self.0 = true;
+            }
+            fn on_exit(&mut self, _plan: &mut Plan<C>) {
+                self.0 = false;
+            }
+        }
+
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct Fixed(f64);
+        impl<C: Config> Behaviour<C> for Fixed {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                None
+            }
+            fn utility(&self, _plan: &Plan<C>) -> f64 {
+                self.0
+            }
+        }
+
+        #[enum_dispatch(Behaviour<C>)]
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub enum EstimateBehaviours<C: Config> {
+            MaxUtilBehaviour,
+            PacedSelectorBehaviour,
+            Charger,
+            Fixed,
+            ModifyStatus(ModifyStatus<C>),
+            ModifyUtilityBehaviour(ModifyUtilityBehaviour<C>),
+        }
+
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct EstimateConfig;
+        impl Config for EstimateConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = EstimateBehaviours<Self>;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        type EC = EstimateConfig;
+
+        let make_plan = |behaviour: EstimateBehaviours<EC>| {
+            let mut plan = Plan::<EC>::new(behaviour, "root", 1, true);
+            plan.insert(Plan::new(Charger::default().into(), "a", 1, false));
+            plan.insert(Plan::new(Fixed(1.).into(), "b", 1, false));
+            plan
+        };
+
+        // estimate differs from status and utility while inactive
+        let mut plan = make_plan(MaxUtilBehaviour::default().into());
+        let charger = plan.get("a").unwrap();
+        assert_eq!((charger.status(), charger.utility()), (Some(false), 0.));
+        let estimate = Hypothetical {
+            status: None,
+            utility: 5.,
+        };
+        assert_eq!(plan.evaluate_hypothetical("a"), Some(estimate));
+        // without an estimate, status and utility are used as is
+        let fixed = Hypothetical {
+            status: None,
+            utility: 1.,
+        };
+        assert_eq!(plan.evaluate_hypothetical("b"), Some(fixed));
+        assert_eq!(plan.evaluate_hypothetical("c"), None);
+
+        // selectors ignore estimates unless asked to
+        plan.run();
+        assert_eq!(plan.plans.iter().find(|p| p.active()).unwrap().name(), "b");
+        plan.cast_mut::<MaxUtilBehaviour>().unwrap().use_estimates = true;
+        plan.run();
+        assert_eq!(plan.plans.iter().find(|p| p.active()).unwrap().name(), "a");
+        // once active the actual status and utility are reported
+        assert_eq!(plan.evaluate_hypothetical("a"), Some(estimate));
+        assert_eq!(plan.utility(), 5.);
+
+        let mut plan = make_plan(PacedSelectorBehaviour::new(0, SelectMode::Priority).into());
+        plan.run();
+        assert_eq!(plan.plans.iter().find(|p| p.active()).unwrap().name(), "b");
+        plan.cast_mut::<PacedSelectorBehaviour>()
+            .unwrap()
+            .use_estimates = true;
+        plan.run();
+        assert_eq!(plan.plans.iter().find(|p| p.active()).unwrap().name(), "a");
+
+        // wrappers forward estimates, adjusted as they adjust status and utility
+        let mut plan = make_plan(MaxUtilBehaviour::default().into());
+        plan.cast_mut::<MaxUtilBehaviour>().unwrap().use_estimates = true;
+        let invert = ModifyStatus(Box::new(Charger::default().into()), Some(true));
+        plan.insert(Plan::new(invert.into(), "a", 1, false));
+        assert_eq!(plan.evaluate_hypothetical("a"), Some(estimate));
+        plan.run();
+        assert_eq!(plan.plans.iter().find(|p| p.active()).unwrap().name(), "a");
+        let scaled = ModifyUtilityBehaviour::new(Charger::default().into(), 0.1, 0.);
+        plan.exit_plan("a");
+        plan.insert(Plan::new(scaled.into(), "a", 1, false));
+        let estimate = Hypothetical {
+            status: None,
+            utility: 0.5,
+        };
+        assert_eq!(plan.evaluate_hypothetical("a"), Some(estimate));
+        plan.run();
+        assert_eq!(plan.plans.iter().find(|p| p.active()).unwrap().name(), "b");
+    }
+
+    #[test]
+    fn catch_panic_behaviour() {
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct PanicBehaviour(u32);
+        impl<C: Config> Behaviour<C> for PanicBehaviour {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                None
+            }
+            fn on_entry(&mut self, _plan: &mut Plan<C>) {
+                self.0 = 2;
+            }
+            fn on_run(&mut self, _plan: &mut Plan<C>) {
+                self.0 -= 1;
+                if self.0 == 0 {
+                    panic!("out of fuel");
+                }
+            }
+        }
+
+        #[enum_dispatch(Behaviour<C>)]
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub enum PanicBehaviours<C: Config> {
+            CatchPanicBehaviour(CatchPanicBehaviour<C>),
+            PanicBehaviour,
+        }
+
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct PanicConfig;
+        impl Config for PanicConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = PanicBehaviours<Self>;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        let behaviour = CatchPanicBehaviour::new(PanicBehaviour(0).into());
+        let mut plan = Plan::<PanicConfig>::new_stub("root", true);
+        plan.insert(Plan::new(behaviour.into(), "risky", 1, true));
+        let catch = |plan: &Plan<PanicConfig>| {
+            let risky = plan.get("risky").unwrap();
+            (
+                risky.status(),
+                risky
+                    .cast::<CatchPanicBehaviour<PanicConfig>>()
+                    .unwrap()
+                    .panic_message()
+                    .map(String::from),
+            )
+        };
+        plan.run();
+        assert_eq!(catch(&plan), (None, None));
+        // panic in on_run is contained and surfaces as failure
+        plan.run();
+        assert_eq!(catch(&plan), (Some(false), Some("out of fuel".into())));
+        // inner behaviour is no longer called
+        plan.run();
+        assert_eq!(catch(&plan), (Some(false), Some("out of fuel".into())));
+        // re-entry gives the inner behaviour another chance
+        plan.exit(false);
+        plan.run();
+        assert_eq!(catch(&plan), (None, None));
+    }
+
+    #[test]
+    fn outcome_counts() {
+        let transition = |src: &str, dst: &str, predicate: predicate::Predicates| {
+            Transition::new(vec![src.into()], vec![dst.into()], predicate)
+        };
+        // repeated task re-entered after each success
+        let mut repeat = RepeatBehaviour::new(AllSuccessStatus.into());
+        repeat.iterations = 1;
+        let mut plan = Plan::<DC>::new_stub("root", true);
+        plan.insert(Plan::new(repeat.into(), "task", 1, true));
+        plan.insert(Plan::new_stub("idle", false));
+        plan.transitions = vec![
+            transition("task", "idle", predicate::AllSuccess.into()),
+            transition("idle", "task", predicate::True.into()),
+        ];
+        for _ in 0..15 {
+            plan.run();
+        }
+        let counts = |successes, failures| OutcomeCounts {
+            successes,
+            failures,
+        };
+        assert_eq!(plan.get("task").unwrap().outcome_counts(), counts(5, 0));
+        assert_eq!(plan.outcome_summary(), counts(5, 0));
+        plan.reset_outcome_counts();
+        assert_eq!(plan.outcome_summary(), counts(0, 0));
+
+        // failures within a fallback count even though it succeeds
+        let names: Vec<_> = (0..3).map(|i| i.to_string()).collect();
+        let fallback = FallbackBehaviour::default();
+        let transitions = fallback.generate_transitions(&names);
+        let mut plan = Plan::<DC>::new(fallback.into(), "root", 1, true);
+        plan.transitions = transitions;
+        plan.insert(Plan::new(AnySuccessStatus.into(), "0", 0, true));
+        plan.insert(Plan::new(AnySuccessStatus.into(), "1", 0, false));
+        plan.insert(Plan::new(AllSuccessStatus.into(), "2", 0, false));
+        for _ in 0..5 {
+            plan.run();
+        }
+        assert_eq!(plan.status(), Some(true));
+        assert_eq!(plan.outcome_counts(), counts(1, 0));
+        assert_eq!(plan.outcome_summary(), counts(2, 2));
+    }
+
+    #[test]
+    fn event_broadcast_behaviour() {
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct SetStatus(Option<bool>);
+        impl<C: Config> Behaviour<C> for SetStatus {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                self.0
+            }
+        }
+
+        #[enum_dispatch(Behaviour<C>)]
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub enum BroadcastBehaviours<C: Config> {
+            EventBroadcastBehaviour(EventBroadcastBehaviour<C>),
+            SetStatus,
+        }
+
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct BroadcastIs(predicate::EventMatches<Broadcast>);
+        impl Predicate for BroadcastIs {
+            fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool {
+                self.0.evaluate(plan, src)
+            }
+        }
+
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub enum BroadcastEvents {
+            Broadcast(Broadcast),
+        }
+
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct BroadcastConfig;
+        impl Config for BroadcastConfig {
+            type Predicate = BroadcastIs;
+            type Behaviour = BroadcastBehaviours<Self>;
+            type Event = BroadcastEvents;
+            type Name = DefaultName;
+        }
+        type BC = BroadcastConfig;
+        let broadcast =
+            EventBroadcastBehaviour::new(SetStatus(None).into(), Some("done".into()), None);
+        let mut plan = Plan::<BC>::new(broadcast.into(), "root", 1, true);
+        let mut listener = Plan::new_stub("listener", true);
+        listener.insert(Plan::new_stub("wait", true));
+        listener.insert(Plan::new_stub("go", false));
+        listener.transitions.push(Transition::new(
+            vec!["wait".into()],
+            vec!["go".into()],
+            BroadcastIs(predicate::EventMatches(Broadcast("done".into()))),
+        ));
+        plan.insert(listener);
+        let set_status = |plan: &mut Plan<BC>, status| {
+            let broadcast = plan.cast_mut::<EventBroadcastBehaviour<BC>>().unwrap();
+            broadcast.behaviour.cast_mut::<SetStatus>().unwrap().0 = status;
+        };
+        let go = |plan: &Plan<BC>| plan.get("listener").unwrap().get("go").unwrap().active();
+        for _ in 0..3 {
+            plan.run();
+            assert!(!go(&plan));
+        }
+        // failure has no event configured
+        set_status(&mut plan, Some(false));
+        plan.run();
+        plan.run();
+        assert!(!go(&plan));
+        // success broadcasts to descendants, visible on their next run
+        set_status(&mut plan, Some(true));
+        plan.run();
+        assert!(!go(&plan));
+        plan.run();
+        assert!(go(&plan));
+        assert_eq!(
+            plan.get("listener").unwrap().events()[0].cast::<Broadcast>(),
+            Some(&Broadcast("done".into()))
+        );
+        // broadcast only once while status stays settled
+        plan.run();
+        assert!(plan.get("listener").unwrap().events().is_empty());
+        assert!(plan.warnings().is_empty());
+
+        // events without a broadcast variant warn instead of posting
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct SilentConfig;
+        impl Config for SilentConfig {
+            type Predicate = BroadcastIs;
+            type Behaviour = BroadcastBehaviours<Self>;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        let broadcast =
+            EventBroadcastBehaviour::new(SetStatus(Some(true)).into(), Some("done".into()), None);
+        let mut plan = Plan::<SilentConfig>::new(broadcast.into(), "root", 1, true);
+        plan.insert(Plan::new_stub("listener", true));
+        plan.run();
+        plan.run();
+        assert!(plan.get("listener").unwrap().events().is_empty());
+        let warnings = plan.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            (warnings[0].code, warnings[0].path.as_str()),
+            ("W204", "root")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn timeline_behaviour() {
+        let timeline = |steps: &[(u32, &str)]| {
+            let steps = steps.iter().map(|(x, y)| (*x, y.to_string())).collect();
+            Plan::<DC>::new(TimelineBehaviour::new(steps).into(), "root", 1, true)
+        };
+        let active = |plan: &Plan<DC>| {
+            let active = plan.plans.iter().filter(|x| x.active());
+            active.map(|x| x.name().clone()).collect::<Vec<_>>()
+        };
+
+        // empty timeline completes immediately
+        let mut plan = timeline(&[]);
+        assert_eq!(plan.status(), Some(true));
+        plan.run();
+        assert_eq!(plan.status(), Some(true));
+
+        // children are activated for their duration regardless of status, skipping empty steps
+        let mut plan = timeline(&[(2, "a"), (0, "b"), (1, "c"), (3, "a")]);
+        for name in ["a", "b", "c"] {
+            let status = EvaluateStatus(predicate::True.into(), predicate::False.into());
+            plan.insert(Plan::new(status.into(), name, 1, false));
+        }
+        let mut history = Vec::new();
+        for _ in 0..7 {
+            plan.run();
+            history.push((active(&plan).join(","), plan.status()));
+        }
+        let expected = [
+            ("a".into(), None),
+            ("a".into(), None),
+            ("c".into(), None),
+            ("a".into(), None),
+            ("a".into(), None),
+            ("a".into(), None),
+            ("".into(), Some(true)),
+        ];
+        assert_eq!(history, expected);
+        let behaviour = plan.cast::<TimelineBehaviour<DC>>().unwrap();
+        assert_eq!(behaviour.step(), 4);
+
+        // restarts upon entry
+        plan.exit(false);
+        plan.run();
+        assert_eq!(active(&plan), ["a"]);
+        assert_eq!(plan.cast::<TimelineBehaviour<DC>>().unwrap().step(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn streak_behaviour() {
+        use predicate::{False, True};
+        use serde_value::Value;
+        let status = |status: Option<bool>| -> Behaviours<DC> {
+            match status {
+                Some(true) => EvaluateStatus(True.into(), False.into()).into(),
+                Some(false) => EvaluateStatus(False.into(), True.into()).into(),
+                None => EvaluateStatus(False.into(), False.into()).into(),
+            }
+        };
+        let streak = StreakBehaviour::new(status(None), "wins".into(), "losses".into());
+        let mut plan = Plan::<DC>::new(streak.into(), "root", 1, true);
+        let set_status = |plan: &mut Plan<DC>, value| {
+            *plan.cast_mut::<StreakBehaviour<DC>>().unwrap().behaviour = status(value);
+            plan.run();
+        };
+        let streaks = |plan: &Plan<DC>| {
+            let count = |key| plan.data().get(key).cloned();
+            (count("wins"), count("losses"))
+        };
+        let counts = |wins, losses| (Some(Value::U64(wins)), Some(Value::U64(losses)));
+        plan.run();
+        assert_eq!(streaks(&plan), (None, None));
+        // settled status is counted once, in progress does not break the streak
+        for wins in 1..=3 {
+            set_status(&mut plan, Some(true));
+            set_status(&mut plan, Some(true));
+            assert_eq!(streaks(&plan), counts(wins, 0));
+            set_status(&mut plan, None);
+        }
+        // failure resets the success streak
+        for losses in 1..=2 {
+            set_status(&mut plan, Some(false));
+            assert_eq!(streaks(&plan), counts(0, losses));
+            set_status(&mut plan, None);
+        }
+        set_status(&mut plan, Some(true));
+        assert_eq!(streaks(&plan), counts(1, 0));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn reward_accumulator_behaviour() {
+        use serde_value::Value;
+        let inner = ModifyUtilityBehaviour::new(AllSuccessStatus.into(), 1., 0.);
+        let reward = RewardAccumulatorBehaviour::new(inner.into(), "return".into());
+        let mut plan = Plan::<DC>::new(reward.into(), "root", 1, true);
+        let reward = |plan: &Plan<DC>| plan.data().get("return").cloned();
+        assert_eq!(reward(&plan), None);
+
+        let utilities = [0.5, 1.25, -2., 3.];
+        for utility in utilities {
+            let behaviour = plan.cast_mut::<RewardAccumulatorBehaviour<DC>>().unwrap();
+            let inner = behaviour.behaviour.cast_mut::<ModifyUtilityBehaviour<DC>>();
+            inner.unwrap().offset = utility;
+            plan.run();
+            assert_eq!(plan.utility(), utility);
+        }
+        assert_eq!(reward(&plan), Some(Value::F64(utilities.iter().sum())));
+        assert_eq!(plan.status(), Some(true));
+
+        // persists across activations until removed by external code
+        plan.exit(false);
+        plan.run();
+        assert_eq!(reward(&plan), Some(Value::F64(5.75)));
+        plan.set_data("return", Value::Unit);
+        plan.run();
+        assert_eq!(reward(&plan), Some(Value::F64(3.)));
+    }
+
+    #[test]
+    fn delayed_status_behaviour() {
+        use predicate::{False, True};
+        // status of the inner behaviour follows the status of the child plan
+        let inner = EvaluateStatus(predicate::AllSuccess.into(), predicate::AnyFailure.into());
+        let delayed = DelayedStatusBehaviour::new(inner.into());
+        let mut plan = Plan::<DC>::new(delayed.into(), "root", 1, true);
+        let set_status = |plan: &mut Plan<DC>, t, f| {
+            let child = EvaluateStatus::<DC>(t, f);
+            plan.insert(Plan::new(child.into(), "child", 0, true));
+        };
+        set_status(&mut plan, False.into(), False.into());
+        assert_eq!(plan.status(), None);
+        plan.run();
+        assert_eq!(plan.status(), None);
+        // inner status changes are only reported after the next run
+        set_status(&mut plan, True.into(), False.into());
+        assert_eq!(plan.status(), None);
+        plan.run();
+        assert_eq!(plan.status(), Some(true));
+        set_status(&mut plan, False.into(), True.into());
+        assert_eq!(plan.status(), Some(true));
+        plan.run();
+        assert_eq!(plan.status(), Some(false));
+        // reset upon re-entry
+        plan.exit(false);
+        plan.enter(None);
+        assert_eq!(plan.status(), None);
+    }
+
+    #[test]
+    fn graceful_shutdown() {
+        #[derive(Default)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct WindDownBehaviour(Option<u32>);
+        impl<C: Config> Behaviour<C> for WindDownBehaviour {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                match self.0 {
+                    Some(0) => Some(true),
+                    _ => None,
+                }
+            }
+            fn on_run(&mut self, _plan: &mut Plan<C>) {
+                if let Some(ticks) = &mut self.0 {
+                    *ticks = ticks.saturating_sub(1);
+                }
+            }
+            fn on_shutdown(&mut self, _plan: &mut Plan<C>) {
+                self.0 = Some(3);
+            }
+        }
+
+        #[enum_dispatch(Behaviour<C>)]
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub enum ShutdownBehaviours<C: Config> {
+            MultiBehaviour(MultiBehaviour<C>),
+            WindDownBehaviour,
+        }
+
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct ShutdownConfig;
+        impl Config for ShutdownConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = ShutdownBehaviours<Self>;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        let mut plan = Plan::<ShutdownConfig>::new_stub("root", true);
+        // wrapped behaviour should receive shutdown as well
+        plan.insert(Plan::new(
+            MultiBehaviour(vec![WindDownBehaviour::default().into()]).into(),
+            "A",
+            1,
+            true,
+        ));
+        plan.insert(Plan::new(
+            WindDownBehaviour::default().into(),
+            "B",
+            1,
+            false,
+        ));
+        plan.transitions.push(Transition::new(
+            vec!["A".into()],
+            vec!["B".into()],
+            predicate::AllSuccess.into(),
+        ));
+        for _ in 0..5 {
+            plan.run();
+            assert!(plan.get("A").unwrap().active());
+            assert!(!plan.shutdown_complete());
+        }
+        plan.begin_shutdown();
+        assert!(plan.shutting_down());
+        // entries are refused while draining
+        assert!(!plan.enter_plan("B").unwrap().active());
+        for _ in 0..2 {
+            plan.run();
+            assert!(!plan.shutdown_complete());
+        }
+        plan.run();
+        assert_eq!(plan.get("A").unwrap().status(), Some(true));
+        assert!(plan.shutdown_complete());
+        // transition no longer fires despite its condition holding
+        plan.run();
+        assert!(plan.get("A").unwrap().active());
+        assert!(!plan.get("B").unwrap().active());
+        // final exit, then the tree may start again
+        plan.exit(false);
+        plan.run();
+        assert!(!plan.shutting_down());
+        assert!(plan.get("B").unwrap().active());
+    }
+
+    #[test]
+    fn smoothed_utility_behaviour() {
+        use serde_value::Value;
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct DataUtility;
+        impl<C: Config> Behaviour<C> for DataUtility {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                None
+            }
+            fn utility(&self, plan: &Plan<C>) -> f64 {
+                match plan.data().get("utility") {
+                    Some(Value::F64(x)) => *x,
+                    _ => 0.,
+                }
+            }
+        }
+
+        #[enum_dispatch(Behaviour<C>)]
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub enum SmoothBehaviours<C: Config> {
+            MaxUtilBehaviour,
+            SmoothedUtilityBehaviour(SmoothedUtilityBehaviour<C>),
+            DataUtility,
+        }
+
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct SmoothConfig;
+        impl Config for SmoothConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = SmoothBehaviours<Self>;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        type SC = SmoothConfig;
+        let set_utility = |plan: &mut Plan<SC>, utility: f64| {
+            plan.set_data("utility", Value::F64(utility));
+        };
+
+        // smoothed sequence of a noisy signal
+        let mut plan = Plan::<SC>::new(
+            SmoothedUtilityBehaviour::new(DataUtility.into(), 0.5).into(),
+            "root",
+            1,
+            true,
+        );
+        set_utility(&mut plan, 4.);
+        plan.enter(None);
+        assert_eq!(plan.utility(), 4.);
+        let noisy = [8., 0., 8., 0., 4.];
+        let expected = [6., 3., 5.5, 2.75, 3.375];
+        for (input, output) in noisy.iter().zip(expected) {
+            set_utility(&mut plan, *input);
+            plan.run();
+            assert_eq!(plan.utility(), output);
+        }
+        // entry primes the average with the current utility
+        plan.exit(false);
+        assert_eq!(plan.utility(), 3.375);
+        set_utility(&mut plan, 1.);
+        plan.enter(None);
+        assert_eq!(plan.utility(), 1.);
+        // alpha outside of range is clamped
+        plan.cast_mut::<SmoothedUtilityBehaviour<SC>>()
+            .unwrap()
+            .alpha = 2.;
+        set_utility(&mut plan, 7.);
+        plan.run();
+        assert_eq!(plan.utility(), 7.);
+
+        // count selection switches given the same noisy input
+        let count_switches = |smoothed: bool| {
+            let mut plan = Plan::<SC>::new(MaxUtilBehaviour::default().into(), "root", 1, true);
+            for name in ["A", "B"] {
+                let behaviour = match smoothed {
+                    true => SmoothedUtilityBehaviour::new(DataUtility.into(), 0.2).into(),
+                    false => DataUtility.into(),
+                };
+                plan.insert(Plan::new(behaviour, name, 1, false));
+            }
+            let mut switches = 0;
+            let mut active = String::new();
+            for i in 0..40 {
+                // both signals have the same mean with alternating noise
+                let noise = if i % 2 == 0 { 0.3 } else { -0.3 };
+                set_utility(plan.get_mut("A").unwrap(), 1. + noise);
+                set_utility(plan.get_mut("B").unwrap(), 1. - noise * 0.5);
+                plan.run();
+                let current = plan.plans.iter().find(|x| x.active()).unwrap().name();
+                if *current != active {
+                    switches += 1;
+                    active = current.clone();
+                }
+            }
+            switches
+        };
+        let raw = count_switches(false);
+        let smoothed = count_switches(true);
+        assert_eq!(raw, 40);
+        assert!(smoothed < raw / 4, "{} switches", smoothed);
+    }
 
-/// Find and return the plan with highest utility.
-pub fn max_utility<C: Config>(plans: &[Plan<C>]) -> Option<(&Plan<C>, f64)> {
-    if plans.is_empty() {
-        None
-    } else {
-        let (pos, utility) = plans
-            .iter()
-            .map(|plan| plan.utility())
-            .enumerate()
-            .fold((0, f64::NAN), |max, x| if max.1 > x.1 { max } else { x });
-        Some((&plans[pos], utility))
-    }
-}
+    #[test]
+    fn context_util_behaviour() {
+        use std::collections::BTreeMap;
+        use std::sync::{Arc, Mutex};
+        #[derive(Default)]
+        struct Scores(Mutex<BTreeMap<String, f64>>);
+        impl ScoreContext for Scores {
+            fn score(&self, name: &String) -> Option<f64> {
+                self.0.lock().unwrap().get(name).copied()
+            }
+        }
+        let scores = Arc::new(Scores::default());
+        let set_score = |name: &str, score| {
+            scores.0.lock().unwrap().insert(name.into(), score);
+        };
+        let active = |plan: &Plan<DC>| {
+            let active = plan.plans.iter().filter(|x| x.active());
+            active.map(|x| x.name().clone()).collect::<Vec<_>>()
+        };
+        let scored = |name: &str| {
+            let behaviour = ContextUtilBehaviour { default: 0.5 };
+            Plan::<DC>::new(behaviour.into(), name, 1, false)
+        };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        // default utility without a context
+        let mut plan = Plan::<DC>::new(MaxUtilBehaviour::default().into(), "root", 1, true);
+        plan.insert(scored("a"));
+        plan.insert(scored("b"));
+        plan.run();
+        assert_eq!(plan.get("a").unwrap().utility(), 0.5);
+        assert_eq!(plan.get("a").unwrap().status(), None);
+
+        // changing scores of the context changes the selected child
+        plan.set_context(Some(scores.clone()));
+        set_score("a", 2.);
+        set_score("b", 1.);
+        plan.run();
+        assert_eq!(active(&plan), ["a"]);
+        set_score("b", 3.);
+        plan.run();
+        assert_eq!(active(&plan), ["b"]);
+        assert_eq!(plan.utility(), 3.);
+
+        // subplans inserted later inherit the context, falling back to default without a score
+        plan.insert(scored("c"));
+        set_score("c", 4.);
+        plan.run();
+        assert_eq!(active(&plan), ["c"]);
+        scores.0.lock().unwrap().remove("c");
+        plan.run();
+        assert_eq!(active(&plan), ["b"]);
+        assert_eq!(plan.get("c").unwrap().utility(), 0.5);
 
-    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-    struct DefaultConfig;
-    impl Config for DefaultConfig {
-        type Predicate = predicate::Predicates;
-        type Behaviour = behaviour::Behaviours<Self>;
+        // unset context
+        plan.set_context(None);
+        assert!(plan.get("b").unwrap().context().is_none());
+        assert_eq!(plan.get("b").unwrap().utility(), 0.5);
     }
-    type DC = DefaultConfig;
 
     #[test]
-    fn evaluate_status() {
-        let make_plan = |t: bool, f: bool| {
-            Plan::<DC>::new(
-                EvaluateStatus(
-                    if t {
-                        predicate::True.into()
-                    } else {
-                        predicate::False.into()
-                    },
-                    if f {
-                        predicate::True.into()
-                    } else {
-                        predicate::False.into()
-                    },
-                )
-                .into(),
-                "root",
-                1,
-                true,
-            )
+    #[cfg(feature = "serde")]
+    fn table_util_behaviour() {
+        use serde_value::Value;
+        let behaviour = TableUtilBehaviour {
+            input_key: "distance".into(),
+            table_key: "curve".into(),
         };
+        let mut plan = Plan::<DC>::new(behaviour.into(), "root", 1, true);
+        // missing entries
+        assert_eq!(plan.utility(), 0.);
+        plan.set_data("distance", Value::F64(5.));
+        assert_eq!(plan.utility(), 0.);
 
-        let plan = make_plan(false, false);
+        let curve = vec![(0., 1.), (10., 0.5), (20., 0.)];
+        plan.set_data("curve", serde_value::to_value(curve).unwrap());
+        let utility = |plan: &mut Plan<DC>, distance: Value| {
+            plan.set_data("distance", distance);
+            plan.utility()
+        };
+        // at table points
+        assert_eq!(utility(&mut plan, Value::F64(0.)), 1.);
+        assert_eq!(utility(&mut plan, Value::F64(10.)), 0.5);
+        assert_eq!(utility(&mut plan, Value::U32(20)), 0.);
+        // between table points
+        assert_eq!(utility(&mut plan, Value::F64(5.)), 0.75);
+        assert_eq!(utility(&mut plan, Value::I64(15)), 0.25);
+        // out of range inputs are clamped
+        assert_eq!(utility(&mut plan, Value::F64(-3.)), 1.);
+        assert_eq!(utility(&mut plan, Value::F64(100.)), 0.);
+        // malformed input
+        assert_eq!(utility(&mut plan, Value::String("far".into())), 0.);
         assert_eq!(plan.status(), None);
+    }
 
-        let plan = make_plan(false, true);
-        assert_eq!(plan.status(), Some(false));
-
-        let plan = make_plan(true, false);
-        assert_eq!(plan.status(), Some(true));
-
-        let plan = make_plan(true, true);
-        assert_eq!(plan.status(), Some(false));
+    #[test]
+    fn interpolate_table_bounds() {
+        let table = [(0., 1.), (10., 0.5)];
+        assert_eq!(interpolate_table(&table, 5.), Some(0.75));
+        assert_eq!(interpolate_table(&[], 5.), None);
+        // non-finite inputs have no place in the table
+        assert_eq!(interpolate_table(&table, f64::NAN), None);
+        assert_eq!(interpolate_table(&table, f64::INFINITY), None);
+        assert_eq!(interpolate_table(&table, f64::NEG_INFINITY), None);
+        // malformed tables don't panic
+        assert_eq!(interpolate_table(&[(f64::NAN, 1.), (10., 0.5)], 5.), None);
     }
 
     #[test]
-    fn repeat_behaviour() {
-        //use tracing::info;
-        //let _ = tracing_subscriber::fmt::try_init();
-        let mut repeat = RepeatBehaviour::new(AllSuccessStatus.into());
-        repeat.iterations = 5;
-        let mut plan = Plan::<DC>::new(repeat.into(), "root", 1, true);
-        // test iteration limit
-        for _ in 0..5 {
-            plan.run();
-            assert_eq!(plan.status(), None);
+    #[cfg(feature = "serde")]
+    fn utility_distribution_behaviour() {
+        use serde_value::Value;
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct FixedUtility(pub f64);
+        impl<C: Config> Behaviour<C> for FixedUtility {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                None
+            }
+            fn utility(&self, _plan: &Plan<C>) -> f64 {
+                self.0
+            }
         }
-        plan.run();
-        assert_eq!(plan.status(), Some(true));
 
-        // test reset
-        plan.exit(false);
-        for _ in 0..5 {
-            plan.run();
-            assert_eq!(plan.status(), None);
+        #[enum_dispatch(Behaviour<C>)]
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub enum DistributionBehaviours<C: Config> {
+            EvaluateStatus(EvaluateStatus<C>),
+            UtilityDistributionBehaviour,
+            FixedUtility,
+        }
+
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct DistributionConfig;
+        impl Config for DistributionConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = DistributionBehaviours<Self>;
+            type Event = ();
+            type Name = DefaultName;
         }
+        let behaviour = UtilityDistributionBehaviour {
+            temperature: 1.,
+            out_key: "distribution".into(),
+        };
+        let mut plan = Plan::<DistributionConfig>::new(behaviour.into(), "root", 1, true);
+        let distribution = |plan: &Plan<DistributionConfig>| {
+            let entries = match &plan.data()["distribution"] {
+                Value::Seq(x) => x.clone(),
+                _ => panic!("distribution is not a sequence"),
+            };
+            entries
+                .into_iter()
+                .map(|x| match x {
+                    Value::Seq(x) => match (&x[0], &x[1]) {
+                        (Value::String(name), Value::F64(p)) => (name.clone(), *p),
+                        _ => panic!("unexpected entry"),
+                    },
+                    _ => panic!("unexpected entry"),
+                })
+                .collect::<Vec<_>>()
+        };
+        // no children yields empty distribution
         plan.run();
-        assert_eq!(plan.status(), Some(true));
+        assert!(distribution(&plan).is_empty());
+        assert_eq!(plan.status(), None);
 
-        // test stop on failure
-        plan.exit(false);
-        for _ in 0..3 {
-            plan.run();
-            assert_eq!(plan.status(), None);
+        // equal utilities yield a uniform distribution
+        for i in 0..4 {
+            plan.insert(Plan::new(FixedUtility(1.).into(), i.to_string(), 0, false));
         }
-        plan.cast_mut::<RepeatBehaviour<DC>>().unwrap().behaviour =
-            Box::new(AnySuccessStatus.into());
         plan.run();
-        assert_eq!(plan.status(), Some(false));
+        for (i, (name, p)) in distribution(&plan).into_iter().enumerate() {
+            assert_eq!(name, i.to_string());
+            assert!((p - 0.25).abs() < 1e-9);
+        }
 
-        // test retry bool
-        plan.exit(false);
-        plan.cast_mut::<RepeatBehaviour<DC>>().unwrap().stop_value = true;
-        for _ in 0..3 {
-            plan.run();
-            assert_eq!(plan.status(), None);
+        // probabilities follow utility ordering and sum to one
+        for i in 0..4 {
+            plan.insert(Plan::new(
+                FixedUtility(i as f64 * 100.).into(),
+                i.to_string(),
+                0,
+                false,
+            ));
         }
-        plan.cast_mut::<RepeatBehaviour<DC>>().unwrap().behaviour =
-            Box::new(AllSuccessStatus.into());
         plan.run();
-        assert_eq!(plan.status(), Some(true));
+        let dist = distribution(&plan);
+        assert!((dist.iter().map(|x| x.1).sum::<f64>() - 1.).abs() < 1e-9);
+        assert!(dist.windows(2).all(|x| x[0].1 < x[1].1));
+        assert!(dist[3].1 > 0.99);
+
+        // higher temperature flattens the distribution
+        plan.cast_mut::<UtilityDistributionBehaviour>()
+            .unwrap()
+            .temperature = 1e6;
+        plan.run();
+        let dist = distribution(&plan);
+        assert!((dist.iter().map(|x| x.1).sum::<f64>() - 1.).abs() < 1e-9);
+        assert!(dist.iter().all(|x| (x.1 - 0.25).abs() < 1e-3));
     }
 
     #[test]
-    fn sequence_behaviour() {
-        //use tracing::info;
-        //let _ = tracing_subscriber::fmt::try_init();
-        let mut plan = Plan::<DC>::new(SequenceBehaviour::default().into(), "root", 1, true);
-        // the first 5 child plans return success
-        for i in 0..5 {
-            plan.insert(Plan::new(AllSuccessStatus.into(), i.to_string(), 0, i == 0));
-            plan.transitions.push(Transition {
-                src: vec![i.to_string()],
-                dst: vec![(i + 1).to_string()],
-                predicate: predicate::True.into(),
-            });
+    fn paced_selector_behaviour() {
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct Candidate {
+            utility: f64,
+            status: Option<bool>,
         }
-        // the last child plan returns None
-        plan.insert(Plan::new_stub("5", false));
-        // check that child plans sequentually transition as long current child status succeeds
-        for i in 0..5 {
-            plan.run();
-            let active = plan.plans.iter().find(|x| x.active()).unwrap().name();
-            assert_eq!(active, &(i + 1).to_string());
-            assert_eq!(plan.status(), None);
+        impl<C: Config> Behaviour<C> for Candidate {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                self.status
+            }
+            fn utility(&self, _plan: &Plan<C>) -> f64 {
+                self.utility
+            }
         }
-        // check that child plans stop transitioning when current child status is None
-        for _ in 0..5 {
+
+        #[enum_dispatch(Behaviour<C>)]
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub enum PacedBehaviours<C: Config> {
+            EvaluateStatus(EvaluateStatus<C>),
+            PacedSelectorBehaviour,
+            Candidate,
+        }
+
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct PacedConfig;
+        impl Config for PacedConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = PacedBehaviours<Self>;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        let new_plan = |select| {
+            let behaviour = PacedSelectorBehaviour::new(2, select);
+            let mut plan = Plan::<PacedConfig>::new(behaviour.into(), "root", 1, true);
+            for name in ["a", "b"] {
+                let candidate = Candidate {
+                    utility: 0.,
+                    status: None,
+                };
+                plan.insert(Plan::new(candidate.into(), name, 1, false));
+            }
+            plan
+        };
+        let active = |plan: &Plan<PacedConfig>| {
+            let mut active = plan.plans.iter().filter(|x| x.active());
+            let name = active.next().map(|x| x.name().clone());
+            assert!(active.next().is_none());
+            name
+        };
+
+        // best child alternates every tick, but switches are held back by the cooldown
+        let mut plan = new_plan(SelectMode::Util);
+        let mut history = Vec::new();
+        for tick in 1..=9 {
+            let best = ["a", "b"][tick % 2];
+            for name in ["a", "b"] {
+                let candidate = plan.get_cast_mut::<Candidate, _>(name).unwrap();
+                candidate.utility = (name == best) as u8 as f64;
+            }
             plan.run();
-            let active = plan.plans.iter().find(|x| x.active()).unwrap().name();
-            assert_eq!(active, "5");
-            assert_eq!(plan.status(), None);
+            history.push(active(&plan).unwrap());
         }
-        // change the last child plan to success as well
-        plan.insert(Plan::new(AllSuccessStatus.into(), "5", 0, false));
-        // expect sequence behaviour to return success when all children are successful
+        assert_eq!(history, ["b", "b", "b", "a", "a", "a", "b", "b", "b"]);
+
+        // highest priority child that is not failing
+        let mut plan = new_plan(SelectMode::Priority);
+        plan.get_cast_mut::<Candidate, _>("a").unwrap().status = Some(false);
         plan.run();
-        assert_eq!(plan.status(), Some(true));
-        // expect that sequence will jump back to previusly successful child if status changes
-        plan.insert(Plan::new_stub("3", false));
+        assert_eq!(active(&plan).unwrap(), "b");
+        assert_eq!(
+            plan.cast::<PacedSelectorBehaviour>().unwrap().countdown(),
+            2
+        );
+        plan.get_cast_mut::<Candidate, _>("a").unwrap().status = None;
         plan.run();
-        assert_eq!(plan.plans.iter().find(|x| x.active()).unwrap().name(), "3");
-        assert_eq!(plan.status(), None);
-        // same test above with failure status instead
-        plan.insert(Plan::new(AnySuccessStatus.into(), "1", 0, false));
         plan.run();
-        assert_eq!(plan.plans.iter().find(|x| x.active()).unwrap().name(), "1");
+        assert_eq!(active(&plan).unwrap(), "b");
+        plan.run();
+        assert_eq!(active(&plan).unwrap(), "a");
+        // re-selecting the active child does not restart the cooldown
+        plan.run();
+        plan.run();
+        plan.run();
+        assert_eq!(
+            plan.cast::<PacedSelectorBehaviour>().unwrap().countdown(),
+            0
+        );
+    }
+
+    #[test]
+    fn conditional_invert_behaviour() {
+        use predicate::{False, True};
+        let behaviour = ConditionalInvertBehaviour::<DC> {
+            behaviour: Box::new(EvaluateStatus(True.into(), False.into()).into()),
+            invert_when: False.into(),
+        };
+        let mut plan = Plan::<DC>::new(behaviour.into(), "root", 1, true);
+        plan.run();
+        // pass through
+        assert_eq!(plan.status(), Some(true));
+        fn invert(plan: &mut Plan<DC>) -> &mut ConditionalInvertBehaviour<DC> {
+            plan.cast_mut().unwrap()
+        }
+        // invert once the predicate holds
+        invert(&mut plan).invert_when = True.into();
         assert_eq!(plan.status(), Some(false));
+        // in progress is never inverted
+        *invert(&mut plan).behaviour = EvaluateStatus(False.into(), False.into()).into();
+        assert_eq!(plan.status(), None);
+        invert(&mut plan).invert_when = False.into();
+        assert_eq!(plan.status(), None);
+    }
+
+    #[test]
+    fn discounted_util_behaviour() {
+        let fixed = |utility| -> Behaviours<DC> {
+            ModifyUtilityBehaviour::new(AllSuccessStatus.into(), 1., utility).into()
+        };
+        let discounted = |utility, gamma| -> Behaviours<DC> {
+            DiscountedUtilBehaviour::new(fixed(utility), gamma).into()
+        };
+        // root(1) -> { a(2) -> { x(4), y(8) }, b(16) } with gamma 0.5 at each level
+        let mut root = Plan::<DC>::new(discounted(1., 0.5), "root", 1, true);
+        let mut a = Plan::new(discounted(2., 0.5), "a", 1, false);
+        a.insert(Plan::new(fixed(4.), "x", 1, false));
+        a.insert(Plan::new(fixed(8.), "y", 1, false));
+        root.insert(a);
+        root.insert(Plan::new(fixed(16.), "b", 1, false));
+        assert_eq!(root.get("a").unwrap().utility(), 2. + 0.5 * 12.);
+        assert_eq!(root.utility(), 1. + 0.5 * (8. + 16.));
+        let parts = root.utility_breakdown();
+        assert_eq!(parts.last().unwrap(), &("children".to_string(), 12.));
+
+        // gamma is clamped within [0, 1]
+        root.cast_mut::<DiscountedUtilBehaviour<DC>>()
+            .unwrap()
+            .gamma = 2.;
+        assert_eq!(root.utility(), 1. + 24.);
+        root.cast_mut::<DiscountedUtilBehaviour<DC>>()
+            .unwrap()
+            .gamma = -1.;
+        assert_eq!(root.utility(), 1.);
+    }
+
+    #[test]
+    fn utility_breakdown() {
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct FixedUtil(pub f64);
+        impl<C: Config> Behaviour<C> for FixedUtil {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                None
+            }
+            fn utility(&self, _plan: &Plan<C>) -> f64 {
+                self.0
+            }
+        }
+
+        #[enum_dispatch(Behaviour<C>)]
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub enum BreakdownBehaviours<C: Config> {
+            ModifyUtilityBehaviour(ModifyUtilityBehaviour<C>),
+            MultiBehaviour(MultiBehaviour<C>),
+            MaxUtilBehaviour,
+            FixedUtil,
+        }
+
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct BreakdownConfig;
+        impl Config for BreakdownConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = BreakdownBehaviours<Self>;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        type BC = BreakdownConfig;
+
+        // 0.5 * (2 + (3 * 2 + 1)) + 0.25
+        let scaled = ModifyUtilityBehaviour::<BC>::new(FixedUtil(3.).into(), 2., 1.);
+        let sum = MultiBehaviour::<BC>(vec![FixedUtil(2.).into(), scaled.into()]);
+        let behaviour = ModifyUtilityBehaviour::new(sum.into(), 0.5, 0.25);
+        let mut plan = Plan::<BC>::new(MaxUtilBehaviour::default().into(), "root", 1, true);
+        plan.insert(Plan::new(behaviour.into(), "a", 1, false));
+        plan.insert(Plan::new(FixedUtil(1.).into(), "b", 1, false));
+        plan.run();
+        assert!(plan.get("a").unwrap().active());
+
+        let a = plan.get("a").unwrap();
+        let parts = a.utility_breakdown();
+        let expected = [
+            ("inner.0", 2.),
+            ("inner.1.inner", 3.),
+            ("inner.1.scale", 3.),
+            ("inner.1.offset", 1.),
+            ("scale", -4.5),
+            ("offset", 0.25),
+        ];
+        let expected = expected.map(|(label, x)| (label.to_string(), x));
+        assert_eq!(parts, expected);
+        let total = parts.iter().map(|(_, x)| x).sum::<f64>();
+        assert!((total - a.utility()).abs() < 1e-9);
+        assert_eq!(a.utility(), 4.75);
+
+        // leaf behaviours report their whole utility
+        let b = plan.get("b").unwrap();
+        assert_eq!(b.utility_breakdown(), [("utility".to_string(), 1.)]);
+        assert!(Plan::<BC>::new_stub("c", false)
+            .utility_breakdown()
+            .is_empty());
     }
 
     #[test]
@@ -537,9 +5399,11 @@ mod tests {
         impl Config for TestConfig {
             type Predicate = predicate::Predicates;
             type Behaviour = TestBehaviours<Self>;
+            type Event = ();
+            type Name = DefaultName;
         }
         type TC = TestConfig;
-        let mut plan = Plan::<TC>::new(MaxUtilBehaviour.into(), "root", 1, true);
+        let mut plan = Plan::<TC>::new(MaxUtilBehaviour::default().into(), "root", 1, true);
         // insert 5 child plans with ascending utility
         for i in 0..5 {
             plan.insert(Plan::new(
@@ -583,4 +5447,132 @@ mod tests {
         assert_eq!(active.len(), 1);
         assert_eq!(active[0].name(), "2");
     }
+
+    #[test]
+    fn max_util_floor() {
+        use predicate::False;
+        let scored = |name: String, utility: f64| {
+            let pending = EvaluateStatus(False.into(), False.into()).into();
+            let behaviour = ModifyUtilityBehaviour::new(pending, 0., utility);
+            Plan::<DC>::new(behaviour.into(), name, 1, false)
+        };
+        let active = |plan: &Plan<DC>| {
+            let active = plan.plans.iter().filter(|x| x.active());
+            active.map(|x| x.name().clone()).collect::<Vec<_>>()
+        };
+        let mut plan = Plan::<DC>::new(MaxUtilBehaviour::default().into(), "root", 1, true);
+        for i in 0..5 {
+            plan.insert(scored(i.to_string(), i.into()));
+        }
+        plan.run();
+        assert_eq!(active(&plan), ["4"]);
+        // expect no plan selected while the best utility is below the floor
+        plan.cast_mut::<MaxUtilBehaviour>().unwrap().min_utility = Some(20.0);
+        for _ in 0..2 {
+            plan.run();
+            assert!(active(&plan).is_empty());
+        }
+        // raise all utilities above the floor and expect selection to resume
+        for i in 0..5 {
+            plan.insert(scored(i.to_string(), 30. + i as f64));
+        }
+        plan.run();
+        assert_eq!(active(&plan), ["4"]);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn max_util_serde() {
+        // unit struct serialized before the behaviour had fields
+        let legacy = serde_json::json!({ "MaxUtilBehaviour": null });
+        let behaviour = serde_json::from_value::<Behaviours<DC>>(legacy).unwrap();
+        let behaviour = behaviour.cast::<MaxUtilBehaviour>().unwrap();
+        assert_eq!(behaviour.min_utility, None);
+        let floor = MaxUtilBehaviour {
+            min_utility: Some(1.5),
+            use_estimates: true,
+        };
+        let json = serde_json::to_value(Behaviours::<DC>::from(floor)).unwrap();
+        let behaviour = serde_json::from_value::<Behaviours<DC>>(json).unwrap();
+        let behaviour = behaviour.cast::<MaxUtilBehaviour>().unwrap();
+        assert_eq!(behaviour.min_utility, Some(1.5));
+        assert!(behaviour.use_estimates);
+    }
+
+    #[test]
+    fn normalized_util_behaviour() {
+        use predicate::False;
+        // same preferences sampled on scales of 1 and 1000, never below a tenth of the scale
+        let preference = |name: &str, tick: u32| {
+            let x = derive_seed(7, &(name, tick)) >> 11;
+            0.1 + 0.9 * x as f64 / (1u64 << 53) as f64
+        };
+        let scales = [("a", 1.), ("b", 1000.)];
+        let selections = |behaviour: Behaviours<DC>| {
+            let mut plan = Plan::<DC>::new(behaviour, "root", 1, true);
+            for (name, _) in scales {
+                let pending = EvaluateStatus(False.into(), False.into()).into();
+                let util = ModifyUtilityBehaviour::new(pending, 0., 0.);
+                plan.insert(Plan::new(util.into(), name, 1, false));
+            }
+            let mut counts = [0; 2];
+            for tick in 0..400 {
+                for (name, scale) in scales {
+                    let util = plan.get_mut(name).unwrap().cast_mut();
+                    let util: &mut ModifyUtilityBehaviour<DC> = util.unwrap();
+                    util.offset = scale * preference(name, tick);
+                }
+                plan.run();
+                let active = plan.plans.iter().position(|x| x.active()).unwrap();
+                if tick >= 10 {
+                    counts[active] += 1;
+                }
+            }
+            (counts, plan)
+        };
+
+        let (counts, _) = selections(MaxUtilBehaviour::default().into());
+        assert_eq!(counts, [0, 390]);
+        for normalization in [Normalization::MinMax, Normalization::ZScore] {
+            let normalized = NormalizedUtilBehaviour::new(normalization, 20, 10);
+            let (counts, plan) = selections(normalized.into());
+            assert!(counts.iter().all(|x| (156..=234).contains(x)), "{counts:?}");
+            let behaviour = plan.cast::<NormalizedUtilBehaviour<DC>>().unwrap();
+            assert_eq!(behaviour.samples(&"b".into()).unwrap().len(), 20);
+        }
+
+        // raw utilities during warm-up
+        let normalized = NormalizedUtilBehaviour::<DC>::new(Normalization::MinMax, 20, 10);
+        let mut plan = Plan::<DC>::new(normalized.into(), "root", 1, true);
+        let pending = || EvaluateStatus(False.into(), False.into()).into();
+        plan.insert(Plan::new(
+            ModifyUtilityBehaviour::new(pending(), 0., 1.).into(),
+            "a",
+            1,
+            false,
+        ));
+        plan.run();
+        plan.insert(Plan::new(
+            ModifyUtilityBehaviour::new(pending(), 0., 2.).into(),
+            "b",
+            1,
+            false,
+        ));
+        for _ in 0..9 {
+            plan.run();
+            assert!(plan.get("b").unwrap().active());
+        }
+        // all samples of b are equal, so a at the top of its range wins
+        let a = plan.get_mut("a").unwrap();
+        a.cast_mut::<ModifyUtilityBehaviour<DC>>().unwrap().offset = 1.5;
+        plan.run();
+        assert!(plan.get("a").unwrap().active());
+        #[cfg(feature = "serde")]
+        {
+            let plan: Plan<DC> =
+                serde_json::from_value(serde_json::to_value(&plan).unwrap()).unwrap();
+            let behaviour = plan.cast::<NormalizedUtilBehaviour<DC>>().unwrap();
+            assert_eq!(behaviour.samples(&"a".into()).unwrap().len(), 11);
+        }
+    }
 }
@@ -0,0 +1,334 @@
+use crate::*;
+use std::fmt::Debug;
+
+/// Entry of the message [CATALOG].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Message {
+    /// Stable code prefixed by `E` for errors and `W` for warnings, never reused or renumbered.
+    pub code: &'static str,
+    /// Stable name of the kind of message.
+    pub name: &'static str,
+    /// English text with a `{field}` placeholder for each of the [Diagnostic::fields].
+    pub template: &'static str,
+}
+
+/// Every error and warning reported by this crate, ordered by code.
+pub const CATALOG: &[Message] = &[
+    Message {
+        code: "E001",
+        name: "PlanNotFound",
+        template: "plan not found: {path}",
+    },
+    Message {
+        code: "E002",
+        name: "InvalidTransitions",
+        template: "invalid transitions: {issues}",
+    },
+    Message {
+        code: "E003",
+        name: "Json",
+        template: "json error: {message}",
+    },
+    Message {
+        code: "E101",
+        name: "UnknownTransitionTarget",
+        template: "transition {transition} of plan {path} refers to missing plan {plan}",
+    },
+    Message {
+        code: "E201",
+        name: "PlanPanic",
+        template: "plan {path} panicked in hook {hook}: {message}",
+    },
+    Message {
+        code: "W101",
+        name: "UnreachableTransition",
+        template: "transition {transition} of plan {path} can never fire",
+    },
+    Message {
+        code: "W102",
+        name: "UnreachablePlan",
+        template: "plan {path} can never become active",
+    },
+    Message {
+        code: "W201",
+        name: "MissingTransitionSource",
+        template: "transition plan missing in plan {path}",
+    },
+    Message {
+        code: "W202",
+        name: "TagLimitRefusal",
+        template: "entry refused by tag limit in plan {path}",
+    },
+    Message {
+        code: "W203",
+        name: "ContainedPanic",
+        template: "panic contained by isolated plan {path}",
+    },
+    Message {
+        code: "W204",
+        name: "UndeliverableBroadcast",
+        template: "broadcast dropped by plan {path}",
+    },
+    Message {
+        code: "W301",
+        name: "AlwaysFiringTransition",
+        template: "transition {transition} of plan {path} always fires and has no max_firings",
+    },
+    Message {
+        code: "W302",
+        name: "BehaviourNeverRuns",
+        template: "plan {path} has a behaviour that its execution mode never runs",
+    },
+    Message {
+        code: "W303",
+        name: "UnsortedSequence",
+        template: "sequence {path} steps in transition {transition} from {src} to {dst}, which sorts first",
+    },
+    Message {
+        code: "W304",
+        name: "UnreadDataKey",
+        template: "data key {key} of plan {path} is never read",
+    },
+    Message {
+        code: "W305",
+        name: "UnusedUtility",
+        template: "plan {path} has utility that its parent never uses",
+    },
+    Message {
+        code: "W306",
+        name: "EmptySelector",
+        template: "plan {path} selects among subplans but has none",
+    },
+    Message {
+        code: "W307",
+        name: "SelfTransition",
+        template: "transition {transition} of plan {path} leads back to its own source",
+    },
+    Message {
+        code: "W308",
+        name: "DuplicateTransition",
+        template: "transition {transition} of plan {path} duplicates transition {other}",
+    },
+    Message {
+        code: "W309",
+        name: "ExclusiveAutostart",
+        template: "plan {path} expects one active subplan but {count} autostart",
+    },
+    Message {
+        code: "W310",
+        name: "DuplicateLabel",
+        template: "transition label {label} is used more than once in plan {path}",
+    },
+    Message {
+        code: "W311",
+        name: "ExhaustedTransition",
+        template: "transition {transition} of plan {path} has max_firings 0 and never fires",
+    },
+    Message {
+        code: "W312",
+        name: "UnusedTagLimit",
+        template: "tag limit {tag} of plan {path} matches no plan",
+    },
+];
+
+/// Catalog entry of the given code.
+pub fn catalog_message(code: &str) -> Option<&'static Message> {
+    CATALOG.iter().find(|x| x.code == code)
+}
+
+/// Replace the `{field}` placeholders of `template` with the values of matching fields.
+pub fn render_template(template: &str, fields: &[(&'static str, String)]) -> String {
+    let mut text = template.to_string();
+    for (name, value) in fields {
+        text = text.replace(&format!("{{{name}}}"), value);
+    }
+    text
+}
+
+/// Error or warning with a stable code and structured fields, for tools rendering their own messages.
+pub trait Diagnostic {
+    /// Code of the [CATALOG] entry describing this diagnostic.
+    fn code(&self) -> &'static str;
+    /// Named values referred to by the catalog template, formatted as text.
+    fn fields(&self) -> Vec<(&'static str, String)>;
+    /// English message rendered from the catalog.
+    fn message(&self) -> String {
+        let message = catalog_message(self.code()).expect("code missing from catalog");
+        render_template(message.template, &self.fields())
+    }
+}
+
+impl<N: Debug> Diagnostic for Error<N> {
+    fn code(&self) -> &'static str {
+        match self {
+            Error::PlanNotFound(_) => "E001",
+            Error::InvalidTransitions(_) => "E002",
+            #[cfg(feature = "serde")]
+            Error::Json(_) => "E003",
+        }
+    }
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Error::PlanNotFound(path) => vec![("path", format!("{path:?}"))],
+            Error::InvalidTransitions(issues) => vec![("issues", format!("{issues:?}"))],
+            #[cfg(feature = "serde")]
+            Error::Json(message) => vec![("message", message.clone())],
+        }
+    }
+}
+
+impl<N: Debug> Diagnostic for ValidationIssue<N> {
+    fn code(&self) -> &'static str {
+        match self {
+            ValidationIssue::MissingPlan { .. } => "E101",
+            ValidationIssue::UnreachableTransition { .. } => "W101",
+            ValidationIssue::UnreachablePlan { .. } => "W102",
+        }
+    }
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        match self {
+            ValidationIssue::MissingPlan {
+                path,
+                transition,
+                plan,
+            } => vec![
+                ("path", format!("{path:?}")),
+                ("transition", transition.to_string()),
+                ("plan", format!("{plan:?}")),
+            ],
+            ValidationIssue::UnreachableTransition { path, transition } => vec![
+                ("path", format!("{path:?}")),
+                ("transition", transition.to_string()),
+            ],
+            ValidationIssue::UnreachablePlan { path } => vec![("path", format!("{path:?}"))],
+        }
+    }
+}
+
+impl Diagnostic for Warning {
+    fn code(&self) -> &'static str {
+        self.code
+    }
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![("path", self.path.clone())]
+    }
+}
+
+impl Diagnostic for PlanPanic {
+    fn code(&self) -> &'static str {
+        "E201"
+    }
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("path", self.path.join("/")),
+            ("hook", self.hook.to_string()),
+            ("message", self.message.clone()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// Placeholders of a template, in order of appearance.
+    fn placeholders(template: &str) -> BTreeSet<&str> {
+        let starts = template.split('{').skip(1);
+        starts
+            .filter_map(|x| x.split_once('}'))
+            .map(|x| x.0)
+            .collect()
+    }
+
+    fn assert_complete(diagnostic: &impl Diagnostic) {
+        let message = catalog_message(diagnostic.code()).unwrap();
+        let fields = diagnostic.fields();
+        let names = fields.iter().map(|x| x.0).collect::<BTreeSet<_>>();
+        assert_eq!(names, placeholders(message.template), "{}", message.code);
+        let text = diagnostic.message();
+        assert!(names.iter().all(|x| !text.contains(&format!("{{{x}}}"))));
+    }
+
+    #[test]
+    fn stable_codes() {
+        // codes may be appended but never changed
+        let golden = [
+            ("E001", "PlanNotFound"),
+            ("E002", "InvalidTransitions"),
+            ("E003", "Json"),
+            ("E101", "UnknownTransitionTarget"),
+            ("E201", "PlanPanic"),
+            ("W101", "UnreachableTransition"),
+            ("W102", "UnreachablePlan"),
+            ("W201", "MissingTransitionSource"),
+            ("W202", "TagLimitRefusal"),
+            ("W203", "ContainedPanic"),
+            ("W204", "UndeliverableBroadcast"),
+            ("W301", "AlwaysFiringTransition"),
+            ("W302", "BehaviourNeverRuns"),
+            ("W303", "UnsortedSequence"),
+            ("W304", "UnreadDataKey"),
+            ("W305", "UnusedUtility"),
+            ("W306", "EmptySelector"),
+            ("W307", "SelfTransition"),
+            ("W308", "DuplicateTransition"),
+            ("W309", "ExclusiveAutostart"),
+            ("W310", "DuplicateLabel"),
+            ("W311", "ExhaustedTransition"),
+            ("W312", "UnusedTagLimit"),
+        ];
+        let codes = CATALOG.iter().map(|x| (x.code, x.name)).collect::<Vec<_>>();
+        assert_eq!(codes, golden);
+    }
+
+    #[test]
+    fn diagnostic_fields() {
+        let path = vec!["a".to_string(), "b".to_string()];
+        let missing = ValidationIssue::MissingPlan {
+            path: path.clone(),
+            transition: 2,
+            plan: "c".to_string(),
+        };
+        assert_eq!(missing.code(), "E101");
+        assert_eq!(
+            missing.message(),
+            r#"transition 2 of plan ["a", "b"] refers to missing plan "c""#
+        );
+        assert_complete(&missing);
+        assert_complete(&ValidationIssue::UnreachableTransition {
+            path: path.clone(),
+            transition: 0,
+        });
+        assert_complete(&ValidationIssue::UnreachablePlan { path: path.clone() });
+
+        let error = Error::PlanNotFound(path.clone());
+        assert_eq!(error.fields(), [("path", r#"["a", "b"]"#.to_string())]);
+        assert_eq!(error.to_string(), r#"plan not found: ["a", "b"]"#);
+        assert_complete(&error);
+        assert_complete(&Error::InvalidTransitions(vec![missing]));
+        #[cfg(feature = "serde")]
+        assert_complete(&Error::<String>::Json("eof".into()));
+
+        for code in ["W201", "W202", "W203", "W204"] {
+            let warning = Warning {
+                code,
+                path: "a".to_string(),
+                count: 1,
+                suppressed: 0,
+                logged_tick: None,
+            };
+            assert_complete(&warning);
+        }
+        let panic = PlanPanic {
+            path: path.iter().map(|x| format!("{x:?}")).collect(),
+            hook: "run",
+            message: "boom".into(),
+        };
+        assert_eq!(
+            panic.to_string(),
+            r#"plan "a"/"b" panicked in hook run: boom"#
+        );
+        assert_complete(&panic);
+    }
+}
@@ -0,0 +1,104 @@
+//! Bounds checked by [assert_config](crate::assert_config) and related macros, with targeted error messages.
+//!
+//! Each trait is implemented exactly for the types satisfying the bound it is named after,
+//! which depends on the enabled crate features as described in [features](crate::features).
+
+use crate::features::*;
+use crate::*;
+
+/// Implemented for types implementing [Behaviour] for config `C`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `Behaviour<{C}>`",
+    label = "not a behaviour of `{C}`",
+    note = "implement `dynamic_plan_tree::Behaviour<C>` for `{Self}`, or dispatch it with `#[enum_dispatch(Behaviour<C>)]`"
+)]
+pub trait IsBehaviour<C> {}
+impl<C: Config, T: Behaviour<C>> IsBehaviour<C> for T {}
+
+/// Implemented for types implementing [Predicate].
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `Predicate`",
+    label = "not a predicate",
+    note = "implement `dynamic_plan_tree::Predicate` for `{Self}`, or dispatch it with `#[enum_dispatch(Predicate)]`"
+)]
+pub trait IsPredicate {}
+impl<T: Predicate> IsPredicate for T {}
+
+/// Implemented for types implementing [EnumCast].
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `EnumCast`",
+    label = "cannot be cast to its variants",
+    note = "add `#[derive(EnumCast)]` to the enum"
+)]
+pub trait IsEnumCast {}
+impl<T: EnumCast> IsEnumCast for T {}
+
+/// Implemented for enums convertible from `T`, as for each variant of an enum_dispatch enum.
+#[diagnostic::on_unimplemented(
+    message = "`{T}` is not a variant of `{Self}`",
+    label = "not a variant of `{Self}`",
+    note = "add a variant holding `{T}` to the enum, for which `#[enum_dispatch]` implements `From`"
+)]
+pub trait HasVariant<T> {}
+impl<T, E: From<T>> HasVariant<T> for E {}
+
+/// Implemented for all types, or only `Send` types when the `rayon` feature is enabled.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not `Send`, as required by the `rayon` feature",
+    label = "shared across threads by parallel execution",
+    note = "avoid non-`Send` fields such as `Rc` or `RefCell` in behaviours, predicates, events, and names"
+)]
+pub trait IsSend {}
+impl<T: MaybeSend> IsSend for T {}
+
+/// Implemented for all types, or only `Sync` types when the `rayon` feature is enabled.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not `Sync`, as required by the `rayon` feature",
+    label = "shared across threads by parallel execution"
+)]
+pub trait IsSync {}
+impl<T: MaybeSync> IsSync for T {}
+
+/// Implemented for all types, or only serializable types when the `serde` feature is enabled.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `Serialize` and `DeserializeOwned`, as required by the `serde` feature",
+    label = "saved and loaded along with the tree",
+    note = "add `#[cfg_attr(feature = \"serde\", derive(Serialize, Deserialize))]` to `{Self}`"
+)]
+pub trait IsSerde {}
+impl<T: MaybeSerde> IsSerde for T {}
+
+/// Implemented for types that may name plans.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot name plans",
+    label = "not a plan name",
+    note = "plan names must implement `Ord + Clone + Debug + 'static`"
+)]
+pub trait IsName {}
+impl<T: Ord + Clone + std::fmt::Debug + 'static> IsName for T {}
+
+/// Check every bound of the associated types of config `C`. See [assert_config](crate::assert_config).
+pub fn config<C: Config>()
+where
+    BehaviourOf<C>: IsBehaviour<C> + IsEnumCast + IsSend + IsSerde,
+    PredicateOf<C>: IsPredicate + IsEnumCast + IsSend + IsSerde,
+    EventOf<C>: IsEnumCast + IsSend + IsSerde,
+    NameOf<C>: IsName + IsSend + IsSync + IsSerde,
+{
+}
+
+/// Check that `B` is a behaviour of config `C`. See [assert_behaviour_in_config](crate::assert_behaviour_in_config).
+pub fn behaviour_in_config<C: Config, B>()
+where
+    B: IsBehaviour<C> + IsSend + IsSerde,
+    BehaviourOf<C>: HasVariant<B>,
+{
+}
+
+/// Check that `P` is a predicate of config `C`. See [assert_predicate_in_config](crate::assert_predicate_in_config).
+pub fn predicate_in_config<C: Config, P>()
+where
+    P: IsPredicate + IsSend + IsSerde,
+    PredicateOf<C>: HasVariant<P>,
+{
+}
@@ -0,0 +1,150 @@
+//! Ready-made [Config] types, for examples, quick experiments, and trees needing nothing custom.
+//!
+//! [Default] has all the built-in [Behaviours] and [Predicates] with string names,
+//! while [Minimal] trades most of them for a smaller footprint on constrained targets.
+//!
+//! ```
+//! use dynamic_plan_tree::config::*;
+//! use dynamic_plan_tree::prelude::*;
+//! # use dynamic_plan_tree::config::fixtures::active_names as active;
+//!
+//! let mut root = Plan::<config::Default>::new_stub("root", true);
+//! root.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+//! root.insert(Plan::new_stub("b", false));
+//! root.transitions.push(transition!("a" => "b"));
+//! root.run();
+//! assert_eq!(active(&root), ["b"]);
+//! ```
+
+use crate::behaviour::*;
+use crate::predicate::{AllFailure, AllSuccess, AnyFailure, AnySuccess, False, Predicates, True};
+
+/// Config with the built-in behaviours and predicates, string names, and [DefaultEvent] events.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Default;
+impl Config for Default {
+    type Predicate = Predicates;
+    type Behaviour = Behaviours<Self>;
+    type Event = DefaultEvent;
+    type Name = DefaultName;
+}
+
+/// Events of [Default], including those posted by [EventBroadcastBehaviour].
+#[derive(EnumCast)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DefaultEvent {
+    Broadcast(Broadcast),
+}
+
+/// Config with a handful of status predicates and composite behaviours, integer names, and no events.
+///
+/// Keeps plans small and the dispatch enums short where [Default] is too heavy, e.g. embedded targets.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Minimal;
+impl Config for Minimal {
+    type Predicate = MinimalPredicates;
+    type Behaviour = MinimalBehaviours<Self>;
+    type Event = ();
+    type Name = u16;
+}
+
+/// Predicates of [Minimal].
+#[enum_dispatch(Predicate)]
+#[derive(EnumCast)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MinimalPredicates {
+    True,
+    False,
+    AllSuccess,
+    AnySuccess,
+    AllFailure,
+    AnyFailure,
+}
+
+/// Behaviours of [Minimal].
+#[enum_dispatch(Behaviour<C>)]
+#[derive(EnumCast)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MinimalBehaviours<C: Config> {
+    AllSuccessStatus,
+    AnySuccessStatus,
+    EvaluateStatus(EvaluateStatus<C>),
+    SequenceBehaviour(SequenceBehaviour<C>),
+    FallbackBehaviour(FallbackBehaviour<C>),
+    MaxUtilBehaviour,
+}
+
+/// Plans of [Default] for the tests and examples of this crate.
+#[doc(hidden)]
+pub mod fixtures {
+    use super::*;
+
+    /// Plan without subplans whose status is fixed to `status`.
+    pub fn leaf(name: &str, autostart: bool, status: Option<bool>) -> Plan<Default> {
+        let behaviour = match status {
+            Some(true) => AllSuccessStatus.into(),
+            Some(false) => AnySuccessStatus.into(),
+            None => EvaluateStatus(False.into(), False.into()).into(),
+        };
+        Plan::new(behaviour, name, 1, autostart)
+    }
+
+    /// Plan without subplans whose status is `None` and utility is fixed to `utility`.
+    pub fn scored(name: &str, autostart: bool, utility: f64) -> Plan<Default> {
+        let pending = EvaluateStatus(False.into(), False.into()).into();
+        let behaviour = ModifyUtilityBehaviour::new(pending, 0., utility);
+        Plan::new(behaviour.into(), name, 1, autostart)
+    }
+
+    /// Names of the active subplans of `plan` in priority order.
+    pub fn active_names(plan: &Plan<Default>) -> Vec<&str> {
+        let active = plan.plans.iter().filter(|x| x.active());
+        active.map(|x| x.name().as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::*;
+    use super::*;
+
+    #[test]
+    fn example_leaves() {
+        let status = |x| leaf("a", true, x).status();
+        assert_eq!(status(Some(true)), Some(true));
+        assert_eq!(status(Some(false)), Some(false));
+        assert_eq!(status(None), None);
+        let plan = scored("a", true, 2.5);
+        assert_eq!((plan.status(), plan.utility()), (None, 2.5));
+
+        let mut root = Plan::new_stub("root", true);
+        root.insert(leaf("b", true, None));
+        root.insert(leaf("a", false, None));
+        root.insert(leaf("c", true, None));
+        root.run();
+        assert_eq!(active_names(&root), ["b", "c"]);
+    }
+
+    #[test]
+    fn minimal() {
+        let new_plan = |behaviour: MinimalBehaviours<Minimal>, name: u16, autostart| {
+            Plan::<Minimal>::new(behaviour, name, 1, autostart)
+        };
+        let sequence = SequenceBehaviour::<Minimal>::new();
+        let transitions = sequence.generate_transitions(&[1, 2, 10]);
+        let mut root = new_plan(sequence.into(), 0, true);
+        root.transitions = transitions;
+        root.insert(new_plan(AllSuccessStatus.into(), 10, false));
+        root.insert(new_plan(AllSuccessStatus.into(), 1, true));
+        let pending = EvaluateStatus(False.into(), False.into());
+        root.insert(new_plan(pending.into(), 2, false));
+        // 1 succeeds right away, while 2 stays pending and 10 is never reached
+        for _ in 0..2 {
+            root.run();
+            let active = root.plans.iter().filter(|x| x.active());
+            assert_eq!(active.map(|x| *x.name()).collect::<Vec<_>>(), [2]);
+        }
+        assert_eq!(root.status(), None);
+        assert!(!AnyFailure.evaluate(&root, &[]));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+
+/// Change to a plan tree requested from outside of the thread running it. See [Plan::controller].
+///
+/// Paths are names of subplans relative to the plan applying the command, where an empty path refers to that plan.
+pub enum PlanCommand<C: Config> {
+    /// Enter the subplan at the path, with no effect while its parent is inactive. See [Plan::enter_plan].
+    EnterPlan(Vec<C::Name>),
+    /// Exit the subplan at the path if active. See [Plan::exit_plan].
+    ExitPlan(Vec<C::Name>),
+    /// Insert a data entry into the plan at the path. See [Plan::set_data].
+    SetData(Vec<C::Name>, String, serde_value::Value),
+    /// Post an event to the plan at the path. See [Plan::post_event].
+    PostEvent(Vec<C::Name>, C::Event),
+    /// Set [Plan::run_interval] of the plan at the path.
+    SetRunInterval(Vec<C::Name>, u32),
+}
+
+impl<C: Config> PlanCommand<C> {
+    /// Path of the plan this command applies to.
+    pub fn path(&self) -> &[C::Name] {
+        match self {
+            PlanCommand::EnterPlan(path)
+            | PlanCommand::ExitPlan(path)
+            | PlanCommand::SetData(path, ..)
+            | PlanCommand::PostEvent(path, _)
+            | PlanCommand::SetRunInterval(path, _) => path,
+        }
+    }
+}
+
+/// Result of a command applied by [Plan::apply_commands], along with the ID returned when it was sent.
+pub type CommandAck<N = DefaultName> = (u64, Result<(), Error<N>>);
+
+/// Sending side of a command queue, which may be cloned and sent to other threads. See [Plan::controller].
+pub struct ControllerHandle<C: Config> {
+    sender: SyncSender<(u64, PlanCommand<C>)>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl<C: Config> Clone for ControllerHandle<C> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            next_id: self.next_id.clone(),
+        }
+    }
+}
+
+impl<C: Config> ControllerHandle<C> {
+    /// Enqueue a command, blocking while the queue is full, and return its ID.
+    ///
+    /// Returns the command back if the inbox has been dropped.
+    pub fn send(&self, command: PlanCommand<C>) -> Result<u64, PlanCommand<C>> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        match self.sender.send((id, command)) {
+            Ok(()) => Ok(id),
+            Err(e) => Err(e.0 .1),
+        }
+    }
+
+    /// Enqueue a command without blocking and return its ID.
+    ///
+    /// Returns the command back if the queue is full or the inbox has been dropped.
+    pub fn try_send(&self, command: PlanCommand<C>) -> Result<u64, PlanCommand<C>> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        match self.sender.try_send((id, command)) {
+            Ok(()) => Ok(id),
+            Err(TrySendError::Full(x) | TrySendError::Disconnected(x)) => Err(x.1),
+        }
+    }
+}
+
+/// Receiving side of a command queue, kept by the thread running the tree. See [Plan::apply_commands].
+pub struct CommandInbox<C: Config> {
+    receiver: Receiver<(u64, PlanCommand<C>)>,
+}
+
+impl<C: Config> Plan<C> {
+    /// Create a bounded queue of commands to apply to a tree from other threads.
+    ///
+    /// Once `capacity` commands are pending, [ControllerHandle::send] blocks until the tree applies them,
+    /// while [ControllerHandle::try_send] fails, leaving controllers to retry or drop the command.
+    /// Each command sent is assigned a unique ID.
+    pub fn controller(capacity: usize) -> (ControllerHandle<C>, CommandInbox<C>) {
+        let (sender, receiver) = sync_channel(capacity);
+        let handle = ControllerHandle {
+            sender,
+            next_id: Arc::default(),
+        };
+        (handle, CommandInbox { receiver })
+    }
+
+    /// Apply all pending commands of the inbox in the order they were received, typically once per tick before [Plan::run].
+    ///
+    /// Returns the result of each command by its ID, failing with [Error::PlanNotFound] for missing plans.
+    pub fn apply_commands(&mut self, inbox: &mut CommandInbox<C>) -> Vec<CommandAck<C::Name>> {
+        inbox
+            .receiver
+            .try_iter()
+            .map(|(id, command)| (id, self.apply_command(command)))
+            .collect()
+    }
+
+    fn apply_command(&mut self, command: PlanCommand<C>) -> Result<(), Error<C::Name>> {
+        let path = command.path();
+        let not_found = || Error::PlanNotFound(path.to_vec());
+        // enter and exit through the parent of the target plan
+        if let PlanCommand::EnterPlan(_) | PlanCommand::ExitPlan(_) = command {
+            let (name, parent) = path.split_last().ok_or_else(not_found)?;
+            let parent = self.descendant_mut(parent).ok_or_else(not_found)?;
+            parent.get(name).ok_or_else(not_found)?;
+            match command {
+                PlanCommand::EnterPlan(_) => parent.enter_plan(name),
+                _ => parent.exit_plan(name),
+            };
+            return Ok(());
+        }
+        let plan = self.descendant_mut(path).ok_or_else(not_found)?;
+        match command {
+            PlanCommand::SetData(_, key, value) => {
+                plan.set_data(key, value);
+            }
+            PlanCommand::PostEvent(_, event) => plan.post_event(event),
+            PlanCommand::SetRunInterval(_, run_interval) => plan.run_interval = run_interval,
+            PlanCommand::EnterPlan(_) | PlanCommand::ExitPlan(_) => unreachable!(),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_value::Value;
+
+    type DC = config::Default;
+
+    #[test]
+    fn concurrent_commands() {
+        let mut root_plan = Plan::<DC>::new_stub("root", true);
+        let a = root_plan.insert(Plan::new_stub("a", false));
+        a.insert(Plan::new_stub("x", false));
+        root_plan.insert(Plan::new_stub("b", true));
+        let (handle, mut inbox) = Plan::<DC>::controller(4);
+        let path = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+
+        let controller = {
+            let handle = handle.clone();
+            std::thread::spawn(move || {
+                let commands = (0..20).flat_map(|i| {
+                    [
+                        PlanCommand::EnterPlan(path(&["a"])),
+                        PlanCommand::EnterPlan(path(&["a", "x"])),
+                        PlanCommand::SetData(path(&["a", "x"]), "i".into(), Value::U32(i)),
+                        PlanCommand::SetRunInterval(path(&["b"]), i),
+                        PlanCommand::ExitPlan(path(&["c"])),
+                    ]
+                });
+                commands
+                    .map(|x| handle.send(x).ok().unwrap())
+                    .collect::<Vec<_>>()
+            })
+        };
+        let mut acks = Vec::new();
+        while !controller.is_finished() || acks.len() < 100 {
+            acks.extend(root_plan.apply_commands(&mut inbox));
+            root_plan.run();
+        }
+        let sent = controller.join().unwrap();
+
+        // applied exactly once in order of sending
+        let ids = acks.iter().map(|x| x.0).collect::<Vec<_>>();
+        assert_eq!(ids, sent);
+        assert_eq!(ids, (0..100).collect::<Vec<_>>());
+        for (id, result) in &acks {
+            match id % 5 {
+                4 => assert_eq!(result, &Err(Error::PlanNotFound(path(&["c"])))),
+                _ => assert_eq!(result, &Ok(())),
+            }
+        }
+        let x = root_plan.get("a").unwrap().get("x").unwrap();
+        assert!(x.active());
+        assert_eq!(x.data().get("i"), Some(&Value::U32(19)));
+        assert_eq!(root_plan.get("b").unwrap().run_interval, 19);
+
+        // back-pressure once the queue is full
+        for _ in 0..4 {
+            handle.try_send(PlanCommand::ExitPlan(path(&["a"]))).ok();
+        }
+        let rejected = handle.try_send(PlanCommand::ExitPlan(path(&["a"])));
+        assert!(rejected.is_err());
+        let acks = root_plan.apply_commands(&mut inbox);
+        assert_eq!(acks.len(), 4);
+        assert!(!root_plan.get("a").unwrap().active());
+        // empty paths refer to the plan itself, which cannot be entered this way
+        handle
+            .send(PlanCommand::PostEvent(
+                vec![],
+                config::DefaultEvent::Broadcast(behaviour::Broadcast("x".into())),
+            ))
+            .ok()
+            .unwrap();
+        handle.send(PlanCommand::EnterPlan(vec![])).ok().unwrap();
+        let acks = root_plan.apply_commands(&mut inbox);
+        assert_eq!(acks[0].1, Ok(()));
+        assert_eq!(acks[1].1, Err(Error::PlanNotFound(vec![])));
+    }
+}
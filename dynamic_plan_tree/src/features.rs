@@ -0,0 +1,77 @@
+//! Crate feature detection for code generic over [Config].
+//!
+//! The bounds required of `Config` types change with the enabled crate features:
+//! `rayon` requires `Send` (and `Sync` for names) and `serde` requires `Serialize + DeserializeOwned`.
+//! Bounding on the `Maybe*` traits instead compiles under every feature combination.
+//!
+//! ```
+//! use dynamic_plan_tree::features::*;
+//! use dynamic_plan_tree::*;
+//!
+//! // compiles under any feature combination, since the traits are implied by `Config`
+//! fn erase<C: Config>(behaviour: BehaviourOf<C>) -> impl MaybeSend + MaybeSerde {
+//!     behaviour
+//! }
+//! ```
+
+use crate::*;
+
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+
+/// Whether the `serde` feature is enabled.
+pub const SERDE: bool = cfg!(feature = "serde");
+/// Whether the `rayon` feature is enabled.
+pub const RAYON: bool = cfg!(feature = "rayon");
+/// Whether the `journal` feature is enabled.
+pub const JOURNAL: bool = cfg!(feature = "journal");
+/// Whether the `timing` feature is enabled.
+pub const TIMING: bool = cfg!(feature = "timing");
+/// Whether the `invariants` feature is enabled.
+pub const INVARIANTS: bool = cfg!(feature = "invariants");
+/// Whether the `arena` feature is enabled.
+pub const ARENA: bool = cfg!(feature = "arena");
+/// Whether the `test-util` feature is enabled.
+pub const TEST_UTIL: bool = cfg!(feature = "test-util");
+
+/// `Send` when the `rayon` feature is enabled, otherwise implemented for all types.
+#[cfg(feature = "rayon")]
+pub trait MaybeSend: Send {}
+#[cfg(feature = "rayon")]
+impl<T: Send> MaybeSend for T {}
+/// `Send` when the `rayon` feature is enabled, otherwise implemented for all types.
+#[cfg(not(feature = "rayon"))]
+pub trait MaybeSend {}
+#[cfg(not(feature = "rayon"))]
+impl<T> MaybeSend for T {}
+
+/// `Sync` when the `rayon` feature is enabled, otherwise implemented for all types.
+#[cfg(feature = "rayon")]
+pub trait MaybeSync: Sync {}
+#[cfg(feature = "rayon")]
+impl<T: Sync> MaybeSync for T {}
+/// `Sync` when the `rayon` feature is enabled, otherwise implemented for all types.
+#[cfg(not(feature = "rayon"))]
+pub trait MaybeSync {}
+#[cfg(not(feature = "rayon"))]
+impl<T> MaybeSync for T {}
+
+/// `Serialize + DeserializeOwned` when the `serde` feature is enabled, otherwise implemented for all types.
+#[cfg(feature = "serde")]
+pub trait MaybeSerde: Serialize + DeserializeOwned {}
+#[cfg(feature = "serde")]
+impl<T: Serialize + DeserializeOwned> MaybeSerde for T {}
+/// `Serialize + DeserializeOwned` when the `serde` feature is enabled, otherwise implemented for all types.
+#[cfg(not(feature = "serde"))]
+pub trait MaybeSerde {}
+#[cfg(not(feature = "serde"))]
+impl<T> MaybeSerde for T {}
+
+/// Predicate type of a config.
+pub type PredicateOf<C> = <C as Config>::Predicate;
+/// Behaviour type of a config.
+pub type BehaviourOf<C> = <C as Config>::Behaviour;
+/// Event type of a config.
+pub type EventOf<C> = <C as Config>::Event;
+/// Plan name type of a config.
+pub type NameOf<C> = <C as Config>::Name;
@@ -1,12 +1,36 @@
+#[cfg(feature = "arena")]
+pub use arena::Arena;
 pub use behaviour::Behaviour;
+pub use catalog::*;
+pub use control::*;
 pub use enum_cast::*;
 pub use enum_dispatch::enum_dispatch;
+use features::{MaybeSend, MaybeSerde, MaybeSync};
+pub use matrix::*;
 pub use plan::*;
 pub use predicate::Predicate;
+pub use serde_value;
+pub use snapshot::*;
 
 #[cfg(feature = "serde")]
 pub use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "arena")]
+pub mod arena;
 pub mod behaviour;
+pub mod catalog;
+pub mod checks;
+pub mod control;
+pub mod features;
+#[cfg(feature = "serde")]
+pub mod lint;
+mod macros;
+pub mod matrix;
 pub mod plan;
 pub mod predicate;
+// after the traits its enums dispatch through
+pub mod config;
+pub mod prelude;
+pub mod snapshot;
+#[cfg(feature = "test-util")]
+pub mod test_util;
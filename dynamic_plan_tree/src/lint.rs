@@ -0,0 +1,791 @@
+//! Style and robustness warnings on plan trees, beyond the structural checks of [Plan::validate].
+//!
+//! Each [Lint] rule reports issues under a stable code in the [CATALOG], with a default [Severity]
+//! that may be overridden, or the rule disabled, by a [LintConfig].
+//!
+//! ```
+//! use dynamic_plan_tree::{config::*, lint::*, *};
+//!
+//! let mut root = Plan::<config::Default>::new_stub("root", true);
+//! root.insert(Plan::new_stub("a", true));
+//! root.insert(Plan::new_stub("b", false));
+//! root.transitions.push(transition!("a" => "b"));
+//! let diagnostics = lint::run(&root, &LintConfig::default());
+//! assert_eq!(diagnostics[0].code, "W301");
+//! assert_eq!(
+//!     diagnostics[0].message(),
+//!     "transition 0 of plan [] always fires and has no max_firings"
+//! );
+//! // silenced by disabling the rule
+//! let mut config = LintConfig::default();
+//! config.disabled.insert("W301".into());
+//! assert!(lint::run(&root, &config).is_empty());
+//! ```
+
+use crate::*;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Debug;
+
+/// How seriously an issue found by a [Lint] rule should be taken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Selection of [Lint] rules and their severities, by code.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LintConfig {
+    /// Codes of the rules to skip.
+    #[serde(default)]
+    pub disabled: BTreeSet<String>,
+    /// Severities overriding the defaults of rules.
+    #[serde(default)]
+    pub severities: BTreeMap<String, Severity>,
+}
+
+impl LintConfig {
+    /// Whether the rule with the given code is run.
+    pub fn is_enabled(&self, code: &str) -> bool {
+        !self.disabled.contains(code)
+    }
+
+    /// Severity of issues with the given code, falling back to the default of the rule.
+    pub fn severity(&self, code: &str, default: Severity) -> Severity {
+        self.severities.get(code).copied().unwrap_or(default)
+    }
+}
+
+/// Issue found by a [Lint] rule, before its code and severity are attached.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding<N = DefaultName> {
+    /// Path of the plan the issue was found in, relative to the linted plan.
+    pub path: Vec<N>,
+    /// Values of the placeholders of the catalog template besides `path`.
+    pub fields: Vec<(&'static str, String)>,
+}
+
+/// Issue reported by [run], rendered through [Diagnostic].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LintDiagnostic<N = DefaultName> {
+    /// Code of the rule in the [CATALOG].
+    pub code: &'static str,
+    pub severity: Severity,
+    /// Path of the plan the issue was found in, relative to the linted plan.
+    pub path: Vec<N>,
+    fields: Vec<(&'static str, String)>,
+}
+
+impl<N: Debug> Diagnostic for LintDiagnostic<N> {
+    fn code(&self) -> &'static str {
+        self.code
+    }
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![("path", format!("{:?}", self.path))];
+        fields.extend(self.fields.iter().cloned());
+        fields
+    }
+}
+
+/// See [Diagnostic].
+impl<N: Debug> std::fmt::Display for LintDiagnostic<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+/// Rule checking a plan tree for a single kind of issue.
+pub trait Lint<C: Config> {
+    /// Code of the [CATALOG] entry describing the issues found by this rule.
+    fn code(&self) -> &'static str;
+    /// Severity of the issues found unless overridden by [LintConfig::severities].
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+    /// Issues found in `tree` and all of its subplans.
+    fn check(&self, tree: &Plan<C>) -> Vec<Finding<C::Name>>;
+}
+
+/// Every built-in rule, ordered by code.
+pub fn rules<C: Config>() -> Vec<Box<dyn Lint<C>>> {
+    vec![
+        Box::new(AlwaysFiringTransition),
+        Box::new(BehaviourNeverRuns),
+        Box::new(UnsortedSequence),
+        Box::new(UnreadDataKey),
+        Box::new(UnusedUtility),
+        Box::new(EmptySelector),
+        Box::new(SelfTransition),
+        Box::new(DuplicateTransition),
+        Box::new(ExclusiveAutostart),
+        Box::new(DuplicateLabel),
+        Box::new(ExhaustedTransition),
+        Box::new(UnusedTagLimit),
+    ]
+}
+
+/// Check `plan` and all of its subplans with the built-in [rules] enabled by `config`.
+pub fn run<C: Config>(plan: &Plan<C>, config: &LintConfig) -> Vec<LintDiagnostic<C::Name>> {
+    run_rules(plan, config, &rules())
+}
+
+/// Same as [run] with the given rules, e.g. the built-in [rules] extended by custom ones.
+///
+/// Diagnostics are grouped by rule, in the order of `rules`.
+pub fn run_rules<C: Config>(
+    plan: &Plan<C>,
+    config: &LintConfig,
+    rules: &[Box<dyn Lint<C>>],
+) -> Vec<LintDiagnostic<C::Name>> {
+    let rules = rules.iter().filter(|x| config.is_enabled(x.code()));
+    rules
+        .flat_map(|rule| {
+            let severity = config.severity(rule.code(), rule.severity());
+            rule.check(plan).into_iter().map(move |x| LintDiagnostic {
+                code: rule.code(),
+                severity,
+                path: x.path,
+                fields: x.fields,
+            })
+        })
+        .collect()
+}
+
+/// Visit `plan` and all of its subplans depth first, along with their paths relative to `plan`.
+pub fn visit<C: Config>(plan: &Plan<C>, f: &mut impl FnMut(&[C::Name], &Plan<C>)) {
+    fn visit_path<C: Config>(
+        plan: &Plan<C>,
+        path: &mut Vec<C::Name>,
+        f: &mut impl FnMut(&[C::Name], &Plan<C>),
+    ) {
+        f(path, plan);
+        for x in &plan.plans {
+            path.push(x.name().clone());
+            visit_path(x, path, f);
+            path.pop();
+        }
+    }
+    visit_path(plan, &mut Vec::new(), f)
+}
+
+/// Findings of `check` on each plan in `tree`, given the path of the plan.
+fn check_each<C: Config>(
+    tree: &Plan<C>,
+    mut check: impl FnMut(&Plan<C>) -> Vec<Vec<(&'static str, String)>>,
+) -> Vec<Finding<C::Name>> {
+    let mut findings = Vec::new();
+    visit(tree, &mut |path, plan| {
+        findings.extend(check(plan).into_iter().map(|fields| Finding {
+            path: path.to_vec(),
+            fields,
+        }));
+    });
+    findings
+}
+
+/// Visit every object entry within `value` recursively, which includes every enum variant and its content.
+fn visit_entries(value: &Value, f: &mut impl FnMut(&str, &Value)) {
+    match value {
+        Value::Object(entries) => entries.iter().for_each(|(key, value)| {
+            f(key, value);
+            visit_entries(value, f);
+        }),
+        Value::Array(values) => values.iter().for_each(|x| visit_entries(x, f)),
+        _ => {}
+    }
+}
+
+/// String fields of every `variant` within `value`, such as the `key` of [predicate::DataValue].
+fn variant_fields(value: &Value, variant: &str, fields: &[&str]) -> Vec<String> {
+    let mut found = Vec::new();
+    visit_entries(value, &mut |key, content| {
+        if key == variant {
+            let strings = fields.iter().filter_map(|x| content.get(x)?.as_str());
+            found.extend(strings.map(String::from));
+        }
+    });
+    found
+}
+
+/// Behaviour and predicates of `plan` serialized for inspection, or nulls if serialization fails.
+fn serialized<C: Config>(plan: &Plan<C>) -> Vec<Value> {
+    let behaviour = serde_json::to_value(&plan.behaviour).unwrap_or_default();
+    let autostart_if = serde_json::to_value(plan.autostart_if()).unwrap_or_default();
+    let transitions = plan.transitions.iter();
+    let transitions = transitions.flat_map(|x| {
+        [&x.predicate]
+            .into_iter()
+            .chain(x.clauses.iter().map(|x| &x.1))
+    });
+    let transitions = transitions.map(|x| serde_json::to_value(x).unwrap_or_default());
+    [behaviour, autostart_if]
+        .into_iter()
+        .chain(transitions)
+        .collect()
+}
+
+/// Whether the predicates of `transition` hold regardless of the state of the tree.
+fn always_fires<P: Predicate, N>(transition: &Transition<P, N>) -> bool {
+    let threshold = transition.threshold.unwrap_or(1.);
+    let clauses = transition.clauses.iter();
+    transition.predicate.is_statically_false() == Some(true)
+        && threshold <= 1.
+        && clauses
+            .map(|x| x.1.is_statically_false())
+            .all(|x| x == Some(true))
+}
+
+/// Behaviours whose utility is meant to be compared by a parent.
+const UTILITY_SOURCES: &[&str] = &[
+    "ModifyUtilityBehaviour",
+    "SmoothedUtilityBehaviour",
+    "DiscountedUtilBehaviour",
+    "ContextUtilBehaviour",
+    "TableUtilBehaviour",
+];
+
+/// Behaviours that compare the utility of their subplans.
+const UTILITY_CONSUMERS: &[&str] = &[
+    "MaxUtilBehaviour",
+    "NormalizedUtilBehaviour",
+    "UtilityDistributionBehaviour",
+];
+
+fn type_name<C: Config>(plan: &Plan<C>) -> Option<&'static str> {
+    Some(plan.behaviour_dyn()?.type_name())
+}
+
+/// `W301`: Transition with a predicate that always holds and no [Transition::max_firings],
+/// so it fires on every tick its source plans are active.
+pub struct AlwaysFiringTransition;
+impl<C: Config> Lint<C> for AlwaysFiringTransition {
+    fn code(&self) -> &'static str {
+        "W301"
+    }
+    fn check(&self, tree: &Plan<C>) -> Vec<Finding<C::Name>> {
+        check_each(tree, |plan| {
+            let transitions = plan.transitions.iter().enumerate();
+            let transitions =
+                transitions.filter(|(_, x)| always_fires(x) && x.max_firings.is_none());
+            transitions
+                .map(|(i, _)| vec![("transition", i.to_string())])
+                .collect()
+        })
+    }
+}
+
+/// `W302`: Plan with a behaviour that its [Plan::execution] never runs, such as [ExecMode::StructureOnly].
+pub struct BehaviourNeverRuns;
+impl<C: Config> Lint<C> for BehaviourNeverRuns {
+    fn code(&self) -> &'static str {
+        "W302"
+    }
+    fn check(&self, tree: &Plan<C>) -> Vec<Finding<C::Name>> {
+        check_each(tree, |plan| {
+            if plan.behaviour.is_some() && !plan.execution.runs_behaviour() {
+                vec![vec![]]
+            } else {
+                vec![]
+            }
+        })
+    }
+}
+
+/// `W303`: Transition of a [behaviour::SequenceBehaviour] or [behaviour::FallbackBehaviour]
+/// stepping to a subplan whose name sorts first, which suggests the steps are not in the intended order.
+pub struct UnsortedSequence;
+impl<C: Config> Lint<C> for UnsortedSequence {
+    fn code(&self) -> &'static str {
+        "W303"
+    }
+    fn severity(&self) -> Severity {
+        Severity::Info
+    }
+    fn check(&self, tree: &Plan<C>) -> Vec<Finding<C::Name>> {
+        check_each(tree, |plan| {
+            let sequences = ["SequenceBehaviour", "FallbackBehaviour"];
+            if !type_name(plan).is_some_and(|x| sequences.contains(&x)) {
+                return Vec::new();
+            }
+            let steps = plan.transitions.iter().enumerate().filter_map(|(i, x)| {
+                let ([src], [dst], []) = (x.src.as_slice(), x.dst.as_slice(), x.clauses.as_slice())
+                else {
+                    return None;
+                };
+                (dst < src).then_some((i, src, dst))
+            });
+            steps
+                .map(|(i, src, dst)| {
+                    vec![
+                        ("transition", i.to_string()),
+                        ("src", format!("{src:?}")),
+                        ("dst", format!("{dst:?}")),
+                    ]
+                })
+                .collect()
+        })
+    }
+}
+
+/// `W304`: Data key present in a plan or written by a built-in behaviour, which no [predicate::DataValue]
+/// or built-in behaviour anywhere in the tree reads.
+pub struct UnreadDataKey;
+impl<C: Config> Lint<C> for UnreadDataKey {
+    fn code(&self) -> &'static str {
+        "W304"
+    }
+    fn check(&self, tree: &Plan<C>) -> Vec<Finding<C::Name>> {
+        let mut read = BTreeSet::new();
+        visit(tree, &mut |_, plan| {
+            for value in serialized(plan) {
+                read.extend(variant_fields(&value, "DataValue", &["key"]));
+                let table = ["input_key", "table_key"];
+                read.extend(variant_fields(&value, "TableUtilBehaviour", &table));
+                // kept in the data of the root
+                read.extend(variant_fields(
+                    &value,
+                    "SemaphoreBehaviour",
+                    &["counter_key"],
+                ));
+            }
+        });
+        check_each(tree, |plan| {
+            let behaviour = serde_json::to_value(&plan.behaviour).unwrap_or_default();
+            let mut written = plan.data().keys().cloned().collect::<BTreeSet<_>>();
+            let writers = [
+                ("RewardAccumulatorBehaviour", "reward_key"),
+                ("UtilityDistributionBehaviour", "out_key"),
+            ];
+            for (variant, field) in writers {
+                written.extend(variant_fields(&behaviour, variant, &[field]));
+            }
+            let unread = written.into_iter().filter(|x| !read.contains(x));
+            unread.map(|x| vec![("key", format!("{x:?}"))]).collect()
+        })
+    }
+}
+
+/// `W305`: Subplan with a behaviour providing utility under a parent that never compares utilities,
+/// either by its behaviour or by [predicate::UtilityDelta] and thresholds in its transitions.
+pub struct UnusedUtility;
+impl<C: Config> Lint<C> for UnusedUtility {
+    fn code(&self) -> &'static str {
+        "W305"
+    }
+    fn check(&self, tree: &Plan<C>) -> Vec<Finding<C::Name>> {
+        let mut findings = Vec::new();
+        visit(tree, &mut |path, plan| {
+            let paced = plan.cast::<behaviour::PacedSelectorBehaviour>();
+            let uses_utility = type_name(plan).is_some_and(|x| UTILITY_CONSUMERS.contains(&x))
+                || paced.is_some_and(|x| x.select == behaviour::SelectMode::Util)
+                || plan.transitions.iter().any(|x| x.threshold.is_some())
+                || serialized(plan)
+                    .iter()
+                    .any(|x| !variant_fields(x, "UtilityDelta", &["a", "b"]).is_empty());
+            if uses_utility {
+                return;
+            }
+            let sources = plan.plans.iter();
+            let sources =
+                sources.filter(|x| type_name(x).is_some_and(|x| UTILITY_SOURCES.contains(&x)));
+            findings.extend(sources.map(|x| Finding {
+                path: path.iter().chain([x.name()]).cloned().collect(),
+                fields: Vec::new(),
+            }));
+        });
+        findings
+    }
+}
+
+/// `W306`: Plan whose behaviour expects to select one of its subplans, see [Behaviour::exclusive_subplans],
+/// but has none.
+pub struct EmptySelector;
+impl<C: Config> Lint<C> for EmptySelector {
+    fn code(&self) -> &'static str {
+        "W306"
+    }
+    fn check(&self, tree: &Plan<C>) -> Vec<Finding<C::Name>> {
+        check_each(tree, |plan| {
+            let selector = plan.behaviour_dyn().is_some_and(|x| x.exclusive_subplans());
+            if selector && plan.plans.is_empty() {
+                vec![vec![]]
+            } else {
+                vec![]
+            }
+        })
+    }
+}
+
+/// `W307`: Transition whose `dst` plans are the same as its `src` plans, which re-enters nothing
+/// since active plans stay active.
+pub struct SelfTransition;
+impl<C: Config> Lint<C> for SelfTransition {
+    fn code(&self) -> &'static str {
+        "W307"
+    }
+    fn check(&self, tree: &Plan<C>) -> Vec<Finding<C::Name>> {
+        check_each(tree, |plan| {
+            let transitions = plan.transitions.iter().enumerate().filter(|(_, x)| {
+                let src = x.sources().collect::<BTreeSet<_>>();
+                !src.is_empty() && src == x.dst.iter().collect()
+            });
+            transitions
+                .map(|(i, _)| vec![("transition", i.to_string())])
+                .collect()
+        })
+    }
+}
+
+/// `W308`: Transition with the same plans and predicate as an earlier transition of the same plan,
+/// which can only fire if the earlier one has exhausted its [Transition::max_firings].
+pub struct DuplicateTransition;
+impl<C: Config> Lint<C> for DuplicateTransition {
+    fn code(&self) -> &'static str {
+        "W308"
+    }
+    fn check(&self, tree: &Plan<C>) -> Vec<Finding<C::Name>> {
+        check_each(tree, |plan| {
+            let mut seen = BTreeMap::new();
+            let mut found = Vec::new();
+            for (i, x) in plan.transitions.iter().enumerate() {
+                let predicate = (&x.predicate, &x.clauses);
+                let predicate = serde_json::to_string(&predicate).unwrap_or_default();
+                let key = (&x.src, &x.dst, predicate, x.threshold.map(f64::to_bits));
+                match seen.get(&key) {
+                    Some(other) => found.push(vec![
+                        ("transition", i.to_string()),
+                        ("other", format!("{other}")),
+                    ]),
+                    None => {
+                        seen.insert(key, i);
+                    }
+                }
+            }
+            found
+        })
+    }
+}
+
+/// `W309`: Plan whose behaviour expects one active subplan at a time, see [Behaviour::exclusive_subplans],
+/// with several subplans that autostart.
+pub struct ExclusiveAutostart;
+impl<C: Config> Lint<C> for ExclusiveAutostart {
+    fn code(&self) -> &'static str {
+        "W309"
+    }
+    fn check(&self, tree: &Plan<C>) -> Vec<Finding<C::Name>> {
+        check_each(tree, |plan| {
+            let exclusive = plan.behaviour_dyn().is_some_and(|x| x.exclusive_subplans());
+            let count = plan.plans.iter().filter(|x| x.autostart).count();
+            if exclusive && count > 1 {
+                vec![vec![("count", count.to_string())]]
+            } else {
+                vec![]
+            }
+        })
+    }
+}
+
+/// `W310`: Label shared by several transitions of the same plan, which [predicate::TransitionFired]
+/// cannot tell apart.
+pub struct DuplicateLabel;
+impl<C: Config> Lint<C> for DuplicateLabel {
+    fn code(&self) -> &'static str {
+        "W310"
+    }
+    fn check(&self, tree: &Plan<C>) -> Vec<Finding<C::Name>> {
+        check_each(tree, |plan| {
+            let mut counts = BTreeMap::<&str, usize>::new();
+            for label in plan.transitions.iter().filter_map(|x| x.label.as_deref()) {
+                *counts.entry(label).or_default() += 1;
+            }
+            let repeated = counts.into_iter().filter(|x| x.1 > 1);
+            repeated
+                .map(|(label, _)| vec![("label", format!("{label:?}"))])
+                .collect()
+        })
+    }
+}
+
+/// `W311`: Transition with a [Transition::max_firings] of 0, which never fires.
+pub struct ExhaustedTransition;
+impl<C: Config> Lint<C> for ExhaustedTransition {
+    fn code(&self) -> &'static str {
+        "W311"
+    }
+    fn check(&self, tree: &Plan<C>) -> Vec<Finding<C::Name>> {
+        check_each(tree, |plan| {
+            let transitions = plan.transitions.iter().enumerate();
+            let transitions = transitions.filter(|(_, x)| x.max_firings == Some(0));
+            transitions
+                .map(|(i, _)| vec![("transition", i.to_string())])
+                .collect()
+        })
+    }
+}
+
+/// `W312`: Entry of [Plan::tag_limits] for a tag that no plan in the tree carries.
+///
+/// Only checked on the linted plan itself, since tag limits only take effect on the root.
+pub struct UnusedTagLimit;
+impl<C: Config> Lint<C> for UnusedTagLimit {
+    fn code(&self) -> &'static str {
+        "W312"
+    }
+    fn check(&self, tree: &Plan<C>) -> Vec<Finding<C::Name>> {
+        let mut tags = BTreeSet::new();
+        visit(tree, &mut |_, plan| {
+            tags.extend(plan.tags().iter().cloned())
+        });
+        let mut unused = tree.tag_limits().0.keys().filter(|x| !tags.contains(*x));
+        let mut unused = unused.by_ref().collect::<Vec<_>>();
+        unused.sort();
+        let findings = unused.into_iter().map(|x| Finding {
+            path: Vec::new(),
+            fields: vec![("tag", format!("{x:?}"))],
+        });
+        findings.collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behaviour::*;
+    use crate::config::fixtures::*;
+    use crate::predicate::*;
+
+    fn finding(path: &[&str], fields: &[(&'static str, &str)]) -> Finding {
+        Finding {
+            path: path.iter().map(|x| x.to_string()).collect(),
+            fields: fields.iter().map(|x| (x.0, x.1.to_string())).collect(),
+        }
+    }
+
+    fn stub_ab() -> Plan<config::Default> {
+        let mut root = Plan::new_stub("root", true);
+        root.insert(leaf("a", true, None));
+        root.insert(leaf("b", false, None));
+        root
+    }
+
+    fn sequence(order: &[&str]) -> Plan<config::Default> {
+        let sequence = SequenceBehaviour::<config::Default>::new();
+        let names = order.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        let transitions = sequence.generate_transitions(&names);
+        let mut plan = Plan::new(sequence.into(), "seq", 1, true);
+        plan.transitions = transitions;
+        for (i, name) in order.iter().enumerate() {
+            plan.insert(leaf(name, i == 0, None));
+        }
+        plan
+    }
+
+    #[test]
+    fn always_firing_transition() {
+        let mut root = stub_ab();
+        root.transitions.push(transition!("a" => "b"));
+        let found = vec![finding(&[], &[("transition", "0")])];
+        assert_eq!(AlwaysFiringTransition.check(&root), found);
+        root.transitions[0].max_firings = Some(1);
+        assert!(AlwaysFiringTransition.check(&root).is_empty());
+        root.transitions[0] = transition!("a" => "b", AllSuccess);
+        assert!(AlwaysFiringTransition.check(&root).is_empty());
+    }
+
+    #[test]
+    fn behaviour_never_runs() {
+        let mut root = stub_ab();
+        root.insert(Plan::new(AllSuccessStatus.into(), "c", 0, false));
+        assert!(BehaviourNeverRuns.check(&root).is_empty());
+        root.get_mut("c").unwrap().execution = ExecMode::StructureOnly;
+        assert_eq!(BehaviourNeverRuns.check(&root), [finding(&["c"], &[])]);
+    }
+
+    #[test]
+    fn unsorted_sequence() {
+        let fields = [("transition", "0"), ("src", "\"b\""), ("dst", "\"a\"")];
+        assert_eq!(
+            UnsortedSequence.check(&sequence(&["b", "a"])),
+            [finding(&[], &fields)]
+        );
+        assert!(UnsortedSequence.check(&sequence(&["a", "b"])).is_empty());
+        // plans without sequence behaviours may transition in any order
+        let mut root = stub_ab();
+        root.transitions.push(transition!("b" => "a", AllSuccess));
+        assert!(UnsortedSequence.check(&root).is_empty());
+    }
+
+    #[test]
+    fn unread_data_key() {
+        let mut root = stub_ab();
+        root.get_mut("a")
+            .unwrap()
+            .set_data("x", serde_value::Value::Bool(true));
+        let found = [finding(&["a"], &[("key", "\"x\"")])];
+        assert_eq!(UnreadDataKey.check(&root), found);
+        let read = DataValue { key: "x".into() };
+        root.transitions.push(transition!("a" => "b", read));
+        assert!(UnreadDataKey.check(&root).is_empty());
+        // keys written by behaviours count as well
+        let accumulator = RewardAccumulatorBehaviour::new(AllSuccessStatus.into(), "reward".into());
+        root.insert(Plan::new(accumulator.into(), "c", 1, false));
+        let found = [finding(&["c"], &[("key", "\"reward\"")])];
+        assert_eq!(UnreadDataKey.check(&root), found);
+        root.remove("c");
+        // semaphore counters kept in the data of the root are read by the semaphores
+        let semaphore = SemaphoreBehaviour::new("n", 1, AllSuccessStatus.into());
+        root.insert(Plan::new(semaphore.into(), "c", 1, true));
+        root.run();
+        assert!(root.data().contains_key("n"));
+        assert!(UnreadDataKey.check(&root).is_empty());
+    }
+
+    #[test]
+    fn unused_utility() {
+        let mut root = Plan::<config::Default>::new_stub("root", true);
+        root.insert(scored("a", true, 1.));
+        root.insert(leaf("b", false, None));
+        assert_eq!(UnusedUtility.check(&root), [finding(&["a"], &[])]);
+        root.behaviour = Some(Box::new(MaxUtilBehaviour::default().into()));
+        assert!(UnusedUtility.check(&root).is_empty());
+        root.behaviour = None;
+        let delta = UtilityDelta {
+            a: "a".into(),
+            b: "b".into(),
+        };
+        root.transitions.push(transition!("a" => "b", delta));
+        assert!(UnusedUtility.check(&root).is_empty());
+    }
+
+    #[test]
+    fn empty_selector() {
+        let plan = Plan::<config::Default>::new(SequenceBehaviour::new().into(), "seq", 1, true);
+        assert_eq!(EmptySelector.check(&plan), [finding(&[], &[])]);
+        assert!(EmptySelector.check(&sequence(&["a"])).is_empty());
+        assert!(EmptySelector.check(&leaf("a", true, None)).is_empty());
+    }
+
+    #[test]
+    fn self_transition() {
+        let mut root = stub_ab();
+        root.transitions.push(transition!("a" => "b"));
+        root.transitions.push(transition!(["a", "b"] => ["b", "a"]));
+        let found = [finding(&[], &[("transition", "1")])];
+        assert_eq!(SelfTransition.check(&root), found);
+        root.transitions.pop();
+        assert!(SelfTransition.check(&root).is_empty());
+    }
+
+    #[test]
+    fn duplicate_transition() {
+        let mut root = stub_ab();
+        root.transitions.push(transition!("a" => "b", AllSuccess));
+        root.transitions.push(transition!("a" => "b", AnySuccess));
+        assert!(DuplicateTransition.check(&root).is_empty());
+        root.transitions.push(transition!("a" => "b", AllSuccess));
+        let found = [finding(&[], &[("transition", "2"), ("other", "0")])];
+        assert_eq!(DuplicateTransition.check(&root), found);
+    }
+
+    #[test]
+    fn exclusive_autostart() {
+        let mut plan = sequence(&["a", "b"]);
+        assert!(ExclusiveAutostart.check(&plan).is_empty());
+        plan.get_mut("b").unwrap().autostart = true;
+        let found = [finding(&[], &[("count", "2")])];
+        assert_eq!(ExclusiveAutostart.check(&plan), found);
+        // plans without selectors may autostart any number of subplans
+        let mut root = stub_ab();
+        root.get_mut("b").unwrap().autostart = true;
+        assert!(ExclusiveAutostart.check(&root).is_empty());
+    }
+
+    #[test]
+    fn duplicate_label() {
+        let mut root = stub_ab();
+        for label in ["x", "y"] {
+            let mut transition = transition!("a" => "b", AllSuccess);
+            transition.label = Some(label.into());
+            root.transitions.push(transition);
+        }
+        assert!(DuplicateLabel.check(&root).is_empty());
+        root.transitions[1].label = Some("x".into());
+        let found = [finding(&[], &[("label", "\"x\"")])];
+        assert_eq!(DuplicateLabel.check(&root), found);
+    }
+
+    #[test]
+    fn exhausted_transition() {
+        let mut root = stub_ab();
+        root.transitions.push(transition!("a" => "b"));
+        root.transitions[0].max_firings = Some(1);
+        assert!(ExhaustedTransition.check(&root).is_empty());
+        root.transitions[0].max_firings = Some(0);
+        let found = [finding(&[], &[("transition", "0")])];
+        assert_eq!(ExhaustedTransition.check(&root), found);
+    }
+
+    #[test]
+    fn unused_tag_limit() {
+        let mut root = stub_ab();
+        root.tag_limits_mut().0.insert("t".into(), 1);
+        let found = [finding(&[], &[("tag", "\"t\"")])];
+        assert_eq!(UnusedTagLimit.check(&root), found);
+        root.get_mut("b").unwrap().tags_mut().push("t".into());
+        assert!(UnusedTagLimit.check(&root).is_empty());
+    }
+
+    #[test]
+    fn run_config() {
+        let mut root = stub_ab();
+        root.transitions.push(transition!("a" => "a"));
+        let diagnostics = run(&root, &LintConfig::default());
+        let codes = diagnostics.iter().map(|x| (x.code, x.severity));
+        let expected = [("W301", Severity::Warning), ("W307", Severity::Warning)];
+        assert_eq!(codes.collect::<Vec<_>>(), expected);
+        for diagnostic in &diagnostics {
+            let message = catalog_message(diagnostic.code).unwrap();
+            let names = diagnostic.fields().into_iter().map(|x| x.0);
+            let placeholders = message.template.split('{').skip(1);
+            let placeholders = placeholders.map(|x| x.split('}').next().unwrap());
+            assert_eq!(
+                names.collect::<BTreeSet<_>>(),
+                placeholders.collect::<BTreeSet<_>>()
+            );
+        }
+        assert_eq!(
+            diagnostics[1].to_string(),
+            "transition 0 of plan [] leads back to its own source"
+        );
+
+        let mut config = LintConfig::default();
+        config.disabled.insert("W301".into());
+        config.severities.insert("W307".into(), Severity::Error);
+        let diagnostics = run(&root, &config);
+        let codes = diagnostics.iter().map(|x| (x.code, x.severity));
+        assert_eq!(codes.collect::<Vec<_>>(), [("W307", Severity::Error)]);
+    }
+
+    #[test]
+    fn custom_rule() {
+        struct NoPlans;
+        impl Lint<config::Default> for NoPlans {
+            fn code(&self) -> &'static str {
+                "W306"
+            }
+            fn check(&self, tree: &Plan<config::Default>) -> Vec<Finding> {
+                let empty = tree.plans.is_empty();
+                empty.then(|| finding(&[], &[])).into_iter().collect()
+            }
+        }
+        let mut rules = rules::<config::Default>();
+        rules.push(Box::new(NoPlans));
+        let plan = leaf("a", true, None);
+        let diagnostics = run_rules(&plan, &LintConfig::default(), &rules);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].path, Vec::<String>::new());
+    }
+}
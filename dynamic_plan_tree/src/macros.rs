@@ -0,0 +1,175 @@
+/// Macro to declaratively construct a plan tree.
+///
+/// Each plan is declared by its name, followed by optional attributes `interval = <u32>`, `autostart`,
+/// and `description = <str>`,
+/// then a body of optional fields `behaviour`, `data`, `children`, and `transitions`.
+/// Plans with a behaviour default to a run interval of 1.
+/// Plan names are given as string literals, so the config's `Name` must implement `From<&str>`.
+///
+/// ```ignore
+/// let plan = plan_tree!(MyConfig; "root" interval = 1 autostart {
+///     behaviour: SequenceBehaviour::default(),
+///     data: { "speed": 2.5 },
+///     children: {
+///         "a" autostart { behaviour: Wait(3) },
+///         "b" {},
+///     },
+///     transitions: [
+///         ["a"] -> ["b"] if predicate::AllSuccess,
+///     ],
+/// });
+/// ```
+#[macro_export]
+macro_rules! plan_tree {
+    ($config:ty; $name:literal $($key:ident $(= $value:literal)?)* { $($body:tt)* }) => {{
+        #[allow(unused_mut)]
+        let mut plan = $crate::Plan::<$config>::new_stub($name, false);
+        $crate::plan_tree!(@body $config; plan; $($body)*);
+        $($crate::plan_tree!(@attr plan; $key $(= $value)?);)*
+        plan
+    }};
+
+    (@attr $plan:ident; interval = $value:literal) => {
+        $plan.run_interval = $value;
+    };
+    (@attr $plan:ident; autostart) => {
+        $plan.autostart = true;
+    };
+    (@attr $plan:ident; description = $value:literal) => {
+        $plan.set_description(Some($value.into()));
+    };
+
+    (@body $config:ty; $plan:ident;) => {};
+    (@body $config:ty; $plan:ident; behaviour: $behaviour:expr $(, $($rest:tt)*)?) => {
+        $plan.set_behaviour($behaviour.into());
+        $crate::plan_tree!(@body $config; $plan; $($($rest)*)?);
+    };
+    (@body $config:ty; $plan:ident; data: { $($key:literal: $value:expr),* $(,)? } $(, $($rest:tt)*)?) => {
+        $($plan.set_data($key, $crate::serde_value::to_value($value).unwrap());)*
+        $crate::plan_tree!(@body $config; $plan; $($($rest)*)?);
+    };
+    (@body $config:ty; $plan:ident; children: {
+        $($name:literal $($key:ident $(= $value:literal)?)* { $($body:tt)* }),* $(,)?
+    } $(, $($rest:tt)*)?) => {
+        $($plan.insert($crate::plan_tree!($config; $name $($key $(= $value)?)* { $($body)* }));)*
+        $crate::plan_tree!(@body $config; $plan; $($($rest)*)?);
+    };
+    (@body $config:ty; $plan:ident; transitions: [
+        $([$($src:literal),* $(,)?] -> [$($dst:literal),* $(,)?] $(if $predicate:expr)?),* $(,)?
+    ] $(, $($rest:tt)*)?) => {
+        $($plan.transitions.push($crate::transition!([$($src),*] => [$($dst),*] $(, $predicate)?));)*
+        $crate::plan_tree!(@body $config; $plan; $($($rest)*)?);
+    };
+}
+
+/// Macro to construct an interruptible [Transition](crate::Transition) from `src` to `dst` plans.
+///
+/// Takes either single names or bracketed lists of names, and an optional predicate that defaults to
+/// [predicate::True](crate::predicate::True). Remaining fields can be set on the result.
+///
+/// ```ignore
+/// plan.transitions.push(transition!("a" => "b"));
+/// plan.transitions.push(transition!("a" => "b", predicate::AllSuccess));
+/// plan.transitions.push(transition!(["a", "b"] => ["c"], predicate::AnyFailure));
+/// ```
+#[macro_export]
+macro_rules! transition {
+    ([$($src:expr),* $(,)?] => [$($dst:expr),* $(,)?] $(, $predicate:expr)? $(,)?) => {
+        $crate::Transition::new(
+            vec![$($src.into()),*],
+            vec![$($dst.into()),*],
+            $crate::transition!(@predicate $($predicate)?),
+        )
+    };
+    (@predicate) => {
+        $crate::predicate::True.into()
+    };
+    (@predicate $predicate:expr) => {
+        $predicate.into()
+    };
+    ($src:expr => $dst:expr $(, $predicate:expr)? $(,)?) => {
+        $crate::transition!([$src] => [$dst] $(, $predicate)?)
+    };
+}
+
+/// Macro to return early from a behaviour hook once the tree has been cancelled.
+///
+/// Takes the plan reference and an optional return value. See [CancelToken](crate::CancelToken).
+///
+/// ```ignore
+/// fn on_run(&mut self, plan: &mut Plan<C>) {
+///     for item in &self.work {
+///         bail_if_cancelled!(plan);
+///         process(item);
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! bail_if_cancelled {
+    ($plan:expr $(, $value:expr)?) => {
+        if $plan.cancel_token().is_cancelled() {
+            return $($value)?;
+        }
+    };
+}
+
+/// Expand the tokens only if the `serde` feature of this crate is enabled, regardless of the calling crate.
+#[doc(hidden)]
+#[cfg(feature = "serde")]
+#[macro_export]
+macro_rules! __if_serde {
+    ($($tokens:tt)*) => {
+        $($tokens)*
+    };
+}
+#[doc(hidden)]
+#[cfg(not(feature = "serde"))]
+#[macro_export]
+macro_rules! __if_serde {
+    ($($tokens:tt)*) => {};
+}
+
+/// Macro to check at compile time that a type is a valid [Config](crate::Config) under the enabled crate features.
+///
+/// Reports each unsatisfied bound of the config and its associated types by a targeted message
+/// at the macro call, rather than by trait bound errors within uses of the config. See [checks](crate::checks).
+///
+/// ```
+/// use dynamic_plan_tree::{config::*, *};
+///
+/// assert_config!(config::Default);
+/// ```
+#[macro_export]
+macro_rules! assert_config {
+    ($config:ty $(,)?) => {
+        const _: fn() = $crate::checks::config::<$config>;
+    };
+}
+
+/// Macro to check at compile time that a behaviour is a variant of the behaviour enum of a config.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, *};
+///
+/// assert_behaviour_in_config!(config::Default, SequenceBehaviour<config::Default>);
+/// ```
+#[macro_export]
+macro_rules! assert_behaviour_in_config {
+    ($config:ty, $behaviour:ty $(,)?) => {
+        const _: fn() = $crate::checks::behaviour_in_config::<$config, $behaviour>;
+    };
+}
+
+/// Macro to check at compile time that a predicate is a variant of the predicate enum of a config.
+///
+/// ```
+/// use dynamic_plan_tree::{config::*, predicate::*, *};
+///
+/// assert_predicate_in_config!(config::Default, Not<Predicates>);
+/// ```
+#[macro_export]
+macro_rules! assert_predicate_in_config {
+    ($config:ty, $predicate:ty $(,)?) => {
+        const _: fn() = $crate::checks::predicate_in_config::<$config, $predicate>;
+    };
+}
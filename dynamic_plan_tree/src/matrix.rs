@@ -0,0 +1,109 @@
+use crate::*;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+/// Transitions among the subplans of a single plan as an adjacency matrix. See [Plan::transition_matrix].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransitionMatrix<N = DefaultName> {
+    /// Subplan names in priority order, followed by `None` standing for no subplan,
+    /// which is the source of transitions without `src` and the destination of those without `dst`.
+    pub states: Vec<Option<N>>,
+    /// Descriptors of the transitions from `states[i]` to `states[j]` at `cells[i][j]`.
+    ///
+    /// A descriptor is the transition label, or [Predicate::describe] if unlabelled,
+    /// followed by the [Transition::description] if any.
+    /// Transitions with several `src` or `dst` plans appear in each pairwise entry, annotated with both sets.
+    pub cells: Vec<Vec<Vec<String>>>,
+}
+
+impl<N: Ord + Clone> TransitionMatrix<N> {
+    /// Index of the given state within [TransitionMatrix::states].
+    pub fn index(&self, state: Option<&N>) -> Option<usize> {
+        self.states.iter().position(|x| x.as_ref() == state)
+    }
+
+    /// Subplans that may become active given the `initial` active subplans, including themselves.
+    ///
+    /// Over-approximates by assuming a transition may fire once any of its `src` plans is active.
+    /// Transitions without `src` may fire at any time.
+    pub fn reachable_from(&self, initial: &[N]) -> BTreeSet<N> {
+        let mut reached = vec![false; self.states.len()];
+        let mut stack = initial
+            .iter()
+            .filter_map(|x| self.index(Some(x)))
+            .chain(self.index(None))
+            .collect::<Vec<_>>();
+        while let Some(i) = stack.pop() {
+            if std::mem::replace(&mut reached[i], true) {
+                continue;
+            }
+            let next = self.cells[i].iter().enumerate();
+            stack.extend(next.filter(|(_, x)| !x.is_empty()).map(|(j, _)| j));
+        }
+        let states = self.states.iter().zip(reached);
+        states
+            .filter_map(|(state, reached)| state.clone().filter(|_| reached))
+            .collect()
+    }
+}
+
+impl<N: Display> TransitionMatrix<N> {
+    /// Comma separated table with a header row and column of state names, where `None` is written as `none`.
+    ///
+    /// Several descriptors in the same entry are separated by `; `.
+    pub fn to_csv(&self) -> String {
+        fn escape(field: String) -> String {
+            match field.contains([',', '"', '\n']) {
+                true => format!("\"{}\"", field.replace('"', "\"\"")),
+                false => field,
+            }
+        }
+        let label = |state: &Option<N>| match state {
+            Some(name) => escape(name.to_string()),
+            None => "none".into(),
+        };
+        let mut csv = String::new();
+        for state in &self.states {
+            csv += ",";
+            csv += &label(state);
+        }
+        csv += "\n";
+        for (state, row) in self.states.iter().zip(&self.cells) {
+            csv += &label(state);
+            for cell in row {
+                csv += ",";
+                csv += &escape(cell.join("; "));
+            }
+            csv += "\n";
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matrix_csv() {
+        let cell = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        // none -> a, a -> b twice, c unreachable
+        let matrix = TransitionMatrix {
+            states: vec![Some("a"), Some("b"), Some("c"), None],
+            cells: vec![
+                vec![cell(&[]), cell(&["x, y", "z"]), cell(&[]), cell(&[])],
+                vec![cell(&[]), cell(&[]), cell(&[]), cell(&[])],
+                vec![cell(&[]), cell(&[]), cell(&[]), cell(&[])],
+                vec![cell(&["\"w\""]), cell(&[]), cell(&[]), cell(&[])],
+            ],
+        };
+        let csv = ",a,b,c,none\na,,\"x, y; z\",,\nb,,,,\nc,,,,\nnone,\"\"\"w\"\"\",,,\n";
+        assert_eq!(matrix.to_csv(), csv);
+        assert_eq!(matrix.reachable_from(&[]), BTreeSet::from(["a", "b"]));
+        assert_eq!(
+            matrix.reachable_from(&["c"]),
+            BTreeSet::from(["a", "b", "c"])
+        );
+        assert_eq!(matrix.index(None), Some(3));
+    }
+}
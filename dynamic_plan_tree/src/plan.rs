@@ -2,561 +2,7495 @@ use crate::*;
 
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
-
-#[cfg(feature = "serde")]
-use serde::de::DeserializeOwned;
-use std::collections::HashMap;
-use tracing::{debug, debug_span, Span};
+use std::any::Any;
+use std::borrow::Borrow;
+#[cfg(feature = "journal")]
+use std::collections::VecDeque;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Debug;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+#[cfg(feature = "journal")]
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use tracing::{debug, debug_span, warn, Span};
 
 /// A user provided object to statically pass in custom implementation for `Behaviour` and `Predicate`.
+///
+/// Associated type bounds depend on crate features, see [features] for bounds that hold under all of them.
+/// Use [assert_config] to check them with targeted error messages.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `Config`",
+    label = "not a config",
+    note = "implement `dynamic_plan_tree::Config` for `{Self}` to choose its behaviour, predicate, event, and name types"
+)]
 pub trait Config: Sized + 'static {
-    #[cfg(all(feature = "rayon", feature = "serde"))]
-    type Predicate: Predicate + Send + Serialize + DeserializeOwned + EnumCast;
-    #[cfg(all(not(feature = "rayon"), feature = "serde"))]
-    type Predicate: Predicate + Serialize + DeserializeOwned + EnumCast;
-    #[cfg(all(feature = "rayon", not(feature = "serde")))]
-    type Predicate: Predicate + Send + EnumCast;
-    #[cfg(all(not(feature = "rayon"), not(feature = "serde")))]
-    type Predicate: Predicate + EnumCast;
-
-    #[cfg(all(feature = "rayon", feature = "serde"))]
-    type Behaviour: Behaviour<Self> + Send + Serialize + DeserializeOwned + EnumCast;
-    #[cfg(all(not(feature = "rayon"), feature = "serde"))]
-    type Behaviour: Behaviour<Self> + Serialize + DeserializeOwned + EnumCast;
-    #[cfg(all(feature = "rayon", not(feature = "serde")))]
-    type Behaviour: Behaviour<Self> + Send + EnumCast;
-    #[cfg(all(not(feature = "rayon"), not(feature = "serde")))]
-    type Behaviour: Behaviour<Self> + EnumCast;
+    type Predicate: Predicate + MaybeSend + MaybeSerde + EnumCast;
+    type Behaviour: Behaviour<Self> + MaybeSend + MaybeSerde + EnumCast;
+    type Event: 'static + MaybeSend + MaybeSerde + EnumCast;
+
+    /// Type identifying plans among their siblings, whose ordering determines [Plan::priority].
+    ///
+    /// Use [DefaultName] for string names. Compact types such as integers or a domain enum avoid allocation.
+    /// Generic code converting string literals into names may need the config annotated for inference,
+    /// e.g. `Plan::<MyConfig>::new_stub("a", true)`.
+    type Name: Ord + Clone + Debug + 'static + MaybeSend + MaybeSync + MaybeSerde;
+
+    /// Stable tags to serialize [Plan::behaviour] variants by in place of their identifiers, as `(variant, tag)` pairs.
+    ///
+    /// Renaming a listed variant while keeping its tag keeps previously saved trees loadable.
+    /// Unlisted variants are serialized by identifier. Behaviours nested within others are tagged alike
+    /// where serialized by `TaggedBehaviour` of the `serde` feature, as by the built-in behaviours.
+    const BEHAVIOUR_TAGS: &'static [(&'static str, &'static str)] = &[];
+
+    /// Serialized tag of the given behaviour variant, only used while [Config::BEHAVIOUR_TAGS] is not empty.
+    fn behaviour_tag(variant: &str) -> Option<&'static str> {
+        let mut tags = Self::BEHAVIOUR_TAGS.iter();
+        tags.find(|x| x.0 == variant).map(|x| x.1)
+    }
+
+    /// Behaviour variant of the given serialized tag, the inverse of [Config::behaviour_tag].
+    fn behaviour_variant(tag: &str) -> Option<&'static str> {
+        let mut tags = Self::BEHAVIOUR_TAGS.iter();
+        tags.find(|x| x.1 == tag).map(|x| x.0)
+    }
 }
 
+/// Plan name type for configs without special requirements, i.e. `type Name = DefaultName;`.
+pub type DefaultName = String;
+
 /// Transition from `src` plans to `dst` plans within the parent plan upon the result of `predicate` evaluation.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct Transition<P> {
-    pub src: Vec<String>,
-    pub dst: Vec<String>,
+pub struct Transition<P, N = DefaultName> {
+    pub src: Vec<N>,
+    pub dst: Vec<N>,
     pub predicate: P,
+    /// When unset, defer firing while any `src` plan status is still in progress.
+    #[cfg_attr(feature = "serde", serde(default = "default_true"))]
+    pub interruptible: bool,
+    /// Name to refer to this transition by. See [predicate::TransitionFired].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub label: Option<String>,
+    /// Maximum number of times to fire per activation of the parent plan, unlimited when unset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_firings: Option<u32>,
+    /// When set, fire once the score of `predicate` reaches this threshold instead of upon its evaluation.
+    ///
+    /// See [Predicate::score]. Scores are reported in [TickReport::transition_scores].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub threshold: Option<f64>,
+    /// Free text explaining the purpose of this transition, for tooling and diagnostics only.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub description: Option<String>,
+    /// Further predicates each evaluated over its own plans, all of which must hold alongside `predicate`.
+    ///
+    /// Their plans join `src` in being required active and being exited upon firing. See [Transition::sources].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")
+    )]
+    pub clauses: Vec<(Vec<N>, P)>,
 }
 
-/// A node in the plan tree containing some behaviour, subplans, and possible transitions.
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct Plan<C: Config> {
-    name: String,
-    #[cfg_attr(feature = "serde", serde(default = "u32::max_value"))]
-    run_countdown: u32,
-    /// Number of ticks between each run.
-    pub run_interval: u32,
-    /// Automatically enter following the entry of parent plan.
-    pub autostart: bool,
-    /// Customizable run-time logic.
-    pub behaviour: Option<Box<C::Behaviour>>,
-    /// List of transition conditions between sets of subplans.
-    pub transitions: Vec<Transition<C::Predicate>>,
-    /// Contains instances of subplans recursively.
-    pub plans: Vec<Self>,
-    /// Storage for arbitrary serializable data.
-    pub data: HashMap<String, serde_value::Value>,
-    #[cfg_attr(feature = "serde", serde(skip, default = "Span::none"))]
-    span: Span,
+impl<P, N> Transition<P, N> {
+    /// Interruptible transition from `src` to `dst` upon `predicate`, with every optional field unset.
+    ///
+    /// Set the others with struct update syntax, e.g. `Transition { label, ..Transition::new(src, dst, predicate) }`.
+    pub fn new(src: Vec<N>, dst: Vec<N>, predicate: P) -> Self {
+        Self {
+            src,
+            dst,
+            predicate,
+            interruptible: true,
+            label: None,
+            max_firings: None,
+            threshold: None,
+            description: None,
+            clauses: Vec::new(),
+        }
+    }
+
+    /// Plans of `src` followed by those of each of the `clauses`, which may repeat.
+    pub fn sources(&self) -> impl Iterator<Item = &N> {
+        let clauses = self.clauses.iter().flat_map(|(src, _)| src);
+        self.src.iter().chain(clauses)
+    }
 }
 
-impl<C: Config> Plan<C> {
-    /// ID unique among subplans.
-    pub fn name(&self) -> &String {
-        &self.name
+#[cfg(feature = "serde")]
+fn default_true() -> bool {
+    true
+}
+
+/// Serializes behaviours of the config `C` by [Config::BEHAVIOUR_TAGS], such as [Plan::behaviour].
+///
+/// Behaviours wrapping others should serialize their inner behaviours with it as well,
+/// e.g. `#[serde(with = "TaggedBehaviour::<C>")] behaviour: Box<C::Behaviour>`,
+/// so that the inner ones are tagged alike.
+#[cfg(feature = "serde")]
+pub struct TaggedBehaviour<C>(std::marker::PhantomData<C>);
+
+#[cfg(feature = "serde")]
+impl<C: Config> TaggedBehaviour<C> {
+    /// Replace the variant of an externally tagged enum value.
+    fn retag(value: serde_value::Value, f: fn(&str) -> Option<&'static str>) -> serde_value::Value {
+        use serde_value::Value;
+        match value {
+            Value::String(x) => Value::String(f(&x).map_or(x, Into::into)),
+            Value::Map(map) if map.len() == 1 => {
+                let map = map.into_iter().map(|(k, v)| (Self::retag(k, f), v));
+                Value::Map(map.collect())
+            }
+            value => value,
+        }
     }
 
-    /// Whether the inner behaviour is scheduled to run.
-    pub fn active(&self) -> bool {
-        self.run_countdown < u32::MAX
+    pub fn serialize<S: serde::Serializer>(
+        behaviour: &C::Behaviour,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if C::BEHAVIOUR_TAGS.is_empty() {
+            return behaviour.serialize(serializer);
+        }
+        let value = serde_value::to_value(behaviour).map_err(serde::ser::Error::custom)?;
+        Self::retag(value, C::behaviour_tag).serialize(serializer)
     }
 
-    /// Number of ticks until next run.
-    pub fn run_countdown(&self) -> u32 {
-        self.run_countdown
+    pub fn deserialize<'de, B: From<C::Behaviour>, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<B, D::Error> {
+        if C::BEHAVIOUR_TAGS.is_empty() {
+            return C::Behaviour::deserialize(deserializer).map(Into::into);
+        }
+        let value = serde_value::Value::deserialize(deserializer)?;
+        let behaviour = Self::retag(value, C::behaviour_variant).deserialize_into::<C::Behaviour>();
+        behaviour.map(Into::into).map_err(serde::de::Error::custom)
     }
 
-    /// Status of the inner behaviour.
-    pub fn status(&self) -> Option<bool> {
-        self.behaviour.as_ref()?.status(self)
+    pub fn serialize_option<S: serde::Serializer>(
+        behaviour: &Option<Box<C::Behaviour>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        behaviour.as_deref().map(Tagged::<C>).serialize(serializer)
     }
 
-    /// Utility of the inner behaviour.
-    pub fn utility(&self) -> f64 {
-        self.behaviour
-            .as_ref()
-            .map(|b| b.utility(self))
-            .unwrap_or(0.)
+    pub fn deserialize_option<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Box<C::Behaviour>>, D::Error> {
+        let behaviour = Option::<Untagged<C>>::deserialize(deserializer)?;
+        Ok(behaviour.map(|x| Box::new(x.0)))
     }
 
-    /// New plan with behaviour and no subplans.
-    pub fn new(
-        behaviour: C::Behaviour,
-        name: impl Into<String>,
-        run_interval: u32,
-        autostart: bool,
-    ) -> Self {
-        let mut s = Self::new_stub(name, autostart);
-        s.run_interval = run_interval;
-        s.behaviour = Some(Box::new(behaviour));
-        s
+    pub fn serialize_vec<S: serde::Serializer>(
+        behaviours: &[C::Behaviour],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(behaviours.iter().map(Tagged::<C>))
     }
 
-    /// New plan without any behaviour.
-    pub fn new_stub(name: impl Into<String>, autostart: bool) -> Self {
+    pub fn deserialize_vec<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<C::Behaviour>, D::Error> {
+        let behaviours = Vec::<Untagged<C>>::deserialize(deserializer)?;
+        Ok(behaviours.into_iter().map(|x| x.0).collect())
+    }
+}
+
+/// Behaviour serialized by [TaggedBehaviour].
+#[cfg(feature = "serde")]
+struct Tagged<'a, C: Config>(&'a C::Behaviour);
+
+#[cfg(feature = "serde")]
+impl<C: Config> Serialize for Tagged<'_, C> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TaggedBehaviour::<C>::serialize(self.0, serializer)
+    }
+}
+
+/// Behaviour deserialized by [TaggedBehaviour].
+#[cfg(feature = "serde")]
+struct Untagged<C: Config>(C::Behaviour);
+
+#[cfg(feature = "serde")]
+impl<'de, C: Config> Deserialize<'de> for Untagged<C> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        TaggedBehaviour::<C>::deserialize(deserializer).map(Self)
+    }
+}
+
+/// Fields of a [Plan] in their serialized order, borrowed from the plan and its [Inner] fields.
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+#[serde(rename = "Plan")]
+struct PlanRef<'a, C: Config> {
+    name: &'a C::Name,
+    run_countdown: u32,
+    run_interval: u32,
+    interval_jitter: u32,
+    jitter_delay: u32,
+    execution: ExecMode,
+    frozen: bool,
+    autostart: bool,
+    autostart_if: Option<&'a C::Predicate>,
+    reachable_hint: bool,
+    skip_idle: bool,
+    exit_order: ExitOrder,
+    report_cached_when_inactive: bool,
+    isolate: bool,
+    poison_after: u32,
+    seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(serialize_with = "TaggedBehaviour::<C>::serialize_option")]
+    behaviour: &'a Option<Box<C::Behaviour>>,
+    transitions: &'a [Transition<C::Predicate, C::Name>],
+    plans: &'a [Plan<C>],
+    data: &'a HashMap<String, serde_value::Value>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    slots: &'a [String],
+    tags: &'a [String],
+    tag_limits: &'a TagLimits,
+    warning_interval: u64,
+    #[cfg(feature = "journal")]
+    journal_capacity: usize,
+    paused: bool,
+    outcomes: OutcomeCounts,
+    panics: u32,
+    panicked: bool,
+    poisoned: bool,
+}
+
+#[cfg(feature = "serde")]
+impl<'a, C: Config> From<&'a Plan<C>> for PlanRef<'a, C> {
+    fn from(plan: &'a Plan<C>) -> Self {
         Self {
-            name: name.into(),
-            run_countdown: u32::MAX,
-            run_interval: 0,
-            autostart,
-            behaviour: None,
-            transitions: Vec::new(),
-            plans: Vec::new(),
-            data: HashMap::new(),
-            span: Span::none(),
+            name: &plan.name,
+            run_countdown: plan.run_countdown,
+            run_interval: plan.run_interval,
+            interval_jitter: plan.interval_jitter,
+            jitter_delay: plan.activation.as_ref().map_or(0, |x| x.jitter_delay),
+            execution: plan.execution,
+            frozen: plan.frozen,
+            autostart: plan.autostart,
+            autostart_if: plan.autostart_if(),
+            reachable_hint: plan.reachable_hint,
+            skip_idle: plan.skip_idle,
+            exit_order: plan.exit_order,
+            report_cached_when_inactive: plan.report_cached_when_inactive,
+            isolate: plan.isolate,
+            poison_after: plan.poison_after,
+            seed: plan.seed,
+            description: plan.description(),
+            behaviour: &plan.behaviour,
+            transitions: &plan.transitions,
+            plans: &plan.plans,
+            data: plan.data(),
+            slots: plan.held_slots(),
+            tags: plan.tags(),
+            tag_limits: plan.tag_limits(),
+            warning_interval: plan.warning_interval(),
+            #[cfg(feature = "journal")]
+            journal_capacity: plan.journal_capacity(),
+            paused: plan.paused,
+            outcomes: plan.outcomes,
+            panics: plan.panics,
+            panicked: plan.panicked,
+            poisoned: plan.poisoned,
         }
     }
+}
 
-    /// Insert plan instance as a subplan then return its reference.
-    ///
-    /// Subplan will be exited if current plan is inactive.
-    /// Subplan will be entered if current plan is active and autostart is set.
-    /// Existing subplan with the same name will be overwritten.
-    pub fn insert(&mut self, mut plan: Self) -> &mut Self {
-        debug!(parent: &self.span, plan=%plan.name, "insert");
-        if self.active() {
-            // overwrite preview span with new parent if already active
-            if plan.active() {
-                plan.span = debug_span!(parent: &self.span, "plan", name=%plan.name);
-            // when autostart is set, enter inserted plan if parent is active
-            } else if plan.autostart {
-                plan.enter(Some(&self.span));
-            }
-        // exit inserted span if parent plan is inactive
-        } else if plan.active() {
-            plan.exit(false);
+/// Fields of a [Plan] in their serialized order, with defaults for those added by later versions.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(rename = "Plan")]
+struct PlanFields<C: Config> {
+    name: C::Name,
+    #[serde(default = "u32::max_value")]
+    run_countdown: u32,
+    run_interval: u32,
+    #[serde(default)]
+    interval_jitter: u32,
+    #[serde(default)]
+    jitter_delay: u32,
+    #[serde(default)]
+    execution: ExecMode,
+    #[serde(default)]
+    frozen: bool,
+    autostart: bool,
+    #[serde(default)]
+    autostart_if: Option<C::Predicate>,
+    #[serde(default)]
+    reachable_hint: bool,
+    #[serde(default)]
+    skip_idle: bool,
+    #[serde(default)]
+    exit_order: ExitOrder,
+    #[serde(default)]
+    report_cached_when_inactive: bool,
+    #[serde(default)]
+    isolate: bool,
+    #[serde(default)]
+    poison_after: u32,
+    #[serde(default)]
+    seed: Option<u64>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(deserialize_with = "TaggedBehaviour::<C>::deserialize_option")]
+    behaviour: Option<Box<C::Behaviour>>,
+    transitions: Vec<Transition<C::Predicate, C::Name>>,
+    plans: Vec<Plan<C>>,
+    data: HashMap<String, serde_value::Value>,
+    #[serde(default)]
+    slots: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    tag_limits: TagLimits,
+    #[serde(default = "default_warning_interval")]
+    warning_interval: u64,
+    #[cfg(feature = "journal")]
+    #[serde(default = "default_journal_capacity")]
+    journal_capacity: usize,
+    #[serde(default)]
+    paused: bool,
+    #[serde(default)]
+    outcomes: OutcomeCounts,
+    #[serde(default)]
+    panics: u32,
+    #[serde(default)]
+    panicked: bool,
+    #[serde(default)]
+    poisoned: bool,
+}
+
+#[cfg(feature = "serde")]
+impl<C: Config> From<PlanFields<C>> for Plan<C> {
+    fn from(fields: PlanFields<C>) -> Self {
+        let mut plan = Plan::from_parts(fields.name, fields.autostart, PlanParts::default());
+        // refreshed once entered, see Plan::path
+        plan.path.clear();
+        plan.run_countdown = fields.run_countdown;
+        plan.run_interval = fields.run_interval;
+        plan.interval_jitter = fields.interval_jitter;
+        if fields.jitter_delay > 0 {
+            plan.activation().jitter_delay = fields.jitter_delay;
         }
-        // sorted insert
-        let (pos, _) = match self.priority(&plan.name) {
-            // overwrite if there is already one
-            Ok(pos) => (pos, self.plans[pos] = plan),
-            Err(pos) => (pos, self.plans.insert(pos, plan)),
+        plan.execution = fields.execution;
+        plan.frozen = fields.frozen;
+        plan.reachable_hint = fields.reachable_hint;
+        plan.skip_idle = fields.skip_idle;
+        plan.exit_order = fields.exit_order;
+        plan.report_cached_when_inactive = fields.report_cached_when_inactive;
+        plan.isolate = fields.isolate;
+        plan.poison_after = fields.poison_after;
+        plan.seed = fields.seed;
+        plan.behaviour = fields.behaviour;
+        plan.transitions = fields.transitions;
+        plan.plans = fields.plans;
+        plan.paused = fields.paused;
+        plan.outcomes = fields.outcomes;
+        plan.panics = fields.panics;
+        plan.panicked = fields.panicked;
+        plan.poisoned = fields.poisoned;
+        let inner = Inner {
+            autostart_if: fields.autostart_if,
+            description: fields.description,
+            data: fields.data,
+            slots: fields.slots,
+            tags: fields.tags,
+            tag_limits: fields.tag_limits,
+            warning_interval: fields.warning_interval,
+            #[cfg(feature = "journal")]
+            journal_capacity: fields.journal_capacity,
+            ..Inner::default()
         };
-        &mut self.plans[pos]
+        #[cfg(feature = "journal")]
+        let default_journal = inner.journal_capacity == default_journal_capacity();
+        #[cfg(not(feature = "journal"))]
+        let default_journal = true;
+        let unused = inner.autostart_if.is_none()
+            && inner.description.is_none()
+            && inner.data.is_empty()
+            && inner.slots.is_empty()
+            && inner.tags.is_empty()
+            && inner.tag_limits.0.is_empty()
+            && inner.warning_interval == default_warning_interval()
+            && default_journal;
+        plan.inner = (!unused).then(|| Box::new(inner));
+        plan
     }
+}
 
-    /// Remove a subplan by name, and return it if successful.
-    pub fn remove(&mut self, name: &str) -> Option<Self> {
-        let pos = self.priority(name).ok()?;
-        debug!(parent: &self.span, plan=%name, "remove");
-        Some(self.plans.remove(pos))
+#[cfg(feature = "serde")]
+impl<C: Config + Serialize> Serialize for Plan<C> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PlanRef::from(self).serialize(serializer)
     }
+}
 
-    /// Find the priority of a subplan by name.
-    ///
-    /// Subplans run in order of their priority (unless rayon parallel execution is enabled).
-    ///
-    /// Priority is determined by the ordering of the subplans sorted by name.
-    /// For example, plan with names `"plan0" < "plan1"` means `"plan0"` has higher priority.
-    pub fn priority(&self, name: &str) -> Result<usize, usize> {
-        self.plans.binary_search_by(|plan| (*plan.name).cmp(name))
+/// Fills in fields missing from plans serialized by earlier versions before deserializing.
+#[cfg(feature = "serde")]
+impl<'de, C: Config + Deserialize<'de>> Deserialize<'de> for Plan<C> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde_value::Value;
+        let mut value = Value::deserialize(deserializer)?;
+        if let Value::Map(map) = &mut value {
+            // a zero interval meant structure only execution before Plan::execution existed
+            let interval = map.get(&Value::String("run_interval".into()));
+            let interval = interval.and_then(|x| x.clone().deserialize_into::<u64>().ok());
+            let execution = Value::String("execution".into());
+            if interval == Some(0) && !map.contains_key(&execution) {
+                map.insert(execution, Value::String("StructureOnly".into()));
+            }
+        }
+        let value = serde_value::ValueDeserializer::<D::Error>::new(value);
+        let mut plan = Plan::from(PlanFields::deserialize(value)?);
+        // subplans share the state of the tree they are loaded into, such as slot counters
+        let tree = plan.tree.clone();
+        plan.visit_mut(&mut |x| x.tree = tree.clone());
+        // counted by the slots held until the first tick reads the counters from the data of the root
+        let mut slots = tree.slots.lock().unwrap();
+        plan.visit(&mut |x| {
+            for key in x.held_slots() {
+                slots.entry(key.clone()).or_default().taken += 1;
+            }
+        });
+        drop(slots);
+        Ok(plan)
     }
+}
 
-    /// Returns reference to subplan by name.
-    pub fn get(&self, name: &str) -> Option<&Self> {
-        let pos = self.priority(name).ok()?;
-        Some(&self.plans[pos])
+#[cfg(feature = "journal")]
+fn default_journal_capacity() -> usize {
+    256
+}
+
+fn default_warning_interval() -> u64 {
+    100
+}
+
+/// Deterministically derive a distinct seed from `seed` for each `salt`, such as the name of a subplan.
+///
+/// Depends only on the debug representation of `salt`, so it is reproducible across runs and platforms.
+pub fn derive_seed<T: Debug + ?Sized>(seed: u64, salt: &T) -> u64 {
+    // FNV-1a hash of the salt
+    let hash = format!("{:?}", salt)
+        .bytes()
+        .fold(0xcbf29ce484222325, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x100000001b3)
+        });
+    // SplitMix64 finalizer to decorrelate nearby seeds
+    let mut z = (seed ^ hash).wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Order in which a plan and its subplans trigger `on_exit()`. See [Plan::exit].
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ExitOrder {
+    /// Subplans exit before the plan itself.
+    #[default]
+    ChildrenFirst,
+    /// The plan exits before its subplans, for tearing down resources they depend on.
+    SelfFirst,
+}
+
+/// Parts of a plan executed on each tick of [Plan::run]. See [Plan::execution].
+///
+/// Entry and exit of the plan and its subplans are unaffected.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ExecMode {
+    /// Evaluate transitions, run active subplans, and run the behaviour every `run_interval` ticks.
+    #[default]
+    Full,
+    /// Evaluate transitions and run active subplans without running the behaviour.
+    StructureOnly,
+    /// Run the behaviour every `run_interval` ticks without evaluating transitions or running subplans.
+    BehaviourOnly,
+    /// Neither run the behaviour nor subplans.
+    Disabled,
+}
+
+impl ExecMode {
+    /// Whether `on_prepare()` and `on_run()` of the behaviour are triggered.
+    pub fn runs_behaviour(self) -> bool {
+        matches!(self, ExecMode::Full | ExecMode::BehaviourOnly)
     }
 
-    /// Returns mutable reference to subplan by name.
-    pub fn get_mut(&mut self, name: &str) -> Option<&mut Self> {
-        let pos = self.priority(name).ok()?;
-        Some(&mut self.plans[pos])
+    /// Whether transitions are evaluated and active subplans are run.
+    pub fn runs_subplans(self) -> bool {
+        matches!(self, ExecMode::Full | ExecMode::StructureOnly)
     }
+}
 
-    /// Dynamically cast inner behaviour to a reference its known static type.
-    ///
-    /// For referring to concrete behaviours within the implementation of another.
-    pub fn cast<B: Behaviour<C>>(&self) -> Option<&B> {
-        self.behaviour.as_ref()?.cast::<B>()
+/// Maximum number of simultaneously active plans sharing each tag across the whole tree.
+///
+/// Only takes effect when set on the root plan. Tags without a limit are unrestricted.
+#[derive(Default, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TagLimits(pub HashMap<String, usize>);
+
+/// Entry of a plan that was refused because one of its tags reached the limit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TagRefusal<N = DefaultName> {
+    pub plan: N,
+    pub tag: String,
+}
+
+/// Summary of notable occurrences since the previous tick. See [Plan::run].
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct TickReport<N = DefaultName> {
+    /// Plan entries refused due to [TagLimits].
+    pub tag_refusals: Vec<TagRefusal<N>>,
+    /// Scores of transitions with a [Transition::threshold], for those evaluated.
+    pub transition_scores: Vec<TransitionScore<N>>,
+    /// Deferred mutations that took effect, in order of application. See [Plan::defer].
+    pub mutations: Vec<AppliedMutation<N>>,
+    /// Panics contained by isolated plans, in order of occurrence. See [Plan::isolate].
+    pub isolated_panics: Vec<IsolatedPanic>,
+}
+
+/// Panic contained by an isolated plan, which was failed and exited. See [Plan::isolate].
+#[derive(Clone, Debug, PartialEq)]
+pub struct IsolatedPanic {
+    /// Path of the isolated plan, see [Plan::path].
+    pub path: String,
+    /// Context of the panic, with a path starting from the isolated plan.
+    pub panic: PlanPanic,
+    /// Number of panics contained by the plan so far, see [Plan::panic_count].
+    pub count: u32,
+    /// Whether the plan was poisoned by this panic, see [Plan::poisoned].
+    pub poisoned: bool,
+}
+
+/// Change to the subplans of a plan, deferred until the end of its run. See [Plan::defer].
+pub enum PlanMutation<C: Config> {
+    /// See [Plan::insert].
+    Insert(Box<Plan<C>>),
+    /// See [Plan::remove].
+    Remove(C::Name),
+    /// See [Plan::enter_plan].
+    EnterPlan(C::Name),
+    /// See [Plan::exit_plan].
+    ExitPlan(C::Name),
+}
+
+/// Kind of a [PlanMutation].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MutationKind {
+    Insert,
+    Remove,
+    EnterPlan,
+    ExitPlan,
+}
+
+/// Deferred mutation that took effect during a tick. See [TickReport].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AppliedMutation<N = DefaultName> {
+    /// Plan whose subplans were mutated.
+    pub plan: N,
+    pub kind: MutationKind,
+    pub subplan: N,
+}
+
+/// Score of a transition with a [Transition::threshold] upon evaluation. See [TickReport].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransitionScore<N = DefaultName> {
+    /// Name of the plan containing the transition.
+    pub plan: N,
+    /// Label of the transition, if any.
+    pub label: Option<String>,
+    /// Source plans of the transition.
+    pub src: Vec<N>,
+    /// Score of the transition predicate.
+    pub score: f64,
+    /// Threshold the score was compared against.
+    pub threshold: f64,
+    /// Whether the score reached the threshold.
+    pub fired: bool,
+}
+
+/// Errors returned by plan tree operations.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error<N = DefaultName> {
+    /// No plan exists at the given path.
+    PlanNotFound(Vec<N>),
+    /// Transitions were rejected for referring to missing plans. See [Plan::validate].
+    InvalidTransitions(Vec<ValidationIssue<N>>),
+    /// Failed to convert to or from JSON.
+    #[cfg(feature = "serde")]
+    Json(String),
+}
+
+/// See [Diagnostic].
+impl<N: Debug> std::fmt::Display for Error<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.message())
     }
+}
 
-    /// See [Plan::cast].
-    pub fn cast_mut<B: Behaviour<C>>(&mut self) -> Option<&mut B> {
-        self.behaviour.as_mut()?.cast_mut::<B>()
+impl<N: Debug> std::error::Error for Error<N> {}
+
+/// Problem in the declared structure of a plan tree found by [Plan::validate].
+///
+/// Paths and transition indices refer to the plan containing the transition, relative to the validated plan.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationIssue<N = DefaultName> {
+    /// Transition refers to a subplan that does not exist.
+    MissingPlan {
+        path: Vec<N>,
+        transition: usize,
+        plan: N,
+    },
+    /// Transition can never fire since some of its `src` plans can never become active.
+    UnreachableTransition { path: Vec<N>, transition: usize },
+    /// Subplan can never become active.
+    UnreachablePlan { path: Vec<N> },
+}
+
+impl<N> ValidationIssue<N> {
+    /// Whether the issue is an error rather than a warning.
+    pub fn is_error(&self) -> bool {
+        matches!(self, ValidationIssue::MissingPlan { .. })
     }
+}
 
-    /// Dynamically cast inner behaviour of a subplan to reference of its known type.
-    /// See [Plan::cast].
-    pub fn get_cast<B: Behaviour<C>>(&self, name: &str) -> Option<&B> {
-        self.get(name)?.cast::<B>()
+/// See [Diagnostic].
+impl<N: Debug> std::fmt::Display for ValidationIssue<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.message())
     }
+}
 
-    /// See [Plan::get_cast].
-    pub fn get_cast_mut<B: Behaviour<C>>(&mut self, name: &str) -> Option<&mut B> {
-        self.get_mut(name)?.cast_mut::<B>()
+/// Transition of the plan at `parent_path`, relative to the plan the transition graph was exported from.
+///
+/// See [Plan::transitions_to_json].
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+pub struct TransitionRecord<P, N = DefaultName> {
+    pub parent_path: Vec<N>,
+    #[serde(flatten)]
+    pub transition: Transition<P, N>,
+}
+
+/// Borrowed form of [TransitionRecord] for export without cloning predicates.
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct TransitionRecordRef<'a, P, N> {
+    parent_path: Vec<N>,
+    #[serde(flatten)]
+    transition: &'a Transition<P, N>,
+}
+
+/// Flag shared by all plans of a tree, signalling long-running behaviours to stop early.
+///
+/// Set by [Plan::cancel] and [Plan::begin_shutdown], cleared when the tree is entered again.
+/// May be cloned and set from another thread.
+#[derive(Default, Clone, Debug)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Whether cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
     }
 
-    /// Run plan tree recursively. Each call at root level constitutes one tick of execution.
-    ///
-    /// Scheduling and transitions for all subplan are handled in the process.
-    pub fn run(&mut self) {
-        // enter plan if not already
-        self.enter(None);
+    /// Request cancellation.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
 
-        // get active set of plans
-        use std::collections::HashSet;
-        let active_plans = self
-            .plans
-            .iter()
-            .filter(|plan| plan.active())
-            .map(|plan| &plan.name)
-            .collect::<HashSet<_>>();
-        debug!(parent: &self.span, plan=?self.name(), active=?active_plans);
+    fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
 
-        // evaluate state transitions
-        let transitions = std::mem::take(&mut self.transitions);
-        transitions
-            .iter()
-            .filter(|t| {
-                t.src.iter().all(|plan| active_plans.contains(plan))
-                    && t.predicate.evaluate(self, &t.src)
-            })
-            .collect::<Vec<_>>()
-            .iter()
-            .for_each(|t| {
-                debug!(parent: &self.span, src=?t.src, dst=?t.dst, "transition");
-                t.src.iter().filter(|p| !t.dst.contains(p)).for_each(|p| {
-                    self.exit_plan(p);
-                });
-                t.dst.iter().filter(|p| !t.src.contains(p)).for_each(|p| {
-                    self.enter_plan(p);
-                });
-            });
-        let _ = std::mem::replace(&mut self.transitions, transitions);
+/// Size and shape of a plan tree, for spotting trees that grow too deep or too wide. See [Plan::shape_metrics].
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ShapeMetrics {
+    /// Number of plans, including the root.
+    pub nodes: usize,
+    /// Number of plans without subplans.
+    pub leaves: usize,
+    /// Number of levels below the root, which is 0 for a lone root.
+    pub max_depth: usize,
+    /// Largest number of subplans of any plan.
+    pub max_branching: usize,
+    /// Average number of subplans over plans that have any, or 0 if there are none.
+    pub mean_branching: f64,
+}
 
-        // call on_prepare() before children behaviours run()
-        if self.run_interval > 0 && self.run_countdown == 0 {
-            self.call(|behaviour, plan| behaviour.on_prepare(plan), "prepare");
-        }
+/// Number of times plans reached a terminal status while active.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OutcomeCounts {
+    pub successes: u32,
+    pub failures: u32,
+}
 
-        // skip plan if exited during prepare
-        if !self.active() {
-            return;
+impl std::ops::Add for OutcomeCounts {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self {
+            successes: self.successes + other.successes,
+            failures: self.failures + other.failures,
         }
+    }
+}
 
-        // call run() recursively
-        let i = self.plans.iter_mut().filter(|plan| plan.active());
-        #[cfg(feature = "rayon")]
-        i.par_bridge().for_each(|plan| plan.run());
-        #[cfg(not(feature = "rayon"))]
-        i.for_each(|plan| plan.run());
+/// Status and utility a plan is expected to have if it were entered now. See [Plan::evaluate_hypothetical].
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Hypothetical {
+    pub status: Option<bool>,
+    pub utility: f64,
+}
 
-        // limit execution frequency
-        if self.run_interval == 0 {
-            return;
-        }
-        if self.run_countdown == 0 {
-            // run the behaviour of this plan
-            self.call(|behaviour, plan| behaviour.on_run(plan), "run");
-            self.run_countdown = self.run_interval;
-        }
-        // ok to countdown without active check because plan must be active by this point
-        self.run_countdown -= 1;
+/// Occurrences of a distinct warning within a tree. See [Plan::warnings].
+///
+/// Displays the message of the warning, see [Diagnostic].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    /// Code of the warning in the [CATALOG].
+    pub code: &'static str,
+    /// Path of the plan the warning concerns, see [Plan::path].
+    pub path: String,
+    /// Total number of occurrences.
+    pub count: u64,
+    /// Occurrences not logged since the warning was last logged.
+    pub suppressed: u64,
+    /// Tick on which the warning was last logged.
+    pub logged_tick: Option<u64>,
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.message())
     }
+}
 
-    ///  Enters the specified subplan if not already active and return its reference.
-    ///  See [Plan::enter].
-    pub fn enter_plan(&mut self, name: &str) -> Option<&mut Self> {
-        // can only enter plans within an active plan
-        if !self.active() {
+/// Mutation of [Plan::data] recorded in the journal of its tree. See [Plan::data_journal].
+#[cfg(feature = "journal")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataChange {
+    /// Tick during which the change was made, `0` if before the first tick.
+    pub tick: u64,
+    /// Path of the plan whose data changed, see [Plan::path].
+    pub path: String,
+    /// Key of the changed entry.
+    pub key: String,
+    /// Hash of the replaced value, if any.
+    pub old_hash: Option<u64>,
+    /// Debug representation of the new value truncated to [DataChange::SUMMARY_LEN] bytes, `None` if removed.
+    pub new_value: Option<String>,
+    /// Behaviour hook in progress when the change was made, `None` if made from outside of a hook.
+    pub hook: Option<&'static str>,
+}
+
+#[cfg(feature = "journal")]
+impl DataChange {
+    /// Maximum length of [DataChange::new_value].
+    pub const SUMMARY_LEN: usize = 64;
+
+    /// Hash identifying a value, to match [DataChange::old_hash] against a known value.
+    pub fn hash_value(value: &serde_value::Value) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn summarize(value: &serde_value::Value) -> String {
+        let mut summary = format!("{:?}", value);
+        if summary.len() > Self::SUMMARY_LEN {
+            let mut end = Self::SUMMARY_LEN;
+            while !summary.is_char_boundary(end) {
+                end -= 1;
+            }
+            summary.truncate(end);
+        }
+        summary
+    }
+}
+
+/// Structural hash of the serialized configuration and state of a behaviour, for detecting changes.
+///
+/// Maps are hashed in key order and numbers by value, so equal behaviours have equal fingerprints regardless of
+/// the order of map entries in their source, and `-0.0` and all NaNs hash the same as `0.0` and each other.
+/// Fingerprints are stable within a process, but not across versions of this crate or of the compiler,
+/// so they should not be persisted. Panics if the behaviour fails to serialize.
+#[cfg(feature = "serde")]
+pub fn behaviour_fingerprint<C: Config>(behaviour: &C::Behaviour) -> u64 {
+    use std::hash::Hasher;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    hash_canonical(&fingerprint_value(behaviour), &mut hasher);
+    hasher.finish()
+}
+
+#[cfg(feature = "serde")]
+fn fingerprint_value(value: &impl Serialize) -> serde_value::Value {
+    serde_value::to_value(value).expect("serializable for fingerprint")
+}
+
+/// Hash a value such that numbers of different widths and equivalent floats hash the same.
+#[cfg(feature = "serde")]
+fn hash_canonical(value: &serde_value::Value, hasher: &mut impl std::hash::Hasher) {
+    use serde_value::Value;
+    use std::hash::Hash;
+    let float = |x: f64, hasher: &mut _| {
+        let canonical = if x.is_nan() {
+            f64::NAN
+        } else if x == 0. {
+            0.
+        } else {
+            x
+        };
+        canonical.to_bits().hash(hasher)
+    };
+    // tag each kind of value so that differently shaped values do not collide
+    match value {
+        Value::Bool(x) => (0u8, x).hash(hasher),
+        Value::U8(x) => (1u8, *x as u64).hash(hasher),
+        Value::U16(x) => (1u8, *x as u64).hash(hasher),
+        Value::U32(x) => (1u8, *x as u64).hash(hasher),
+        Value::U64(x) => (1u8, x).hash(hasher),
+        Value::I8(x) => (2u8, *x as i64).hash(hasher),
+        Value::I16(x) => (2u8, *x as i64).hash(hasher),
+        Value::I32(x) => (2u8, *x as i64).hash(hasher),
+        Value::I64(x) => (2u8, x).hash(hasher),
+        Value::F32(x) => {
+            3u8.hash(hasher);
+            float(*x as f64, hasher);
+        }
+        Value::F64(x) => {
+            3u8.hash(hasher);
+            float(*x, hasher);
+        }
+        Value::Char(x) => (4u8, x).hash(hasher),
+        Value::String(x) => (5u8, x).hash(hasher),
+        Value::Unit => 6u8.hash(hasher),
+        Value::Option(x) => {
+            (7u8, x.is_some()).hash(hasher);
+            if let Some(x) = x {
+                hash_canonical(x, hasher);
+            }
+        }
+        Value::Newtype(x) => {
+            8u8.hash(hasher);
+            hash_canonical(x, hasher);
+        }
+        Value::Seq(x) => {
+            (9u8, x.len()).hash(hasher);
+            x.iter().for_each(|x| hash_canonical(x, hasher));
+        }
+        Value::Map(x) => {
+            (10u8, x.len()).hash(hasher);
+            for (k, v) in x {
+                hash_canonical(k, hasher);
+                hash_canonical(v, hasher);
+            }
+        }
+        Value::Bytes(x) => (11u8, x).hash(hasher),
+    }
+}
+
+/// Paths of the active plans within a tree, relative to the plan it was captured from. See [Plan::active_state].
+///
+/// The plan captured from is included as the empty path while active.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(deserialize = "N: Deserialize<'de> + Ord"))
+)]
+pub struct ActiveState<N = DefaultName>(pub BTreeSet<Vec<N>>);
+
+impl<N> Default for ActiveState<N> {
+    fn default() -> Self {
+        Self(BTreeSet::new())
+    }
+}
+
+/// Paths of plans that entered or exited between two captures of [ActiveState]. See [Plan::active_delta].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveDelta<N = DefaultName> {
+    /// Plans active now but not previously, sorted by path.
+    pub entered: Vec<Vec<N>>,
+    /// Plans active previously but not now, sorted by path.
+    pub exited: Vec<Vec<N>>,
+}
+
+/// Depth-first iterator over a plan and its descendants, in priority order. See [Plan::iter].
+pub struct PlanIter<'a, C: Config> {
+    root: Option<&'a Plan<C>>,
+    stack: Vec<std::slice::Iter<'a, Plan<C>>>,
+}
+
+impl<C: Config> PlanIter<'_, C> {
+    /// Depth of the plan yielded last, relative to the plan the iterator was created from.
+    pub fn depth(&self) -> usize {
+        self.stack.len().saturating_sub(1)
+    }
+}
+
+impl<'a, C: Config> Iterator for PlanIter<'a, C> {
+    type Item = &'a Plan<C>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            self.stack.push(root.plans.iter());
+            return Some(root);
+        }
+        loop {
+            match self.stack.last_mut()?.next() {
+                Some(plan) => {
+                    self.stack.push(plan.plans.iter());
+                    return Some(plan);
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Depth-first iterator over a plan and its descendants along with their paths. See [Plan::iter_with_path].
+pub struct PlanPathIter<'a, C: Config> {
+    root: Option<&'a Plan<C>>,
+    /// Subplans left to visit at each level, with the length of the path of their parent.
+    stack: Vec<(std::slice::Iter<'a, Plan<C>>, usize)>,
+    path: String,
+}
+
+impl<'a, C: Config> Iterator for PlanPathIter<'a, C>
+where
+    C::Name: Borrow<str>,
+{
+    type Item = (String, &'a Plan<C>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            self.stack.push((root.plans.iter(), 0));
+            return Some((String::new(), root));
+        }
+        loop {
+            let (plans, parent_len) = self.stack.last_mut()?;
+            let Some(plan) = plans.next() else {
+                self.stack.pop();
+                continue;
+            };
+            self.path.truncate(*parent_len);
+            if !self.path.is_empty() {
+                self.path.push('/');
+            }
+            self.path.push_str(plan.name.borrow());
+            self.stack.push((plan.plans.iter(), self.path.len()));
+            return Some((self.path.clone(), plan));
+        }
+    }
+}
+
+/// Depth-first cursor over the descendants of a plan, yielding mutable references. See [Plan::iter_descendants_mut].
+///
+/// Not an [Iterator], since each plan yielded may modify its own subplans and therefore borrows the cursor
+/// until the next call of [PlanDescendantsMut::next].
+pub struct PlanDescendantsMut<'a, C: Config> {
+    root: &'a mut Plan<C>,
+    /// Positions of the latest plan yielded and its ancestors among their siblings.
+    indices: Vec<usize>,
+    started: bool,
+}
+
+impl<C: Config> PlanDescendantsMut<'_, C> {
+    fn plan_at(&mut self, depth: usize) -> &mut Plan<C> {
+        let indices = &self.indices[..depth];
+        indices
+            .iter()
+            .fold(&mut *self.root, |plan, &i| &mut plan.plans[i])
+    }
+
+    /// Next descendant in pre-order, or `None` once all were visited.
+    ///
+    /// Subplans inserted into or removed from the latest plan yielded are taken into account.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<&mut Plan<C>> {
+        if !self.started {
+            self.started = true;
+            if self.root.plans.is_empty() {
+                return None;
+            }
+            self.indices.push(0);
+            return Some(self.plan_at(1));
+        }
+        if self.indices.is_empty() {
             return None;
         }
-        // look for requested plan
-        let pos = match self.priority(name) {
-            Ok(pos) => pos,
-            // if plan doesn't exist, create and insert a default plan
-            Err(pos) => {
-                self.plans.insert(pos, Self::new_stub(name, false));
-                pos
+        // descend into the subplans of the latest plan if any
+        if !self.plan_at(self.indices.len()).plans.is_empty() {
+            self.indices.push(0);
+            return Some(self.plan_at(self.indices.len()));
+        }
+        // otherwise move on to the next sibling of it or of its closest ancestor
+        while let Some(index) = self.indices.pop() {
+            let depth = self.indices.len();
+            if index + 1 < self.plan_at(depth).plans.len() {
+                self.indices.push(index + 1);
+                return Some(self.plan_at(depth + 1));
+            }
+        }
+        None
+    }
+}
+
+/// Context of a panic raised by a behaviour hook, replacing its payload while unwinding through the tree.
+///
+/// [Plan::run] and [Plan::run_subtree] re-raise it as a message, so the panic output names the plan and hook.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlanPanic {
+    /// Debug formatted names of the plans unwound through, starting from the outermost.
+    pub path: Vec<String>,
+    /// Name of the hook, such as `"run"` for `on_run()`.
+    pub hook: &'static str,
+    /// Message of the original panic.
+    pub message: String,
+}
+
+impl PlanPanic {
+    /// Message of a panic payload, including the context if it is a [PlanPanic].
+    pub fn message_of(payload: &(dyn std::any::Any + Send)) -> String {
+        if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(panic) = payload.downcast_ref::<PlanPanic>() {
+            panic.to_string()
+        } else {
+            "unknown panic".into()
+        }
+    }
+}
+
+/// See [Diagnostic].
+impl std::fmt::Display for PlanPanic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&Diagnostic::message(self))
+    }
+}
+
+/// Failure of [Plan::run_transactional], after which the tree is left as it was before the tick.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq)]
+pub enum TickError<N = DefaultName> {
+    /// A behaviour hook panicked, so the tick was rolled back.
+    Panic(PlanPanic),
+    /// The tree could not be checkpointed, so the tick was not run.
+    Checkpoint(Error<N>),
+}
+
+#[cfg(feature = "serde")]
+impl<N: Debug> std::fmt::Display for TickError<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TickError::Panic(panic) => write!(f, "{panic}"),
+            TickError::Checkpoint(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+/// Non-empty names of a slash-separated path. See [Plan::get_path].
+fn path_segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|name| !name.is_empty())
+}
+
+/// Segment of [Plan::path] naming a plan, written as is for string names and in `Debug` form otherwise.
+fn path_segment<N: Debug + 'static>(name: &N) -> String {
+    let any: &dyn Any = name;
+    if let Some(name) = any.downcast_ref::<String>() {
+        name.clone()
+    } else if let Some(name) = any.downcast_ref::<&str>() {
+        name.to_string()
+    } else {
+        format!("{name:?}")
+    }
+}
+
+/// Replace the payload of a panic raised by a behaviour hook with a [PlanPanic].
+fn hook_context<R>(hook: &'static str, f: impl FnOnce() -> R) -> R {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(x) => x,
+        Err(payload) if payload.is::<PlanPanic>() => resume_unwind(payload),
+        Err(payload) => resume_unwind(Box::new(PlanPanic {
+            path: Vec::new(),
+            hook,
+            message: PlanPanic::message_of(&*payload),
+        })),
+    }
+}
+
+/// Prepend the name of the plan a [PlanPanic] is unwinding through to its path.
+fn plan_context<R>(name: &impl Debug, result: std::thread::Result<R>) -> R {
+    match result {
+        Ok(x) => x,
+        Err(payload) => match payload.downcast::<PlanPanic>() {
+            Ok(mut panic) => {
+                panic.path.insert(0, format!("{:?}", name));
+                resume_unwind(panic)
+            }
+            Err(payload) => resume_unwind(payload),
+        },
+    }
+}
+
+/// Re-raise a [PlanPanic] as its message, for the context to appear in the panic output.
+fn report_panic<R>(f: impl FnOnce() -> R) -> R {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(x) => x,
+        Err(payload) => match payload.downcast::<PlanPanic>() {
+            Ok(panic) => std::panic::panic_any(panic.to_string()),
+            Err(payload) => resume_unwind(payload),
+        },
+    }
+}
+
+/// Accumulated wall-clock duration of a behaviour hook.
+#[cfg(feature = "timing")]
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct HookTiming {
+    pub calls: u32,
+    pub total: std::time::Duration,
+    pub max: std::time::Duration,
+}
+
+/// Execution time spent in the behaviour hooks of a single plan, keyed by hook name.
+#[cfg(feature = "timing")]
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct TimingStats(pub HashMap<&'static str, HookTiming>);
+
+#[cfg(feature = "timing")]
+impl TimingStats {
+    /// Total duration across all hooks.
+    pub fn total(&self) -> std::time::Duration {
+        self.0.values().map(|x| x.total).sum()
+    }
+
+    fn record(&mut self, hook: &'static str, duration: std::time::Duration) {
+        let timing = self.0.entry(hook).or_default();
+        timing.calls += 1;
+        timing.total += duration;
+        timing.max = timing.max.max(duration);
+    }
+}
+
+/// Source of utility scores external to the plan tree, such as a planner. See [Plan::set_context].
+pub trait ScoreContext<N = DefaultName>: Send + Sync {
+    /// Utility of the plan with the given name, or `None` to leave it to the behaviour.
+    ///
+    /// Called whenever the utility of a plan scored by the context is queried, possibly several times per tick
+    /// and from several threads, so it should be cheap and must not call back into the tree.
+    fn score(&self, name: &N) -> Option<f64>;
+}
+
+/// State shared among all plans of the same tree, inherited from the parent upon entry.
+struct Tree<N> {
+    tags: Mutex<TagState<N>>,
+    draining: AtomicBool,
+    cancel: CancelToken,
+    /// Number of ticks run, for scheduling idle subplans. See [Plan::skip_idle].
+    tick: AtomicU64,
+    /// Incremented whenever an event is posted or the tree is changed between ticks, waking all idle subplans.
+    epoch: AtomicU64,
+    /// Set while a tick runs, to tell changes made between ticks apart.
+    ticking: AtomicBool,
+    /// Warnings keyed by code and path of the plan.
+    warnings: Mutex<BTreeMap<(&'static str, String), Warning>>,
+    warning_interval: AtomicU64,
+    transition_scores: Mutex<Vec<TransitionScore<N>>>,
+    mutations: Mutex<Vec<AppliedMutation<N>>>,
+    isolated_panics: Mutex<Vec<IsolatedPanic>>,
+    /// Slots taken of each counter. See [Plan::acquire_slot].
+    slots: Mutex<HashMap<String, SlotCounter>>,
+    #[cfg(feature = "journal")]
+    journal: Mutex<VecDeque<DataChange>>,
+    #[cfg(feature = "journal")]
+    journal_capacity: AtomicUsize,
+}
+
+impl<N> Default for Tree<N> {
+    fn default() -> Self {
+        Self {
+            tags: Default::default(),
+            draining: Default::default(),
+            cancel: Default::default(),
+            tick: Default::default(),
+            epoch: Default::default(),
+            ticking: Default::default(),
+            warnings: Default::default(),
+            warning_interval: Default::default(),
+            transition_scores: Default::default(),
+            mutations: Default::default(),
+            isolated_panics: Default::default(),
+            slots: Default::default(),
+            #[cfg(feature = "journal")]
+            journal: Default::default(),
+            #[cfg(feature = "journal")]
+            journal_capacity: AtomicUsize::new(default_journal_capacity()),
+        }
+    }
+}
+
+/// Counter of slots shared by the tree, kept in the data of the root between ticks. See [Plan::acquire_slot].
+#[derive(Clone, Copy, Default)]
+struct SlotCounter {
+    taken: usize,
+    /// Value last read from or written to the data of the root, to tell changes made to it apart.
+    synced: Option<usize>,
+}
+
+/// State of a tree saved by [Plan::run_transactional] to roll back a failed tick.
+#[cfg(feature = "serde")]
+struct Checkpoint<C: Config> {
+    plan: serde_value::Value,
+    /// State of each plan that is not serialized, in pre-order.
+    runtime: Vec<RuntimeState<C>>,
+    tick: u64,
+    epoch: u64,
+    tag_counts: HashMap<String, usize>,
+    slot_counts: HashMap<String, SlotCounter>,
+    /// Lengths of the tag refusals, transition scores, mutations, and isolated panics pending report.
+    reports: (usize, usize, usize, usize),
+    #[cfg(feature = "journal")]
+    journal: VecDeque<DataChange>,
+}
+
+/// Fields of a plan skipped by serialization.
+#[cfg(feature = "serde")]
+struct RuntimeState<C: Config> {
+    last_status: Option<bool>,
+    status_tick: Option<u64>,
+    inherited_seed: Option<u64>,
+    path: String,
+    context: Option<Arc<dyn ScoreContext<C::Name>>>,
+    transient: Option<serde_value::Value>,
+    idle: Option<Idle>,
+    transition_index: Option<Box<TransitionIndex<C::Name>>>,
+    tree: Arc<Tree<C::Name>>,
+    span: Span,
+    #[cfg(feature = "timing")]
+    timings: TimingStats,
+}
+
+#[cfg(feature = "serde")]
+impl<C: Config> Checkpoint<C>
+where
+    Plan<C>: Serialize + serde::de::DeserializeOwned,
+{
+    fn save(plan: &Plan<C>) -> Result<Self, Error<C::Name>> {
+        fn to_value<N>(x: &impl Serialize) -> Result<serde_value::Value, Error<N>> {
+            serde_value::to_value(x).map_err(|e| Error::Json(e.to_string()))
+        }
+        fn collect<C: Config>(
+            plan: &Plan<C>,
+            runtime: &mut Vec<RuntimeState<C>>,
+        ) -> Result<(), Error<C::Name>> {
+            runtime.push(RuntimeState {
+                last_status: plan.last_status,
+                status_tick: plan.activation.as_ref().and_then(|x| x.status_tick),
+                inherited_seed: plan.activation.as_ref().and_then(|x| x.inherited_seed),
+                path: plan.path.clone(),
+                context: plan.inner.as_ref().and_then(|x| x.context.clone()),
+                transient: plan.transient_ref().map(to_value).transpose()?,
+                idle: plan.activation.as_ref().and_then(|x| x.idle),
+                transition_index: plan.inner.as_ref().and_then(|x| x.transition_index.clone()),
+                tree: plan.tree.clone(),
+                span: plan.span().clone(),
+                #[cfg(feature = "timing")]
+                timings: plan.timings.clone(),
+            });
+            plan.plans.iter().try_for_each(|x| collect(x, runtime))
+        }
+        let mut runtime = Vec::new();
+        collect(plan, &mut runtime)?;
+        let tree = &plan.tree;
+        let tags = tree.tags.lock().unwrap();
+        Ok(Self {
+            plan: to_value(plan)?,
+            runtime,
+            tick: tree.tick.load(Ordering::Relaxed),
+            epoch: tree.epoch.load(Ordering::Relaxed),
+            tag_counts: tags.counts.clone(),
+            slot_counts: tree.slots.lock().unwrap().clone(),
+            reports: (
+                tags.refusals.len(),
+                tree.transition_scores.lock().unwrap().len(),
+                tree.mutations.lock().unwrap().len(),
+                tree.isolated_panics.lock().unwrap().len(),
+            ),
+            #[cfg(feature = "journal")]
+            journal: tree.journal.lock().unwrap().clone(),
+        })
+    }
+
+    /// Replace the plan with its saved state without triggering any hooks.
+    fn restore(self, plan: &mut Plan<C>) {
+        fn apply<C: Config>(
+            plan: &mut Plan<C>,
+            runtime: &mut impl Iterator<Item = RuntimeState<C>>,
+        ) {
+            let state = runtime.next().expect("checkpoint of every plan");
+            plan.last_status = state.last_status;
+            plan.path = state.path;
+            let transient = state
+                .transient
+                .map(|x| Box::new(x.deserialize_into().expect("checkpoint of transient state")));
+            let activated = state.status_tick.is_some()
+                || state.inherited_seed.is_some()
+                || transient.is_some()
+                || state.idle.is_some()
+                || !state.span.is_none();
+            if activated {
+                let activation = plan.activation();
+                activation.status_tick = state.status_tick;
+                activation.inherited_seed = state.inherited_seed;
+                activation.transient = transient;
+                activation.idle = state.idle;
+                activation.span = state.span;
+            }
+            if state.transition_index.is_some() || state.context.is_some() {
+                let inner = plan.inner_mut();
+                inner.transition_index = state.transition_index;
+                inner.context = state.context;
+            }
+            plan.tree = state.tree;
+            #[cfg(feature = "timing")]
+            {
+                plan.timings = state.timings;
+            }
+            plan.plans.iter_mut().for_each(|x| apply(x, runtime));
+        }
+        fn deactivate<C: Config>(plan: &mut Plan<C>) {
+            plan.run_countdown = u32::MAX;
+            plan.plans.iter_mut().for_each(deactivate);
+        }
+        let mut restored = Plan::<C>::deserialize(self.plan).expect("checkpoint of tree");
+        apply(&mut restored, &mut self.runtime.into_iter());
+        if let Some(inner) = &mut plan.inner {
+            restored.inner_mut().publisher = inner.publisher.take();
+            #[cfg(feature = "rayon")]
+            {
+                restored.inner_mut().thread_pool = inner.thread_pool.take();
+            }
+        }
+        // drop the state reached by the failed tick as if inactive, so no hooks are triggered
+        deactivate(plan);
+        *plan = restored;
+
+        let tree = &plan.tree;
+        tree.tick.store(self.tick, Ordering::Relaxed);
+        tree.epoch.store(self.epoch, Ordering::Relaxed);
+        let mut tags = tree.tags.lock().unwrap();
+        tags.counts = self.tag_counts;
+        *tree.slots.lock().unwrap() = self.slot_counts;
+        tags.refusals.truncate(self.reports.0);
+        tree.transition_scores
+            .lock()
+            .unwrap()
+            .truncate(self.reports.1);
+        tree.mutations.lock().unwrap().truncate(self.reports.2);
+        tree.isolated_panics
+            .lock()
+            .unwrap()
+            .truncate(self.reports.3);
+        #[cfg(feature = "journal")]
+        {
+            *tree.journal.lock().unwrap() = self.journal;
+        }
+    }
+}
+
+/// Transitions indexed by their source plans. See [Plan::compile].
+#[derive(Clone)]
+struct TransitionIndex<N> {
+    /// Transitions listing each plan among their sources, in order.
+    by_src: BTreeMap<N, Vec<usize>>,
+    /// Transitions without sources, which are evaluated whenever the others are.
+    unconditional: Vec<usize>,
+    /// Source plans in order of first appearance.
+    names: Vec<N>,
+    /// Sources of each transition when indexed, to detect changes to the transitions.
+    sources: Vec<Vec<N>>,
+}
+
+impl<N: Ord + Clone> TransitionIndex<N> {
+    fn new<P>(transitions: &[Transition<P, N>]) -> Self {
+        let mut index = Self {
+            by_src: BTreeMap::new(),
+            unconditional: Vec::new(),
+            names: Vec::new(),
+            sources: transitions
+                .iter()
+                .map(|t| t.sources().cloned().collect())
+                .collect(),
+        };
+        for (i, t) in transitions.iter().enumerate() {
+            if t.sources().next().is_none() {
+                index.unconditional.push(i);
+            }
+            for name in t.sources() {
+                let entry = index.by_src.entry(name.clone()).or_insert_with(|| {
+                    index.names.push(name.clone());
+                    Vec::new()
+                });
+                if entry.last() != Some(&i) {
+                    entry.push(i);
+                }
+            }
+        }
+        index
+    }
+
+    /// Whether the sources of `transitions` are still those indexed.
+    fn matches<P>(&self, transitions: &[Transition<P, N>]) -> bool {
+        self.sources.len() == transitions.len()
+            && self
+                .sources
+                .iter()
+                .zip(transitions)
+                .all(|(src, t)| src.iter().eq(t.sources()))
+    }
+
+    /// Transitions in order that list only sources among the `active` plans, along with some that do not.
+    fn candidates(&self, active: &BTreeSet<&N>) -> Vec<usize> {
+        let mut candidates = self.unconditional.clone();
+        for name in active {
+            candidates.extend(self.by_src.get(*name).into_iter().flatten());
+        }
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+}
+
+/// Plan referred to by the transitions of a plan but missing from its subplans, cached until either changes.
+struct MissingPlans<N> {
+    /// Sources and destinations of each transition when checked.
+    endpoints: Vec<(Vec<N>, Vec<N>)>,
+    /// Names of the subplans when checked.
+    names: Vec<N>,
+    /// First missing plan in order of the transitions, sources before destinations.
+    missing: Option<N>,
+}
+
+impl<N: Ord + Clone> MissingPlans<N> {
+    fn new<'a, P>(transitions: &[Transition<P, N>], names: impl Iterator<Item = &'a N>) -> Self
+    where
+        N: 'a,
+    {
+        let names = names.cloned().collect::<Vec<_>>();
+        let endpoints = transitions
+            .iter()
+            .map(|t| (t.sources().cloned().collect(), t.dst.clone()))
+            .collect::<Vec<(Vec<_>, _)>>();
+        let present = names.iter().collect::<BTreeSet<_>>();
+        let missing = endpoints
+            .iter()
+            .flat_map(|(src, dst)| src.iter().chain(dst))
+            .find(|p| !present.contains(p))
+            .cloned();
+        Self {
+            endpoints,
+            names,
+            missing,
+        }
+    }
+
+    /// Whether the transitions and subplan names are still those checked.
+    fn matches<'a, P>(
+        &self,
+        transitions: &[Transition<P, N>],
+        mut names: impl ExactSizeIterator<Item = &'a N>,
+    ) -> bool
+    where
+        N: 'a,
+    {
+        self.names.len() == names.len()
+            && self.names.iter().all(|x| names.next() == Some(x))
+            && self.endpoints.len() == transitions.len()
+            && self
+                .endpoints
+                .iter()
+                .zip(transitions)
+                .all(|((src, dst), t)| src.iter().eq(t.sources()) && *dst == t.dst)
+    }
+}
+
+/// Scheduling state of an idle plan whose subtree is skipped until due.
+#[derive(Clone, Copy)]
+struct Idle {
+    /// Tick on which the plan last ran.
+    since: u64,
+    /// Tick on which the plan is next due to run.
+    until: u64,
+    /// Tree epoch when the plan became idle.
+    epoch: u64,
+}
+
+struct TagState<N> {
+    limits: TagLimits,
+    counts: HashMap<String, usize>,
+    refusals: Vec<TagRefusal<N>>,
+}
+
+impl<N> Default for TagState<N> {
+    fn default() -> Self {
+        Self {
+            limits: Default::default(),
+            counts: Default::default(),
+            refusals: Default::default(),
+        }
+    }
+}
+
+impl<N: Ord + Clone> Tree<N> {
+    /// Record occurrence of a warning, returning the number of suppressed occurrences if it should be logged.
+    ///
+    /// Each distinct warning logs the first time, then at most once every `warning_interval` ticks.
+    fn throttle_warning(&self, code: &'static str, path: &str) -> Option<u64> {
+        let tick = self.tick.load(Ordering::Relaxed);
+        let interval = self.warning_interval.load(Ordering::Relaxed);
+        let mut warnings = self.warnings.lock().unwrap();
+        let warning = warnings
+            .entry((code, path.into()))
+            .or_insert_with(|| Warning {
+                code,
+                path: path.into(),
+                count: 0,
+                suppressed: 0,
+                logged_tick: None,
+            });
+        warning.count += 1;
+        match warning.logged_tick {
+            Some(logged) if tick < logged + interval => {
+                warning.suppressed += 1;
+                None
+            }
+            _ => {
+                warning.logged_tick = Some(tick);
+                Some(std::mem::take(&mut warning.suppressed))
+            }
+        }
+    }
+
+    /// Append a data change to the journal, evicting the oldest entries beyond capacity.
+    #[cfg(feature = "journal")]
+    fn journal_change(&self, change: DataChange) {
+        let capacity = self.journal_capacity.load(Ordering::Relaxed);
+        let mut journal = self.journal.lock().unwrap();
+        journal.push_back(change);
+        while journal.len() > capacity {
+            journal.pop_front();
+        }
+    }
+
+    /// Occupy a slot for each tag if none have reached their limit, otherwise return the full tag.
+    fn acquire_tags(&self, plan: &N, tags: &[String]) -> Result<(), String> {
+        let mut state = self.tags.lock().unwrap();
+        let state = &mut *state;
+        let full = tags.iter().find(|tag| match state.limits.0.get(*tag) {
+            Some(limit) => state.counts.get(*tag).copied().unwrap_or(0) >= *limit,
+            None => false,
+        });
+        if let Some(tag) = full {
+            state.refusals.push(TagRefusal {
+                plan: plan.clone(),
+                tag: tag.clone(),
+            });
+            return Err(tag.clone());
+        }
+        for tag in tags {
+            *state.counts.entry(tag.clone()).or_default() += 1;
+        }
+        Ok(())
+    }
+
+    /// Free the slots occupied by each tag.
+    fn release_tags(&self, tags: &[String]) {
+        let mut state = self.tags.lock().unwrap();
+        for tag in tags {
+            if let Some(count) = state.counts.get_mut(tag) {
+                *count = count.saturating_sub(1);
+            }
+        }
+    }
+}
+
+/// Per-tick and per-activation state of a plan, only allocated once used to keep leaf plans small.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Transient<E> {
+    events: Vec<E>,
+    pending_events: Vec<E>,
+    fired: Vec<String>,
+    /// Times each transition with limited firings fired during this activation, by index.
+    firings: Vec<u32>,
+}
+
+impl<E> Default for Transient<E> {
+    fn default() -> Self {
+        Self {
+            events: Vec::new(),
+            pending_events: Vec::new(),
+            fired: Vec::new(),
+            firings: Vec::new(),
+        }
+    }
+}
+
+/// State of the latest activation of a plan, allocated upon its first entry and kept for later entries.
+struct Activation<E> {
+    span: Span,
+    /// Delay of the latest run from the regular schedule.
+    jitter_delay: u32,
+    /// Tick of the run that last recorded [Plan::last_active_status] during the current activation.
+    status_tick: Option<u64>,
+    inherited_seed: Option<u64>,
+    idle: Option<Idle>,
+    transient: Option<Box<Transient<E>>>,
+}
+
+impl<E> Default for Activation<E> {
+    fn default() -> Self {
+        Self {
+            span: Span::none(),
+            jitter_delay: 0,
+            status_tick: None,
+            inherited_seed: None,
+            idle: None,
+            transient: None,
+        }
+    }
+}
+
+/// Span of the activation, borrowed apart from the other fields of its plan. See [Plan::span].
+fn activation_span<E>(activation: &Option<Box<Activation<E>>>) -> &Span {
+    static NONE: Span = Span::none();
+    activation.as_ref().map_or(&NONE, |x| &x.span)
+}
+
+/// Allocations of a plan that may be reused by another, emptied but keeping their capacity.
+pub(crate) struct PlanParts<C: Config> {
+    transitions: Vec<Transition<C::Predicate, C::Name>>,
+    activation: Option<Box<Activation<C::Event>>>,
+    inner: Option<Box<Inner<C>>>,
+    /// Subplans moved out along with the allocations, to be recycled separately.
+    pub plans: Vec<Plan<C>>,
+    /// Reset for reuse if no longer shared with other plans.
+    tree: Option<Arc<Tree<C::Name>>>,
+}
+
+#[cfg(feature = "arena")]
+impl<C: Config> PlanParts<C> {
+    /// Drop the tree state if shared with other plans, so only the last parts referring to it keep it.
+    pub(crate) fn release_shared_tree(&mut self) {
+        self.tree = self.tree.take().filter(|x| Arc::strong_count(x) == 1);
+    }
+}
+
+impl<C: Config> Default for PlanParts<C> {
+    fn default() -> Self {
+        Self {
+            transitions: Vec::new(),
+            activation: None,
+            inner: None,
+            plans: Vec::new(),
+            tree: None,
+        }
+    }
+}
+
+/// Fields of a plan that leaf plans rarely set, only allocated once used to keep leaf plans small.
+struct Inner<C: Config> {
+    autostart_if: Option<C::Predicate>,
+    description: Option<String>,
+    data: HashMap<String, serde_value::Value>,
+    tags: Vec<String>,
+    tag_limits: TagLimits,
+    warning_interval: u64,
+    #[cfg(feature = "journal")]
+    journal_capacity: usize,
+    /// Keys of the slots held, serialized along with the plan. See [Plan::acquire_slot].
+    slots: Vec<String>,
+    #[cfg(feature = "rayon")]
+    thread_pool: Option<Arc<rayon::ThreadPool>>,
+    transition_index: Option<Box<TransitionIndex<C::Name>>>,
+    missing_plans: Option<Box<MissingPlans<C::Name>>>,
+    deferred: Vec<PlanMutation<C>>,
+    publisher: Option<Box<Publisher<C::Name>>>,
+    context: Option<Arc<dyn ScoreContext<C::Name>>>,
+}
+
+impl<C: Config> Default for Inner<C> {
+    fn default() -> Self {
+        Self {
+            autostart_if: None,
+            description: None,
+            data: HashMap::new(),
+            tags: Vec::new(),
+            tag_limits: TagLimits::default(),
+            warning_interval: default_warning_interval(),
+            #[cfg(feature = "journal")]
+            journal_capacity: default_journal_capacity(),
+            slots: Vec::new(),
+            #[cfg(feature = "rayon")]
+            thread_pool: None,
+            transition_index: None,
+            missing_plans: None,
+            deferred: Vec::new(),
+            publisher: None,
+            context: None,
+        }
+    }
+}
+
+#[cfg(feature = "arena")]
+impl<C: Config> Inner<C> {
+    /// Restore the default state, keeping the capacity of the vectors and maps.
+    fn reset(&mut self) {
+        let old = std::mem::take(self);
+        *self = Self {
+            data: old.data,
+            tags: old.tags,
+            tag_limits: old.tag_limits,
+            slots: old.slots,
+            deferred: old.deferred,
+            ..Self::default()
+        };
+        self.data.clear();
+        self.tags.clear();
+        self.tag_limits.0.clear();
+        self.slots.clear();
+        self.deferred.clear();
+    }
+}
+
+/// A node in the plan tree containing some behaviour, subplans, and possible transitions.
+///
+/// Fields that leaf plans rarely set, such as the data and tags, are kept in a separate allocation
+/// made upon first use and are accessed through methods, such as [Plan::data] and [Plan::tags_mut].
+pub struct Plan<C: Config> {
+    name: C::Name,
+    run_countdown: u32,
+    /// Number of ticks between each run of the behaviour, where 0 is treated as 1.
+    pub run_interval: u32,
+    /// Maximum number of ticks each run is delayed from its regular schedule, to spread out the runs of similar plans.
+    ///
+    /// Delays are pseudo-random within `0..=interval_jitter`, derived from the seed of this plan and the current tick,
+    /// so runs still occur every `run_interval` ticks on average. Limited to `run_interval - 1`.
+    pub interval_jitter: u32,
+    /// Parts of this plan executed on each tick.
+    ///
+    /// Plans serialized before this field existed load as [ExecMode::StructureOnly] if their `run_interval` is 0.
+    pub execution: ExecMode,
+    /// Suspend running behaviours and firing transitions within this subtree, keeping their state intact.
+    ///
+    /// Status and utility remain queryable, and the subtree may still be entered and exited. See [Plan::freeze].
+    pub frozen: bool,
+    /// Automatically enter following the entry of parent plan.
+    pub autostart: bool,
+    /// Consider this plan reachable during [Plan::validate], for plans entered directly by behaviours.
+    pub reachable_hint: bool,
+    /// Skip visiting active subplans with nothing to do until they are next due to run.
+    ///
+    /// Subplans are idle while no active plan within has transitions or events, and no behaviour within is due.
+    /// Changes made between ticks through methods such as [Plan::enter_plan], [Plan::insert], [Plan::set_data]
+    /// and [Plan::get_mut] wake idle plans to be visited on the next tick. Fields of subplans reached otherwise,
+    /// such as through [Plan::plans], should not be changed between ticks.
+    pub skip_idle: bool,
+    /// Order of triggering `on_exit()` for this plan relative to its subplans.
+    pub exit_order: ExitOrder,
+    /// While inactive, [Plan::status] returns [Plan::last_active_status] instead of re-evaluating the behaviour.
+    pub report_cached_when_inactive: bool,
+    /// Contain panics raised by hooks within this subtree instead of unwinding through the parent.
+    ///
+    /// Upon a panic, this plan and its subplans are exited without triggering further hooks,
+    /// since behaviours within may be left in an inconsistent state. Its status is failure until it is entered again,
+    /// and the panic is recorded in the [TickReport] as an [IsolatedPanic]. The rest of the tree carries on.
+    pub isolate: bool,
+    /// Number of panics contained by [Plan::isolate] after which this plan is poisoned and refuses entry,
+    /// where 0 never poisons. See [Plan::clear_poison].
+    pub poison_after: u32,
+    /// Seed for behaviours with random number generation, applied upon entry. See [Behaviour::reseed].
+    ///
+    /// When unset, the seed is derived from the seed of the parent plan and the name of this plan, if any.
+    pub seed: Option<u64>,
+    /// Customizable run-time logic.
+    pub behaviour: Option<Box<C::Behaviour>>,
+    /// List of transition conditions between sets of subplans.
+    pub transitions: Vec<Transition<C::Predicate, C::Name>>,
+    /// Contains instances of subplans recursively.
+    pub plans: Vec<Self>,
+    paused: bool,
+    outcomes: OutcomeCounts,
+    panics: u32,
+    panicked: bool,
+    poisoned: bool,
+    last_status: Option<bool>,
+    path: String,
+    activation: Option<Box<Activation<C::Event>>>,
+    inner: Option<Box<Inner<C>>>,
+    tree: Arc<Tree<C::Name>>,
+    #[cfg(feature = "timing")]
+    timings: TimingStats,
+    #[cfg(feature = "journal")]
+    hook: Option<&'static str>,
+}
+
+impl<C: Config> Plan<C> {
+    /// ID unique among subplans.
+    pub fn name(&self) -> &C::Name {
+        &self.name
+    }
+
+    /// Slash-separated names from the root down to this plan, such as `"root/patrol/scan"`.
+    ///
+    /// Refreshed as plans are inserted and entered, so subtrees moved to another parent take on the new path.
+    /// Plans pushed to [Plan::plans] directly or deserialized are refreshed once entered.
+    /// Names other than strings are written in their `Debug` form.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Labels used to limit the number of simultaneously active plans. See [TagLimits].
+    pub fn tags(&self) -> &[String] {
+        self.inner.as_ref().map_or(&[], |x| &x.tags)
+    }
+
+    /// Same as [Plan::tags], for modifying the tags.
+    pub fn tags_mut(&mut self) -> &mut Vec<String> {
+        &mut self.inner_mut().tags
+    }
+
+    /// Limits on active plans per tag, only effective on the root plan.
+    pub fn tag_limits(&self) -> &TagLimits {
+        static NONE: LazyLock<TagLimits> = LazyLock::new(TagLimits::default);
+        self.inner.as_ref().map_or(&NONE, |x| &x.tag_limits)
+    }
+
+    /// Same as [Plan::tag_limits], for modifying the limits.
+    pub fn tag_limits_mut(&mut self) -> &mut TagLimits {
+        &mut self.inner_mut().tag_limits
+    }
+
+    /// Overrides `autostart` when set, automatically entering only if the predicate holds upon entry of parent plan.
+    ///
+    /// Evaluated against the parent plan once upon its entry, not continuously while it stays active.
+    pub fn autostart_if(&self) -> Option<&C::Predicate> {
+        self.inner.as_ref()?.autostart_if.as_ref()
+    }
+
+    /// See [Plan::autostart_if].
+    pub fn set_autostart_if(&mut self, predicate: Option<C::Predicate>) {
+        if predicate.is_some() || self.inner.is_some() {
+            self.inner_mut().autostart_if = predicate;
+        }
+    }
+
+    /// Free text explaining the purpose of this plan, for tooling and diagnostics only. See [Plan::describe_issue].
+    pub fn description(&self) -> Option<&str> {
+        self.inner.as_ref()?.description.as_deref()
+    }
+
+    /// See [Plan::description].
+    pub fn set_description(&mut self, description: Option<String>) {
+        if description.is_some() || self.inner.is_some() {
+            self.inner_mut().description = description;
+        }
+    }
+
+    /// Minimum number of ticks between logging repeats of the same warning, only effective on the root plan.
+    pub fn warning_interval(&self) -> u64 {
+        self.inner
+            .as_ref()
+            .map_or_else(default_warning_interval, |x| x.warning_interval)
+    }
+
+    /// See [Plan::warning_interval].
+    pub fn set_warning_interval(&mut self, ticks: u64) {
+        self.inner_mut().warning_interval = ticks;
+    }
+
+    /// Maximum number of entries kept in the data journal, only effective on the root plan.
+    #[cfg(feature = "journal")]
+    pub fn journal_capacity(&self) -> usize {
+        self.inner
+            .as_ref()
+            .map_or_else(default_journal_capacity, |x| x.journal_capacity)
+    }
+
+    /// See [Plan::journal_capacity].
+    #[cfg(feature = "journal")]
+    pub fn set_journal_capacity(&mut self, capacity: usize) {
+        self.inner_mut().journal_capacity = capacity;
+    }
+
+    /// Fields rarely set by leaf plans, allocated upon first use.
+    fn inner_mut(&mut self) -> &mut Inner<C> {
+        self.inner.get_or_insert_with(Default::default)
+    }
+
+    /// Whether the inner behaviour is scheduled to run.
+    pub fn active(&self) -> bool {
+        self.run_countdown < u32::MAX
+    }
+
+    /// Number of ticks until next run.
+    pub fn run_countdown(&self) -> u32 {
+        self.run_countdown
+    }
+
+    /// Status of the inner behaviour.
+    ///
+    /// While inactive, the behaviour is evaluated against subplans that may have since exited,
+    /// unless [Plan::report_cached_when_inactive] is set.
+    pub fn status(&self) -> Option<bool> {
+        if self.panicked {
+            return Some(false);
+        }
+        if self.report_cached_when_inactive && !self.active() {
+            return self.last_status;
+        }
+        self.behaviour.as_ref()?.status(self)
+    }
+
+    /// Status as of the end of the latest run or exit during the current or most recent activation.
+    ///
+    /// Exiting in the same tick as the latest run keeps the status recorded by that run.
+    pub fn last_active_status(&self) -> Option<bool> {
+        self.last_status
+    }
+
+    /// Capture the paths of this plan and all active subplans recursively.
+    pub fn active_state(&self) -> ActiveState<C::Name> {
+        fn collect<C: Config>(
+            plan: &Plan<C>,
+            path: &mut Vec<C::Name>,
+            active: &mut BTreeSet<Vec<C::Name>>,
+        ) {
+            active.insert(path.clone());
+            for x in plan.plans.iter().filter(|x| x.active()) {
+                path.push(x.name().clone());
+                collect(x, path, active);
+                path.pop();
+            }
+        }
+        let mut active = BTreeSet::new();
+        if self.active() {
+            collect(self, &mut Vec::new(), &mut active);
+        }
+        ActiveState(active)
+    }
+
+    /// Plans that entered or exited since `previous` was captured from this plan by [Plan::active_state].
+    pub fn active_delta(&self, previous: &ActiveState<C::Name>) -> ActiveDelta<C::Name> {
+        let current = self.active_state();
+        ActiveDelta {
+            entered: current.0.difference(&previous.0).cloned().collect(),
+            exited: previous.0.difference(&current.0).cloned().collect(),
+        }
+    }
+
+    /// Deepest active plan reached by descending through active subplans, or `None` if this plan is inactive.
+    ///
+    /// Where several subplans are active, the one with the highest priority is followed.
+    pub fn active_leaf(&self) -> Option<&Self> {
+        let mut plan = self;
+        if !plan.active() {
+            return None;
+        }
+        while let Some(next) = plan.plans.iter().find(|x| x.active()) {
+            plan = next;
+        }
+        Some(plan)
+    }
+
+    /// Depth-first iterator over this plan and all of its descendants, visiting subplans in priority order.
+    ///
+    /// Only allocates to grow the traversal stack as deeper plans are reached.
+    pub fn iter(&self) -> PlanIter<'_, C> {
+        PlanIter {
+            root: Some(self),
+            stack: Vec::new(),
+        }
+    }
+
+    /// Same as [Plan::iter], excluding this plan.
+    pub fn iter_descendants(&self) -> PlanIter<'_, C> {
+        PlanIter {
+            root: None,
+            stack: vec![self.plans.iter()],
+        }
+    }
+
+    /// Same as [Plan::iter_descendants], yielding mutable references through a [PlanDescendantsMut] cursor.
+    ///
+    /// ```
+    /// use dynamic_plan_tree::{config::*, *};
+    ///
+    /// let mut root = Plan::<config::Default>::new_stub("root", true);
+    /// root.insert(Plan::new_stub("a", false));
+    /// root.insert(Plan::new_stub("b", false));
+    /// let mut descendants = root.iter_descendants_mut();
+    /// while let Some(plan) = descendants.next() {
+    ///     plan.autostart = true;
+    /// }
+    /// assert!(root.iter_descendants().all(|x| x.autostart));
+    /// ```
+    pub fn iter_descendants_mut(&mut self) -> PlanDescendantsMut<'_, C> {
+        self.wake_idle();
+        PlanDescendantsMut {
+            root: self,
+            indices: Vec::new(),
+            started: false,
+        }
+    }
+
+    /// Same as [Plan::iter], along with the slash-separated path of each plan relative to this plan.
+    ///
+    /// This plan has the empty path, and paths are accepted by [Plan::get_path].
+    pub fn iter_with_path(&self) -> PlanPathIter<'_, C>
+    where
+        C::Name: Borrow<str>,
+    {
+        PlanPathIter {
+            root: Some(self),
+            stack: Vec::new(),
+            path: String::new(),
+        }
+    }
+
+    /// Same as [Plan::iter], skipping inactive plans.
+    pub fn iter_active(&self) -> impl Iterator<Item = &Self> {
+        self.iter().filter(|x| x.active())
+    }
+
+    /// Status rolled up over this plan and all of its active descendants.
+    ///
+    /// `Some(false)` if any of them report `Some(false)`, `Some(true)` if all of them report
+    /// `Some(true)`, otherwise `None`. Inactive subplans are ignored along with their descendants.
+    pub fn subtree_status(&self) -> Option<bool> {
+        let mut all_success = match self.status() {
+            Some(false) => return Some(false),
+            status => status.is_some(),
+        };
+        for plan in self.plans.iter().filter(|plan| plan.active()) {
+            match plan.subtree_status() {
+                Some(false) => return Some(false),
+                None => all_success = false,
+                Some(true) => {}
+            }
+        }
+        all_success.then_some(true)
+    }
+
+    /// Storage for arbitrary serializable data.
+    pub fn data(&self) -> &HashMap<String, serde_value::Value> {
+        static NONE: LazyLock<HashMap<String, serde_value::Value>> = LazyLock::new(HashMap::new);
+        self.inner.as_ref().map_or(&NONE, |x| &x.data)
+    }
+
+    /// Insert a data entry, returning the replaced value if any.
+    pub fn set_data(
+        &mut self,
+        key: impl Into<String>,
+        value: serde_value::Value,
+    ) -> Option<serde_value::Value> {
+        self.wake_idle();
+        let key = key.into();
+        #[cfg(feature = "journal")]
+        let new_value = Some(DataChange::summarize(&value));
+        let old = self.inner_mut().data.insert(key.clone(), value);
+        #[cfg(feature = "journal")]
+        self.journal_change(key, old.as_ref(), new_value);
+        old
+    }
+
+    /// Remove a data entry, returning its value if any.
+    pub fn remove_data(&mut self, key: &str) -> Option<serde_value::Value> {
+        self.wake_idle();
+        let old = self.inner.as_mut()?.data.remove(key);
+        #[cfg(feature = "journal")]
+        if old.is_some() {
+            self.journal_change(key.into(), old.as_ref(), None);
+        }
+        old
+    }
+
+    #[cfg(feature = "journal")]
+    fn journal_change(
+        &self,
+        key: String,
+        old: Option<&serde_value::Value>,
+        new_value: Option<String>,
+    ) {
+        self.tree.journal_change(DataChange {
+            tick: self.tree.tick.load(Ordering::Relaxed),
+            path: self.path.clone(),
+            key,
+            old_hash: old.map(DataChange::hash_value),
+            new_value,
+            hook: self.hook,
+        });
+    }
+
+    /// Utility of the inner behaviour.
+    pub fn utility(&self) -> f64 {
+        self.behaviour
+            .as_ref()
+            .map(|b| b.utility(self))
+            .unwrap_or(0.)
+    }
+
+    /// Status and utility this plan is expected to have if it were entered now, without side effects.
+    ///
+    /// While active, these are the current status and utility.
+    /// While inactive, the behaviour is asked for an [Behaviour::estimate],
+    /// falling back to its status and utility as evaluated against the plan as is,
+    /// regardless of [Plan::report_cached_when_inactive].
+    pub fn hypothetical(&self) -> Hypothetical {
+        if self.active() {
+            return Hypothetical {
+                status: self.status(),
+                utility: self.utility(),
+            };
+        }
+        let Some(behaviour) = &self.behaviour else {
+            return Hypothetical::default();
+        };
+        behaviour.estimate(self).unwrap_or_else(|| Hypothetical {
+            status: behaviour.status(self),
+            utility: behaviour.utility(self),
+        })
+    }
+
+    /// Returns [Plan::hypothetical] of subplan by name.
+    ///
+    /// Lets a parent weigh a candidate subplan before entering it, as selectors do with `use_estimates`.
+    pub fn evaluate_hypothetical<Q>(&self, name: &Q) -> Option<Hypothetical>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(name).map(Self::hypothetical)
+    }
+
+    /// Named contributions to [Plan::utility], summing to it. See [Behaviour::utility_breakdown].
+    ///
+    /// Behaviours without a breakdown report their whole utility as a single `utility` part.
+    pub fn utility_breakdown(&self) -> Vec<(String, f64)> {
+        let Some(behaviour) = &self.behaviour else {
+            return Vec::new();
+        };
+        match behaviour.utility_breakdown(self) {
+            parts if parts.is_empty() => vec![("utility".into(), behaviour.utility(self))],
+            parts => parts,
+        }
+    }
+
+    /// Time spent executing the hooks of the inner behaviour.
+    #[cfg(feature = "timing")]
+    pub fn timings(&self) -> &TimingStats {
+        &self.timings
+    }
+
+    /// Named internal state of the inner behaviour. See [Behaviour::inspect].
+    #[cfg(feature = "serde")]
+    pub fn inspect_behaviour(&self) -> Vec<(&'static str, serde_value::Value)> {
+        self.behaviour
+            .as_ref()
+            .map(|b| b.inspect())
+            .unwrap_or_default()
+    }
+
+    /// Structural hash of this plan and all subplans recursively, for detecting changes to a tree.
+    ///
+    /// Covers the behaviour, see [behaviour_fingerprint], the transitions, the names of subplans, and the fields
+    /// configuring how plans run. Excludes the name of this plan, [Plan::description], data,
+    /// and run-time state other than that of behaviours, so equal fingerprints of two subtrees mean neither
+    /// needs updating to match the other. Stability is the same as that of [behaviour_fingerprint].
+    #[cfg(feature = "serde")]
+    pub fn fingerprint(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        let config = (
+            (self.run_interval, self.interval_jitter, &self.execution),
+            (self.frozen, self.autostart, self.autostart_if()),
+            (self.reachable_hint, self.skip_idle, &self.exit_order),
+            (
+                self.report_cached_when_inactive,
+                self.isolate,
+                self.poison_after,
+            ),
+            (self.seed, self.tags(), self.tag_limits()),
+            &self.transitions,
+        );
+        hash_canonical(&fingerprint_value(&config), &mut hasher);
+        let behaviour = self.behaviour.as_deref().map(behaviour_fingerprint::<C>);
+        behaviour.hash(&mut hasher);
+        self.plans.len().hash(&mut hasher);
+        for plan in &self.plans {
+            hash_canonical(&fingerprint_value(&plan.name), &mut hasher);
+            plan.fingerprint().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Number of plans using each type of behaviour within this plan and all subplans recursively.
+    /// See [Behaviour::type_name].
+    pub fn behaviour_histogram(&self) -> HashMap<&'static str, usize> {
+        self.reduce(HashMap::new(), |mut histogram, plan| {
+            if let Some(behaviour) = &plan.behaviour {
+                *histogram.entry(behaviour.type_name()).or_insert(0) += 1;
+            }
+            histogram
+        })
+    }
+
+    /// Size and shape of this plan and all subplans recursively, computed in a single traversal.
+    pub fn shape_metrics(&self) -> ShapeMetrics {
+        let mut metrics = ShapeMetrics::default();
+        let mut iter = self.iter();
+        while let Some(plan) = iter.next() {
+            metrics.nodes += 1;
+            metrics.max_depth = metrics.max_depth.max(iter.depth());
+            match plan.plans.len() {
+                0 => metrics.leaves += 1,
+                n => metrics.max_branching = metrics.max_branching.max(n),
+            }
+        }
+        let parents = metrics.nodes - metrics.leaves;
+        if parents > 0 {
+            metrics.mean_branching = (metrics.nodes - 1) as f64 / parents as f64;
+        }
+        metrics
+    }
+
+    /// Fold `f` over this plan and all subplans recursively, starting from `init`.
+    ///
+    /// Plans are visited in pre-order, each before its subplans, and subplans in priority order.
+    pub fn reduce<T>(&self, init: T, mut f: impl FnMut(T, &Self) -> T) -> T {
+        let mut acc = init;
+        let mut stack = vec![self];
+        while let Some(plan) = stack.pop() {
+            acc = f(acc, plan);
+            stack.extend(plan.plans.iter().rev());
+        }
+        acc
+    }
+
+    /// Call `f` on this plan and all subplans recursively, in the same order as [Plan::reduce].
+    pub fn visit(&self, f: &mut impl FnMut(&Self)) {
+        f(self);
+        for plan in &self.plans {
+            plan.visit(f);
+        }
+    }
+
+    /// Same as [Plan::visit] with mutable access to each plan.
+    ///
+    /// `f` may insert or remove subplans of the plan it is called on, since subplans are only visited
+    /// after it returns, and the subplans present by then are the ones visited.
+    pub fn visit_mut(&mut self, f: &mut impl FnMut(&mut Self)) {
+        self.wake_idle();
+        f(self);
+        for plan in &mut self.plans {
+            plan.visit_mut(f);
+        }
+    }
+
+    /// First of this plan and all subplans recursively for which `pred` holds, in the order of [Plan::iter].
+    pub fn find(&self, pred: impl Fn(&Self) -> bool) -> Option<&Self> {
+        self.iter().find(|plan| pred(plan))
+    }
+
+    /// See [Plan::find].
+    pub fn find_mut(&mut self, pred: impl Fn(&Self) -> bool) -> Option<&mut Self> {
+        self.wake_idle();
+        // locate the plan by its position at each level first, then descend mutably
+        fn locate<C: Config>(
+            plan: &Plan<C>,
+            pred: &impl Fn(&Plan<C>) -> bool,
+            indices: &mut Vec<usize>,
+        ) -> bool {
+            if pred(plan) {
+                return true;
+            }
+            for (i, x) in plan.plans.iter().enumerate() {
+                indices.push(i);
+                if locate(x, pred, indices) {
+                    return true;
+                }
+                indices.pop();
+            }
+            false
+        }
+        let mut indices = Vec::new();
+        if !locate(self, &pred, &mut indices) {
+            return None;
+        }
+        Some(indices.into_iter().fold(self, |plan, i| &mut plan.plans[i]))
+    }
+
+    /// All of this plan and its subplans recursively for which `pred` holds, in the order of [Plan::iter].
+    pub fn find_all(&self, pred: impl Fn(&Self) -> bool) -> Vec<&Self> {
+        self.iter().filter(|plan| pred(plan)).collect()
+    }
+
+    /// Number of panics contained by this plan since it was created or last cleared. See [Plan::isolate].
+    pub fn panic_count(&self) -> u32 {
+        self.panics
+    }
+
+    /// Whether this plan refuses entry after containing [Plan::poison_after] panics.
+    pub fn poisoned(&self) -> bool {
+        self.poisoned
+    }
+
+    /// Allow entry of a poisoned plan again, resetting [Plan::panic_count].
+    pub fn clear_poison(&mut self) {
+        self.panics = 0;
+        self.poisoned = false;
+    }
+
+    /// Number of times the status of this plan changed into success or failure while active.
+    pub fn outcome_counts(&self) -> OutcomeCounts {
+        self.outcomes
+    }
+
+    /// Sum of [Plan::outcome_counts] over this plan and all subplans recursively.
+    pub fn outcome_summary(&self) -> OutcomeCounts {
+        self.plans
+            .iter()
+            .map(Self::outcome_summary)
+            .fold(self.outcomes, |a, b| a + b)
+    }
+
+    /// Reset outcome counts of this plan and all subplans recursively.
+    pub fn reset_outcome_counts(&mut self) {
+        self.outcomes = OutcomeCounts::default();
+        self.plans.iter_mut().for_each(Self::reset_outcome_counts);
+    }
+
+    /// Events posted to this plan that are visible during the current tick.
+    pub fn events(&self) -> &[C::Event] {
+        match self.transient_ref() {
+            Some(transient) => &transient.events,
+            None => &[],
+        }
+    }
+
+    /// Labels of transitions within this plan that fired during the current tick.
+    pub fn fired_transitions(&self) -> &[String] {
+        match self.transient_ref() {
+            Some(transient) => &transient.fired,
+            None => &[],
+        }
+    }
+
+    /// Index [Plan::transitions] by their source plans, so that each tick only evaluates those
+    /// with an active source instead of checking every transition.
+    ///
+    /// Worthwhile for plans with many transitions, and does not change which transitions fire or their order.
+    /// The index is checked against the transitions on every tick, and dropped once they change,
+    /// falling back to checking every transition until compiled again. Subplans are not compiled.
+    pub fn compile(&mut self) {
+        let index = TransitionIndex::new(&self.transitions);
+        self.inner_mut().transition_index = Some(Box::new(index));
+    }
+
+    /// Whether the transitions are indexed by [Plan::compile], and have not changed since.
+    pub fn compiled(&self) -> bool {
+        self.inner.as_ref().is_some_and(|inner| {
+            let index = inner.transition_index.as_deref();
+            index.is_some_and(|index| index.matches(&self.transitions))
+        })
+    }
+
+    /// Post an event to this plan to be visible during its next run.
+    ///
+    /// Pending events are discarded when the plan exits.
+    pub fn post_event(&mut self, event: C::Event) {
+        self.transient().pending_events.push(event);
+        self.tree.epoch.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Wake idle plans of the tree upon a change made between ticks, which they would skip until due otherwise.
+    fn wake_idle(&self) {
+        if !self.tree.ticking.load(Ordering::Relaxed) {
+            self.tree.epoch.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn transient(&mut self) -> &mut Transient<C::Event> {
+        let activation = self.activation();
+        activation.transient.get_or_insert_with(Default::default)
+    }
+
+    /// Per-tick state of this plan, if allocated.
+    fn transient_ref(&self) -> Option<&Transient<C::Event>> {
+        self.activation.as_ref()?.transient.as_deref()
+    }
+
+    /// State of the latest activation of this plan, allocated upon first use.
+    fn activation(&mut self) -> &mut Activation<C::Event> {
+        self.activation.get_or_insert_with(Default::default)
+    }
+
+    /// Set the seed derived from the parent plan, applied upon entry.
+    fn set_inherited_seed(&mut self, seed: Option<u64>) {
+        if seed.is_some() || self.activation.is_some() {
+            self.activation().inherited_seed = seed;
+        }
+    }
+
+    /// Scheduling state while skipped as idle. See [Plan::skip_idle].
+    fn idle(&self) -> Option<Idle> {
+        self.activation.as_ref()?.idle
+    }
+
+    /// Span of the current activation, or a disabled span while inactive.
+    fn span(&self) -> &Span {
+        activation_span(&self.activation)
+    }
+
+    /// Seed set on this plan, or else derived from its parent upon entry.
+    fn own_seed(&self) -> Option<u64> {
+        self.seed
+            .or_else(|| self.activation.as_ref()?.inherited_seed)
+    }
+
+    /// Reset the per-activation state upon exit.
+    fn deactivate(&mut self) {
+        if let Some(activation) = &mut self.activation {
+            activation.span = Span::none();
+            activation.transient = None;
+            activation.idle = None;
+        }
+    }
+
+    /// New plan with behaviour and no subplans.
+    pub fn new(
+        behaviour: C::Behaviour,
+        name: impl Into<C::Name>,
+        run_interval: u32,
+        autostart: bool,
+    ) -> Self {
+        let mut s = Self::new_stub(name, autostart);
+        s.run_interval = run_interval;
+        s.set_behaviour(behaviour);
+        s
+    }
+
+    /// Replace the inner behaviour without triggering any hooks, returning the previous behaviour.
+    ///
+    /// Inserts the [Behaviour::default_subplans] of the new behaviour missing from the existing subplans.
+    pub fn set_behaviour(&mut self, behaviour: C::Behaviour) -> Option<Box<C::Behaviour>> {
+        self.wake_idle();
+        for plan in behaviour.default_subplans() {
+            if self.get(&plan.name).is_none() {
+                self.insert(plan);
+            }
+        }
+        self.behaviour.replace(Box::new(behaviour))
+    }
+
+    /// New plan without any behaviour.
+    pub fn new_stub(name: impl Into<C::Name>, autostart: bool) -> Self {
+        Self::from_parts(name, autostart, PlanParts::default())
+    }
+
+    /// Same as [Plan::new], reusing storage recycled into the arena.
+    #[cfg(feature = "arena")]
+    pub fn new_in(
+        arena: &Arena<C>,
+        behaviour: C::Behaviour,
+        name: impl Into<C::Name>,
+        run_interval: u32,
+        autostart: bool,
+    ) -> Self {
+        let mut s = Self::new_stub_in(arena, name, autostart);
+        s.run_interval = run_interval;
+        s.set_behaviour(behaviour);
+        s
+    }
+
+    /// Same as [Plan::new_stub], reusing storage recycled into the arena.
+    #[cfg(feature = "arena")]
+    pub fn new_stub_in(arena: &Arena<C>, name: impl Into<C::Name>, autostart: bool) -> Self {
+        Self::from_parts(name, autostart, arena.take().unwrap_or_default())
+    }
+
+    fn from_parts(name: impl Into<C::Name>, autostart: bool, parts: PlanParts<C>) -> Self {
+        // reset the state of a recycled tree instead of allocating another
+        let tree = parts.tree.and_then(|mut tree| {
+            *Arc::get_mut(&mut tree)? = Tree::default();
+            Some(tree)
+        });
+        let name = name.into();
+        Self {
+            path: path_segment(&name),
+            name,
+            run_countdown: u32::MAX,
+            run_interval: 1,
+            interval_jitter: 0,
+            execution: ExecMode::Full,
+            frozen: false,
+            autostart,
+            reachable_hint: false,
+            skip_idle: false,
+            exit_order: ExitOrder::default(),
+            report_cached_when_inactive: false,
+            isolate: false,
+            poison_after: 0,
+            seed: None,
+            behaviour: None,
+            transitions: parts.transitions,
+            plans: parts.plans,
+            paused: false,
+            outcomes: OutcomeCounts::default(),
+            panics: 0,
+            panicked: false,
+            poisoned: false,
+            last_status: None,
+            activation: parts.activation,
+            inner: parts.inner,
+            tree: tree.unwrap_or_default(),
+            #[cfg(feature = "timing")]
+            timings: TimingStats::default(),
+            #[cfg(feature = "journal")]
+            hook: None,
+        }
+    }
+
+    /// Insert plan instance as a subplan then return its reference.
+    ///
+    /// Subplan will be exited if current plan is inactive.
+    /// Subplan will be entered if current plan is active and autostart is set.
+    /// Existing subplan with the same name will be overwritten.
+    /// Behaviour hooks should use [Plan::defer] instead, so the subplans do not change mid-run.
+    pub fn insert(&mut self, mut plan: Self) -> &mut Self {
+        debug!(parent: self.span(), plan=?plan.name, "insert");
+        self.wake_idle();
+        plan.set_inherited_seed(self.inherited_seed(&plan.name));
+        plan.set_parent_path(&self.path, true);
+        if let Some(context) = self.inner.as_ref().and_then(|x| x.context.clone()) {
+            plan.set_context(Some(context));
+        }
+        if self.active() {
+            plan.tree = self.tree.clone();
+            // overwrite preview span with new parent if already active
+            if plan.active() {
+                plan.activation().span = debug_span!(parent: self.span(), "plan", path=%plan.path);
+            // when autostart is set, enter inserted plan if parent is active
+            } else if plan.autostart {
+                plan.enter(Some(self.span()));
+            }
+        // exit inserted span if parent plan is inactive
+        } else if plan.active() {
+            plan.exit(false);
+        }
+        // sorted insert
+        let (pos, _) = match self.priority(&plan.name) {
+            // overwrite if there is already one
+            Ok(pos) => (pos, self.plans[pos] = plan),
+            Err(pos) => (pos, self.plans.insert(pos, plan)),
+        };
+        &mut self.plans[pos]
+    }
+
+    /// Remove a subplan by name, and return it if successful.
+    ///
+    /// Behaviour hooks should use [Plan::defer] instead, so the subplans do not change mid-run.
+    pub fn remove<Q>(&mut self, name: &Q) -> Option<Self>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + Debug + ?Sized,
+    {
+        let pos = self.priority(name).ok()?;
+        debug!(parent: self.span(), plan=?name, "remove");
+        self.wake_idle();
+        Some(self.plans.remove(pos))
+    }
+
+    /// Find the priority of a subplan by name.
+    ///
+    /// Subplans run in order of their priority (unless rayon parallel execution is enabled).
+    ///
+    /// Priority is determined by the ordering of the subplans sorted by name.
+    /// For example, plan with names `"plan0" < "plan1"` means `"plan0"` has higher priority.
+    pub fn priority<Q>(&self, name: &Q) -> Result<usize, usize>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.plans
+            .binary_search_by(|plan| plan.name.borrow().cmp(name))
+    }
+
+    /// Returns reference to subplan by name.
+    pub fn get<Q>(&self, name: &Q) -> Option<&Self>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let pos = self.priority(name).ok()?;
+        Some(&self.plans[pos])
+    }
+
+    /// Returns mutable reference to subplan by name.
+    pub fn get_mut<Q>(&mut self, name: &Q) -> Option<&mut Self>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let pos = self.priority(name).ok()?;
+        self.wake_idle();
+        Some(&mut self.plans[pos])
+    }
+
+    /// Dynamically cast inner behaviour to a reference its known static type.
+    ///
+    /// For referring to concrete behaviours within the implementation of another.
+    pub fn cast<B: Behaviour<C>>(&self) -> Option<&B> {
+        self.behaviour.as_ref()?.cast::<B>()
+    }
+
+    /// See [Plan::cast].
+    pub fn cast_mut<B: Behaviour<C>>(&mut self) -> Option<&mut B> {
+        self.behaviour.as_mut()?.cast_mut::<B>()
+    }
+
+    /// Inner behaviour as a trait object, for calling hooks without knowing its concrete type.
+    pub fn behaviour_dyn(&self) -> Option<&dyn Behaviour<C>> {
+        Some(self.behaviour.as_deref()?)
+    }
+
+    /// See [Plan::behaviour_dyn].
+    pub fn behaviour_dyn_mut(&mut self) -> Option<&mut dyn Behaviour<C>> {
+        Some(self.behaviour.as_deref_mut()?)
+    }
+
+    /// Dynamically cast inner behaviour of a subplan to reference of its known type.
+    /// See [Plan::cast].
+    pub fn get_cast<B: Behaviour<C>, Q>(&self, name: &Q) -> Option<&B>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(name)?.cast::<B>()
+    }
+
+    /// See [Plan::get_cast].
+    pub fn get_cast_mut<B: Behaviour<C>, Q>(&mut self, name: &Q) -> Option<&mut B>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_mut(name)?.cast_mut::<B>()
+    }
+
+    /// Returns reference to descendant plan by slash-separated `path` of names, such as `"a/b/c"`.
+    ///
+    /// Empty segments are skipped, so leading, trailing, and repeated slashes are allowed,
+    /// and an empty path refers to this plan.
+    pub fn get_path(&self, path: &str) -> Option<&Self>
+    where
+        C::Name: Borrow<str>,
+    {
+        path_segments(path).try_fold(self, |plan, name| plan.get(name))
+    }
+
+    /// See [Plan::get_path].
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut Self>
+    where
+        C::Name: Borrow<str>,
+    {
+        path_segments(path).try_fold(self, |plan, name| plan.get_mut(name))
+    }
+
+    /// Returns reference to descendant plan by `path` of names, or this plan if `path` is empty.
+    ///
+    /// Unlike [Plan::get_path], names need not be strings.
+    pub fn find_by_path<Q>(&self, path: &[&Q]) -> Option<&Self>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        path.iter().try_fold(self, |plan, name| plan.get(*name))
+    }
+
+    /// See [Plan::find_by_path].
+    pub fn find_by_path_mut<Q>(&mut self, path: &[&Q]) -> Option<&mut Self>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        path.iter().try_fold(self, |plan, name| plan.get_mut(*name))
+    }
+
+    /// Dynamically cast inner behaviour of a descendant plan to reference of its known type.
+    /// See [Plan::get_path] and [Plan::cast].
+    pub fn get_cast_path<B: Behaviour<C>>(&self, path: &str) -> Option<&B>
+    where
+        C::Name: Borrow<str>,
+    {
+        self.get_path(path)?.cast::<B>()
+    }
+
+    /// See [Plan::get_cast_path].
+    pub fn get_cast_path_mut<B: Behaviour<C>>(&mut self, path: &str) -> Option<&mut B>
+    where
+        C::Name: Borrow<str>,
+    {
+        self.get_path_mut(path)?.cast_mut::<B>()
+    }
+
+    /// Run plan tree recursively. Each call at root level constitutes one tick of execution.
+    ///
+    /// Scheduling and transitions for all subplan are handled in the process.
+    /// Does nothing while paused, see [Plan::set_paused].
+    pub fn run(&mut self) -> TickReport<C::Name> {
+        if self.paused {
+            return TickReport {
+                tag_refusals: Vec::new(),
+                transition_scores: Vec::new(),
+                mutations: Vec::new(),
+                isolated_panics: Vec::new(),
+            };
+        }
+        self.in_pool(|plan| report_panic(|| plan.run_tick()));
+        self.finish_tick()
+    }
+
+    /// Same as [Plan::run], except that the tree is rolled back to its state before the tick if any behaviour panics.
+    ///
+    /// The whole tree is checkpointed up front by serializing it, along with the state shared by its plans,
+    /// which costs about as much as a full clone of the tree on every call. Once a panic is caught,
+    /// the tree is restored from the checkpoint without triggering any hooks and the panic is returned,
+    /// naming the plan and hook that raised it. The panic is still reported by the panic hook as usual.
+    ///
+    /// Only state within the tree is rolled back. Side effects of behaviours outside of it are not,
+    /// and nor are the warnings logged during the tick.
+    #[cfg(feature = "serde")]
+    pub fn run_transactional(&mut self) -> Result<TickReport<C::Name>, TickError<C::Name>>
+    where
+        Self: Serialize + serde::de::DeserializeOwned,
+    {
+        if self.paused {
+            return Ok(self.run());
+        }
+        let checkpoint = Checkpoint::save(self).map_err(TickError::Checkpoint)?;
+        match self.in_pool(|plan| catch_unwind(AssertUnwindSafe(|| plan.run_tick()))) {
+            Ok(()) => Ok(self.finish_tick()),
+            Err(payload) => {
+                checkpoint.restore(self);
+                Err(TickError::Panic(match payload.downcast::<PlanPanic>() {
+                    Ok(panic) => *panic,
+                    Err(payload) => PlanPanic {
+                        path: Vec::new(),
+                        hook: "run",
+                        message: PlanPanic::message_of(&*payload),
+                    },
+                }))
+            }
+        }
+    }
+
+    /// Run one tick of the tree from the root, letting a [PlanPanic] unwind.
+    fn run_tick(&mut self) {
+        /// Clears [Tree::ticking] once the tick is over, even if unwinding.
+        struct Ticking<N>(Arc<Tree<N>>);
+        impl<N> Drop for Ticking<N> {
+            fn drop(&mut self) {
+                self.0.ticking.store(false, Ordering::Relaxed);
+            }
+        }
+        self.tree.ticking.store(true, Ordering::Relaxed);
+        let _ticking = Ticking(self.tree.clone());
+        self.configure(&self.tree);
+        self.load_slots();
+        // enter plan if not already
+        self.enter(None);
+        self.tree.tick.fetch_add(1, Ordering::Relaxed);
+        self.run_plan();
+        self.store_slots();
+    }
+
+    /// Read the counters of slots changed in the data of this plan as the root since they were last synced.
+    fn load_slots(&self) {
+        let mut slots = self.tree.slots.lock().unwrap();
+        for (key, counter) in slots.iter_mut() {
+            let value = self.data().get(key).cloned();
+            let value = value.and_then(|x| x.deserialize_into::<usize>().ok());
+            if let Some(taken) = value.filter(|x| counter.synced != Some(*x)) {
+                counter.taken = taken;
+                counter.synced = Some(taken);
+            }
+        }
+    }
+
+    /// Write the counters of slots changed during the tick into the data of this plan as the root.
+    fn store_slots(&mut self) {
+        let mut changed = Vec::new();
+        for (key, counter) in self.tree.slots.lock().unwrap().iter_mut() {
+            if counter.synced != Some(counter.taken) {
+                counter.synced = Some(counter.taken);
+                changed.push((key.clone(), counter.taken));
+            }
+        }
+        for (key, taken) in changed {
+            self.set_data(key, serde_value::Value::U64(taken as u64));
+        }
+    }
+
+    /// Apply root level configuration to the state shared by the whole tree.
+    fn configure(&self, tree: &Tree<C::Name>) {
+        tree.tags.lock().unwrap().limits = self.tag_limits().clone();
+        let interval = &tree.warning_interval;
+        interval.store(self.warning_interval(), Ordering::Relaxed);
+        #[cfg(feature = "journal")]
+        {
+            let capacity = &tree.journal_capacity;
+            capacity.store(self.journal_capacity(), Ordering::Relaxed);
+        }
+    }
+
+    /// Check and publish the tree after a tick, then collect the report.
+    fn finish_tick(&mut self) -> TickReport<C::Name> {
+        #[cfg(feature = "invariants")]
+        self.check_invariants(&mut Vec::new());
+        self.publish();
+
+        TickReport {
+            tag_refusals: std::mem::take(&mut self.tree.tags.lock().unwrap().refusals),
+            transition_scores: std::mem::take(&mut self.tree.transition_scores.lock().unwrap()),
+            mutations: std::mem::take(&mut self.tree.mutations.lock().unwrap()),
+            isolated_panics: std::mem::take(&mut self.tree.isolated_panics.lock().unwrap()),
+        }
+    }
+
+    /// Suspend all execution when called on the root plan, such that [Plan::run] does nothing,
+    /// not even counting the tick. Unlike [Plan::frozen], it has no effect on subplans run by their parent.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// See [Plan::set_paused].
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Handle to snapshots of this tree published at the end of each [Plan::run], readable from any thread.
+    ///
+    /// Publishing starts upon the first call, which publishes the current state immediately.
+    /// Buffers of the previous snapshot are reused unless a reader still holds it.
+    pub fn shared_view(&mut self) -> PlanView<C::Name> {
+        if self.inner.as_ref().is_none_or(|x| x.publisher.is_none()) {
+            let view = PlanView {
+                latest: Default::default(),
+            };
+            let spare = Default::default();
+            self.inner_mut().publisher = Some(Box::new(Publisher { view, spare }));
+            self.publish();
+        }
+        self.inner
+            .as_ref()
+            .unwrap()
+            .publisher
+            .as_ref()
+            .unwrap()
+            .view
+            .clone()
+    }
+
+    /// Publish a snapshot of this tree to the shared view, if any.
+    fn publish(&mut self) {
+        fn collect<C: Config>(
+            plan: &Plan<C>,
+            parent: Option<usize>,
+            states: &mut Vec<PlanState<C::Name>>,
+            len: &mut usize,
+        ) {
+            let status = plan.status();
+            match states.get_mut(*len) {
+                // reuse the allocations of the previous snapshot
+                Some(state) => {
+                    state.name.clone_from(&plan.name);
+                    state.parent = parent;
+                    state.active = plan.active();
+                    state.status = status;
+                }
+                None => states.push(PlanState {
+                    name: plan.name.clone(),
+                    parent,
+                    active: plan.active(),
+                    status,
+                }),
+            }
+            let index = *len;
+            *len += 1;
+            for x in &plan.plans {
+                collect(x, Some(index), states, len);
+            }
+        }
+        let Some(mut publisher) = self.inner.as_mut().and_then(|x| x.publisher.take()) else {
+            return;
+        };
+        let mut snapshot = std::mem::take(&mut publisher.spare);
+        if Arc::get_mut(&mut snapshot).is_none() {
+            snapshot = Default::default();
+        }
+        let next = Arc::get_mut(&mut snapshot).unwrap();
+        next.tick = self.tree.tick.load(Ordering::Relaxed);
+        let mut len = 0;
+        collect(self, None, &mut next.plans, &mut len);
+        next.plans.truncate(len);
+        publisher.spare = std::mem::replace(&mut publisher.view.latest.lock().unwrap(), snapshot);
+        self.inner_mut().publisher = Some(publisher);
+    }
+
+    /// Run one tick of the subplan at `path` of names in isolation, as if it were the root.
+    ///
+    /// The subplan is entered if needed with a detached span, while ancestors and their other subplans
+    /// are left untouched, so transitions defined on ancestors never fire.
+    /// Aggregate predicates and behaviours evaluated inside the subtree only see the subtree.
+    ///
+    /// The subtree runs on state of its own in place of that shared by the tree, such as the tick count,
+    /// tag and slot counts, and shutdown, configured by this plan as the root. An inactive subplan entered
+    /// this way stays active beneath its inactive parent, keeping that state across calls until it exits.
+    /// A subplan already active within the tree is reattached to the shared state after the tick.
+    pub fn run_subtree<Q>(&mut self, path: &[&Q]) -> Result<(), Error<C::Name>>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = C::Name> + ?Sized,
+    {
+        let not_found = || Error::PlanNotFound(path.iter().map(|x| (*x).to_owned()).collect());
+        let plan = self.find_by_path(path).ok_or_else(not_found)?;
+        // plans exited from the tree keep referring to its shared state until entered again
+        let attached = Arc::ptr_eq(&plan.tree, &self.tree);
+        let tree = match attached {
+            true => Arc::new(Tree::default()),
+            false => plan.tree.clone(),
+        };
+        self.configure(&tree);
+        let shared = self.tree.clone();
+        let plan = self.find_by_path_mut(path).ok_or_else(not_found)?;
+        plan.visit_mut(&mut |x| x.tree = tree.clone());
+        let reattach = attached && plan.active();
+        report_panic(|| {
+            plan.enter(None);
+            plan.tree.tick.fetch_add(1, Ordering::Relaxed);
+            plan.run_plan();
+        });
+        if reattach {
+            plan.visit_mut(&mut |x| x.tree = shared.clone());
+        }
+        Ok(())
+    }
+
+    /// Freeze the subplan at `path` of names, see [Plan::frozen].
+    pub fn freeze<Q>(&mut self, path: &[&Q]) -> Result<(), Error<C::Name>>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = C::Name> + ?Sized,
+    {
+        self.set_frozen(path, true)
+    }
+
+    /// Resume running the subplan at `path` of names from the state it was frozen in.
+    pub fn unfreeze<Q>(&mut self, path: &[&Q]) -> Result<(), Error<C::Name>>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = C::Name> + ?Sized,
+    {
+        self.set_frozen(path, false)
+    }
+
+    fn set_frozen<Q>(&mut self, path: &[&Q], frozen: bool) -> Result<(), Error<C::Name>>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = C::Name> + ?Sized,
+    {
+        let plan = self
+            .find_by_path_mut(path)
+            .ok_or_else(|| Error::PlanNotFound(path.iter().map(|x| (*x).to_owned()).collect()))?;
+        plan.frozen = frozen;
+        // wake idle ancestors
+        plan.tree.epoch.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Run this plan and active subplans recursively for one tick.
+    fn run_plan(&mut self) {
+        self.contain(|plan| {
+            let result = catch_unwind(AssertUnwindSafe(|| plan.run_plan_unchecked()));
+            plan_context(&plan.name, result)
+        });
+    }
+
+    /// Call `f` on this plan, containing any panic within if isolated. See [Plan::isolate].
+    ///
+    /// Returns `None` if a panic was contained.
+    fn contain<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> Option<R> {
+        if !self.isolate {
+            return Some(f(self));
+        }
+        let payload = match catch_unwind(AssertUnwindSafe(|| f(self))) {
+            Ok(x) => return Some(x),
+            Err(payload) => payload,
+        };
+        let panic = match payload.downcast::<PlanPanic>() {
+            Ok(panic) => *panic,
+            Err(payload) => PlanPanic {
+                path: vec![format!("{:?}", self.name)],
+                hook: "run",
+                message: PlanPanic::message_of(&*payload),
+            },
+        };
+        self.panics += 1;
+        self.poisoned |= self.poison_after > 0 && self.panics >= self.poison_after;
+        if let Some(suppressed) = self.tree.throttle_warning("W203", &self.path) {
+            warn!(parent: self.span(), path=%self.path, panic=%panic, count=self.panics, poisoned=self.poisoned, suppressed, "panic contained");
+        }
+        self.panicked = true;
+        if self.active() {
+            self.record_outcome();
+            self.abandon();
+        }
+        let record = IsolatedPanic {
+            path: self.path.clone(),
+            panic,
+            count: self.panics,
+            poisoned: self.poisoned,
+        };
+        self.tree.isolated_panics.lock().unwrap().push(record);
+        None
+    }
+
+    /// Exit this plan and all active subplans recursively without triggering any hooks, giving back held slots.
+    fn abandon(&mut self) {
+        self.plans
+            .iter_mut()
+            .filter(|plan| plan.active())
+            .for_each(Self::abandon);
+        self.run_countdown = u32::MAX;
+        self.deactivate();
+        if let Some(inner) = &mut self.inner {
+            inner.deferred.clear();
+        }
+        self.tree.release_tags(self.tags());
+        let slots = self.inner.as_mut().map(|x| std::mem::take(&mut x.slots));
+        for key in slots.unwrap_or_default() {
+            if let Some(counter) = self.tree.slots.lock().unwrap().get_mut(&key) {
+                counter.taken = counter.taken.saturating_sub(1);
+            }
+        }
+    }
+
+    /// See [Plan::run_plan], without panic context.
+    fn run_plan_unchecked(&mut self) {
+        if self.frozen {
+            return;
+        }
+
+        // events posted since last run become visible for this tick
+        if let Some(transient) = self.activation.as_mut().and_then(|x| x.transient.as_mut()) {
+            transient.events = std::mem::take(&mut transient.pending_events);
+        }
+
+        let execution = self.execution;
+        if execution.runs_subplans() {
+            self.fire_transitions();
+        }
+
+        // call on_prepare() before children behaviours run()
+        if execution.runs_behaviour() && self.run_countdown == 0 {
+            self.call(|behaviour, plan| behaviour.on_prepare(plan), "prepare");
+        }
+
+        // skip plan if exited during prepare
+        if !self.active() {
+            return;
+        }
+
+        // call run() recursively
+        if execution.runs_subplans() {
+            let skip_idle = self.skip_idle;
+            let i = self.plans.iter_mut().filter(|plan| plan.active());
+            #[cfg(feature = "rayon")]
+            i.par_bridge()
+                .for_each(|plan| plan.run_scheduled(skip_idle));
+            #[cfg(not(feature = "rayon"))]
+            i.for_each(|plan| plan.run_scheduled(skip_idle));
+        }
+
+        // limit execution frequency
+        if execution.runs_behaviour() {
+            if self.run_countdown == 0 {
+                // run the behaviour of this plan
+                self.call(|behaviour, plan| behaviour.on_run(plan), "run");
+                // shift the next run by the change in delay, keeping the average interval
+                let delay = self.next_jitter_delay();
+                let interval = self.run_interval.max(1) + delay;
+                let activation = self.activation();
+                let previous = std::mem::replace(&mut activation.jitter_delay, delay);
+                self.run_countdown = interval.saturating_sub(previous).max(1);
+            }
+            // ok to countdown without active check because plan must be active by this point
+            self.run_countdown -= 1;
+        }
+
+        self.apply_deferred();
+        self.record_outcome();
+        let tick = self.tree.tick.load(Ordering::Relaxed);
+        self.activation().status_tick = Some(tick);
+    }
+
+    /// Queue a change to the subplans of this plan, applied in order at the end of its current or next run,
+    /// after `on_run()` and all subplans have run. Queued changes are discarded when the plan exits.
+    ///
+    /// Unlike [Plan::insert] and other direct mutations, safe to call from behaviour hooks mid-run.
+    /// Inserted plans that are entered run from the following tick.
+    pub fn defer(&mut self, mutation: PlanMutation<C>) {
+        self.inner_mut().deferred.push(mutation);
+    }
+
+    /// Apply mutations queued by [Plan::defer], recording those that took effect.
+    fn apply_deferred(&mut self) {
+        let Some(inner) = &mut self.inner else {
+            return;
+        };
+        for mutation in std::mem::take(&mut inner.deferred) {
+            let applied = match mutation {
+                PlanMutation::Insert(plan) => {
+                    let name = plan.name.clone();
+                    self.insert(*plan);
+                    Some((MutationKind::Insert, name))
+                }
+                PlanMutation::Remove(name) => {
+                    self.remove(&name).map(|_| (MutationKind::Remove, name))
+                }
+                PlanMutation::EnterPlan(name) => {
+                    let entered = self.get(&name).is_some_and(|x| !x.active())
+                        && self.enter_plan(&name).is_some();
+                    entered.then_some((MutationKind::EnterPlan, name))
+                }
+                PlanMutation::ExitPlan(name) => self
+                    .exit_plan(&name)
+                    .map(|_| (MutationKind::ExitPlan, name)),
+            };
+            if let Some((kind, subplan)) = applied {
+                let plan = self.name.clone();
+                let record = AppliedMutation {
+                    plan,
+                    kind,
+                    subplan,
+                };
+                self.tree.mutations.lock().unwrap().push(record);
+            }
+        }
+    }
+
+    /// Evaluate transitions between subplans and fire those that hold.
+    fn fire_transitions(&mut self) {
+        // get active set of plans, only needed for transitions
+        let active = || {
+            self.plans
+                .iter()
+                .filter(|plan| plan.active())
+                .map(|plan| &plan.name)
+        };
+        debug!(parent: self.span(), plan=?self.name(), active=?active().collect::<Vec<_>>());
+        let active_plans = match self.transitions.is_empty() {
+            true => BTreeSet::new(),
+            false => active().collect::<BTreeSet<_>>(),
+        };
+
+        // warn about transitions that can never fire, checking again only once transitions or subplans change
+        let names = || self.plans.iter().map(|plan| &plan.name);
+        let checked = self.inner.as_ref().and_then(|x| x.missing_plans.as_deref());
+        // plans without transitions have nothing to check until some are added
+        let unchanged = match checked {
+            Some(x) => x.matches(&self.transitions, names()),
+            None => self.transitions.is_empty(),
+        };
+        if !unchanged {
+            let check = MissingPlans::new(&self.transitions, names());
+            let inner = self.inner.get_or_insert_with(Default::default);
+            inner.missing_plans = Some(Box::new(check));
+        }
+        if let Some(missing) = self
+            .inner
+            .as_ref()
+            .and_then(|x| x.missing_plans.as_deref())
+            .and_then(|x| x.missing.as_ref())
+        {
+            if let Some(suppressed) = self.tree.throttle_warning("W201", &self.path) {
+                warn!(parent: self.span(), path=%self.path, ?missing, suppressed, "transition plan missing");
+            }
+        }
+
+        // fall back to evaluating every transition once they no longer match the index
+        let compiled_index = self
+            .inner
+            .as_ref()
+            .and_then(|x| x.transition_index.as_deref());
+        let index = compiled_index.filter(|index| index.matches(&self.transitions));
+        let stale = index.is_none() && compiled_index.is_some();
+
+        // evaluate state transitions unless shutting down
+        let draining = self.shutting_down();
+        let firings = match self.transient_ref() {
+            Some(transient) => &transient.firings[..],
+            None => &[],
+        };
+        let (mut all, mut compiled);
+        let candidates: &mut dyn Iterator<Item = usize> = match index {
+            Some(index) => {
+                compiled = index.candidates(&active_plans).into_iter();
+                &mut compiled
+            }
+            None => {
+                all = 0..self.transitions.len();
+                &mut all
+            }
+        };
+        let fired = candidates
+            .map(|i| (i, &self.transitions[i]))
+            .filter(|(i, t)| {
+                !draining
+                    && t.max_firings
+                        .is_none_or(|max| firings.get(*i).copied().unwrap_or(0) < max)
+                    && t.sources().all(|plan| active_plans.contains(plan))
+                    && (t.interruptible
+                        || t.sources()
+                            .filter_map(|p| self.get(p))
+                            .all(|p| p.status().is_some()))
+                    && t.clauses.iter().all(|(src, p)| p.evaluate(self, src))
+            })
+            .filter_map(|(i, t)| match t.threshold {
+                None => t.predicate.evaluate(self, &t.src).then_some((i, None)),
+                Some(threshold) => {
+                    let score = t.predicate.score(self, &t.src);
+                    let fired = score >= threshold;
+                    self.tree
+                        .transition_scores
+                        .lock()
+                        .unwrap()
+                        .push(TransitionScore {
+                            plan: self.name.clone(),
+                            label: t.label.clone(),
+                            src: t.src.clone(),
+                            score,
+                            threshold,
+                            fired,
+                        });
+                    fired.then_some((i, Some(score)))
+                }
+            })
+            .collect::<Vec<_>>();
+        if stale {
+            debug!(parent: self.span(), plan=?self.name, "transitions changed since compiled");
+            self.inner_mut().transition_index = None;
+        }
+        // record labels of fired transitions for the duration of this tick
+        if let Some(transient) = self.activation.as_mut().and_then(|x| x.transient.as_mut()) {
+            transient.fired.clear();
+        }
+        let transitions = std::mem::take(&mut self.transitions);
+        fired.iter().for_each(|&(i, score)| {
+            let t = &transitions[i];
+            debug!(parent: self.span(), src=?t.src, dst=?t.dst, label=?t.label, ?score, "transition");
+            let sources = t.sources().collect::<BTreeSet<_>>();
+            sources.iter().filter(|p| !t.dst.contains(p)).for_each(|p| {
+                self.exit_plan(p);
+            });
+            t.dst.iter().filter(|p| !sources.contains(p)).for_each(|p| {
+                self.enter_plan(p);
+            });
+            if let Some(label) = &t.label {
+                self.transient().fired.push(label.clone());
+            }
+            if t.max_firings.is_some() {
+                let firings = &mut self.transient().firings;
+                firings.resize(firings.len().max(i + 1), 0);
+                firings[i] += 1;
+            }
+        });
+        let _ = std::mem::replace(&mut self.transitions, transitions);
+    }
+
+    /// Run this plan unless idle, and become idle afterwards if allowed. See [Plan::skip_idle].
+    fn run_scheduled(&mut self, skip_idle: bool) {
+        let tick = self.tree.tick.load(Ordering::Relaxed);
+        let epoch = self.tree.epoch.load(Ordering::Relaxed);
+        if let Some(idle) = self.activation.as_mut().and_then(|x| x.idle.take()) {
+            if tick < idle.until && epoch == idle.epoch {
+                self.activation().idle = Some(idle);
+                return;
+            }
+            // catch up on the ticks skipped while idle
+            self.skip_ticks(tick - idle.since - 1);
+        }
+        self.run_plan();
+        if skip_idle && self.active() {
+            let until = self.due(tick);
+            if until > tick + 1 {
+                self.activation().idle = Some(Idle {
+                    since: tick,
+                    until,
+                    epoch,
+                });
+            }
+        }
+    }
+
+    /// Earliest tick this plan or any active subplan needs to run, given it ran on `tick`.
+    fn due(&self, tick: u64) -> u64 {
+        // never idle while frozen, since catching up afterwards would count the frozen ticks
+        if self.frozen {
+            return tick + 1;
+        }
+        let execution = self.execution;
+        let busy = (execution.runs_subplans() && !self.transitions.is_empty())
+            || self.inner.as_ref().is_some_and(|x| !x.deferred.is_empty())
+            || self
+                .transient_ref()
+                .is_some_and(|x| !x.events.is_empty() || !x.pending_events.is_empty());
+        if busy {
+            return tick + 1;
+        }
+        let due = match execution.runs_behaviour() && self.behaviour.is_some() {
+            false => u64::MAX,
+            true => tick + self.run_countdown as u64 + 1,
+        };
+        self.plans
+            .iter()
+            .filter(|plan| execution.runs_subplans() && plan.active())
+            .map(|plan| match plan.idle() {
+                Some(idle) => idle.until,
+                None => plan.due(tick),
+            })
+            .fold(due, u64::min)
+    }
+
+    /// Count down run schedules of this plan and active subplans that are not idle themselves.
+    ///
+    /// Subplans entered while idle, which have yet to run, have no ticks to catch up on.
+    fn skip_ticks(&mut self, ticks: u64) {
+        if self.execution.runs_behaviour() && self.behaviour.is_some() {
+            self.run_countdown -= ticks as u32;
+        }
+        self.plans
+            .iter_mut()
+            .filter(|plan| plan.active() && plan.idle().is_none())
+            .filter(|plan| {
+                plan.activation
+                    .as_ref()
+                    .is_some_and(|x| x.status_tick.is_some())
+            })
+            .for_each(|plan| plan.skip_ticks(ticks));
+    }
+
+    /// Count each time status becomes terminal while active.
+    fn record_outcome(&mut self) {
+        let status = self.status();
+        if status != self.last_status {
+            match status {
+                Some(true) => self.outcomes.successes += 1,
+                Some(false) => self.outcomes.failures += 1,
+                None => {}
+            }
+            self.last_status = status;
+        }
+    }
+
+    ///  Enters the specified subplan if not already active and return its reference.
+    ///  See [Plan::enter].
+    pub fn enter_plan<Q>(&mut self, name: &Q) -> Option<&mut Self>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + ToOwned<Owned = C::Name> + ?Sized,
+    {
+        // can only enter plans within an active plan
+        if !self.active() {
+            return None;
+        }
+        self.wake_idle();
+        // look for requested plan
+        let pos = match self.priority(name) {
+            Ok(pos) => pos,
+            // if plan doesn't exist, create and insert a default plan
+            Err(pos) => {
+                self.plans
+                    .insert(pos, Self::new_stub(name.to_owned(), false));
+                pos
+            }
+        };
+        let seed = self.inherited_seed(&self.plans[pos].name);
+        let plan = &mut self.plans[pos];
+        plan.tree = self.tree.clone();
+        plan.set_inherited_seed(seed);
+        plan.set_parent_path(&self.path, false);
+        plan.enter(Some(activation_span(&self.activation)));
+        Some(plan)
+    }
+
+    ///  Exits the specified subplan if currently active and return its reference.
+    ///  See [Plan::exit].
+    pub fn exit_plan<Q>(&mut self, name: &Q) -> Option<&mut Self>
+    where
+        C::Name: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        // ignore if plan is not found
+        let pos = self.priority(name).ok()?;
+        self.wake_idle();
+        let plan = &mut self.plans[pos];
+        plan.exit(false);
+        Some(plan)
+    }
+
+    /// Enters all subplans carrying the tag recursively wherever their parent is active.
+    ///
+    /// Returns the number of plans entered.
+    pub fn enter_by_tag(&mut self, tag: &str) -> usize {
+        if !self.active() {
+            return 0;
+        }
+        self.wake_idle();
+        let mut count = 0;
+        for pos in 0..self.plans.len() {
+            let seed = self.inherited_seed(&self.plans[pos].name);
+            let plan = &mut self.plans[pos];
+            if !plan.active() && plan.tags().iter().any(|x| x == tag) {
+                plan.tree = self.tree.clone();
+                plan.set_inherited_seed(seed);
+                plan.set_parent_path(&self.path, false);
+                count += plan.enter(Some(activation_span(&self.activation))) as usize;
+            }
+            count += self.plans[pos].enter_by_tag(tag);
+        }
+        count
+    }
+
+    /// Exits all active subplans carrying the tag recursively.
+    ///
+    /// Returns the number of plans exited.
+    pub fn exit_by_tag(&mut self, tag: &str) -> usize {
+        self.wake_idle();
+        self.plans
+            .iter_mut()
+            .filter(|plan| plan.active())
+            .map(|plan| match plan.tags().iter().any(|x| x == tag) {
+                true => plan.exit(false) as usize,
+                false => plan.exit_by_tag(tag),
+            })
+            .sum()
+    }
+
+    /// Enter this plan if not already active.
+    ///
+    /// Also recursively enters all subplans with autostart enabled.
+    /// Entry is refused if any of the plan's tags has reached its limit.
+    pub fn enter(&mut self, parent_span: Option<&Span>) -> bool {
+        self.in_pool(|plan| {
+            plan.contain(|plan| {
+                let result = catch_unwind(AssertUnwindSafe(|| plan.enter_unchecked(parent_span)));
+                plan_context(&plan.name, result)
+            })
+        })
+        .unwrap_or(false)
+    }
+
+    /// See [Plan::enter], without panic context.
+    fn enter_unchecked(&mut self, parent_span: Option<&Span>) -> bool {
+        // only enter if plan is inactive
+        if self.active() {
+            return false;
+        }
+        if self.poisoned {
+            debug!(parent: parent_span.and_then(|x| x.id()), plan=?self.name, "entry refused while poisoned");
+            return false;
+        }
+        // entering as root starts a new activation of the tree
+        if parent_span.is_none() {
+            self.tree.draining.store(false, Ordering::Relaxed);
+            self.tree.cancel.reset();
+        // no new plans may be entered while the tree is shutting down
+        } else if self.shutting_down() {
+            debug!(parent: parent_span.and_then(|x| x.id()), plan=?self.name, "entry refused during shutdown");
+            return false;
+        }
+        // only enter if all tags are below their limits
+        if !self.tags().is_empty() {
+            if let Err(tag) = self.tree.acquire_tags(&self.name, self.tags()) {
+                if let Some(suppressed) = self.tree.throttle_warning("W202", &self.path) {
+                    warn!(parent: parent_span.and_then(|x| x.id()), path=%self.path, tag=%tag, suppressed, "tag limit reached");
+                }
+                return false;
+            }
+        }
+        self.panicked = false;
+        // create new span
+        if self.path.is_empty() {
+            self.path = path_segment(&self.name);
+        }
+        let span = match parent_span {
+            Some(x) => debug_span!(parent: x, "plan", path=%self.path),
+            None => debug_span!("plan", path=%self.path),
+        };
+        // trigger on_entry() for self
+        let jitter_delay = self.next_jitter_delay();
+        let activation = self.activation();
+        activation.span = span;
+        activation.jitter_delay = jitter_delay;
+        activation.status_tick = None;
+        activation.idle = None;
+        self.run_countdown = jitter_delay;
+        self.last_status = None;
+        if let (Some(seed), Some(behaviour)) = (self.own_seed(), &mut self.behaviour) {
+            behaviour.reseed(seed);
+        }
+        self.call(|behaviour, plan| behaviour.on_entry(plan), "entry");
+        // recursively enter all autostart child plans
+        let autostart = self
+            .plans
+            .iter()
+            .map(|plan| match plan.autostart_if() {
+                Some(predicate) => predicate.evaluate(self, &[]),
+                None => plan.autostart,
+            })
+            .collect::<Vec<_>>();
+        let seed = self.own_seed();
+        let (tree, span) = (&self.tree, activation_span(&self.activation));
+        let path = &self.path;
+        let enter = |plan: &mut Self| {
+            plan.tree = tree.clone();
+            plan.set_inherited_seed(seed.map(|seed| derive_seed(seed, &plan.name)));
+            plan.set_parent_path(path, false);
+            plan.enter(Some(span));
+        };
+        let i = self
+            .plans
+            .iter_mut()
+            .zip(autostart)
+            .filter(|(plan, autostart)| *autostart && !plan.active())
+            .map(|(plan, _)| plan);
+        #[cfg(feature = "rayon")]
+        {
+            // enter tagged plans sequentially so that tag limits are resolved by priority
+            let (tagged, untagged): (Vec<_>, Vec<_>) = i.partition(|plan| !plan.tags().is_empty());
+            tagged.into_iter().for_each(&enter);
+            untagged.into_par_iter().for_each(enter);
+        }
+        #[cfg(not(feature = "rayon"))]
+        i.for_each(enter);
+        true
+    }
+
+    /// Exit this plan and all subplans recursively if currently active.
+    ///
+    /// Subplans trigger `on_exit()` in the order given by [Plan::exit_order] of their parent.
+    pub fn exit(&mut self, exclude_self: bool) -> bool {
+        self.in_pool(|plan| {
+            plan.contain(|plan| {
+                let result = catch_unwind(AssertUnwindSafe(|| plan.exit_unchecked(exclude_self)));
+                plan_context(&plan.name, result)
+            })
+        })
+        .unwrap_or(true)
+    }
+
+    /// See [Plan::exit], without panic context.
+    fn exit_unchecked(&mut self, exclude_self: bool) -> bool {
+        // only exit if plan is active
+        if !self.active() {
+            return false;
+        }
+        // plans may exit due to a terminal status before running, observed while subplans are still active,
+        // otherwise the status recorded at the end of the run this tick is reused
+        let tick = self.tree.tick.load(Ordering::Relaxed);
+        let status_tick = self.activation.as_ref().and_then(|x| x.status_tick);
+        if !exclude_self && status_tick != Some(tick) {
+            self.record_outcome();
+        }
+        // trigger on_exit() for self before subplans if requested
+        let self_first = self.exit_order == ExitOrder::SelfFirst;
+        if !exclude_self && self_first {
+            self.call(|behaviour, plan| behaviour.on_exit(plan), "exit");
+        }
+        // recursively exit all active child plans
+        let i = self.plans.iter_mut().filter(|plan| plan.active());
+        #[cfg(feature = "rayon")]
+        i.par_bridge().for_each(|plan| {
+            plan.exit(false);
+        });
+        #[cfg(not(feature = "rayon"))]
+        i.for_each(|plan| {
+            plan.exit(false);
+        });
+        // trigger on_exit() for self
+        if !exclude_self {
+            if !self_first {
+                self.call(|behaviour, plan| behaviour.on_exit(plan), "exit");
+            }
+            self.run_countdown = u32::MAX;
+            self.deactivate();
+            if let Some(inner) = &mut self.inner {
+                inner.deferred.clear();
+            }
+            self.tree.release_tags(self.tags());
+        }
+        true
+    }
+
+    /// Begin draining the tree over multiple ticks for a graceful shutdown.
+    ///
+    /// Triggers `on_shutdown()` once for every active plan so behaviours may switch to wind-down logic.
+    /// While shutting down, no new plans may be entered and transitions no longer fire.
+    /// Once [Plan::shutdown_complete] holds, the caller is expected to [Plan::exit] the tree.
+    pub fn begin_shutdown(&mut self) {
+        if !self.active() || self.tree.draining.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        debug!(parent: self.span(), plan=?self.name, "shutdown");
+        self.tree.cancel.cancel();
+        self.in_pool(|plan| plan.shutdown());
+    }
+
+    /// Signal long-running behaviours of the tree to stop early. See [CancelToken].
+    pub fn cancel(&self) {
+        debug!(parent: self.span(), plan=?self.name, "cancel");
+        self.tree.cancel.cancel();
+    }
+
+    /// Scoring context of this plan. See [Plan::set_context].
+    pub fn context(&self) -> Option<&dyn ScoreContext<C::Name>> {
+        self.inner.as_ref()?.context.as_deref()
+    }
+
+    /// Set the scoring context of this plan and all subplans recursively.
+    ///
+    /// Used by [behaviour::ContextUtilBehaviour] to let an external scorer drive selection among plans.
+    /// Subplans inserted later inherit the context of their parent, if set.
+    pub fn set_context(&mut self, context: Option<Arc<dyn ScoreContext<C::Name>>>) {
+        for plan in &mut self.plans {
+            plan.set_context(context.clone());
+        }
+        if context.is_some() || self.inner.is_some() {
+            self.inner_mut().context = context;
+        }
+    }
+
+    /// Thread pool running the subplans of this plan in parallel. See [Plan::set_thread_pool].
+    #[cfg(feature = "rayon")]
+    pub fn thread_pool(&self) -> Option<&rayon::ThreadPool> {
+        self.inner.as_ref()?.thread_pool.as_deref()
+    }
+
+    /// Set the thread pool on which [Plan::run], [Plan::enter], [Plan::exit], and [Plan::begin_shutdown]
+    /// of this plan execute subplans in parallel, instead of the global rayon pool.
+    ///
+    /// Typically set on the root plan, with the size of the pool capping the threads used by the tree.
+    #[cfg(feature = "rayon")]
+    pub fn set_thread_pool(&mut self, thread_pool: Option<Arc<rayon::ThreadPool>>) {
+        self.inner_mut().thread_pool = thread_pool;
+    }
+
+    /// Call `f` within the thread pool of this plan, if set.
+    #[cfg(feature = "rayon")]
+    fn in_pool<R: Send>(&mut self, f: impl FnOnce(&mut Self) -> R + Send) -> R {
+        match self.inner.as_ref().and_then(|x| x.thread_pool.clone()) {
+            Some(pool) => pool.install(|| f(self)),
+            None => f(self),
+        }
+    }
+
+    /// Call `f` within the thread pool of this plan, if set.
+    #[cfg(not(feature = "rayon"))]
+    fn in_pool<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        f(self)
+    }
+
+    /// Token shared by all plans of the tree this plan belongs to.
+    pub fn cancel_token(&self) -> &CancelToken {
+        &self.tree.cancel
+    }
+
+    /// Recent changes to the data of plans within the tree this plan belongs to, oldest first.
+    ///
+    /// Changes are recorded in the tree the changed plan belonged to at the time,
+    /// so changes made to a plan before it was first entered are not seen by the rest of the tree.
+    #[cfg(feature = "journal")]
+    pub fn data_journal(&self) -> Vec<DataChange> {
+        self.tree.journal.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Number of ticks run by the tree this plan belongs to, `0` before the first tick.
+    pub fn tick(&self) -> u64 {
+        self.tree.tick.load(Ordering::Relaxed)
+    }
+
+    /// Take one of `limit` slots of the counter `key` shared by all plans of the tree, returning whether one was free.
+    ///
+    /// Taken slots are held by this plan until given back by [Plan::release_slot], even if the plan exits,
+    /// so each successful call must be paired with a release. Slots are also given back if the plan is abandoned
+    /// after a panic contained by [Plan::isolate]. Held slots are serialized along with the plan.
+    /// See [behaviour::SemaphoreBehaviour].
+    ///
+    /// The counter is kept in the data of the root under `key` between ticks, as the number of slots taken.
+    /// It is read at the start of each tick if changed since, such as by [Plan::set_data] or loading a saved tree,
+    /// and written back at the end of the tick if any slots were taken or given back. Within a tick,
+    /// it is shared by all plans of the tree, so concurrent plans see the slots taken by each other.
+    pub fn acquire_slot(&mut self, key: &str, limit: usize) -> bool {
+        let mut slots = self.tree.slots.lock().unwrap();
+        let counter = slots.entry(key.into()).or_default();
+        if counter.taken >= limit {
+            return false;
+        }
+        counter.taken += 1;
+        drop(slots);
+        self.inner_mut().slots.push(key.into());
+        true
+    }
+
+    /// Give back a slot of the counter `key` taken by [Plan::acquire_slot], if this plan holds one.
+    pub fn release_slot(&mut self, key: &str) {
+        let Some(slots) = self.inner.as_mut().map(|x| &mut x.slots) else {
+            return;
+        };
+        let Some(pos) = slots.iter().position(|x| x == key) else {
+            return;
+        };
+        slots.swap_remove(pos);
+        if let Some(counter) = self.tree.slots.lock().unwrap().get_mut(key) {
+            counter.taken = counter.taken.saturating_sub(1);
+        }
+    }
+
+    /// Whether this plan holds a slot of the counter `key`. See [Plan::acquire_slot].
+    pub fn holds_slot(&self, key: &str) -> bool {
+        self.held_slots().iter().any(|x| x == key)
+    }
+
+    /// Keys of the slots held by this plan, once per slot.
+    fn held_slots(&self) -> &[String] {
+        self.inner.as_ref().map_or(&[], |x| &x.slots)
+    }
+
+    /// Number of slots of the counter `key` currently taken within the tree. See [Plan::acquire_slot].
+    pub fn slots_taken(&self, key: &str) -> usize {
+        let slots = self.tree.slots.lock().unwrap();
+        slots.get(key).map_or(0, |x| x.taken)
+    }
+
+    /// Warnings that occurred within the tree this plan belongs to, sorted by code and plan path.
+    pub fn warnings(&self) -> Vec<Warning> {
+        let warnings = self.tree.warnings.lock().unwrap();
+        warnings.values().cloned().collect()
+    }
+
+    /// Record occurrence of a warning in this plan, returning the number of suppressed occurrences if it should be logged.
+    pub(crate) fn throttle_warning(&self, code: &'static str) -> Option<u64> {
+        self.tree.throttle_warning(code, &self.path)
+    }
+
+    /// Whether the tree this plan belongs to is shutting down. See [Plan::begin_shutdown].
+    pub fn shutting_down(&self) -> bool {
+        self.tree.draining.load(Ordering::Relaxed)
+    }
+
+    /// Whether shutting down and every active plan with a behaviour has reached a terminal status.
+    pub fn shutdown_complete(&self) -> bool {
+        fn complete<C: Config>(plan: &Plan<C>) -> bool {
+            !plan.active()
+                || ((plan.behaviour.is_none() || plan.status().is_some())
+                    && plan.plans.iter().all(complete))
+        }
+        self.shutting_down() && complete(self)
+    }
+
+    /// Paths relative to this plan of behaviours that can never run, found by static analysis.
+    ///
+    /// A behaviour never runs if its [Plan::execution] excludes the behaviour, or its plan is unreachable.
+    /// Plans are unreachable if the parent is, or by the same rule as [Plan::validate].
+    pub fn dead_behaviours(&self) -> Vec<Vec<C::Name>> {
+        fn collect<C: Config>(
+            plan: &Plan<C>,
+            reachable: bool,
+            path: &mut Vec<C::Name>,
+            dead: &mut Vec<Vec<C::Name>>,
+        ) {
+            let execution = plan.execution;
+            if plan.behaviour.is_some() && (!reachable || !execution.runs_behaviour()) {
+                dead.push(path.clone());
+            }
+            let reached = match reachable {
+                true => plan.reachable_subplans(),
+                false => Default::default(),
+            };
+            for x in &plan.plans {
+                path.push(x.name().clone());
+                collect(x, reached.contains(x.name()), path, dead);
+                path.pop();
+            }
+        }
+        let mut dead = Vec::new();
+        collect(self, true, &mut Vec::new(), &mut dead);
+        dead
+    }
+
+    /// Transitions of this plan and all subplans recursively that can never fire, found by static analysis.
+    ///
+    /// Returns the path relative to this plan of the plan containing each transition, and its index.
+    /// A transition is dead if its predicate is provably false, see [Predicate::is_statically_false],
+    /// or if it has a [Transition::threshold] above the constant score of its predicate.
+    pub fn dead_transitions(&self) -> Vec<(Vec<C::Name>, usize)> {
+        fn collect<C: Config>(
+            plan: &Plan<C>,
+            path: &mut Vec<C::Name>,
+            dead: &mut Vec<(Vec<C::Name>, usize)>,
+        ) {
+            for (i, t) in plan.transitions.iter().enumerate() {
+                let dead_transition = match (t.predicate.is_statically_false(), t.threshold) {
+                    (Some(value), Some(threshold)) => (value as u8 as f64) < threshold,
+                    (Some(value), None) => !value,
+                    (None, _) => false,
+                };
+                if dead_transition {
+                    dead.push((path.clone(), i));
+                }
+            }
+            for x in &plan.plans {
+                path.push(x.name().clone());
+                collect(x, path, dead);
+                path.pop();
+            }
+        }
+        let mut dead = Vec::new();
+        collect(self, &mut Vec::new(), &mut dead);
+        dead
+    }
+
+    /// Check the declared structure of this plan and all subplans recursively, assuming this plan is reachable.
+    ///
+    /// Subplans are reachable if autostart, conditionally autostart, hinted by [Plan::reachable_hint],
+    /// or the `dst` of a transition whose `src` plans are all reachable. All subplans are reachable
+    /// if the behaviour selects among them, see [Behaviour::exclusive_subplans], since it enters them directly,
+    /// and none are if the [Plan::execution] does not run subplans.
+    /// Unreachable subplans are reported without descending into them.
+    pub fn validate(&self) -> Vec<ValidationIssue<C::Name>> {
+        fn collect<C: Config>(
+            plan: &Plan<C>,
+            path: &mut Vec<C::Name>,
+            issues: &mut Vec<ValidationIssue<C::Name>>,
+        ) {
+            let reached = plan.reachable_subplans();
+            for (i, t) in plan.transitions.iter().enumerate() {
+                let missing = t.sources().chain(&t.dst).filter(|x| plan.get(x).is_none());
+                issues.extend(missing.map(|x| ValidationIssue::MissingPlan {
+                    path: path.clone(),
+                    transition: i,
+                    plan: x.clone(),
+                }));
+                if t.sources()
+                    .any(|x| plan.get(x).is_some() && !reached.contains(x))
+                {
+                    issues.push(ValidationIssue::UnreachableTransition {
+                        path: path.clone(),
+                        transition: i,
+                    });
+                }
+            }
+            for x in &plan.plans {
+                path.push(x.name().clone());
+                match reached.contains(x.name()) {
+                    true => collect(x, path, issues),
+                    false => issues.push(ValidationIssue::UnreachablePlan { path: path.clone() }),
+                }
+                path.pop();
+            }
+        }
+        let mut issues = Vec::new();
+        collect(self, &mut Vec::new(), &mut issues);
+        issues
+    }
+
+    /// Message of an issue reported by [Plan::validate], followed by the descriptions of the plan
+    /// and transition it refers to, if any.
+    pub fn describe_issue(&self, issue: &ValidationIssue<C::Name>) -> String {
+        let mut message = issue.message();
+        let (path, transition) = match issue {
+            ValidationIssue::MissingPlan {
+                path, transition, ..
+            }
+            | ValidationIssue::UnreachableTransition { path, transition } => {
+                (path, Some(*transition))
+            }
+            ValidationIssue::UnreachablePlan { path } => (path, None),
+        };
+        let Some(plan) = self.descendant(path) else {
+            return message;
+        };
+        if let Some(description) = plan.description() {
+            message += &format!(" (plan {:?}: {description})", plan.name);
+        }
+        let transition = transition.and_then(|x| plan.transitions.get(x));
+        if let Some(description) = transition.and_then(|x| x.description.as_ref()) {
+            message += &format!(" (transition: {description})");
+        }
+        message
+    }
+
+    /// Export the transitions of this plan and all subplans recursively as a JSON list of [TransitionRecord].
+    ///
+    /// Records are ordered depth first, keeping the order of transitions within each plan.
+    #[cfg(feature = "serde")]
+    pub fn transitions_to_json(&self) -> Result<String, Error<C::Name>> {
+        fn collect<'a, C: Config>(
+            plan: &'a Plan<C>,
+            path: &mut Vec<C::Name>,
+            records: &mut Vec<TransitionRecordRef<'a, C::Predicate, C::Name>>,
+        ) {
+            records.extend(
+                plan.transitions
+                    .iter()
+                    .map(|transition| TransitionRecordRef {
+                        parent_path: path.clone(),
+                        transition,
+                    }),
+            );
+            for x in &plan.plans {
+                path.push(x.name().clone());
+                collect(x, path, records);
+                path.pop();
+            }
+        }
+        let mut records = Vec::new();
+        collect(self, &mut Vec::new(), &mut records);
+        serde_json::to_string(&records).map_err(|e| Error::Json(e.to_string()))
+    }
+
+    /// Replace the transitions of this plan and all subplans recursively with a JSON list of [TransitionRecord].
+    ///
+    /// Plans without records are left with no transitions. Behaviours and subplans are untouched.
+    /// Nothing is replaced if any record refers to a missing plan, as reported by [Plan::validate].
+    #[cfg(feature = "serde")]
+    pub fn apply_transitions_json(&mut self, json: &str) -> Result<(), Error<C::Name>> {
+        type Transitions<C> = Vec<Transition<<C as Config>::Predicate, <C as Config>::Name>>;
+        fn take<C: Config>(plan: &mut Plan<C>, taken: &mut Vec<Transitions<C>>) {
+            taken.push(std::mem::take(&mut plan.transitions));
+            plan.plans.iter_mut().for_each(|x| take(x, taken));
+        }
+        fn restore<C: Config>(
+            plan: &mut Plan<C>,
+            taken: &mut impl Iterator<Item = Transitions<C>>,
+        ) {
+            plan.transitions = taken.next().unwrap_or_default();
+            plan.plans.iter_mut().for_each(|x| restore(x, taken));
+        }
+        let records: Vec<TransitionRecord<C::Predicate, C::Name>> =
+            serde_json::from_str(json).map_err(|e| Error::Json(e.to_string()))?;
+        if let Some(x) = records
+            .iter()
+            .find(|x| self.descendant(&x.parent_path).is_none())
+        {
+            return Err(Error::PlanNotFound(x.parent_path.clone()));
+        }
+        let mut previous = Vec::new();
+        take(self, &mut previous);
+        for x in records {
+            let plan = self.descendant_mut(&x.parent_path).unwrap();
+            plan.transitions.push(x.transition);
+        }
+        let issues = self
+            .validate()
+            .into_iter()
+            .filter(|x| x.is_error())
+            .collect::<Vec<_>>();
+        if !issues.is_empty() {
+            restore(self, &mut previous.into_iter());
+            return Err(Error::InvalidTransitions(issues));
+        }
+        Ok(())
+    }
+
+    /// Panic upon corruption of this plan or any subplan recursively, reporting the path of the offending plan.
+    ///
+    /// Checks that subplans are sorted by unique names, that active plans only have active parents,
+    /// and that behaviours with [Behaviour::exclusive_subplans] have at most one active subplan.
+    /// The run countdown is not checked against `run_interval`, which may change while active.
+    #[cfg(feature = "invariants")]
+    fn check_invariants(&self, path: &mut Vec<C::Name>) {
+        path.push(self.name.clone());
+        let violated =
+            |message: String| -> ! { panic!("invariant violated in plan {:?}: {}", path, message) };
+        if let Some(x) = self.plans.windows(2).find(|x| x[0].name >= x[1].name) {
+            violated(format!(
+                "subplans {:?} and {:?} are not sorted by unique name",
+                x[0].name, x[1].name
+            ));
+        }
+        let mut active = self.plans.iter().filter(|x| x.active()).map(|x| &x.name);
+        if !self.active() {
+            if let Some(name) = active.next() {
+                violated(format!(
+                    "subplan {:?} is active within an inactive plan",
+                    name
+                ));
+            }
+        } else if self
+            .behaviour
+            .as_ref()
+            .is_some_and(|x| x.exclusive_subplans())
+        {
+            let active = active.collect::<Vec<_>>();
+            if active.len() > 1 {
+                violated(format!(
+                    "exclusive behaviour has active subplans {:?}",
+                    active
+                ));
+            }
+        }
+        for plan in &self.plans {
+            plan.check_invariants(path);
+        }
+        path.pop();
+    }
+
+    /// Subplan at `path` of names relative to this plan.
+    fn descendant(&self, path: &[C::Name]) -> Option<&Self> {
+        path.iter().try_fold(self, |plan, name| plan.get(name))
+    }
+
+    /// See [Plan::descendant].
+    pub(crate) fn descendant_mut(&mut self, path: &[C::Name]) -> Option<&mut Self> {
+        path.iter().try_fold(self, |plan, name| plan.get_mut(name))
+    }
+
+    /// Transitions among the subplans of this plan as an adjacency matrix, not descending further.
+    ///
+    /// Each transition appears in the entries pairing its [Transition::sources] and `dst` plans, excluding pairs of the same plan.
+    /// Transitions with a missing source plan never fire and are left out, as are missing `dst` plans.
+    pub fn transition_matrix(&self) -> TransitionMatrix<C::Name> {
+        let mut states = self
+            .plans
+            .iter()
+            .map(|x| Some(x.name.clone()))
+            .collect::<Vec<_>>();
+        states.push(None);
+        let none = self.plans.len();
+        let mut cells = vec![vec![Vec::new(); states.len()]; states.len()];
+        for t in &self.transitions {
+            let Ok(mut src) = t
+                .sources()
+                .map(|x| self.priority(x))
+                .collect::<Result<Vec<_>, _>>()
+            else {
+                continue;
+            };
+            src.sort_unstable();
+            src.dedup();
+            let mut dst = t
+                .dst
+                .iter()
+                .filter_map(|x| self.priority(x).ok())
+                .collect::<Vec<_>>();
+            let joint = src.len() > 1 || dst.len() > 1;
+            let mut descriptor = t.label.clone().unwrap_or_else(|| t.predicate.describe());
+            if joint {
+                let sources = t.sources().collect::<Vec<_>>();
+                descriptor = format!("{descriptor} ({sources:?} -> {:?})", t.dst);
+            }
+            if let Some(description) = &t.description {
+                descriptor = format!("{descriptor}: {description}");
+            }
+            if src.is_empty() {
+                src.push(none);
+            }
+            if dst.is_empty() {
+                dst.push(none);
+            }
+            for &i in &src {
+                for &j in dst.iter().filter(|&&j| j != i) {
+                    cells[i][j].push(descriptor.clone());
+                }
+            }
+        }
+        TransitionMatrix { states, cells }
+    }
+
+    /// Names of subplans that may become active given this plan is active. See [Plan::validate].
+    fn reachable_subplans(&self) -> BTreeSet<&C::Name> {
+        if !self.execution.runs_subplans() {
+            return BTreeSet::new();
+        }
+        let assume_all = self.behaviour_dyn().is_some_and(|x| x.exclusive_subplans());
+        let mut reached = self
+            .plans
+            .iter()
+            .filter(|x| assume_all || x.autostart || x.autostart_if().is_some() || x.reachable_hint)
+            .map(|x| x.name())
+            .collect::<BTreeSet<_>>();
+        // find fixed point of reachable subplans through transitions
+        loop {
+            let len = reached.len();
+            let dst = self
+                .transitions
+                .iter()
+                .filter(|t| t.sources().all(|x| reached.contains(x)))
+                .flat_map(|t| t.dst.iter())
+                .collect::<Vec<_>>();
+            reached.extend(dst);
+            if reached.len() == len {
+                return reached;
+            }
+        }
+    }
+
+    /// Pseudo-random delay of the next run within the limit of [Plan::interval_jitter].
+    fn next_jitter_delay(&self) -> u32 {
+        let jitter = self
+            .interval_jitter
+            .min(self.run_interval.saturating_sub(1));
+        if jitter == 0 {
+            return 0;
+        }
+        let seed = self.own_seed().unwrap_or_default();
+        let tick = self.tree.tick.load(Ordering::Relaxed);
+        (derive_seed(seed, &(&self.name, tick)) % (jitter as u64 + 1)) as u32
+    }
+
+    /// Seed for the subplan with given name to inherit, derived from the seed of this plan.
+    fn inherited_seed(&self, name: &C::Name) -> Option<u64> {
+        let seed = self.own_seed()?;
+        Some(derive_seed(seed, name))
+    }
+
+    /// Derive [Plan::path] from the path of the parent, and that of all descendants if `recursive`.
+    fn set_parent_path(&mut self, parent: &str, recursive: bool) {
+        self.path.clear();
+        if !parent.is_empty() {
+            self.path.push_str(parent);
+            self.path.push('/');
+        }
+        self.path.push_str(&path_segment(&self.name));
+        if recursive {
+            for plan in &mut self.plans {
+                plan.set_parent_path(&self.path, true);
+            }
+        }
+    }
+
+    /// Trigger on_exit() of this plan alone if active, leaving subplans to do the same as they are dropped.
+    fn exit_dropped(&mut self) {
+        if self.active() {
+            self.call(|behaviour, plan| behaviour.on_exit(plan), "exit");
+            self.tree.release_tags(self.tags());
+            self.run_countdown = u32::MAX;
+        }
+    }
+
+    /// Trigger on_shutdown() for self and all active subplans recursively.
+    fn shutdown(&mut self) {
+        self.contain(|plan| {
+            let result = catch_unwind(AssertUnwindSafe(|| plan.shutdown_unchecked()));
+            plan_context(&plan.name, result)
+        });
+    }
+
+    /// See [Plan::shutdown], without panic context.
+    fn shutdown_unchecked(&mut self) {
+        self.call(|behaviour, plan| behaviour.on_shutdown(plan), "shutdown");
+        let i = self.plans.iter_mut().filter(|plan| plan.active());
+        #[cfg(feature = "rayon")]
+        i.par_bridge().for_each(|plan| plan.shutdown());
+        #[cfg(not(feature = "rayon"))]
+        i.for_each(|plan| plan.shutdown());
+    }
+
+    /// Helper to wrap calling inner behaviour from plan.
+    fn call(&mut self, f: impl FnOnce(&mut Box<C::Behaviour>, &mut Self), name: &'static str) {
+        let mut behaviour = std::mem::take(&mut self.behaviour);
+        if let Some(b) = &mut behaviour {
+            let _span = debug_span!(parent: self.span(), "call", func=%name).entered();
+            #[cfg(feature = "timing")]
+            let start = std::time::Instant::now();
+            #[cfg(feature = "journal")]
+            let hook = self.hook.replace(name);
+            // put the behaviour back even if it panics, for isolated plans to be entered again
+            let result = catch_unwind(AssertUnwindSafe(|| hook_context(name, || f(b, self))));
+            #[cfg(feature = "journal")]
+            {
+                self.hook = hook;
+            }
+            #[cfg(feature = "timing")]
+            self.timings.record(name, start.elapsed());
+            self.behaviour = behaviour;
+            if let Err(payload) = result {
+                resume_unwind(payload);
+            }
+        }
+    }
+}
+
+/// Exit the plan on drop.
+#[cfg(feature = "arena")]
+impl<C: Config> Plan<C> {
+    /// Exit as if dropped then move out the allocations of this plan, with its subplans left in `plans`.
+    pub(crate) fn take_parts(&mut self) -> PlanParts<C> {
+        self.exit_dropped();
+        let mut inner = self.inner.take();
+        if let Some(inner) = &mut inner {
+            inner.reset();
+        }
+        let mut activation = self.activation.take();
+        if let Some(activation) = &mut activation {
+            **activation = Activation::default();
+        }
+        let mut parts = PlanParts {
+            transitions: std::mem::take(&mut self.transitions),
+            activation,
+            inner,
+            plans: std::mem::take(&mut self.plans),
+            tree: Some(self.tree.clone()),
+        };
+        parts.transitions.clear();
+        parts
+    }
+}
+
+impl<C: Config> Drop for Plan<C> {
+    fn drop(&mut self) {
+        self.exit_dropped();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracing_init() {
+        use tracing_subscriber::fmt::format::FmtSpan;
+        let _ = tracing_subscriber::fmt()
+            .with_span_events(FmtSpan::ENTER)
+            .with_target(false)
+            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+            .try_init();
+    }
+
+    #[derive(Default, Debug, EnumCast)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct RunCountBehaviour {
+        pub entry_count: u32,
+        pub exit_count: u32,
+        pub run_count: u32,
+    }
+
+    impl<C: Config> Behaviour<C> for RunCountBehaviour {
+        fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+            None
+        }
+        fn on_entry(&mut self, plan: &mut Plan<C>) {
+            self.entry_count += 1;
+            assert!(plan.behaviour.is_none())
+        }
+        fn on_exit(&mut self, _plan: &mut Plan<C>) {
+            self.exit_count += 1;
+        }
+        fn on_run(&mut self, _plan: &mut Plan<C>) {
+            self.run_count += 1;
+        }
+    }
+
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    struct TestConfig;
+    impl Config for TestConfig {
+        type Predicate = predicate::Predicates;
+        type Behaviour = RunCountBehaviour;
+        type Event = ();
+        type Name = DefaultName;
+    }
+
+    fn new_plan(name: &str, autostart: bool) -> Plan<TestConfig> {
+        Plan::<TestConfig>::new(RunCountBehaviour::default(), name, 1, autostart)
+    }
+
+    fn abc_plan() -> Plan<TestConfig> {
+        let mut root_plan = new_plan("root", true);
+        root_plan.transitions = vec![
+            Transition::new(
+                vec!["A".into()],
+                vec!["B".into()],
+                predicate::True.into_enum().unwrap(),
+            ),
+            Transition::new(
+                vec!["B".into()],
+                vec!["C".into()],
+                predicate::True.into_enum().unwrap(),
+            ),
+            Transition::new(
+                vec!["C".into()],
+                vec!["A".into()],
+                predicate::True.into_enum().unwrap(),
+            ),
+        ];
+        // init plan to A
+        root_plan.insert(new_plan("A", true));
+        root_plan.insert(new_plan("B", false));
+        root_plan.insert(new_plan("C", false));
+        root_plan.insert(new_plan("D", false));
+        root_plan
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn deterministic() {
+        test_util::assert_deterministic(abc_plan, 10);
+    }
+
+    #[test]
+    #[cfg(all(feature = "test-util", feature = "serde"))]
+    fn fuzz_roundtrip() {
+        test_util::fuzz_roundtrip::<config::Default>(200, 7);
+    }
+
+    #[test]
+    fn leaf_footprint() {
+        use std::mem::size_of;
+        // per-tick state costs a single pointer until used
+        assert_eq!(size_of::<Option<Box<Transient<()>>>>(), size_of::<usize>());
+        let mut root_plan = abc_plan();
+        for _ in 0..3 {
+            root_plan.run();
+        }
+        assert!(root_plan.transient_ref().is_none());
+        assert!(root_plan
+            .plans
+            .iter()
+            .all(|plan| plan.transient_ref().is_none()));
+        // allocated upon use and released on exit
+        root_plan.post_event(());
+        root_plan.run();
+        assert_eq!(root_plan.events().len(), 1);
+        root_plan.exit(false);
+        assert!(root_plan.transient_ref().is_none());
+        assert!(root_plan.events().is_empty());
+
+        // leaf plans leave their rarely set fields unallocated, at least one pointer short of inner plans,
+        // and no larger than plans were before those fields were boxed, besides fields of optional features
+        let leaf = size_of::<Plan<DefaultConfig>>();
+        let inner = leaf + size_of::<Inner<DefaultConfig>>();
+        assert!(leaf + size_of::<usize>() <= inner);
+        #[allow(unused_mut)]
+        let mut baseline = 184;
+        #[cfg(feature = "timing")]
+        {
+            baseline += size_of::<TimingStats>();
+        }
+        #[cfg(feature = "journal")]
+        {
+            baseline += size_of::<Option<&'static str>>();
+        }
+        assert!(leaf <= baseline, "{leaf} > {baseline}");
+        root_plan.run();
+        assert!(root_plan.plans.iter().all(|plan| plan.inner.is_none()));
+    }
+
+    #[test]
+    fn plan_tree_macro() {
+        use behaviour::*;
+        let plan = plan_tree!(DefaultConfig; "root" autostart {
+            behaviour: SequenceBehaviour::default(),
+            data: { "speed": 2.5, "name": "scout" },
+            children: {
+                "a" interval = 3 autostart {
+                    behaviour: AllSuccessStatus,
+                    children: { "x" {} },
+                },
+                "b" {},
+            },
+            transitions: [
+                ["a"] -> ["b"] if predicate::AllSuccess,
+                ["b", "a"] -> [],
+            ],
+        });
+        assert!(plan.autostart);
+        assert_eq!(plan.run_interval, 1);
+        assert!(plan.cast::<SequenceBehaviour<DefaultConfig>>().is_some());
+        assert_eq!(plan.data()["speed"], serde_value::Value::F64(2.5));
+        assert_eq!(
+            plan.data()["name"],
+            serde_value::Value::String("scout".into())
+        );
+        let a = plan.get("a").unwrap();
+        assert!(a.autostart);
+        assert_eq!(a.run_interval, 3);
+        assert!(a.get("x").unwrap().behaviour.is_none());
+        assert_eq!(a.get("x").unwrap().run_interval, 1);
+        assert!(!plan.get("b").unwrap().autostart);
+        assert_eq!(plan.transitions.len(), 2);
+        assert_eq!(plan.transitions[0].src, ["a"]);
+        assert_eq!(plan.transitions[0].dst, ["b"]);
+        assert!(plan.transitions[0]
+            .predicate
+            .cast::<predicate::AllSuccess>()
+            .is_some());
+        assert_eq!(plan.transitions[1].src, ["b", "a"]);
+        assert!(plan.transitions[1].dst.is_empty());
+        assert!(plan.transitions[1]
+            .predicate
+            .cast::<predicate::True>()
+            .is_some());
+    }
+
+    #[test]
+    fn default_subplans() {
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct CombatBehaviour {
+            attack: bool,
+        }
+        impl Behaviour<CombatConfig> for CombatBehaviour {
+            fn status(&self, _plan: &Plan<CombatConfig>) -> Option<bool> {
+                None
+            }
+            fn default_subplans(&self) -> Vec<Plan<CombatConfig>> {
+                match self.attack {
+                    true => ["aim", "fire", "reload"]
+                        .into_iter()
+                        .map(|name| Plan::new_stub(name, false))
+                        .collect(),
+                    false => Vec::new(),
+                }
+            }
+        }
+        const ATTACK: CombatBehaviour = CombatBehaviour { attack: true };
+        const AIM: CombatBehaviour = CombatBehaviour { attack: false };
+
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct CombatConfig;
+        impl Config for CombatConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = CombatBehaviour;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        let names = |plan: &Plan<CombatConfig>| {
+            let names = plan.plans.iter().map(|plan| plan.name().clone());
+            names.collect::<Vec<_>>()
+        };
+
+        // expected children are created along with the behaviour
+        let plan = Plan::<CombatConfig>::new(ATTACK, "combat", 1, false);
+        assert_eq!(names(&plan), ["aim", "fire", "reload"]);
+        assert!(plan.plans.iter().all(|plan| plan.behaviour.is_none()));
+
+        // explicitly authored children override the defaults
+        let plan = plan_tree!(CombatConfig; "combat" {
+            behaviour: ATTACK,
+            children: { "aim" autostart { behaviour: AIM } },
+        });
+        assert_eq!(names(&plan), ["aim", "fire", "reload"]);
+        assert!(plan.get("aim").unwrap().autostart);
+
+        // existing children are kept when the behaviour is replaced
+        let mut plan = Plan::<CombatConfig>::new(AIM, "combat", 1, false);
+        assert!(plan.plans.is_empty());
+        plan.insert(Plan::new(AIM, "fire", 1, true));
+        let previous = plan.set_behaviour(ATTACK);
+        assert!(!previous.unwrap().attack);
+        assert_eq!(names(&plan), ["aim", "fire", "reload"]);
+        assert!(plan.get("fire").unwrap().autostart);
+    }
+
+    #[test]
+    fn transition_macro() {
+        let manual = Transition::<predicate::Predicates> {
+            src: vec!["A".into()],
+            dst: vec!["B".into()],
+            predicate: predicate::AllSuccess.into(),
+            interruptible: true,
+            label: None,
+            max_firings: None,
+            threshold: None,
+            description: None,
+            clauses: Vec::new(),
+        };
+        let same = |t: &Transition<predicate::Predicates>, predicate_matches: bool| {
+            t.src == manual.src
+                && t.dst == manual.dst
+                && predicate_matches
+                && t.interruptible == manual.interruptible
+                && t.label == manual.label
+                && t.max_firings == manual.max_firings
+                && t.threshold == manual.threshold
+        };
+        let t: Transition<predicate::Predicates> = transition!("A" => "B", predicate::AllSuccess);
+        assert!(same(
+            &t,
+            t.predicate.cast::<predicate::AllSuccess>().is_some()
+        ));
+        let t: Transition<predicate::Predicates> =
+            transition!(["A"] => ["B"], predicate::AllSuccess,);
+        assert!(same(
+            &t,
+            t.predicate.cast::<predicate::AllSuccess>().is_some()
+        ));
+
+        // predicate defaults to true
+        let t: Transition<predicate::Predicates> = transition!("A" => "B");
+        assert!(same(&t, t.predicate.cast::<predicate::True>().is_some()));
+
+        // multiple and empty sets of plans
+        let t: Transition<predicate::Predicates> = transition!(["A", "B"] => []);
+        assert_eq!(t.src, ["A", "B"]);
+        assert!(t.dst.is_empty());
+    }
+
+    #[test]
+    fn sorted_insert() {
+        tracing_init();
+
+        let mut root_plan = new_plan("root", true);
+        root_plan.insert(new_plan("C", true));
+        root_plan.insert(new_plan("A", true));
+        root_plan.insert(new_plan("B", true));
+        root_plan.insert(new_plan("B", true));
+
+        assert_eq!(root_plan.plans.len(), 3);
+        for (i, plan) in root_plan.plans.iter().enumerate() {
+            assert!(!plan.active());
+            assert_eq!(plan.name(), &((b'A' + (i as u8)) as char).to_string());
+            let sm = plan.behaviour.as_ref().unwrap();
+            assert_eq!(sm.entry_count, 0);
+            assert_eq!(sm.run_count, 0);
+            assert_eq!(sm.exit_count, 0);
+        }
+        root_plan.exit(false);
+        for plan in &root_plan.plans {
+            assert!(!plan.active());
+            assert_eq!(plan.behaviour.as_ref().unwrap().exit_count, 0);
+        }
+    }
+
+    #[test]
+    fn cycle_plans() {
+        tracing_init();
+        let mut root_plan = abc_plan();
+        root_plan.run();
+        root_plan.run();
+        let cycles = 10;
+        for _ in 0..(cycles - 1) {
+            assert!(!root_plan.get("A").unwrap().active());
+            assert!(!root_plan.get("B").unwrap().active());
+            assert!(root_plan.get("C").unwrap().active());
+            assert!(!root_plan.get("D").unwrap().active());
+            root_plan.run();
+            assert!(root_plan.get("A").unwrap().active());
+            assert!(!root_plan.get("B").unwrap().active());
+            assert!(!root_plan.get("C").unwrap().active());
+            assert!(!root_plan.get("D").unwrap().active());
+            root_plan.run();
+            assert!(!root_plan.get("A").unwrap().active());
+            assert!(root_plan.get("B").unwrap().active());
+            assert!(!root_plan.get("C").unwrap().active());
+            assert!(!root_plan.get("D").unwrap().active());
+            root_plan.run();
+        }
+        root_plan.exit(false);
+
+        for plan in &root_plan.plans {
+            if plan.name() == "D" {
+                assert!(!plan.active());
+                continue;
+            }
+            let sm = plan.behaviour.as_ref().unwrap();
+            assert_eq!(sm.entry_count, cycles);
+            assert_eq!(sm.exit_count, cycles);
+            // off by one becase inital plan didn't run
+            let run_cycles = if plan.name() == "A" {
+                cycles - 1
+            } else {
+                cycles
+            };
+            assert_eq!(sm.run_count, run_cycles);
+        }
+    }
+
+    #[test]
+    fn numeric_names() {
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct NumericConfig;
+        impl Config for NumericConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = RunCountBehaviour;
+            type Event = ();
+            type Name = u16;
+        }
+        let new_plan = |name: u16, autostart| {
+            Plan::<NumericConfig>::new(RunCountBehaviour::default(), name, 1, autostart)
+        };
+        let mut root_plan = new_plan(0, true);
+        for name in [10, 2, 1] {
+            root_plan.insert(new_plan(name, name == 1));
+        }
+        // priority follows numeric rather than lexicographic order
+        let names = root_plan
+            .plans
+            .iter()
+            .map(|x| *x.name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, [1, 2, 10]);
+        assert_eq!(root_plan.priority(&10), Ok(2));
+        assert_eq!(root_plan.find_by_path(&[&10]).unwrap().name(), &10);
+        let transition =
+            |src: u16, dst: u16| Transition::new(vec![src], vec![dst], predicate::True.into());
+        root_plan.transitions = vec![transition(1, 2), transition(2, 10), transition(10, 1)];
+        let active = |plan: &Plan<NumericConfig>| {
+            let active = plan.plans.iter().filter(|x| x.active());
+            active.map(|x| *x.name()).collect::<Vec<_>>()
+        };
+        for expected in [2, 10, 1, 2] {
+            root_plan.run();
+            assert_eq!(active(&root_plan), [expected]);
+        }
+        // names are reported in their own type
+        root_plan.transitions.push(transition(7, 1));
+        assert_eq!(
+            root_plan.validate(),
+            [ValidationIssue::MissingPlan {
+                path: vec![],
+                transition: 3,
+                plan: 7,
+            }]
+        );
+        root_plan.run();
+        assert_eq!(root_plan.warnings()[0].path, "0");
+        assert_eq!(
+            root_plan.run_subtree(&[&2, &5]),
+            Err(Error::PlanNotFound(vec![2, 5]))
+        );
+        let run_count = |plan: &Plan<NumericConfig>| {
+            plan.get(&10).unwrap().behaviour.as_ref().unwrap().run_count
+        };
+        let count = run_count(&root_plan);
+        root_plan.run_subtree(&[&10]).unwrap();
+        assert_eq!(run_count(&root_plan), count + 1);
+    }
+
+    #[test]
+    fn tag_limits() {
+        tracing_init();
+        let mut root_plan = new_plan("root", true);
+        root_plan.tag_limits_mut().0.insert("weapon".into(), 2);
+        for name in ["A", "B", "C"] {
+            *root_plan.insert(new_plan(name, true)).tags_mut() = vec!["weapon".into()];
+        }
+        root_plan.insert(new_plan("D", true));
+        let active = |plan: &Plan<TestConfig>| {
+            plan.plans
+                .iter()
+                .filter(|x| x.active())
+                .map(|x| x.name().as_str())
+                .collect::<String>()
+        };
+        // autostart resolves by priority
+        let report = root_plan.run();
+        assert_eq!(active(&root_plan), "ABD");
+        assert_eq!(
+            report.tag_refusals,
+            vec![TagRefusal {
+                plan: "C".into(),
+                tag: "weapon".into()
+            }]
+        );
+        assert!(root_plan.run().tag_refusals.is_empty());
+
+        // freed slot can be taken by another plan
+        root_plan.exit_plan("A");
+        assert!(root_plan.enter_plan("C").unwrap().active());
+        assert!(!root_plan.enter_plan("A").unwrap().active());
+        assert_eq!(active(&root_plan), "BCD");
+        assert_eq!(root_plan.run().tag_refusals.len(), 1);
+
+        // transitions are refused as well
+        root_plan.transitions.push(Transition::new(
+            vec!["D".into()],
+            vec!["A".into()],
+            predicate::True.into_enum().unwrap(),
+        ));
+        assert_eq!(root_plan.run().tag_refusals[0].plan, "A");
+        assert_eq!(active(&root_plan), "BC");
+        root_plan.transitions.clear();
+
+        // raise limit at runtime
+        root_plan.tag_limits_mut().0.insert("weapon".into(), 3);
+        root_plan.run();
+        assert!(root_plan.enter_plan("A").unwrap().active());
+        assert_eq!(active(&root_plan), "ABC");
+
+        // lowering the limit keeps active plans but refuses new entries
+        root_plan.tag_limits_mut().0.insert("weapon".into(), 1);
+        root_plan.run();
+        root_plan.exit_plan("A");
+        root_plan.exit_plan("B");
+        assert!(!root_plan.enter_plan("A").unwrap().active());
+        root_plan.exit_plan("C");
+        assert!(root_plan.enter_plan("B").unwrap().active());
+        assert_eq!(active(&root_plan), "B");
+
+        // re-entering the tree resets counts
+        root_plan.exit(false);
+        root_plan.tag_limits_mut().0.clear();
+        root_plan.run();
+        assert_eq!(active(&root_plan), "ABCD");
+    }
+
+    #[test]
+    fn interval_jitter() {
+        // ticks on which each subplan ran
+        let schedule = |seed, jitter| {
+            let mut root_plan = Plan::<TestConfig>::new_stub("root", true);
+            root_plan.seed = seed;
+            for i in 0..100 {
+                let mut plan = Plan::new(RunCountBehaviour::default(), i.to_string(), 10, true);
+                plan.interval_jitter = jitter;
+                root_plan.insert(plan);
+            }
+            let mut runs = vec![Vec::new(); 100];
+            for tick in 0..100 {
+                root_plan.run();
+                for (plan, runs) in root_plan.plans.iter().zip(&mut runs) {
+                    let run_count = plan.cast::<RunCountBehaviour>().unwrap().run_count;
+                    if run_count as usize > runs.len() {
+                        runs.push(tick);
+                    }
+                }
+            }
+            runs
+        };
+
+        // identical plans run in lockstep without jitter
+        let regular = (0..100).step_by(10).collect::<Vec<_>>();
+        assert!(schedule(Some(1), 0).iter().all(|x| *x == regular));
+
+        // jitter spreads out the phases while keeping the average rate
+        let spread = schedule(Some(1), 3);
+        let mut phases = [0; 4];
+        for runs in &spread {
+            assert_eq!(runs.len(), 10);
+            for (i, tick) in runs.iter().enumerate() {
+                assert!((i * 10..=i * 10 + 3).contains(tick));
+            }
+            phases[runs[0]] += 1;
+        }
+        assert!(phases.iter().all(|x| *x >= 10), "{phases:?}");
+        let run_ticks = spread.iter().flatten().collect::<BTreeSet<_>>();
+        assert!(run_ticks.len() > 30);
+
+        // reproducible by seed
+        assert_eq!(schedule(Some(1), 3), spread);
+        assert_ne!(schedule(Some(2), 3), spread);
+
+        // limited to less than the run interval
+        let mut plan = Plan::<TestConfig>::new(RunCountBehaviour::default(), "root", 2, true);
+        plan.interval_jitter = 5;
+        for _ in 0..50 {
+            plan.run();
+            assert!(plan.run_countdown() <= 2);
+        }
+        let run_count = plan.cast::<RunCountBehaviour>().unwrap().run_count;
+        assert!((24..=25).contains(&run_count));
+    }
+
+    #[test]
+    fn seeded_subtrees() {
+        #[derive(Default, EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct CoinFlip(u64);
+        impl<C: Config> Behaviour<C> for CoinFlip {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                Some(self.0 & 2 == 0)
+            }
+            fn on_run(&mut self, _plan: &mut Plan<C>) {
+                // xorshift64
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+            }
+            fn reseed(&mut self, seed: u64) {
+                self.0 = seed | 1;
+            }
+        }
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct SeedConfig;
+        impl Config for SeedConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = CoinFlip;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        let build = |seed| {
+            let mut root_plan = Plan::<SeedConfig>::new(CoinFlip::default(), "root", 1, true);
+            root_plan.seed = seed;
+            for name in ["A", "B", "C"] {
+                root_plan.insert(Plan::new(CoinFlip::default(), name, 1, name == "A"));
+            }
+            let transition = |src: &str, dst: &str| {
+                Transition::new(
+                    vec![src.into()],
+                    vec![dst.into()],
+                    predicate::AllSuccess.into(),
+                )
+            };
+            root_plan.transitions = vec![
+                transition("A", "B"),
+                transition("B", "C"),
+                transition("C", "A"),
+            ];
+            root_plan
+        };
+        let record = |root_plan: &mut Plan<SeedConfig>| {
+            (0..40)
+                .map(|_| {
+                    root_plan.run();
+                    let active = root_plan.plans.iter().find(|x| x.active()).unwrap();
+                    active.name().clone()
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut root_plan = build(Some(42));
+        let sequence = record(&mut root_plan);
+        assert_eq!(record(&mut build(Some(42))), sequence);
+        assert_ne!(record(&mut build(Some(7))), sequence);
+        // subplans derive distinct seeds from their names
+        let state =
+            |plan: &Plan<SeedConfig>, name: &str| plan.get_cast::<CoinFlip, _>(name).unwrap().0;
+        let mut root_plan = build(Some(42));
+        root_plan.enter(None);
+        let a = state(&root_plan, "A");
+        assert_ne!(a, root_plan.cast::<CoinFlip>().unwrap().0);
+        assert_eq!(a, derive_seed(42, &"A".to_string()) | 1);
+        // explicit seeds take precedence, and re-entry reproduces the sequence
+        root_plan.exit(false);
+        assert_eq!(record(&mut root_plan), sequence);
+        root_plan.exit(false);
+        root_plan.get_mut("A").unwrap().seed = Some(3);
+        root_plan.enter(None);
+        assert_eq!(state(&root_plan, "A"), 3);
+    }
+
+    #[test]
+    fn enter_exit_by_tag() {
+        tracing_init();
+        let mut root_plan = new_plan("root", true);
+        let active = |plan: &Plan<TestConfig>| {
+            plan.plans
+                .iter()
+                .filter(|x| x.active())
+                .map(|x| x.name().as_str())
+                .collect::<String>()
+        };
+        for name in ["A", "B", "C", "D"] {
+            let plan = root_plan.insert(new_plan(name, name == "D"));
+            if name != "C" {
+                *plan.tags_mut() = vec!["combat".into()];
+            }
+            // nested plans in active parent
+            for child in ["x", "y"] {
+                *plan.insert(new_plan(child, false)).tags_mut() = vec![child.into()];
+            }
+        }
+        // entry requires an active parent
+        assert_eq!(root_plan.enter_by_tag("combat"), 0);
+        root_plan.run();
+        assert_eq!(active(&root_plan), "D");
+
+        // only tagged plans are entered
+        assert_eq!(root_plan.enter_by_tag("combat"), 2);
+        assert_eq!(active(&root_plan), "ABD");
+        assert_eq!(root_plan.enter_by_tag("combat"), 0);
+
+        // deep entry only reaches into active parents
+        assert_eq!(root_plan.enter_by_tag("x"), 3);
+        for name in ["A", "B", "D"] {
+            assert_eq!(active(root_plan.get(name).unwrap()), "x");
+        }
+        assert_eq!(active(root_plan.get("C").unwrap()), "");
+
+        // exit leaves untagged plans untouched
+        root_plan.enter_plan("C");
+        assert_eq!(root_plan.exit_by_tag("combat"), 3);
+        assert_eq!(active(&root_plan), "C");
+        assert_eq!(root_plan.exit_by_tag("combat"), 0);
+        root_plan.enter_by_tag("x");
+        root_plan.enter_by_tag("y");
+        assert_eq!(root_plan.exit_by_tag("x"), 1);
+        assert_eq!(active(root_plan.get("C").unwrap()), "y");
+    }
+
+    #[test]
+    fn conditional_autostart() {
+        use serde_value::Value;
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct IsNight;
+        impl Predicate for IsNight {
+            fn evaluate<C: Config>(&self, plan: &Plan<C>, _: &[C::Name]) -> bool {
+                plan.data().get("night") == Some(&Value::Bool(true))
+            }
+        }
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct NightConfig;
+        impl Config for NightConfig {
+            type Predicate = IsNight;
+            type Behaviour = RunCountBehaviour;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        let mut root_plan = Plan::<NightConfig>::new_stub("root", true);
+        root_plan.insert(Plan::new_stub("patrol", true));
+        // predicate overrides the boolean
+        let mut watch = Plan::new_stub("watch", false);
+        watch.set_autostart_if(Some(IsNight));
+        root_plan.insert(watch);
+        let mut rest = Plan::new_stub("rest", true);
+        rest.set_autostart_if(Some(IsNight));
+        root_plan.insert(rest);
+        let active = |plan: &Plan<NightConfig>| {
+            plan.plans
+                .iter()
+                .filter(|x| x.active())
+                .map(|x| x.name().as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        // day time
+        root_plan.set_data("night", Value::Bool(false));
+        root_plan.run();
+        assert_eq!(active(&root_plan), "patrol");
+        // only evaluated upon entry
+        root_plan.set_data("night", Value::Bool(true));
+        root_plan.run();
+        assert_eq!(active(&root_plan), "patrol");
+        // re-entry picks up the new outcome
+        root_plan.exit(false);
+        root_plan.run();
+        assert_eq!(active(&root_plan), "patrol,rest,watch");
+    }
+
+    #[test]
+    fn validate() {
+        let mut root_plan = Plan::<TestConfig>::new_stub("root", true);
+        root_plan.insert(new_plan("A", true));
+        root_plan.insert(new_plan("B", false));
+        // unreachable, and so is its subtree
+        let mut c = new_plan("C", false);
+        c.insert(new_plan("x", false));
+        root_plan.insert(c);
+        // entered directly by some behaviour
+        let mut d = new_plan("D", false);
+        d.reachable_hint = true;
+        d.insert(new_plan("y", false));
+        root_plan.insert(d);
+        let transition = |src: &[&str], dst: &str| {
+            Transition::new(
+                src.iter().map(|x| x.to_string()).collect(),
+                vec![dst.into()],
+                predicate::True.into(),
+            )
+        };
+        root_plan.transitions = vec![
+            transition(&["A"], "B"),
+            transition(&["B", "C"], "A"),
+            transition(&["A"], "E"),
+        ];
+        let path = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        let issues = root_plan.validate();
+        assert_eq!(
+            issues,
+            vec![
+                ValidationIssue::UnreachableTransition {
+                    path: vec![],
+                    transition: 1
+                },
+                ValidationIssue::MissingPlan {
+                    path: vec![],
+                    transition: 2,
+                    plan: "E".into()
+                },
+                ValidationIssue::UnreachablePlan { path: path(&["C"]) },
+                ValidationIssue::UnreachablePlan {
+                    path: path(&["D", "y"])
+                },
+            ]
+        );
+        assert_eq!(
+            issues.iter().map(|x| x.is_error()).collect::<Vec<_>>(),
+            [false, true, false, false]
+        );
+    }
+
+    #[test]
+    fn skip_idle() {
+        let build = |skip_idle: bool| {
+            let mut root_plan = new_plan("root", true);
+            root_plan.skip_idle = skip_idle;
+            for (name, interval) in [("A", 3), ("B", 5), ("C", 7)] {
+                let mut plan = new_plan(name, true);
+                plan.run_interval = interval;
+                plan.skip_idle = skip_idle;
+                let mut x = new_plan("x", true);
+                x.run_interval = interval + 1;
+                plan.insert(x);
+                root_plan.insert(plan);
+            }
+            root_plan
+        };
+        let run_counts = |plan: &Plan<TestConfig>| {
+            ["A", "B", "C"]
+                .iter()
+                .flat_map(|name| {
+                    let plan = plan.get(*name).unwrap();
+                    [plan, plan.get("x").unwrap()]
+                })
+                .map(|plan| {
+                    (
+                        plan.run_countdown(),
+                        plan.cast::<RunCountBehaviour>().unwrap().run_count,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut expected = build(false);
+        let mut plan = build(true);
+        let mut skipped = 0;
+        for tick in 0..50 {
+            // events wake idle plans to be visible on the next tick as usual
+            if tick == 20 {
+                for plan in [&mut expected, &mut plan] {
+                    plan.get_mut("B")
+                        .unwrap()
+                        .get_mut("x")
+                        .unwrap()
+                        .post_event(());
+                }
+            }
+            expected.run();
+            plan.run();
+            skipped += plan
+                .plans
+                .iter()
+                .filter(|plan| plan.idle().is_some())
+                .count();
+            // run counts match each tick, and countdowns once caught up
+            let (a, b) = (run_counts(&expected), run_counts(&plan));
+            assert_eq!(
+                a.iter().map(|x| x.1).collect::<Vec<_>>(),
+                b.iter().map(|x| x.1).collect::<Vec<_>>()
+            );
+            for (name, plan) in ["A", "B", "C"].iter().zip(plan.plans.iter()) {
+                if plan.idle().is_none() {
+                    assert_eq!(
+                        plan.run_countdown(),
+                        expected.get(*name).unwrap().run_countdown()
+                    );
+                }
+            }
+            let events =
+                |plan: &Plan<TestConfig>| plan.get("B").unwrap().get("x").unwrap().events().len();
+            assert_eq!(events(&plan), events(&expected));
+        }
+        assert!(skipped > 50);
+        // transitions keep the parent busy every tick
+        plan.get_mut("A").unwrap().transitions.push(Transition::new(
+            vec!["x".into()],
+            vec!["y".into()],
+            predicate::False.into(),
+        ));
+        plan.run();
+        plan.run();
+        assert!(plan.get("A").unwrap().idle().is_none());
+    }
+
+    #[test]
+    fn skip_idle_external_changes() {
+        let build = |skip_idle: bool| {
+            let mut root_plan = new_plan("root", true);
+            root_plan.skip_idle = skip_idle;
+            let mut plan = new_plan("A", true);
+            plan.run_interval = 10;
+            plan.skip_idle = skip_idle;
+            plan.insert(new_plan("x", false));
+            root_plan.insert(plan);
+            root_plan
+        };
+        let runs = |plan: &Plan<TestConfig>, name: &str| {
+            let plan = plan.get("A").unwrap().get(name)?;
+            Some(plan.cast::<RunCountBehaviour>().unwrap().run_count)
+        };
+        // ticks the tree without and with skipping idle plans alike, after changing them alike
+        let run = |trees: &mut [Plan<TestConfig>; 2], f: &dyn Fn(&mut Plan<TestConfig>)| {
+            for plan in trees.iter_mut() {
+                f(plan);
+                plan.run();
+            }
+        };
+        let mut trees = [build(false), build(true)];
+        run(&mut trees, &|_| {});
+        run(&mut trees, &|_| {});
+        let idle = |trees: &[Plan<TestConfig>; 2]| trees[1].get("A").unwrap().idle();
+        assert!(idle(&trees).is_some());
+        // changes between ticks wake the idle plan to pick them up on the next tick
+        run(&mut trees, &|plan| {
+            plan.get_mut("A").unwrap().enter_plan("x");
+        });
+        run(&mut trees, &|plan| {
+            plan.get_mut("A").unwrap().insert(new_plan("y", true));
+        });
+        run(&mut trees, &|_| {});
+        assert_eq!(runs(&trees[0], "x"), Some(3));
+        assert_eq!(runs(&trees[1], "x"), Some(3));
+        assert_eq!(runs(&trees[1], "y"), runs(&trees[0], "y"));
+        run(&mut trees, &|plan| {
+            let plan = plan.get_mut("A").unwrap();
+            plan.exit_plan("x");
+            plan.exit_plan("y");
+        });
+        run(&mut trees, &|_| {});
+        let since = idle(&trees).unwrap().since;
+        // as do data changes
+        run(&mut trees, &|plan| {
+            let plan = plan.get_mut("A").unwrap();
+            plan.set_data("speed", serde_value::Value::F64(2.));
+        });
+        assert!(idle(&trees).unwrap().since > since);
+
+        // while the tick itself leaves idle plans as they are
+        let since = idle(&trees).unwrap().since;
+        trees[1].run();
+        assert_eq!(idle(&trees).unwrap().since, since);
+    }
+
+    #[test]
+    fn scored_transitions() {
+        use serde_value::Value;
+        use tracing::field::{Field, Visit};
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+        struct ScoreVisitor<'a>(&'a mut Option<String>);
+        impl Visit for ScoreVisitor<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "score" {
+                    *self.0 = Some(format!("{:?}", value));
+                }
+            }
+        }
+        // capture the score of each logged transition
+        #[derive(Clone, Default)]
+        struct ScoreCapture(Arc<Mutex<Vec<String>>>);
+        impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for ScoreCapture {
+            fn on_event(&self, event: &tracing::Event, _: Context<S>) {
+                let mut score = None;
+                event.record(&mut ScoreVisitor(&mut score));
+                self.0.lock().unwrap().extend(score);
+            }
+        }
+        let capture = ScoreCapture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+
+        let mut root_plan = new_plan("root", true);
+        root_plan.insert(new_plan("A", true));
+        root_plan.insert(new_plan("B", false));
+        root_plan.transitions.push(Transition {
+            label: Some("scored".into()),
+            threshold: Some(0.5),
+            ..Transition::new(
+                vec!["A".into()],
+                vec!["B".into()],
+                predicate::DataValue {
+                    key: "pressure".into(),
+                }
+                .into(),
+            )
+        });
+        // boolean transitions are unaffected and not scored
+        root_plan.transitions.push(Transition::new(
+            vec!["B".into()],
+            vec!["A".into()],
+            predicate::True.into(),
+        ));
+        let score = |score, fired| TransitionScore {
+            plan: "root".to_string(),
+            label: Some("scored".into()),
+            src: vec!["A".to_string()],
+            score,
+            threshold: 0.5,
+            fired,
+        };
+        let active = |plan: &Plan<TestConfig>| plan.get("B").unwrap().active();
+
+        tracing::subscriber::with_default(subscriber, || {
+            // below threshold
+            root_plan.set_data("pressure", Value::F64(0.25));
+            let report = root_plan.run();
+            assert_eq!(report.transition_scores, vec![score(0.25, false)]);
+            assert!(!active(&root_plan));
+            // threshold crossed
+            root_plan.set_data("pressure", Value::F64(0.75));
+            let report = root_plan.run();
+            assert_eq!(report.transition_scores, vec![score(0.75, true)]);
+            assert!(active(&root_plan));
+            // boolean transition back, scored transition not evaluated while its source is inactive
+            let report = root_plan.run();
+            assert_eq!(report.transition_scores, vec![]);
+            assert!(!active(&root_plan));
+            // spans must close within the scope of the subscriber
+            drop(root_plan);
+        });
+        assert_eq!(*capture.0.lock().unwrap(), ["Some(0.75)", "None"]);
+    }
+
+    #[test]
+    fn warning_dedup() {
+        use tracing::field::{Field, Visit};
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+        struct SuppressedVisitor<'a>(&'a mut Option<u64>);
+        impl Visit for SuppressedVisitor<'_> {
+            fn record_u64(&mut self, field: &Field, value: u64) {
+                if field.name() == "suppressed" {
+                    *self.0 = Some(value);
+                }
+            }
+            fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+        }
+        // capture the suppressed count of each logged warning
+        #[derive(Clone, Default)]
+        struct WarnCapture(Arc<Mutex<Vec<Option<u64>>>>);
+        impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for WarnCapture {
+            fn on_event(&self, event: &tracing::Event, _: Context<S>) {
+                if *event.metadata().level() == tracing::Level::WARN {
+                    let mut suppressed = None;
+                    event.record(&mut SuppressedVisitor(&mut suppressed));
+                    self.0.lock().unwrap().push(suppressed);
+                }
+            }
+        }
+        let capture = WarnCapture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+
+        let mut root_plan = new_plan("root", true);
+        root_plan.set_warning_interval(30);
+        root_plan.transitions.push(Transition::new(
+            vec!["ghost".into()],
+            vec!["A".into()],
+            predicate::True.into(),
+        ));
+        tracing::subscriber::with_default(subscriber, || {
+            for _ in 0..100 {
+                root_plan.run();
+            }
+            // spans must close within the scope of the subscriber
+            root_plan.exit(false);
+        });
+        // logged on ticks 1, 31, 61, 91
+        let logged = capture.0.lock().unwrap().clone();
+        assert_eq!(logged, [Some(0), Some(29), Some(29), Some(29)]);
+        assert_eq!(
+            root_plan.warnings(),
+            [Warning {
+                code: "W201",
+                path: "root".into(),
+                count: 100,
+                suppressed: 9,
+                logged_tick: Some(91),
+            }]
+        );
+
+        // plans of the same name elsewhere in the tree are warned about separately
+        let mut root_plan = new_plan("root", true);
+        for parent in ["A", "B"] {
+            let mut x = new_plan("x", true);
+            x.transitions.push(Transition::new(
+                vec!["ghost".into()],
+                vec![],
+                predicate::True.into(),
+            ));
+            let mut parent = new_plan(parent, true);
+            parent.insert(x);
+            root_plan.insert(parent);
+        }
+        root_plan.run();
+        let warnings = root_plan.warnings();
+        let paths = warnings.iter().map(|x| x.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, ["root/A/x", "root/B/x"]);
+    }
+
+    #[test]
+    fn missing_transition_plans() {
+        let mut root_plan = new_plan("root", true);
+        root_plan.insert(new_plan("A", false));
+        root_plan.transitions.push(transition!("A" => "ghost"));
+        let count = |plan: &Plan<TestConfig>| plan.warnings().first().map(|x| x.count);
+        let check = |plan: &Plan<TestConfig>| {
+            let check = plan
+                .inner
+                .as_ref()
+                .unwrap()
+                .missing_plans
+                .as_deref()
+                .unwrap();
+            (check as *const MissingPlans<_>, check.missing.clone())
+        };
+
+        // missing destinations are warned about like sources
+        root_plan.run();
+        let first = check(&root_plan);
+        assert_eq!(first.1.as_deref(), Some("ghost"));
+        root_plan.run();
+        assert_eq!(count(&root_plan), Some(2));
+        // and only checked again once the subplans change
+        assert_eq!(check(&root_plan).0, first.0);
+        root_plan.insert(new_plan("ghost", false));
+        root_plan.run();
+        assert_eq!(check(&root_plan).1, None);
+        assert_eq!(count(&root_plan), Some(2));
+        // or the transitions do
+        root_plan.transitions.push(transition!("phantom" => "A"));
+        root_plan.run();
+        assert_eq!(check(&root_plan).1.as_deref(), Some("phantom"));
+        assert_eq!(count(&root_plan), Some(3));
+    }
+
+    #[test]
+    fn exit_order() {
+        static EXITS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct ExitRecorder;
+        impl<C: Config<Name = DefaultName>> Behaviour<C> for ExitRecorder {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                None
+            }
+            fn on_exit(&mut self, plan: &mut Plan<C>) {
+                EXITS.lock().unwrap().push(plan.name().clone());
+            }
+        }
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct ExitConfig;
+        impl Config for ExitConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = ExitRecorder;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        let mut root_plan = Plan::<ExitConfig>::new(ExitRecorder, "root", 1, true);
+        let mut a = Plan::new(ExitRecorder, "A", 1, true);
+        a.insert(Plan::new(ExitRecorder, "x", 1, true));
+        root_plan.insert(a);
+        let exits = |root_plan: &mut Plan<ExitConfig>| {
+            root_plan.run();
+            root_plan.exit(false);
+            std::mem::take(&mut *EXITS.lock().unwrap())
+        };
+        // default order is bottom up
+        assert_eq!(exits(&mut root_plan), ["x", "A", "root"]);
+        // top down when each parent exits first
+        root_plan.exit_order = ExitOrder::SelfFirst;
+        root_plan.get_mut("A").unwrap().exit_order = ExitOrder::SelfFirst;
+        assert_eq!(exits(&mut root_plan), ["root", "A", "x"]);
+        // order only affects the plan it is set on
+        root_plan.exit_order = ExitOrder::ChildrenFirst;
+        assert_eq!(exits(&mut root_plan), ["A", "x", "root"]);
+    }
+
+    #[test]
+    fn shared_view() {
+        let mut root_plan = abc_plan();
+        for name in ["A", "B", "C"] {
+            root_plan.get_mut(name).unwrap().insert(new_plan("x", true));
+        }
+        let view = root_plan.shared_view();
+        let initial = view.snapshot();
+        assert_eq!(initial.tick, 0);
+        assert_eq!(initial.plans.len(), 8);
+        assert!(initial.plans.iter().all(|state| !state.active));
+
+        let done = AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            let reader = scope.spawn(|| {
+                let mut observed = 0;
+                let mut last_tick = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let snapshot = view.snapshot();
+                    assert!(snapshot.tick >= last_tick);
+                    last_tick = snapshot.tick;
+                    // every active child has an active parent
+                    for state in &snapshot.plans {
+                        if let (true, Some(parent)) = (state.active, state.parent) {
+                            assert!(snapshot.plans[parent].active);
+                        }
+                    }
+                    // exactly one of the cycling plans is active after each tick
+                    let top = snapshot
+                        .plans
+                        .iter()
+                        .filter(|state| state.parent == Some(0));
+                    let active = top.filter(|state| state.active).count();
+                    assert_eq!(active, (snapshot.tick > 0) as usize);
+                    observed += 1;
+                }
+                observed
+            });
+            for _ in 0..1000 {
+                root_plan.run();
+            }
+            done.store(true, Ordering::Relaxed);
+            assert!(reader.join().unwrap() > 0);
+        });
+        assert_eq!(view.snapshot().tick, 1000);
+
+        // held snapshots are unaffected by later ticks
+        assert_eq!(initial.tick, 0);
+        assert!(initial.plans.iter().all(|state| !state.active));
+
+        // buffers alternate once no longer held
+        drop(initial);
+        let ptr = |view: &PlanView| Arc::as_ptr(&view.snapshot());
+        let first = ptr(&view);
+        root_plan.run();
+        assert_ne!(ptr(&view), first);
+        root_plan.run();
+        assert_eq!(ptr(&view), first);
+    }
+
+    #[test]
+    fn active_delta() {
+        let mut root_plan = abc_plan();
+        for name in ["A", "B", "C"] {
+            root_plan.get_mut(name).unwrap().insert(new_plan("x", true));
+        }
+        let path = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        let paths = |x: &[&[&str]]| x.iter().map(|x| path(x)).collect::<Vec<_>>();
+
+        let initial = root_plan.active_state();
+        assert!(initial.0.is_empty());
+        root_plan.run();
+        let delta = root_plan.active_delta(&initial);
+        assert_eq!(delta.entered, paths(&[&[], &["B"], &["B", "x"]]));
+        assert!(delta.exited.is_empty());
+
+        // transition from B to C
+        let previous = root_plan.active_state();
+        root_plan.run();
+        let delta = root_plan.active_delta(&previous);
+        assert_eq!(delta.entered, paths(&[&["C"], &["C", "x"]]));
+        assert_eq!(delta.exited, paths(&[&["B"], &["B", "x"]]));
+
+        // no change
+        let delta = root_plan.active_delta(&root_plan.active_state());
+        assert!(delta.entered.is_empty() && delta.exited.is_empty());
+
+        root_plan.exit(false);
+        let delta = root_plan.active_delta(&previous);
+        assert!(delta.entered.is_empty());
+        assert_eq!(delta.exited, paths(&[&[], &["B"], &["B", "x"]]));
+    }
+
+    #[test]
+    fn exec_modes() {
+        let counts = |plan: &Plan<TestConfig>, name: &str| {
+            let plan = match name {
+                "root" => plan,
+                _ => plan.get(name).unwrap(),
+            };
+            let b = plan.cast::<RunCountBehaviour>().unwrap();
+            (b.entry_count, b.run_count, plan.active())
+        };
+        let run = |execution| {
+            let mut root_plan = abc_plan();
+            root_plan.execution = execution;
+            for _ in 0..3 {
+                root_plan.run();
+            }
+            let names = ["root", "A", "B", "C"];
+            names.map(|name| counts(&root_plan, name))
+        };
+        // transitions cycle A -> B -> C -> A before subplans run on each tick
+        assert_eq!(
+            run(ExecMode::Full),
+            [(1, 3, true), (2, 1, true), (1, 1, false), (1, 1, false)]
+        );
+        assert_eq!(
+            run(ExecMode::StructureOnly),
+            [(1, 0, true), (2, 1, true), (1, 1, false), (1, 1, false)]
+        );
+        // subplans are still entered, but neither run nor transitioned
+        assert_eq!(
+            run(ExecMode::BehaviourOnly),
+            [(1, 3, true), (1, 0, true), (0, 0, false), (0, 0, false)]
+        );
+        assert_eq!(
+            run(ExecMode::Disabled),
+            [(1, 0, true), (1, 0, true), (0, 0, false), (0, 0, false)]
+        );
+
+        // zero interval runs every tick
+        let mut root_plan = abc_plan();
+        root_plan.run_interval = 0;
+        root_plan.run();
+        root_plan.run();
+        assert_eq!(counts(&root_plan, "root"), (1, 2, true));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn exec_mode_serde() {
+        let roundtrip = |plan: &Plan<TestConfig>| {
+            let json = serde_json::to_string(plan).unwrap();
+            serde_json::from_str::<Plan<TestConfig>>(&json).unwrap()
+        };
+        let mut plan = new_plan("root", false);
+        plan.run_interval = 0;
+        assert_eq!(roundtrip(&plan).execution, ExecMode::Full);
+        plan.execution = ExecMode::BehaviourOnly;
+        assert_eq!(roundtrip(&plan).execution, ExecMode::BehaviourOnly);
+
+        // plans predating the field meant structure only execution by a zero interval
+        let mut json = serde_json::to_value(&plan).unwrap();
+        json.as_object_mut().unwrap().remove("execution");
+        let legacy = |json| serde_json::from_value::<Plan<TestConfig>>(json).unwrap();
+        assert_eq!(legacy(json.clone()).execution, ExecMode::StructureOnly);
+        json["run_interval"] = 2.into();
+        assert_eq!(legacy(json).execution, ExecMode::Full);
+    }
+
+    #[test]
+    fn dead_behaviours() {
+        let mut root_plan = Plan::<TestConfig>::new_stub("root", true);
+        root_plan.insert(new_plan("A", true));
+        root_plan.insert(new_plan("B", false));
+        root_plan.insert(new_plan("C", false));
+        root_plan.insert(new_plan("D", false));
+        // only reachable through a transition from unreachable plan
+        root_plan.insert(new_plan("E", false));
+        root_plan.get_mut("A").unwrap().execution = ExecMode::StructureOnly;
+        root_plan.get_mut("D").unwrap().insert(new_plan("x", true));
+        root_plan.get_mut("B").unwrap().insert(new_plan("y", false));
+        let transition = |src: &str, dst: &str| {
+            Transition::new(vec![src.into()], vec![dst.into()], predicate::True.into())
+        };
+        root_plan.transitions = vec![transition("A", "B"), transition("D", "E")];
+        let path = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            root_plan.dead_behaviours(),
+            vec![
+                path(&["A"]),
+                path(&["B", "y"]),
+                path(&["C"]),
+                path(&["D"]),
+                path(&["D", "x"]),
+                path(&["E"])
+            ]
+        );
+    }
+
+    #[test]
+    fn selected_subplans_reachable() {
+        use behaviour::{MaxUtilBehaviour, SequenceBehaviour};
+        use config::fixtures::leaf;
+        type DefaultConfig = config::Default;
+        let mut root_plan = Plan::<DefaultConfig>::new_stub("root", true);
+        // selectors enter subplans directly, without autostart or transitions
+        let mut max_util = Plan::new(MaxUtilBehaviour::default().into(), "max_util", 1, true);
+        max_util.insert(leaf("a", false, None));
+        max_util.insert(leaf("b", false, None));
+        root_plan.insert(max_util);
+        let mut sequence = Plan::new(SequenceBehaviour::new().into(), "sequence", 1, true);
+        sequence.insert(leaf("a", false, None));
+        root_plan.insert(sequence);
+        // while other behaviours leave reachability to the structure
+        let mut status = leaf("status", true, None);
+        status.insert(leaf("x", false, None));
+        root_plan.insert(status);
+
+        let path = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            root_plan.validate(),
+            [ValidationIssue::UnreachablePlan {
+                path: path(&["status", "x"])
+            }]
+        );
+        assert_eq!(root_plan.dead_behaviours(), [path(&["status", "x"])]);
+        // nor do selectors reach subplans their execution does not run
+        root_plan.get_mut("max_util").unwrap().execution = ExecMode::BehaviourOnly;
+        assert_eq!(root_plan.validate().len(), 3);
+        assert_eq!(root_plan.dead_behaviours().len(), 3);
+    }
+
+    #[test]
+    fn dead_transitions() {
+        use predicate::*;
+        let mut root_plan = abc_plan();
+        root_plan.get_mut("A").unwrap().insert(new_plan("x", true));
+        root_plan.get_mut("A").unwrap().insert(new_plan("y", false));
+        let transition = |predicate: Predicates| transition!("x" => "y", predicate);
+        root_plan.get_mut("A").unwrap().transitions = vec![
+            transition(And(vec![True.into(), False.into()]).into()),
+            transition(And(vec![AllSuccess.into(), False.into()]).into()),
+            transition(Or(vec![AllSuccess.into(), False.into()]).into()),
+            transition(Not(Box::new(Or(vec![True.into(), AllSuccess.into()]).into())).into()),
+            transition(Xor(vec![True.into(), True.into()]).into()),
+            transition(Xor(vec![True.into(), AllSuccess.into()]).into()),
+        ];
+        // a constant score below the threshold never fires
+        let mut scored = transition(True.into());
+        scored.threshold = Some(1.5);
+        root_plan.transitions.push(scored);
+        let path = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            root_plan.dead_transitions(),
+            vec![
+                (path(&[]), 3),
+                (path(&["A"]), 0),
+                (path(&["A"]), 1),
+                (path(&["A"]), 3),
+                (path(&["A"]), 4),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "timing")]
+    fn timings() {
+        use std::time::Duration;
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct SleepBehaviour(u64);
+        impl<C: Config> Behaviour<C> for SleepBehaviour {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                None
+            }
+            fn on_run(&mut self, _plan: &mut Plan<C>) {
+                std::thread::sleep(Duration::from_millis(self.0));
+            }
+        }
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct TimingConfig;
+        impl Config for TimingConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = SleepBehaviour;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        let mut root_plan = Plan::<TimingConfig>::new(SleepBehaviour(0), "root", 1, true);
+        root_plan.insert(Plan::new(SleepBehaviour(5), "slow", 1, true));
+        root_plan.insert(Plan::new(SleepBehaviour(0), "fast", 1, true));
+        for _ in 0..3 {
+            root_plan.run();
+        }
+        let slow = root_plan.get("slow").unwrap().timings();
+        let fast = root_plan.get("fast").unwrap().timings();
+        let run = &slow.0["run"];
+        assert_eq!(run.calls, 3);
+        assert_eq!(slow.0["entry"].calls, 1);
+        assert!(run.total >= Duration::from_millis(15));
+        assert!(run.max >= Duration::from_millis(5));
+        assert!(slow.total() > fast.total() * 10);
+        assert!(slow.total() > root_plan.timings().total() * 10);
+    }
+
+    type DefaultConfig = config::Default;
+
+    #[test]
+    fn max_firings() {
+        let mut root_plan = Plan::<TestConfig>::new_stub("root", true);
+        root_plan.insert(new_plan("A", true));
+        root_plan.insert(new_plan("B", false));
+        let transition = |src: &str, dst: &str, max_firings| Transition {
+            max_firings,
+            ..Transition::new(vec![src.into()], vec![dst.into()], predicate::True.into())
+        };
+        root_plan.transitions = vec![transition("A", "B", Some(1)), transition("B", "A", None)];
+        let active = |plan: &Plan<TestConfig>| {
+            let active = plan.plans.iter().filter(|x| x.active());
+            active.map(|x| x.name().clone()).collect::<Vec<_>>()
+        };
+        for _ in 0..2 {
+            // escalation fires once then stays disabled for the rest of the activation
+            root_plan.run();
+            assert_eq!(active(&root_plan), ["B"]);
+            for _ in 0..5 {
+                root_plan.run();
+                assert_eq!(active(&root_plan), ["A"]);
+            }
+            // re-entering the parent resets the limit
+            root_plan.exit(false);
+        }
+    }
+
+    #[derive(EnumCast)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct PanicBehaviour(Option<String>);
+    impl<C: Config> Behaviour<C> for PanicBehaviour {
+        fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+            None
+        }
+        fn on_exit(&mut self, _plan: &mut Plan<C>) {
+            if self.0.as_deref() == Some("exit") {
+                panic!("boom");
+            }
+        }
+        fn on_run(&mut self, _plan: &mut Plan<C>) {
+            if self.0.as_deref() == Some("run") {
+                panic!("boom");
+            }
+        }
+    }
+
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    struct PanicConfig;
+    impl Config for PanicConfig {
+        type Predicate = predicate::Predicates;
+        type Behaviour = PanicBehaviour;
+        type Event = ();
+        type Name = DefaultName;
+    }
+
+    fn panic_plan(hook: &str) -> Plan<PanicConfig> {
+        let mut root_plan = Plan::new(PanicBehaviour(None), "root", 1, true);
+        let mut a = Plan::new(PanicBehaviour(None), "A", 1, true);
+        a.insert(Plan::new(PanicBehaviour(Some(hook.into())), "x", 1, true));
+        a.insert(Plan::new(PanicBehaviour(None), "y", 1, true));
+        root_plan.insert(a);
+        root_plan
+    }
+
+    #[test]
+    #[should_panic(expected = r#"plan "root"/"A"/"x" panicked in hook run: boom"#)]
+    fn panic_context() {
+        panic_plan("run").run();
+    }
+
+    #[test]
+    fn panic_context_payload() {
+        let mut root_plan = panic_plan("exit");
+        root_plan.run();
+        let payload = catch_unwind(AssertUnwindSafe(|| root_plan.exit(false))).unwrap_err();
+        assert_eq!(
+            payload.downcast_ref::<PlanPanic>(),
+            Some(&PlanPanic {
+                path: vec![r#""root""#.into(), r#""A""#.into(), r#""x""#.into()],
+                hook: "exit",
+                message: "boom".into(),
+            })
+        );
+        // dropping would exit the panicking plan again
+        std::mem::forget(root_plan);
+    }
+
+    #[test]
+    fn abandon_releases_slots() {
+        let mut root_plan = panic_plan("run");
+        root_plan.get_mut("A").unwrap().isolate = true;
+        root_plan.enter(None);
+        assert!(root_plan.acquire_slot("n", 3));
+        assert!(root_plan.get_mut("A").unwrap().acquire_slot("n", 3));
+        assert!(root_plan.get_path_mut("A/x").unwrap().acquire_slot("n", 3));
+        assert!(!root_plan.acquire_slot("n", 3));
+
+        // the panic abandons the isolated subtree along with its slots
+        let report = root_plan.run();
+        assert_eq!(report.isolated_panics.len(), 1);
+        assert!(!root_plan.get("A").unwrap().holds_slot("n"));
+        assert!(!root_plan.get_path("A/x").unwrap().holds_slot("n"));
+        assert!(root_plan.holds_slot("n"));
+        assert_eq!(root_plan.slots_taken("n"), 1);
+
+        // released slots are given back once only
+        root_plan.get_mut("A").unwrap().release_slot("n");
+        assert_eq!(root_plan.slots_taken("n"), 1);
+        root_plan.release_slot("n");
+        assert_eq!(root_plan.slots_taken("n"), 0);
+    }
+
+    #[test]
+    fn isolate() {
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct EveryThird {
+            runs: u32,
+            panics: bool,
+        }
+        impl<C: Config> Behaviour<C> for EveryThird {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                None
             }
+            fn on_run(&mut self, _plan: &mut Plan<C>) {
+                self.runs += 1;
+                if self.panics && self.runs.is_multiple_of(3) {
+                    panic!("flaky");
+                }
+            }
+        }
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct IsolateConfig;
+        impl Config for IsolateConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = EveryThird;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        let flaky = |panics| EveryThird { runs: 0, panics };
+        let mut root_plan = Plan::<IsolateConfig>::new(flaky(false), "root", 1, true);
+        let mut x = Plan::new(flaky(false), "x", 1, true);
+        x.isolate = true;
+        x.poison_after = 3;
+        x.insert(Plan::new(flaky(true), "inner", 1, true));
+        root_plan.insert(x);
+        root_plan.insert(Plan::new(flaky(false), "y", 1, true));
+        let runs =
+            |plan: &Plan<IsolateConfig>, name| plan.get_cast::<EveryThird, _>(name).unwrap().runs;
+
+        for count in 1..=3 {
+            assert!(root_plan.run().isolated_panics.is_empty());
+            assert!(root_plan.run().isolated_panics.is_empty());
+            // the panic fails and exits the isolated subtree only
+            let report = root_plan.run();
+            let panic = PlanPanic {
+                path: vec![r#""x""#.into(), r#""inner""#.into()],
+                hook: "run",
+                message: "flaky".into(),
+            };
+            let poisoned = count == 3;
+            let expected = IsolatedPanic {
+                path: "root/x".to_string(),
+                panic,
+                count,
+                poisoned,
+            };
+            assert_eq!(report.isolated_panics, [expected]);
+            let x = root_plan.get("x").unwrap();
+            assert!(!x.active() && !x.get("inner").unwrap().active());
+            assert_eq!(x.status(), Some(false));
+            assert_eq!((x.panic_count(), x.poisoned()), (count, poisoned));
+            // while the rest of the tree carries on
+            assert!(root_plan.active() && root_plan.get("y").unwrap().active());
+            assert_eq!(runs(&root_plan, "y"), 3 * count);
+            // the isolated plan runs after its subplans, so not on the tick of the panic
+            assert_eq!(runs(&root_plan, "x"), 2 * count);
+
+            // re-entry resets the failure until poisoned
+            let entered = root_plan.enter_plan("x").unwrap().active();
+            assert_eq!(entered, !poisoned);
+            let status = root_plan.get("x").unwrap().status();
+            assert_eq!(status, if poisoned { Some(false) } else { None });
+        }
+        // contained panics are warned about at most once per warning interval
+        let warning = Warning {
+            code: "W203",
+            path: "root/x".into(),
+            count: 3,
+            suppressed: 2,
+            logged_tick: Some(3),
         };
-        let plan = &mut self.plans[pos];
-        plan.enter(Some(&self.span));
-        Some(plan)
+        assert_eq!(root_plan.warnings(), [warning]);
+        root_plan.run();
+        assert!(!root_plan.get("x").unwrap().active());
+        root_plan.get_mut("x").unwrap().clear_poison();
+        assert!(root_plan.enter_plan("x").unwrap().active());
+        assert_eq!(root_plan.get("x").unwrap().panic_count(), 0);
+
+        // not contained without isolation
+        root_plan.get_mut("x").unwrap().isolate = false;
+        root_plan.run();
+        root_plan.run();
+        let result = catch_unwind(AssertUnwindSafe(|| root_plan.run()));
+        assert!(result.is_err());
+        std::mem::forget(root_plan);
     }
 
-    ///  Exits the specified subplan if currently active and return its reference.
-    ///  See [Plan::exit].
-    pub fn exit_plan(&mut self, name: &str) -> Option<&mut Self> {
-        // ignore if plan is not found
-        let pos = self.priority(name).ok()?;
-        let plan = &mut self.plans[pos];
-        plan.exit(false);
-        Some(plan)
+    #[test]
+    #[cfg(feature = "serde")]
+    fn run_transactional() {
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct FlakyBehaviour {
+            runs: u32,
+            panic_at: u32,
+        }
+        impl<C: Config> Behaviour<C> for FlakyBehaviour {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                None
+            }
+            fn on_run(&mut self, plan: &mut Plan<C>) {
+                self.runs += 1;
+                plan.set_data("runs", serde_value::Value::U32(self.runs));
+                if self.runs == self.panic_at {
+                    panic!("flaky");
+                }
+            }
+        }
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct FlakyConfig;
+        impl Config for FlakyConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = FlakyBehaviour;
+            type Event = ();
+            type Name = DefaultName;
+        }
+
+        let flaky = |panic_at| FlakyBehaviour { runs: 0, panic_at };
+        let mut root_plan = Plan::<FlakyConfig>::new(flaky(0), "root", 1, true);
+        let a = root_plan.insert(Plan::new(flaky(0), "A", 1, true));
+        a.insert(Plan::new(flaky(5), "x", 1, true));
+        a.insert(Plan::new(flaky(0), "y", 1, true));
+        for _ in 0..4 {
+            assert!(root_plan.run_transactional().is_ok());
+        }
+        let before = serde_json::to_value(&root_plan).unwrap();
+        let err = root_plan.run_transactional().unwrap_err();
+        assert_eq!(
+            err,
+            TickError::Panic(PlanPanic {
+                path: vec![r#""root""#.into(), r#""A""#.into(), r#""x""#.into()],
+                hook: "run",
+                message: "flaky".into(),
+            })
+        );
+
+        // rolled back to the state after tick 4, still active and sharing the tree
+        assert_eq!(serde_json::to_value(&root_plan).unwrap(), before);
+        let a = root_plan.get("A").unwrap();
+        let x = a.get("x").unwrap();
+        assert!(x.active() && Arc::ptr_eq(&x.tree, &root_plan.tree));
+        assert_eq!(x.cast::<FlakyBehaviour>().unwrap().runs, 4);
+        assert_eq!(root_plan.tree.tick.load(Ordering::Relaxed), 4);
+
+        // the same tick fails again until the behaviour recovers
+        assert!(root_plan.run_transactional().is_err());
+        let x = root_plan.get_mut("A").unwrap().get_mut("x").unwrap();
+        x.cast_mut::<FlakyBehaviour>().unwrap().panic_at = 0;
+        assert!(root_plan.run_transactional().is_ok());
+        let a = root_plan.get("A").unwrap();
+        assert_eq!(
+            a.get("y").unwrap().cast::<FlakyBehaviour>().unwrap().runs,
+            5
+        );
+        assert_eq!(root_plan.tree.tick.load(Ordering::Relaxed), 5);
     }
 
-    /// Enter this plan if not already active.
-    ///
-    /// Also recursively enters all subplans with autostart enabled.
-    pub fn enter(&mut self, parent_span: Option<&Span>) -> bool {
-        // only enter if plan is inactive
-        if self.active() {
-            return false;
+    #[test]
+    #[cfg(feature = "journal")]
+    fn data_journal() {
+        use serde_value::Value;
+
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct WriterBehaviour(u64);
+        impl<C: Config> Behaviour<C> for WriterBehaviour {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                None
+            }
+            fn on_run(&mut self, plan: &mut Plan<C>) {
+                self.0 += 1;
+                plan.set_data("target", Value::U64(self.0));
+            }
         }
-        // create new span
-        match parent_span {
-            Some(x) => self.span = debug_span!(parent: x, "plan", name=%self.name),
-            None => self.span = debug_span!("plan", name=%self.name),
+
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct WriterConfig;
+        impl Config for WriterConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = WriterBehaviour;
+            type Event = ();
+            type Name = DefaultName;
         }
-        // trigger on_entry() for self
-        self.run_countdown = 0;
-        self.call(|behaviour, plan| behaviour.on_entry(plan), "entry");
-        // recursively enter all autostart child plans
-        let i = self
-            .plans
-            .iter_mut()
-            .filter(|plan| plan.autostart && !plan.active());
-        #[cfg(feature = "rayon")]
-        i.par_bridge().for_each(|plan| {
-            plan.enter(Some(&self.span));
-        });
-        #[cfg(not(feature = "rayon"))]
-        i.for_each(|plan| {
-            plan.enter(Some(&self.span));
+
+        // the child runs before its parent within each tick
+        let mut root_plan = Plan::<WriterConfig>::new(WriterBehaviour(0), "root", 1, true);
+        root_plan.insert(Plan::new(WriterBehaviour(10), "A", 1, true));
+        root_plan.set_data("target", Value::U64(0));
+        for _ in 0..3 {
+            root_plan.run();
+        }
+        root_plan.get_mut("A").unwrap().remove_data("target");
+
+        let entry = |tick, path: &str, old: Option<u64>, new: Option<u64>, hook| DataChange {
+            tick,
+            path: path.to_string(),
+            key: "target".into(),
+            old_hash: old.map(|x| DataChange::hash_value(&Value::U64(x))),
+            new_value: new.map(|x| format!("{:?}", Value::U64(x))),
+            hook,
+        };
+        let journal = root_plan.data_journal();
+        assert_eq!(
+            journal,
+            vec![
+                entry(0, "root", None, Some(0), None),
+                entry(1, "root/A", None, Some(11), Some("run")),
+                entry(1, "root", Some(0), Some(1), Some("run")),
+                entry(2, "root/A", Some(11), Some(12), Some("run")),
+                entry(2, "root", Some(1), Some(2), Some("run")),
+                entry(3, "root/A", Some(12), Some(13), Some("run")),
+                entry(3, "root", Some(2), Some(3), Some("run")),
+                entry(3, "root/A", Some(13), None, None),
+            ]
+        );
+
+        // the oldest entries are evicted beyond capacity
+        root_plan.set_journal_capacity(3);
+        root_plan.run();
+        root_plan.run();
+        assert_eq!(
+            root_plan.data_journal(),
+            vec![
+                entry(4, "root", Some(3), Some(4), Some("run")),
+                entry(5, "root/A", Some(14), Some(15), Some("run")),
+                entry(5, "root", Some(4), Some(5), Some("run")),
+            ]
+        );
+    }
+
+    #[test]
+    fn uninterruptible_transition() {
+        use behaviour::*;
+        let mut root_plan = Plan::<DefaultConfig>::new_stub("root", true);
+        // plan A is in progress while its child is
+        let a = root_plan.insert(Plan::new(AllSuccessStatus.into(), "A", 1, true));
+        a.insert(Plan::new_stub("x", true));
+        root_plan.insert(Plan::new_stub("B", false));
+        root_plan.transitions.push(Transition {
+            interruptible: false,
+            ..Transition::new(vec!["A".into()], vec!["B".into()], predicate::True.into())
         });
-        true
+        for _ in 0..3 {
+            root_plan.run();
+            assert_eq!(root_plan.get("A").unwrap().status(), None);
+            assert!(root_plan.get("A").unwrap().active());
+            assert!(!root_plan.get("B").unwrap().active());
+        }
+        // transition fires once the status of A settles
+        root_plan.get_mut("A").unwrap().remove("x");
+        assert_eq!(root_plan.get("A").unwrap().status(), Some(true));
+        root_plan.run();
+        assert!(!root_plan.get("A").unwrap().active());
+        assert!(root_plan.get("B").unwrap().active());
     }
 
-    /// Exit this plan and all subplans recursively if currently active.
-    pub fn exit(&mut self, exclude_self: bool) -> bool {
-        // only exit if plan is active
-        if !self.active() {
-            return false;
+    #[test]
+    #[cfg(feature = "serde")]
+    fn behaviour_tags() {
+        use behaviour::{EvaluateStatus, ModifyStatus, MultiBehaviour};
+        // the same behaviour before and after renaming its variant
+        #[enum_dispatch(Behaviour<C>)]
+        #[derive(EnumCast, Serialize, Deserialize)]
+        pub enum LegacyBehaviours<C: Config> {
+            Counter(RunCountBehaviour),
+            EvaluateStatus(EvaluateStatus<C>),
+            ModifyStatus(ModifyStatus<C>),
+            MultiBehaviour(MultiBehaviour<C>),
         }
-        // recursively exit all active child plans
-        let i = self.plans.iter_mut().filter(|plan| plan.active());
-        #[cfg(feature = "rayon")]
-        i.par_bridge().for_each(|plan| {
-            plan.exit(false);
+        #[enum_dispatch(Behaviour<C>)]
+        #[derive(EnumCast, Serialize, Deserialize)]
+        pub enum RenamedBehaviours<C: Config> {
+            RunCounter(RunCountBehaviour),
+            EvaluateStatus(EvaluateStatus<C>),
+            ModifyStatus(ModifyStatus<C>),
+            MultiBehaviour(MultiBehaviour<C>),
+        }
+
+        #[derive(Serialize, Deserialize)]
+        struct LegacyConfig;
+        impl Config for LegacyConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = LegacyBehaviours<Self>;
+            type Event = ();
+            type Name = DefaultName;
+            const BEHAVIOUR_TAGS: &'static [(&'static str, &'static str)] = &[("Counter", "count")];
+        }
+        #[derive(Serialize, Deserialize)]
+        struct RenamedConfig;
+        impl Config for RenamedConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = RenamedBehaviours<Self>;
+            type Event = ();
+            type Name = DefaultName;
+            const BEHAVIOUR_TAGS: &'static [(&'static str, &'static str)] =
+                &[("RunCounter", "count")];
+        }
+
+        let mut root_plan = Plan::<LegacyConfig>::new(
+            EvaluateStatus(predicate::True.into(), predicate::False.into()).into(),
+            "root",
+            1,
+            true,
+        );
+        root_plan.insert(Plan::new(RunCountBehaviour::default().into(), "A", 1, true));
+        // behaviours nested within others are tagged alike
+        let counter = || Box::new(RunCountBehaviour::default().into());
+        let invert = ModifyStatus(counter(), None);
+        root_plan.insert(Plan::new(invert.into(), "B", 1, true));
+        let multi = MultiBehaviour(vec![*counter(), *counter()]);
+        root_plan.insert(Plan::new(multi.into(), "C", 1, true));
+        root_plan.run();
+        let json = serde_json::to_value(&root_plan).unwrap();
+        assert!(json["plans"][0]["behaviour"].get("count").is_some());
+        assert!(json["plans"][1]["behaviour"]["ModifyStatus"][0]
+            .get("count")
+            .is_some());
+        assert!(json["plans"][2]["behaviour"]["MultiBehaviour"][1]
+            .get("count")
+            .is_some());
+        assert!(json["behaviour"].get("EvaluateStatus").is_some());
+
+        let mut loaded = serde_json::from_value::<Plan<RenamedConfig>>(json).unwrap();
+        let counter = loaded.get_cast::<RunCountBehaviour, _>("A").unwrap();
+        assert_eq!((counter.entry_count, counter.run_count), (1, 1));
+        let invert = loaded.get_cast::<ModifyStatus<_>, _>("B").unwrap();
+        let counter = invert.0.cast::<RunCountBehaviour>().unwrap();
+        assert_eq!((counter.entry_count, counter.run_count), (1, 1));
+        let multi = loaded.get_cast::<MultiBehaviour<_>, _>("C").unwrap();
+        let counter = multi.0[1].cast::<RunCountBehaviour>().unwrap();
+        assert_eq!((counter.entry_count, counter.run_count), (1, 1));
+        assert_eq!(loaded.status(), Some(true));
+        loaded.run();
+        let counter = loaded.get_cast::<RunCountBehaviour, _>("A").unwrap();
+        assert_eq!(counter.run_count, 2);
+        // plain variant identifiers still load, but the renamed one is gone
+        let mut json = serde_json::to_value(&loaded).unwrap();
+        let counter = json["plans"][0]["behaviour"]["count"].take();
+        json["plans"][0]["behaviour"] = serde_json::json!({ "RunCounter": counter });
+        assert!(serde_json::from_value::<Plan<RenamedConfig>>(json.clone()).is_ok());
+        json["plans"][0]["behaviour"] = serde_json::json!({ "Counter": counter });
+        assert!(serde_json::from_value::<Plan<RenamedConfig>>(json).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn generate_schema() {
+        tracing_init();
+        // generate and print plan schema
+        use serde_reflection::{Tracer, TracerConfig};
+        let mut tracer = Tracer::new(TracerConfig::default());
+        tracer.trace_simple_type::<behaviour::SelectMode>().unwrap();
+        tracer
+            .trace_simple_type::<behaviour::Normalization>()
+            .unwrap();
+        tracer
+            .trace_simple_type::<behaviour::Behaviours<DefaultConfig>>()
+            .unwrap();
+        tracer.trace_simple_type::<predicate::Predicates>().unwrap();
+        let registry = tracer.registry().unwrap();
+        debug!("{}", serde_json::to_string_pretty(&registry).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn transitions_json() {
+        use serde_json::{json, Value};
+        let mut root_plan = Plan::<DefaultConfig>::new_stub("root", true);
+        let mut a = Plan::new_stub("A", true);
+        a.insert(Plan::new_stub("x", true));
+        a.insert(Plan::new_stub("y", false));
+        a.transitions.push(Transition {
+            interruptible: false,
+            label: Some("done".into()),
+            ..Transition::new(
+                vec!["x".into()],
+                vec!["y".into()],
+                predicate::AllSuccess.into(),
+            )
         });
-        #[cfg(not(feature = "rayon"))]
-        i.for_each(|plan| {
-            plan.exit(false);
+        root_plan.insert(a);
+        root_plan.insert(Plan::new_stub("B", false));
+        root_plan.transitions.push(Transition::new(
+            vec!["A".into()],
+            vec!["B".into()],
+            predicate::True.into(),
+        ));
+        let export = |plan: &Plan<DefaultConfig>| {
+            serde_json::from_str::<Value>(&plan.transitions_to_json().unwrap()).unwrap()
+        };
+        let mut records = export(&root_plan);
+        assert_eq!(records.as_array().unwrap().len(), 2);
+        assert_eq!(records[0]["parent_path"], json!([]));
+        assert_eq!(records[1]["parent_path"], json!(["A"]));
+        assert_eq!(records[1]["src"], json!(["x"]));
+        assert_eq!(records[1]["label"], json!("done"));
+        // redirect the nested transition back to the start
+        records[1]["dst"] = json!(["x"]);
+        records[1]["src"] = json!(["y"]);
+        root_plan
+            .apply_transitions_json(&records.to_string())
+            .unwrap();
+        assert_eq!(export(&root_plan), records);
+        let a = root_plan.get("A").unwrap();
+        assert_eq!(a.transitions[0].src, ["y"]);
+        assert_eq!(a.transitions[0].dst, ["x"]);
+        assert!(!a.transitions[0].interruptible);
+        assert!(a.transitions[0]
+            .predicate
+            .cast::<predicate::AllSuccess>()
+            .is_some());
+        assert_eq!(root_plan.transitions[0].dst, ["B"]);
+        // referring to missing plans leaves transitions unchanged
+        let mut missing = records.clone();
+        missing[0]["dst"] = json!(["C"]);
+        assert_eq!(
+            root_plan.apply_transitions_json(&missing.to_string()),
+            Err(Error::InvalidTransitions(vec![
+                ValidationIssue::MissingPlan {
+                    path: vec![],
+                    transition: 0,
+                    plan: "C".into()
+                }
+            ]))
+        );
+        missing[0]["parent_path"] = json!(["A", "z"]);
+        assert_eq!(
+            root_plan.apply_transitions_json(&missing.to_string()),
+            Err(Error::PlanNotFound(vec!["A".into(), "z".into()]))
+        );
+        assert!(matches!(
+            root_plan.apply_transitions_json("{"),
+            Err(Error::Json(_))
+        ));
+        assert_eq!(export(&root_plan), records);
+        // omitted plans lose their transitions
+        root_plan.apply_transitions_json("[]").unwrap();
+        assert!(root_plan.transitions.is_empty());
+        assert!(root_plan.get("A").unwrap().transitions.is_empty());
+    }
+
+    #[test]
+    fn exit_reuses_recorded_status() {
+        use std::sync::atomic::AtomicUsize;
+        /// Succeeds right away, counting how often its status is evaluated.
+        #[derive(EnumCast, Default)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct StatusCounter(AtomicUsize);
+        impl<C: Config> Behaviour<C> for StatusCounter {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                Some(true)
+            }
+        }
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct CounterConfig;
+        impl Config for CounterConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = StatusCounter;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        let calls = |plan: &Plan<CounterConfig>| {
+            let counter = plan.get("a").unwrap().behaviour.as_ref().unwrap();
+            counter.0.load(Ordering::Relaxed)
+        };
+        let mut root_plan = Plan::<CounterConfig>::new_stub("root", true);
+        root_plan.insert(Plan::new(StatusCounter::default(), "a", 1, true));
+        root_plan.run();
+        assert_eq!(calls(&root_plan), 1);
+
+        // exit within the tick the plan ran reuses the status recorded by the run
+        root_plan.get_mut("a").unwrap().exit(false);
+        assert_eq!(calls(&root_plan), 1);
+        assert_eq!(root_plan.get("a").unwrap().outcome_counts().successes, 1);
+
+        // exit before running evaluates the status
+        root_plan.enter_plan("a");
+        root_plan.get_mut("a").unwrap().exit(false);
+        assert_eq!(calls(&root_plan), 2);
+        assert_eq!(root_plan.get("a").unwrap().outcome_counts().successes, 2);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn generate_plan() {
+        tracing_init();
+        let root_plan = Plan::<DefaultConfig>::new_stub("root", true);
+        // serialize and print root plan
+        debug!("{}", serde_json::to_string_pretty(&root_plan).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn thread_pool() {
+        use std::sync::atomic::AtomicUsize;
+        static SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+        /// Records the order, pool size, and thread of each run.
+        #[derive(EnumCast, Default)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct ThreadRecorder {
+            runs: Vec<(usize, usize, Option<usize>)>,
+            exit_pool: Option<usize>,
+        }
+        impl<C: Config> Behaviour<C> for ThreadRecorder {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                None
+            }
+            fn on_exit(&mut self, _plan: &mut Plan<C>) {
+                self.exit_pool = Some(rayon::current_num_threads());
+            }
+            fn on_run(&mut self, _plan: &mut Plan<C>) {
+                std::thread::sleep(std::time::Duration::from_millis(5));
+                let sequence = SEQUENCE.fetch_add(1, Ordering::Relaxed);
+                let thread = rayon::current_thread_index();
+                self.runs
+                    .push((sequence, rayon::current_num_threads(), thread));
+            }
+        }
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct ThreadConfig;
+        impl Config for ThreadConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = ThreadRecorder;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        let pool = |threads| {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads);
+            Some(Arc::new(pool.build().unwrap()))
+        };
+        let run = |threads| {
+            let mut root_plan = Plan::<ThreadConfig>::new_stub("root", true);
+            for i in 0..8 {
+                root_plan.insert(Plan::new(ThreadRecorder::default(), i.to_string(), 1, true));
+            }
+            root_plan.set_thread_pool(pool(threads));
+            for _ in 0..3 {
+                root_plan.run();
+            }
+            root_plan.exit(false);
+            root_plan
+        };
+
+        // sequential in priority order on a single thread
+        let root_plan = run(1);
+        assert_eq!(root_plan.thread_pool().unwrap().current_num_threads(), 1);
+        let recorders = root_plan
+            .plans
+            .iter()
+            .map(|x| x.behaviour.as_ref().unwrap());
+        let recorders = recorders.collect::<Vec<_>>();
+        for tick in 0..3 {
+            let order = recorders.iter().map(|x| x.runs[tick].0).collect::<Vec<_>>();
+            assert!(order.windows(2).all(|x| x[0] < x[1]), "{order:?}");
+        }
+        for recorder in &recorders {
+            assert!(recorder.runs.iter().all(|x| (x.1, x.2) == (1, Some(0))));
+            assert_eq!(recorder.exit_pool, Some(1));
+        }
+
+        // spread across the threads of a larger pool
+        let root_plan = run(4);
+        let recorders = root_plan
+            .plans
+            .iter()
+            .map(|x| x.behaviour.as_ref().unwrap());
+        let runs = recorders.flat_map(|x| &x.runs).collect::<Vec<_>>();
+        assert!(runs.iter().all(|x| x.1 == 4));
+        let threads = runs.iter().map(|x| x.2.unwrap()).collect::<BTreeSet<_>>();
+        assert!(threads.len() > 1);
+    }
+
+    #[test]
+    fn cancel_token() {
+        use std::time::Duration;
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct SpinBehaviour {
+            cancel: bool,
+            limit: u32,
+            spins: u32,
+        }
+        impl<C: Config> Behaviour<C> for SpinBehaviour {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                None
+            }
+            fn on_prepare(&mut self, plan: &mut Plan<C>) {
+                if self.cancel {
+                    plan.cancel();
+                }
+            }
+            fn on_run(&mut self, plan: &mut Plan<C>) {
+                for _ in 0..self.limit {
+                    bail_if_cancelled!(plan);
+                    self.spins += 1;
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+            }
+        }
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct SpinConfig;
+        impl Config for SpinConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = SpinBehaviour;
+            type Event = ();
+            type Name = DefaultName;
+        }
+        let spin = |cancel, limit| SpinBehaviour {
+            cancel,
+            limit,
+            spins: 0,
+        };
+        let spins =
+            |plan: &Plan<SpinConfig>| plan.get("spin").unwrap().behaviour.as_ref().unwrap().spins;
+
+        // cancelled by the parent behaviour earlier in the same tick
+        let mut root_plan = Plan::<SpinConfig>::new(spin(true, 0), "root", 1, true);
+        root_plan.insert(Plan::new(spin(false, 10), "spin", 1, true));
+        root_plan.run();
+        assert!(root_plan.get("spin").unwrap().cancel_token().is_cancelled());
+        assert_eq!(spins(&root_plan), 0);
+
+        // token is cleared when the tree is entered again
+        root_plan.exit(false);
+        root_plan.behaviour.as_mut().unwrap().cancel = false;
+        root_plan.run();
+        assert!(!root_plan.cancel_token().is_cancelled());
+        assert_eq!(spins(&root_plan), 10);
+
+        // cancelled from another thread while spinning
+        root_plan.exit(false);
+        root_plan.insert(Plan::new(spin(false, 10000), "spin", 1, true));
+        root_plan.enter(None);
+        let token = root_plan.cancel_token().clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            token.cancel();
         });
-        // trigger on_exit() for self
-        if !exclude_self {
-            self.call(|behaviour, plan| behaviour.on_exit(plan), "exit");
-            self.run_countdown = u32::MAX;
-            self.span = Span::none();
+        root_plan.run();
+        handle.join().unwrap();
+        assert!(spins(&root_plan) < 10000);
+
+        // shutdown cancels as well
+        root_plan.exit(false);
+        root_plan.enter(None);
+        assert!(!root_plan.cancel_token().is_cancelled());
+        root_plan.begin_shutdown();
+        assert!(root_plan.cancel_token().is_cancelled());
+    }
+
+    #[test]
+    fn downcast() {
+        use behaviour::*;
+        type B = Behaviours<DefaultConfig>;
+        let mut a: B = AnySuccessStatus.into();
+        let mut b: B = AllSuccessStatus.into();
+        a.cast::<AnySuccessStatus>().unwrap();
+        b.cast::<AllSuccessStatus>().unwrap();
+        a.cast_mut::<AnySuccessStatus>().unwrap();
+        b.cast_mut::<AllSuccessStatus>().unwrap();
+    }
+
+    #[test]
+    fn get_path() {
+        let mut root = new_plan("root", true);
+        let mut a = new_plan("a", false);
+        let mut b = new_plan("b", false);
+        b.insert(new_plan("c", false));
+        a.insert(b);
+        root.insert(a);
+
+        assert_eq!(root.get_path("a/b/c").unwrap().name(), "c");
+        assert_eq!(root.get_path("a/b").unwrap().name(), "b");
+        // leading, trailing, and repeated slashes are ignored
+        assert_eq!(root.get_path("/a//b/c/").unwrap().name(), "c");
+        assert_eq!(root.get_path("").unwrap().name(), "root");
+        assert_eq!(root.get_path("/").unwrap().name(), "root");
+        // missing intermediate or final plans
+        assert!(root.get_path("a/x/c").is_none());
+        assert!(root.get_path("a/b/c/d").is_none());
+        assert!(root.get_path("b").is_none());
+
+        root.get_cast_path_mut::<RunCountBehaviour>("a/b/c")
+            .unwrap()
+            .run_count = 3;
+        let c = root.get_cast_path::<RunCountBehaviour>("/a/b/c").unwrap();
+        assert_eq!(c.run_count, 3);
+        assert!(root
+            .get_cast_path::<behaviour::AllSuccessStatus>("a/b/c")
+            .is_none());
+        assert!(root.get_cast_path::<RunCountBehaviour>("a/c").is_none());
+        root.get_path_mut("a/b").unwrap().remove("c");
+        assert!(root.get_path("a/b/c").is_none());
+    }
+
+    #[test]
+    fn shape_metrics() {
+        let metrics = abc_plan().shape_metrics();
+        let expected = ShapeMetrics {
+            nodes: 5,
+            leaves: 4,
+            max_depth: 1,
+            max_branching: 4,
+            mean_branching: 4.,
+        };
+        assert_eq!(metrics, expected);
+        let lone = new_plan("root", false).shape_metrics();
+        assert_eq!((lone.nodes, lone.leaves, lone.max_depth), (1, 1, 0));
+        assert_eq!((lone.max_branching, lone.mean_branching), (0, 0.));
+
+        // chain of 10 plans below the root, each with 2 leaves besides the next link
+        let mut plan = new_plan("9", false);
+        for i in (0..9).rev() {
+            let mut parent = new_plan(&i.to_string(), false);
+            parent.insert(plan);
+            parent.insert(new_plan("x", false));
+            parent.insert(new_plan("y", false));
+            plan = parent;
         }
-        true
+        let mut root = abc_plan();
+        root.insert(plan);
+        let expected = ShapeMetrics {
+            nodes: 5 + 10 + 18,
+            leaves: 4 + 1 + 18,
+            max_depth: 10,
+            max_branching: 5,
+            mean_branching: 32. / 10.,
+        };
+        assert_eq!(root.shape_metrics(), expected);
+        // depth is relative to the plan iterated from
+        let mut iter = root.iter_descendants();
+        iter.next();
+        assert_eq!(iter.depth(), 1);
+    }
+
+    #[test]
+    fn find() {
+        let mut root = abc_plan();
+        root.get_mut("B").unwrap().insert(new_plan("B1", false));
+        root.get_mut("B").unwrap().insert(new_plan("B2", false));
+        root.enter(None);
+        let name = |plan: Option<&Plan<TestConfig>>| plan.map(|x| x.name().clone());
+        let names = |plans: Vec<&Plan<TestConfig>>| {
+            plans
+                .into_iter()
+                .map(|x| x.name().clone())
+                .collect::<Vec<_>>()
+        };
+
+        // match at the root
+        assert_eq!(name(root.find(|x| x.active())), Some("root".into()));
+        assert_eq!(names(root.find_all(|x| x.active())), ["root", "A"]);
+        // matches at leaves, in pre-order
+        let leaf = |x: &Plan<TestConfig>| x.plans.is_empty();
+        assert_eq!(name(root.find(leaf)), Some("A".into()));
+        assert_eq!(names(root.find_all(leaf)), ["A", "B1", "B2", "C", "D"]);
+        assert_eq!(
+            name(root.find(|x| x.name().starts_with('B'))),
+            Some("B".into())
+        );
+        // no match
+        assert!(root.find(|x| x.name() == "E").is_none());
+        assert!(root.find_all(|x| x.name() == "E").is_empty());
+        assert!(root.find_mut(|x| x.name() == "E").is_none());
+
+        let b2 = root.find_mut(|x| x.name() == "B2").unwrap();
+        b2.cast_mut::<RunCountBehaviour>().unwrap().run_count = 7;
+        let b2 = root.get_cast_path::<RunCountBehaviour>("B/B2").unwrap();
+        assert_eq!(b2.run_count, 7);
+        root.find_mut(|_| true).unwrap().autostart = false;
+        assert!(!root.autostart);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn fingerprint() {
+        use crate::config::fixtures::*;
+        let behaviour = |json: &str| {
+            let behaviour = serde_json::from_str::<behaviour::Behaviours<config::Default>>(json);
+            behaviour_fingerprint::<config::Default>(&behaviour.unwrap())
+        };
+        // reordered fields
+        let timeout = behaviour(
+            r#"{"TimeoutBehaviour":{"behaviour":{"AllSuccessStatus":null},"ticks":2,"count_down":null,"timed_out":false}}"#,
+        );
+        let reordered = behaviour(
+            r#"{"TimeoutBehaviour":{"timed_out":false,"ticks":2,"count_down":null,"behaviour":{"AllSuccessStatus":null}}}"#,
+        );
+        assert_eq!(timeout, reordered);
+        // changed fields
+        let changed = behaviour(
+            r#"{"TimeoutBehaviour":{"behaviour":{"AllSuccessStatus":null},"ticks":3,"count_down":null,"timed_out":false}}"#,
+        );
+        assert_ne!(timeout, changed);
+        let changed = behaviour(
+            r#"{"TimeoutBehaviour":{"behaviour":{"AnySuccessStatus":null},"ticks":2,"count_down":null,"timed_out":false}}"#,
+        );
+        assert_ne!(timeout, changed);
+        // equivalent floats
+        let utility = |scale| {
+            let json = r#"{"ModifyUtilityBehaviour":{"behaviour":{"AllSuccessStatus":null},"scale":SCALE,"offset":1.0}}"#;
+            behaviour(&json.replace("SCALE", scale))
+        };
+        assert_eq!(utility("0.0"), utility("-0.0"));
+        assert_ne!(utility("0.0"), utility("0.5"));
+
+        // reordered map entries
+        let mut root = leaf("root", true, None);
+        root.insert(leaf("a", true, Some(true)));
+        root.insert(scored("b", false, 1.));
+        let json = serde_json::to_string(&root).unwrap();
+        let plan = |tag_limits: &str| {
+            let json = json.replacen(r#""tag_limits":{}"#, tag_limits, 1);
+            serde_json::from_str::<Plan<config::Default>>(&json).unwrap()
+        };
+        let a = plan(r#""tag_limits":{"x":1,"y":2}"#);
+        let b = plan(r#""tag_limits":{"y":2,"x":1}"#);
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_ne!(a.fingerprint(), root.fingerprint());
+
+        // changes anywhere in the tree, but not to excluded fields
+        let fingerprint = root.fingerprint();
+        let mut changed = plan(r#""tag_limits":{}"#);
+        assert_eq!(changed.fingerprint(), fingerprint);
+        changed.set_description(Some("ignored".into()));
+        changed.set_data("ignored", serde_value::Value::Unit);
+        assert_eq!(changed.fingerprint(), fingerprint);
+        changed.get_mut("b").unwrap().run_interval = 2;
+        assert_ne!(changed.fingerprint(), fingerprint);
+        let mut changed = plan(r#""tag_limits":{}"#);
+        changed.transitions.push(transition!("a" => "b"));
+        assert_ne!(changed.fingerprint(), fingerprint);
+        // renamed subplans
+        let mut changed = plan(r#""tag_limits":{}"#);
+        changed.remove("b");
+        assert_ne!(changed.fingerprint(), fingerprint);
+        changed.insert(scored("c", false, 1.));
+        assert_ne!(changed.fingerprint(), fingerprint);
+        changed.remove("c");
+        changed.insert(scored("b", false, 1.));
+        assert_eq!(changed.fingerprint(), fingerprint);
     }
 
-    /// Helper to wrap calling inner behaviour from plan.
-    fn call(&mut self, f: impl FnOnce(&mut Box<C::Behaviour>, &mut Self), name: &str) {
-        let mut behaviour = std::mem::take(&mut self.behaviour);
-        if let Some(b) = &mut behaviour {
-            let _span = debug_span!(parent: &self.span, "call", func=%name).entered();
-            f(b, self);
-            self.behaviour = behaviour;
+    #[test]
+    fn run_subtree() {
+        use behaviour::{AllSuccessStatus, SequenceBehaviour};
+        type DefaultConfig = config::Default;
+        let names: Vec<_> = (0..4).map(|i| i.to_string()).collect();
+        let sequence = SequenceBehaviour::default();
+        let transitions = sequence.generate_transitions(&names);
+        let mut seq = Plan::<DefaultConfig>::new(sequence.into(), "seq", 1, false);
+        seq.transitions = transitions;
+        for name in &names[..3] {
+            seq.insert(Plan::new(AllSuccessStatus.into(), name, 0, name == "0"));
+        }
+        seq.insert(Plan::new_stub("3", false));
+        let mut mission = Plan::new_stub("mission", false);
+        mission.insert(seq);
+        let mut plan = Plan::<DefaultConfig>::new_stub("root", true);
+        plan.insert(mission);
+        plan.insert(Plan::new_stub("other", true));
+        plan.transitions.push(Transition::new(
+            vec!["other".into()],
+            vec!["mission".into()],
+            predicate::True.into(),
+        ));
+        let outside_inactive = |plan: &Plan<DefaultConfig>| {
+            !plan.active()
+                && !plan.get("other").unwrap().active()
+                && !plan.get("mission").unwrap().active()
+        };
+        plan.cancel();
+        // sequence advances while the rest of the tree stays inactive
+        for i in 0..3 {
+            plan.run_subtree(&["mission", "seq"]).unwrap();
+            let seq = plan.get("mission").unwrap().get("seq").unwrap();
+            assert!(seq.active());
+            let active = seq.plans.iter().find(|x| x.active()).unwrap().name();
+            assert_eq!(active, &(i + 1).to_string());
+            assert_eq!(seq.status(), None);
+            assert!(outside_inactive(&plan));
         }
+        // run to completion
+        let seq = plan.get_mut("mission").unwrap().get_mut("seq").unwrap();
+        seq.insert(Plan::new(AllSuccessStatus.into(), "3", 0, false));
+        plan.run_subtree(&["mission", "seq"]).unwrap();
+        let seq = plan.get("mission").unwrap().get("seq").unwrap();
+        assert_eq!(seq.status(), Some(true));
+        assert!(outside_inactive(&plan));
+        // state shared by the rest of the tree is left untouched
+        assert_eq!(plan.tick(), 0);
+        assert!(plan.cancel_token().is_cancelled());
+        // subtrees already active within the tree are reattached to its shared state afterwards
+        plan.get_mut("mission").unwrap().exit_plan("seq");
+        plan.run();
+        assert_eq!(plan.tick(), 1);
+        plan.get_mut("mission").unwrap().enter_plan("seq");
+        plan.run_subtree(&["mission", "seq"]).unwrap();
+        assert_eq!(plan.tick(), 1);
+        let seq = plan.find_by_path(&["mission", "seq"]).unwrap();
+        assert!(seq.plans.iter().any(|x| x.active()));
+        assert!(seq.iter().all(|x| Arc::ptr_eq(&x.tree, &plan.tree)));
+        // missing plans are reported
+        assert_eq!(
+            plan.run_subtree(&["mission", "nope"]),
+            Err(Error::PlanNotFound(vec!["mission".into(), "nope".into()]))
+        );
     }
-}
 
-/// Exit the plan on drop.
-impl<C: Config> Drop for Plan<C> {
-    fn drop(&mut self) {
-        if self.active() {
-            self.call(|behaviour, plan| behaviour.on_exit(plan), "exit");
+    #[test]
+    fn find_by_path() {
+        let mut root = new_plan("root", true);
+        let mut combat = new_plan("combat", false);
+        combat.insert(new_plan("aim", false));
+        combat.insert(new_plan("fire", false));
+        root.insert(combat);
+        root.insert(new_plan("idle", false));
+
+        assert_eq!(root.find_by_path(&["combat", "aim"]).unwrap().name(), "aim");
+        assert_eq!(root.find_by_path(&["idle"]).unwrap().name(), "idle");
+        assert_eq!(root.find_by_path::<str>(&[]).unwrap().name(), "root");
+        // any missing segment
+        assert!(root.find_by_path(&["combat", "x"]).is_none());
+        assert!(root.find_by_path(&["x", "aim"]).is_none());
+        assert!(root.find_by_path(&["idle", "aim"]).is_none());
+        // agrees with slash-separated paths
+        for (path, plan) in root.iter_with_path() {
+            let segments = path
+                .split('/')
+                .filter(|x| !x.is_empty())
+                .collect::<Vec<_>>();
+            assert!(std::ptr::eq(root.find_by_path(&segments).unwrap(), plan));
         }
+
+        let aim = root.find_by_path_mut(&["combat", "aim"]).unwrap();
+        aim.cast_mut::<RunCountBehaviour>().unwrap().run_count = 3;
+        let aim = root
+            .get_cast_path::<RunCountBehaviour>("combat/aim")
+            .unwrap();
+        assert_eq!(aim.run_count, 3);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn compile() {
+        let build = || {
+            let mut root = new_plan("root", true);
+            for name in ["A", "B", "C", "D", "E"] {
+                root.insert(new_plan(name, name == "A"));
+            }
+            let labeled = |label: &str, max_firings, t: Transition<_, _>| Transition {
+                label: Some(label.into()),
+                max_firings,
+                ..t
+            };
+            root.transitions = vec![
+                labeled("ab", None, transition!("A" => "B")),
+                labeled("bcd", None, transition!(["B"] => ["C", "D"])),
+                labeled("cde", Some(2), transition!(["C", "D"] => ["E"])),
+                labeled("any", Some(3), transition!([] => ["D"])),
+                labeled("ea", None, transition!("E" => "A")),
+                labeled("db", Some(1), transition!("D" => "B")),
+                labeled("missing", None, transition!("X" => "A")),
+            ];
+            root
+        };
+        let mut plain = build();
+        let mut compiled = build();
+        compiled.compile();
+        assert!(compiled.compiled() && !plain.compiled());
+        let tick = |plan: &mut Plan<TestConfig>| {
+            plan.run();
+            let active = plan.plans.iter().filter(|x| x.active());
+            let active = active.map(|x| x.name().clone()).collect::<Vec<_>>();
+            (active, plan.fired_transitions().to_vec())
+        };
+        // same transitions fire in the same order
+        for _ in 0..10 {
+            assert_eq!(tick(&mut compiled), tick(&mut plain));
+        }
+        assert_eq!(compiled.warnings(), plain.warnings());
 
-    fn tracing_init() {
-        use tracing_subscriber::fmt::format::FmtSpan;
-        let _ = tracing_subscriber::fmt()
-            .with_span_events(FmtSpan::ENTER)
-            .with_target(false)
-            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-            .try_init();
+        // changes to the transitions drop the index
+        for plan in [&mut plain, &mut compiled] {
+            plan.transitions[0].src = vec!["B".into()];
+        }
+        assert!(!compiled.compiled());
+        for _ in 0..10 {
+            assert_eq!(tick(&mut compiled), tick(&mut plain));
+        }
+        assert!(compiled.inner.as_ref().unwrap().transition_index.is_none());
     }
 
-    #[derive(Default, Debug, EnumCast)]
-    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-    pub struct RunCountBehaviour {
-        pub entry_count: u32,
-        pub exit_count: u32,
-        pub run_count: u32,
+    #[test]
+    fn transition_clauses() {
+        use config::fixtures::{active_names, leaf};
+        use predicate::{AllSuccess, AnyFailure};
+        let build = |[a, b, c]: [Option<bool>; 3], compile| {
+            let mut root = Plan::<config::Default>::new_stub("root", true);
+            root.insert(leaf("a", true, a));
+            root.insert(leaf("b", true, b));
+            root.insert(leaf("c", c.is_some(), c));
+            root.insert(leaf("d", false, None));
+            root.transitions.push(Transition {
+                clauses: vec![
+                    (vec!["a".into(), "b".into()], AllSuccess.into()),
+                    (vec!["c".into()], AnyFailure.into()),
+                ],
+                ..transition!([] => ["d"])
+            });
+            if compile {
+                root.compile();
+            }
+            root.run();
+            root
+        };
+        let (s, f) = (Some(true), Some(false));
+        let cases = [
+            ([s, s, f], true),
+            ([s, s, s], false),
+            ([s, f, f], false),
+            ([None, s, f], false),
+            // inactive clause plans block the transition
+            ([s, s, None], false),
+        ];
+        for (statuses, fires) in cases {
+            for compile in [false, true] {
+                let root = build(statuses, compile);
+                // every clause plan is exited upon firing
+                let expected = match (fires, statuses[2]) {
+                    (true, _) => vec!["d"],
+                    (false, Some(_)) => vec!["a", "b", "c"],
+                    (false, None) => vec!["a", "b"],
+                };
+                assert_eq!(active_names(&root), expected, "{statuses:?}");
+            }
+        }
+
+        let t = &build([s, s, f], false).transitions[0];
+        assert_eq!(t.sources().collect::<Vec<_>>(), ["a", "b", "c"]);
+        #[cfg(feature = "serde")]
+        {
+            // transitions without clauses serialize as before
+            let t: Transition<predicate::Predicates> = transition!("a" => "b");
+            let json = serde_json::to_value(t).unwrap();
+            assert!(json.get("clauses").is_none());
+            let t: Transition<predicate::Predicates> = serde_json::from_value(json).unwrap();
+            assert!(t.clauses.is_empty());
+        }
     }
 
-    impl<C: Config> Behaviour<C> for RunCountBehaviour {
-        fn status(&self, _plan: &Plan<C>) -> Option<bool> {
-            None
+    #[test]
+    fn path() {
+        use tracing::field::{Field, Visit};
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+        struct PathVisitor<'a>(&'a mut Vec<String>);
+        impl Visit for PathVisitor<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "path" {
+                    self.0.push(format!("{:?}", value));
+                }
+            }
         }
-        fn on_entry(&mut self, plan: &mut Plan<C>) {
-            self.entry_count += 1;
-            assert!(plan.behaviour.is_none())
+        // capture the path of each plan span
+        #[derive(Clone, Default)]
+        struct PathCapture(Arc<Mutex<Vec<String>>>);
+        impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for PathCapture {
+            fn on_new_span(
+                &self,
+                attrs: &tracing::span::Attributes,
+                _: &tracing::Id,
+                _: Context<S>,
+            ) {
+                attrs.record(&mut PathVisitor(&mut self.0.lock().unwrap()));
+            }
         }
-        fn on_exit(&mut self, _plan: &mut Plan<C>) {
-            self.exit_count += 1;
+        let capture = PathCapture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+
+        let mut root = new_plan("root", true);
+        for name in ["patrol", "guard"] {
+            let mut plan = new_plan(name, true);
+            plan.insert(new_plan("wait", true));
+            root.insert(plan);
         }
-        fn on_run(&mut self, _plan: &mut Plan<C>) {
-            self.run_count += 1;
+        assert_eq!(root.path(), "root");
+        assert_eq!(
+            root.get_path("patrol/wait").unwrap().path(),
+            "root/patrol/wait"
+        );
+        tracing::subscriber::with_default(subscriber, || {
+            root.run();
+            // spans must close within the scope of the subscriber
+            root.exit(false);
+        });
+        let mut paths = capture.0.lock().unwrap().clone();
+        paths.sort();
+        // subplans are entered on the thread pool, out of reach of the scoped subscriber
+        #[cfg(feature = "rayon")]
+        let expected = ["root"];
+        #[cfg(not(feature = "rayon"))]
+        let expected = [
+            "root",
+            "root/guard",
+            "root/guard/wait",
+            "root/patrol",
+            "root/patrol/wait",
+        ];
+        assert_eq!(paths, expected);
+
+        // moved subtrees take on the path of their new parent
+        let patrol = root.remove("patrol").unwrap();
+        root.get_mut("guard").unwrap().insert(patrol);
+        let wait = root.get_path("guard/patrol/wait").unwrap();
+        assert_eq!(wait.path(), "root/guard/patrol/wait");
+        // as do plans added directly once entered
+        let mut watch = new_plan("watch", true);
+        watch.path.clear();
+        root.get_mut("guard").unwrap().plans.push(watch);
+        root.enter(None);
+        assert_eq!(
+            root.get_path("guard/watch").unwrap().path(),
+            "root/guard/watch"
+        );
+
+        // names other than strings are written in their debug form
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct NumericConfig;
+        impl Config for NumericConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = RunCountBehaviour;
+            type Event = ();
+            type Name = u16;
         }
+        let mut root = Plan::<NumericConfig>::new_stub(0u16, true);
+        root.insert(Plan::new_stub(7u16, false))
+            .insert(Plan::new_stub(3u16, false));
+        assert_eq!(root.find_by_path(&[&7u16, &3]).unwrap().path(), "0/7/3");
     }
 
-    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-    struct TestConfig;
-    impl Config for TestConfig {
-        type Predicate = predicate::Predicates;
-        type Behaviour = RunCountBehaviour;
+    #[test]
+    fn iter() {
+        // only a root
+        let root = new_plan("root", true);
+        let names = |iter: &mut dyn Iterator<Item = &Plan<TestConfig>>| {
+            iter.map(|x| x.name().clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(names(&mut root.iter()), ["root"]);
+        assert_eq!(names(&mut root.iter_active()), Vec::<String>::new());
+        let paths = root.iter_with_path().map(|x| x.0).collect::<Vec<_>>();
+        assert_eq!(paths, [""]);
+
+        // chain of 100 plans below the root, each with a sibling leaf
+        let mut plan = new_plan("99", false);
+        for i in (0..99).rev() {
+            let mut parent = new_plan(&i.to_string(), false);
+            parent.insert(plan);
+            parent.insert(new_plan("x", false));
+            plan = parent;
+        }
+        let mut root = new_plan("root", true);
+        root.insert(plan);
+        root.run();
+        assert_eq!(root.iter().count(), 1 + 100 + 99);
+        // depth first, with subplans in priority order
+        let expected = ["root", "0", "1", "2"];
+        assert_eq!(names(&mut root.iter().take(4)), expected);
+        let last = ["x", "x", "x"];
+        assert_eq!(names(&mut root.iter().skip(197)), last);
+        // paths resolve back to the plans
+        let mut count = 0;
+        for (path, plan) in root.iter_with_path() {
+            assert!(std::ptr::eq(root.get_path(&path).unwrap(), plan));
+            count += 1;
+        }
+        assert_eq!(count, 200);
+        let paths = root.iter_with_path().skip(99).take(3).map(|x| x.0);
+        let prefix = (0..98).map(|x| x.to_string()).collect::<Vec<_>>().join("/");
+        let expected = [
+            format!("{prefix}/98"),
+            format!("{prefix}/98/99"),
+            format!("{prefix}/98/x"),
+        ];
+        assert_eq!(paths.collect::<Vec<_>>(), expected);
+
+        // only the root and the autostarted plans are active
+        root.get_path_mut("0").unwrap().autostart = true;
+        root.exit(false);
+        root.run();
+        assert_eq!(names(&mut root.iter_active()), ["root", "0"]);
+        let active = root.iter().filter(|x| x.active()).count();
+        assert_eq!(root.iter_active().count(), active);
     }
 
-    fn new_plan(name: &str, autostart: bool) -> Plan<TestConfig> {
-        Plan::<TestConfig>::new(RunCountBehaviour::default(), name, 1, autostart)
+    #[test]
+    fn iter_descendants() {
+        let names = |iter: &mut dyn Iterator<Item = &Plan<TestConfig>>| {
+            iter.map(|x| x.name().clone()).collect::<Vec<_>>()
+        };
+        let mut root = new_plan("root", true);
+        assert_eq!(root.iter_descendants().count(), 0);
+        assert!(root.iter_descendants_mut().next().is_none());
+
+        // inserted out of order, visited in priority order at each level
+        for name in ["c", "a", "b"] {
+            root.insert(new_plan(name, false));
+        }
+        for name in ["a2", "a1"] {
+            root.get_mut("a").unwrap().insert(new_plan(name, false));
+        }
+        root.get_path_mut("a/a2")
+            .unwrap()
+            .insert(new_plan("a2x", false));
+        let expected = ["a", "a1", "a2", "a2x", "b", "c"];
+        assert_eq!(names(&mut root.iter_descendants()), expected);
+        assert_eq!(names(&mut root.iter().skip(1)), expected);
+
+        // same order when mutable, including subplans inserted along the way
+        let mut visited = Vec::new();
+        let mut descendants = root.iter_descendants_mut();
+        while let Some(plan) = descendants.next() {
+            plan.autostart = true;
+            if plan.name() == "b" {
+                plan.insert(new_plan("b1", false));
+            }
+            visited.push(plan.name().clone());
+        }
+        assert!(descendants.next().is_none());
+        assert_eq!(visited, ["a", "a1", "a2", "a2x", "b", "b1", "c"]);
+        assert!(root.iter_descendants().all(|x| x.autostart));
+        assert_eq!(root.iter().count(), 8);
     }
 
-    fn abc_plan() -> Plan<TestConfig> {
-        let mut root_plan = new_plan("root", true);
-        root_plan.transitions = vec![
-            Transition {
-                src: vec!["A".into()],
-                dst: vec!["B".into()],
-                predicate: predicate::True.into_enum().unwrap(),
-            },
-            Transition {
-                src: vec!["B".into()],
-                dst: vec!["C".into()],
-                predicate: predicate::True.into_enum().unwrap(),
-            },
-            Transition {
-                src: vec!["C".into()],
-                dst: vec!["A".into()],
-                predicate: predicate::True.into_enum().unwrap(),
-            },
+    #[test]
+    fn visit() {
+        let mut root = new_plan("root", true);
+        for name in ["b", "a"] {
+            let mut plan = new_plan(name, false);
+            plan.insert(new_plan(&format!("{name}1"), false));
+            plan.insert(new_plan(&format!("{name}0"), true));
+            root.insert(plan);
+        }
+        root.visit_mut(&mut |plan| plan.autostart = !plan.autostart);
+        let mut autostart = Vec::new();
+        root.visit(&mut |plan| autostart.push((plan.name().clone(), plan.autostart)));
+        let expected = [
+            ("root", false),
+            ("a", true),
+            ("a0", false),
+            ("a1", true),
+            ("b", true),
+            ("b0", false),
+            ("b1", true),
         ];
-        // init plan to A
-        root_plan.insert(new_plan("A", true));
-        root_plan.insert(new_plan("B", false));
-        root_plan.insert(new_plan("C", false));
-        root_plan.insert(new_plan("D", false));
-        root_plan
+        let expected = expected.map(|(name, autostart)| (name.to_string(), autostart));
+        assert_eq!(autostart, expected);
+
+        // subplans inserted or removed by the closure are taken into account
+        root.visit_mut(&mut |plan| {
+            if plan.name() == "a" {
+                plan.remove("a0");
+                plan.insert(new_plan("a2", false));
+            }
+        });
+        let mut names = Vec::new();
+        root.visit(&mut |plan| names.push(plan.name().clone()));
+        assert_eq!(names, ["root", "a", "a1", "a2", "b", "b0", "b1"]);
     }
 
     #[test]
-    fn sorted_insert() {
-        tracing_init();
+    fn behaviour_dyn() {
+        use behaviour::*;
+        use predicate::*;
+        let mut plan = Plan::<DefaultConfig>::new(AllSuccessStatus.into(), "root", 1, true);
+        plan.insert(Plan::new(AnySuccessStatus.into(), "a", 1, true));
+        let failure = EvaluateStatus(False.into(), True.into());
+        plan.insert(Plan::new(failure.into(), "b", 1, true));
+        plan.insert(Plan::new_stub("c", true));
+        plan.run();
+        // same result as matching on the behaviour enum
+        for p in plan.plans.iter().chain([&plan]) {
+            let status = p.behaviour_dyn().map(|b| b.status(p)).unwrap_or(p.status());
+            assert_eq!(status, p.status());
+        }
+        assert_eq!(plan.get("b").unwrap().status(), Some(false));
+        assert!(plan.get("c").unwrap().behaviour_dyn().is_none());
 
-        let mut root_plan = new_plan("root", true);
-        root_plan.insert(new_plan("C", true));
-        root_plan.insert(new_plan("A", true));
-        root_plan.insert(new_plan("B", true));
-        root_plan.insert(new_plan("B", true));
+        assert!(plan.get_mut("b").unwrap().behaviour_dyn_mut().is_some());
+    }
 
-        assert_eq!(root_plan.plans.len(), 3);
-        for (i, plan) in root_plan.plans.iter().enumerate() {
-            assert!(!plan.active());
-            assert_eq!(plan.name(), &((b'A' + (i as u8)) as char).to_string());
-            let sm = plan.behaviour.as_ref().unwrap();
-            assert_eq!(sm.entry_count, 0);
-            assert_eq!(sm.run_count, 0);
-            assert_eq!(sm.exit_count, 0);
+    #[test]
+    fn freeze() {
+        use behaviour::*;
+        use predicate::*;
+        let mut repeat = RepeatBehaviour::new(EvaluateStatus(True.into(), False.into()).into());
+        repeat.iterations = 5;
+        let mut r = Plan::<DefaultConfig>::new(repeat.into(), "r", 1, true);
+        r.insert(Plan::new_stub("x", true));
+        r.insert(Plan::new_stub("y", false));
+        r.transitions.push(transition!("x" => "y"));
+        r.transitions.push(transition!("y" => "x"));
+        let mut root = Plan::<DefaultConfig>::new_stub("root", true);
+        root.skip_idle = true;
+        root.insert(r);
+        root.run();
+        root.run();
+        assert!(root.get("r").unwrap().get("x").unwrap().active());
+
+        // neither the behaviour nor transitions run while frozen
+        root.freeze(&["r"]).unwrap();
+        #[cfg(feature = "serde")]
+        let inspected = root.get("r").unwrap().inspect_behaviour();
+        for _ in 0..10 {
+            root.run();
+            let r = root.get("r").unwrap();
+            assert_eq!(r.status(), None);
+            assert!(r.get("x").unwrap().active());
         }
-        root_plan.exit(false);
-        for plan in &root_plan.plans {
-            assert!(!plan.active());
-            assert_eq!(plan.behaviour.as_ref().unwrap().exit_count, 0);
+        #[cfg(feature = "serde")]
+        {
+            let r = root.get("r").unwrap();
+            assert_eq!(r.inspect_behaviour(), inspected);
+            let json = serde_json::to_string(r).unwrap();
+            assert!(
+                serde_json::from_str::<Plan<DefaultConfig>>(&json)
+                    .unwrap()
+                    .frozen
+            );
+        }
+
+        // resumes with the remaining 3 iterations
+        root.unfreeze(&["r"]).unwrap();
+        for _ in 0..3 {
+            root.run();
+            assert_eq!(root.get("r").unwrap().status(), None);
         }
+        root.run();
+        assert_eq!(root.get("r").unwrap().status(), Some(true));
+        assert!(root.freeze(&["z"]).is_err());
     }
 
     #[test]
-    fn cycle_plans() {
-        tracing_init();
+    fn paused() {
         let mut root_plan = abc_plan();
         root_plan.run();
+        let state = |plan: &Plan<TestConfig>| {
+            let runs = plan.cast::<RunCountBehaviour>().unwrap().run_count;
+            let b = plan.get("B").unwrap();
+            (plan.active_state(), plan.run_countdown(), b.status(), runs)
+        };
+        let before = state(&root_plan);
+        root_plan.set_paused(true);
+        for _ in 0..10 {
+            assert!(root_plan.run().tag_refusals.is_empty());
+        }
+        assert_eq!(state(&root_plan), before);
+
+        // resumes from the same state, as if no ticks had passed
+        root_plan.set_paused(false);
+        assert!(!root_plan.paused());
         root_plan.run();
-        let cycles = 10;
-        for _ in 0..(cycles - 1) {
-            assert!(!root_plan.get("A").unwrap().active());
-            assert!(!root_plan.get("B").unwrap().active());
-            assert!(root_plan.get("C").unwrap().active());
-            assert!(!root_plan.get("D").unwrap().active());
-            root_plan.run();
-            assert!(root_plan.get("A").unwrap().active());
-            assert!(!root_plan.get("B").unwrap().active());
-            assert!(!root_plan.get("C").unwrap().active());
-            assert!(!root_plan.get("D").unwrap().active());
-            root_plan.run();
-            assert!(!root_plan.get("A").unwrap().active());
-            assert!(root_plan.get("B").unwrap().active());
-            assert!(!root_plan.get("C").unwrap().active());
-            assert!(!root_plan.get("D").unwrap().active());
-            root_plan.run();
+        assert!(root_plan.get("C").unwrap().active());
+        assert_eq!(state(&root_plan).3, before.3 + 1);
+    }
+
+    #[test]
+    fn active_leaf() {
+        let mut root_plan = new_plan("root", true);
+        let a = root_plan.insert(new_plan("A", true));
+        a.insert(new_plan("x", false));
+        a.insert(new_plan("y", true)).insert(new_plan("z", true));
+        root_plan.insert(new_plan("B", true));
+        assert!(root_plan.active_leaf().is_none());
+
+        root_plan.run();
+        let leaf = root_plan.active_leaf().unwrap();
+        assert_eq!(leaf.name(), "z");
+        let a = root_plan.get("A").unwrap();
+        assert_eq!(a.active_leaf().unwrap().name(), "z");
+        assert!(a.get("x").unwrap().active_leaf().is_none());
+
+        // falls back to lower priority branches and stops at active plans without active subplans
+        root_plan.exit_plan("A");
+        assert_eq!(root_plan.active_leaf().unwrap().name(), "B");
+        root_plan.exit_plan("B");
+        assert_eq!(root_plan.active_leaf().unwrap().name(), "root");
+    }
+
+    #[test]
+    fn descriptions() {
+        let mut root_plan = plan_tree!(TestConfig; "root" autostart {
+            children: {
+                "A" autostart {},
+                "B" description = "handles the retreat" {},
+            },
+        });
+        root_plan.transitions.push(transition!("A" => "C"));
+        root_plan.transitions[0].description = Some("falls back".into());
+        let issues = root_plan.validate();
+        let messages = issues
+            .iter()
+            .map(|x| root_plan.describe_issue(x))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                r#"transition 0 of plan [] refers to missing plan "C" (transition: falls back)"#,
+                r#"plan ["B"] can never become active (plan "B": handles the retreat)"#,
+            ]
+        );
+        // descriptions follow transition descriptors
+        root_plan.transitions[0].dst = vec!["B".into()];
+        let matrix = root_plan.transition_matrix();
+        assert_eq!(matrix.cells[0][1], ["True: falls back"]);
+
+        #[cfg(feature = "serde")]
+        {
+            // skipped when unset
+            root_plan.transitions.push(transition!("B" => "A"));
+            let json = serde_json::to_value(&root_plan).unwrap();
+            assert!(json.get("description").is_none());
+            assert!(json["plans"][0].get("description").is_none());
+            assert!(json["transitions"][1].get("description").is_none());
+            assert_eq!(json["plans"][1]["description"], "handles the retreat");
+            assert_eq!(json["transitions"][0]["description"], "falls back");
+            let loaded = serde_json::from_value::<Plan<TestConfig>>(json).unwrap();
+            let description = loaded.get("B").unwrap().description();
+            assert_eq!(description, Some("handles the retreat"));
+            assert_eq!(
+                loaded.transitions[0].description.as_deref(),
+                Some("falls back")
+            );
+            assert_eq!(loaded.description(), None);
         }
-        root_plan.exit(false);
+    }
 
-        for plan in &root_plan.plans {
-            if plan.name() == "D" {
-                assert!(!plan.active());
-                continue;
+    #[test]
+    fn transition_matrix() {
+        let root_plan = abc_plan();
+        let matrix = root_plan.transition_matrix();
+        let states = ["A", "B", "C", "D"].map(|x| Some(x.to_string()));
+        assert_eq!(matrix.states, [&states[..], &[None]].concat());
+        let cycle = [("A", "B"), ("B", "C"), ("C", "A")];
+        for (i, src) in matrix.states.iter().enumerate() {
+            for (j, dst) in matrix.states.iter().enumerate() {
+                let expected = cycle
+                    .iter()
+                    .any(|&(a, b)| src.as_deref() == Some(a) && dst.as_deref() == Some(b));
+                let expected = match expected {
+                    true => vec!["True".to_string()],
+                    false => vec![],
+                };
+                assert_eq!(matrix.cells[i][j], expected);
             }
-            let sm = plan.behaviour.as_ref().unwrap();
-            assert_eq!(sm.entry_count, cycles);
-            assert_eq!(sm.exit_count, cycles);
-            // off by one becase inital plan didn't run
-            let run_cycles = if plan.name() == "A" {
-                cycles - 1
-            } else {
-                cycles
-            };
-            assert_eq!(sm.run_count, run_cycles);
         }
-    }
+        let csv = ",A,B,C,D,none\nA,,True,,,\nB,,,True,,\nC,True,,,,\nD,,,,,\nnone,,,,,\n";
+        assert_eq!(matrix.to_csv(), csv);
 
-    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-    struct DefaultConfig;
-    impl Config for DefaultConfig {
-        type Predicate = predicate::Predicates;
-        type Behaviour = behaviour::Behaviours<Self>;
+        // D is never reachable
+        let reached = matrix.reachable_from(&["A".into()]);
+        assert_eq!(reached, ["A", "B", "C"].map(String::from).into());
+        let reached = matrix.reachable_from(&["D".into()]);
+        assert_eq!(reached, ["D".to_string()].into());
+
+        // joint transitions appear in each pairwise entry
+        let mut root_plan = root_plan;
+        let mut joint = transition!(["A", "B"] => ["B", "D"], predicate::False);
+        joint.label = Some("joint".into());
+        root_plan.transitions.push(joint);
+        let matrix = root_plan.transition_matrix();
+        let joint = vec![r#"joint (["A", "B"] -> ["B", "D"])"#.to_string()];
+        assert_eq!(matrix.cells[0][3], joint);
+        assert_eq!(matrix.cells[1][3], joint);
+        assert_eq!(
+            matrix.cells[0][1],
+            [&["True".to_string()][..], &joint].concat()
+        );
+        assert!(matrix.cells[1][1].is_empty());
     }
 
     #[test]
-    #[cfg(feature = "serde")]
-    fn generate_schema() {
-        tracing_init();
-        // generate and print plan schema
-        use serde_reflection::{Tracer, TracerConfig};
-        let mut tracer = Tracer::new(TracerConfig::default());
-        tracer
-            .trace_simple_type::<behaviour::Behaviours<DefaultConfig>>()
-            .unwrap();
-        tracer.trace_simple_type::<predicate::Predicates>().unwrap();
-        let registry = tracer.registry().unwrap();
-        debug!("{}", serde_json::to_string_pretty(&registry).unwrap());
+    #[cfg(feature = "invariants")]
+    fn invariants() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        fn message<C: Config>(root_plan: &mut Plan<C>) -> String {
+            let payload = catch_unwind(AssertUnwindSafe(|| root_plan.run())).unwrap_err();
+            *payload.downcast::<String>().unwrap()
+        }
+        // unsorted children
+        let mut root_plan = abc_plan();
+        root_plan.run();
+        root_plan.get_mut("B").unwrap().insert(new_plan("x", false));
+        root_plan.get_mut("B").unwrap().insert(new_plan("y", false));
+        root_plan.get_mut("B").unwrap().plans.swap(0, 1);
+        assert_eq!(
+            message(&mut root_plan),
+            r#"invariant violated in plan ["root", "B"]: subplans "y" and "x" are not sorted by unique name"#
+        );
+
+        // selectors with several active children
+        use behaviour::*;
+        let mut root_plan = Plan::<DefaultConfig>::new_stub("root", true);
+        let robin = root_plan.insert(Plan::new(RoundRobinBehaviour(0).into(), "robin", 1, true));
+        robin.insert(Plan::new_stub("a", true));
+        robin.insert(Plan::new_stub("b", true));
+        assert_eq!(
+            message(&mut root_plan),
+            r#"invariant violated in plan ["root", "robin"]: exclusive behaviour has active subplans ["a", "b"]"#
+        );
+        root_plan.get_mut("robin").unwrap().exit_plan("b");
+        root_plan.run();
     }
 
     #[test]
-    #[cfg(feature = "serde")]
-    fn generate_plan() {
-        tracing_init();
-        let root_plan = Plan::<DefaultConfig>::new_stub("root", true);
-        // serialize and print root plan
-        debug!("{}", serde_json::to_string_pretty(&root_plan).unwrap());
+    fn reduce() {
+        let mut root_plan = abc_plan();
+        root_plan.get_mut("B").unwrap().insert(new_plan("x", true));
+        root_plan
+            .get_mut("B")
+            .unwrap()
+            .transitions
+            .push(transition!("x" => "x"));
+        let names = root_plan.reduce(Vec::new(), |mut names, plan| {
+            names.push(plan.name().clone());
+            names
+        });
+        assert_eq!(names, ["root", "A", "B", "x", "C", "D"]);
+
+        // total transitions and utility in one pass
+        use behaviour::*;
+        let utility = |x| ModifyUtilityBehaviour::new(AllSuccessStatus.into(), 1.0, x).into();
+        let mut root_plan = Plan::<DefaultConfig>::new(utility(0.5), "root", 1, true);
+        let a = root_plan.insert(Plan::new(utility(1.0), "a", 1, true));
+        a.insert(Plan::new(utility(2.0), "x", 1, true));
+        a.insert(Plan::new_stub("y", false));
+        a.transitions.push(transition!("x" => "y"));
+        root_plan.insert(Plan::new(utility(4.0), "b", 1, false));
+        root_plan.transitions.push(transition!("a" => "b"));
+        root_plan.transitions.push(transition!("b" => "a"));
+        let (transitions, utility) = root_plan.reduce((0, 0.0), |(transitions, utility), plan| {
+            (
+                transitions + plan.transitions.len(),
+                utility + plan.utility(),
+            )
+        });
+        assert_eq!((transitions, utility), (3, 7.5));
     }
 
     #[test]
-    fn downcast() {
+    fn behaviour_histogram() {
         use behaviour::*;
-        type B = Behaviours<DefaultConfig>;
-        let mut a: B = AnySuccessStatus.into();
-        let mut b: B = AllSuccessStatus.into();
-        a.cast::<AnySuccessStatus>().unwrap();
-        b.cast::<AllSuccessStatus>().unwrap();
-        a.cast_mut::<AnySuccessStatus>().unwrap();
-        b.cast_mut::<AllSuccessStatus>().unwrap();
+        let repeat = || RepeatBehaviour::<DefaultConfig>::new(AllSuccessStatus.into()).into();
+        let mut root_plan =
+            Plan::<DefaultConfig>::new(MaxUtilBehaviour::default().into(), "root", 1, true);
+        for i in 0..4 {
+            let mut plan = Plan::new(repeat(), format!("repeat{i}"), 1, false);
+            plan.insert(Plan::new(AllSuccessStatus.into(), "x", 1, true));
+            plan.insert(Plan::new_stub("y", true));
+            root_plan.insert(plan);
+        }
+        root_plan.insert(Plan::new(AnySuccessStatus.into(), "any", 1, false));
+        let histogram = root_plan.behaviour_histogram();
+        let expected = [
+            ("MaxUtilBehaviour", 1),
+            ("RepeatBehaviour", 4),
+            ("AllSuccessStatus", 4),
+            ("AnySuccessStatus", 1),
+        ];
+        assert_eq!(histogram, expected.into());
+    }
+
+    #[test]
+    fn deferred_mutations() {
+        /// Spawns the contained plan as a subplan upon the first run.
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct Spawner<C: Config>(Option<Box<Plan<C>>>);
+        impl<C: Config> Behaviour<C> for Spawner<C> {
+            fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+                None
+            }
+            fn on_run(&mut self, plan: &mut Plan<C>) {
+                if let Some(spawned) = self.0.take() {
+                    let name = spawned.name().clone();
+                    plan.defer(PlanMutation::Insert(spawned));
+                    assert!(plan.get(&name).is_none());
+                }
+            }
+        }
+
+        #[enum_dispatch(Behaviour<C>)]
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub enum MutationBehaviours<C: Config> {
+            Spawner(Spawner<C>),
+            RunCountBehaviour,
+        }
+
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct MutationConfig;
+        impl Config for MutationConfig {
+            type Predicate = predicate::Predicates;
+            type Behaviour = MutationBehaviours<Self>;
+            type Event = ();
+            type Name = DefaultName;
+        }
+
+        let spawned = Plan::new(RunCountBehaviour::default().into(), "spawned", 1, true);
+        let spawner = Spawner(Some(Box::new(spawned)));
+        let mut root_plan = Plan::<MutationConfig>::new(spawner.into(), "root", 1, true);
+        for name in ["a", "z"] {
+            root_plan.insert(Plan::new(
+                RunCountBehaviour::default().into(),
+                name,
+                1,
+                true,
+            ));
+        }
+        let report = root_plan.run();
+        let inserted = AppliedMutation {
+            plan: "root".to_string(),
+            kind: MutationKind::Insert,
+            subplan: "spawned".to_string(),
+        };
+        assert_eq!(report.mutations, [inserted]);
+        let names = root_plan
+            .plans
+            .iter()
+            .map(|x| x.name().as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["a", "spawned", "z"]);
+        // inserted plan is entered, but first runs on the following tick
+        let run_count = |plan: &Plan<MutationConfig>, name| {
+            let x = plan.get_cast::<RunCountBehaviour, _>(name).unwrap();
+            x.run_count
+        };
+        assert!(root_plan.get("spawned").unwrap().active());
+        assert_eq!(run_count(&root_plan, "spawned"), 0);
+        assert!(root_plan.run().mutations.is_empty());
+        assert_eq!(run_count(&root_plan, "spawned"), 1);
+        assert_eq!(run_count(&root_plan, "a"), 2);
+
+        // queued outside of a tick, applied at the end of the next run
+        root_plan.defer(PlanMutation::ExitPlan("a".into()));
+        root_plan.defer(PlanMutation::EnterPlan("z".into()));
+        root_plan.defer(PlanMutation::Remove("missing".into()));
+        root_plan.defer(PlanMutation::Remove("z".into()));
+        let report = root_plan.run();
+        let kinds = report.mutations.iter().map(|x| x.kind).collect::<Vec<_>>();
+        assert_eq!(kinds, [MutationKind::ExitPlan, MutationKind::Remove]);
+        assert_eq!(run_count(&root_plan, "a"), 3);
+        assert!(!root_plan.get("a").unwrap().active());
+        assert!(root_plan.get("z").is_none());
     }
 }
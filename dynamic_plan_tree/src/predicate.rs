@@ -7,13 +7,49 @@ macro_rules! predicate_trait {
         /// An object that implements run-time predicate evaluation logic of an active plan.
         #[enum_dispatch]
         pub trait Predicate: Sized + 'static {
-            fn evaluate(&self, plan: &Plan<impl Config>, src: &[String]) -> bool;
+            fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool;
+            /// Degree to which the predicate holds, compared against [Transition::threshold].
+            ///
+            /// Defaults to `1.0` if `evaluate()` holds, otherwise `0.0`.
+            fn score<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> f64 {
+                if self.evaluate(plan, src) {
+                    1.
+                } else {
+                    0.
+                }
+            }
+            /// Value of the predicate if known without evaluation, for static analysis.
+            ///
+            /// `Some(false)` if provably false, `Some(true)` if provably true, `None` if runtime-dependent.
+            fn is_statically_false(&self) -> Option<bool> {
+                None
+            }
+            /// Short human readable description for tooling. Defaults to the type name without module paths.
+            fn describe(&self) -> String {
+                predicate::short_type_name::<Self>()
+            }
         }
     };
 }
 predicate_trait!();
 
 /// Default set of built-in predicates to serve as example template.
+///
+/// ```
+/// use dynamic_plan_tree::{config::*, predicate::*};
+///
+/// let plan = Plan::<config::Default>::new_stub("root", true);
+/// let holds = |predicate: Predicates| predicate.evaluate(&plan, &[]);
+/// assert!(holds(True.into()));
+/// assert!(!holds(False.into()));
+/// assert!(!holds(And(vec![True.into(), False.into()]).into()));
+/// assert!(holds(Or(vec![True.into(), False.into()]).into()));
+/// assert!(holds(Xor(vec![True.into(), False.into()]).into()));
+/// assert!(holds(Not(Box::new(False.into())).into()));
+/// assert!(holds(Nand(vec![True.into(), False.into()]).into()));
+/// assert!(!holds(Nor(vec![True.into(), False.into()]).into()));
+/// assert!(holds(Xnor(vec![True.into(), True.into()]).into()));
+/// ```
 #[enum_dispatch(Predicate)]
 #[derive(EnumCast)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -32,113 +68,547 @@ pub enum Predicates {
     AnySuccess,
     AllFailure,
     AnyFailure,
+
+    TransitionFired,
+    TransitionWouldFire,
+
+    UtilityDelta(UtilityDelta),
+    DataValue,
+}
+
+/// Macro to implement a `Predicate` trait redefined by [predicate_trait] for all built-in predicates,
+/// so that they can be mixed with external predicates in a remote enum_dispatch definition.
+///
+/// Each method delegates to the implementation of the built-in for this crate's [Predicate] trait,
+/// so predicates composed by the built-in logic operators must implement it as well.
+/// The remote enum may do so once with [forward_predicate], forwarding each method to the redefined trait.
+///
+/// ```
+/// use dynamic_plan_tree::predicate::Not;
+/// use dynamic_plan_tree::{config, enum_dispatch, forward_predicate, Config, Plan};
+///
+/// mod remote {
+///     use dynamic_plan_tree::*;
+///     predicate_trait!();
+///     impl_builtin_predicates!(Predicate);
+/// }
+/// use remote::Predicate;
+///
+/// struct MyPredicate;
+/// impl Predicate for MyPredicate {
+///     fn evaluate<C: Config>(&self, _plan: &Plan<C>, _src: &[C::Name]) -> bool {
+///         true
+///     }
+/// }
+///
+/// #[enum_dispatch(Predicate)]
+/// enum MyPredicates {
+///     Not(Not<Self>),
+///     MyPredicate,
+/// }
+/// forward_predicate!(Predicate, MyPredicates);
+///
+/// fn main() {
+///     let plan = Plan::<config::Default>::new_stub("plan", true);
+///     let not = MyPredicates::from(Not(Box::new(MyPredicate.into())));
+///     assert!(!dynamic_plan_tree::Predicate::evaluate(&not, &plan, &[]));
+/// }
+/// ```
+#[macro_export]
+macro_rules! impl_builtin_predicates {
+    ($($predicate:ident)::+) => {
+        $crate::impl_builtin_predicates!(@impl $($predicate)::+;
+            [] $crate::predicate::Predicates,
+            [] $crate::predicate::True,
+            [] $crate::predicate::False,
+            [P: $crate::Predicate] $crate::predicate::And<P>,
+            [P: $crate::Predicate] $crate::predicate::Or<P>,
+            [P: $crate::Predicate] $crate::predicate::Xor<P>,
+            [P: $crate::Predicate] $crate::predicate::Not<P>,
+            [P: $crate::Predicate] $crate::predicate::Nand<P>,
+            [P: $crate::Predicate] $crate::predicate::Nor<P>,
+            [P: $crate::Predicate] $crate::predicate::Xnor<P>,
+            [] $crate::predicate::AllSuccess,
+            [] $crate::predicate::AnySuccess,
+            [] $crate::predicate::AllFailure,
+            [] $crate::predicate::AnyFailure,
+            [M: $crate::predicate::EventMatcher] $crate::predicate::EventMatches<M>,
+            [] $crate::predicate::TransitionFired,
+            [] $crate::predicate::TransitionWouldFire,
+            [N: 'static] $crate::predicate::UtilityDelta<N>,
+            [] $crate::predicate::DataValue,
+        );
+    };
+
+    (@impl $($predicate:ident)::+;) => {};
+    (@impl $($predicate:ident)::+; [$($generics:tt)*] $ty:ty, $($rest:tt)*) => {
+        impl<$($generics)*> $($predicate)::+ for $ty {
+            fn evaluate<C: $crate::Config>(&self, plan: &$crate::Plan<C>, src: &[C::Name]) -> bool {
+                <Self as $crate::Predicate>::evaluate(self, plan, src)
+            }
+            fn score<C: $crate::Config>(&self, plan: &$crate::Plan<C>, src: &[C::Name]) -> f64 {
+                <Self as $crate::Predicate>::score(self, plan, src)
+            }
+            fn is_statically_false(&self) -> Option<bool> {
+                <Self as $crate::Predicate>::is_statically_false(self)
+            }
+            fn describe(&self) -> String {
+                <Self as $crate::Predicate>::describe(self)
+            }
+        }
+        $crate::impl_builtin_predicates!(@impl $($predicate)::+; $($rest)*);
+    };
+}
+
+/// Macro to implement this crate's [Predicate] trait for a remote enum_dispatch enum,
+/// forwarding each method to the `Predicate` trait redefined by [predicate_trait], so it can be used in a [Config].
+/// See [impl_builtin_predicates] for an example.
+#[macro_export]
+macro_rules! forward_predicate {
+    ($($predicate:ident)::+, $ty:ty) => {
+        impl $crate::Predicate for $ty {
+            fn evaluate<C: $crate::Config>(&self, plan: &$crate::Plan<C>, src: &[C::Name]) -> bool {
+                <Self as $($predicate)::+>::evaluate(self, plan, src)
+            }
+            fn score<C: $crate::Config>(&self, plan: &$crate::Plan<C>, src: &[C::Name]) -> f64 {
+                <Self as $($predicate)::+>::score(self, plan, src)
+            }
+            fn is_statically_false(&self) -> Option<bool> {
+                <Self as $($predicate)::+>::is_statically_false(self)
+            }
+            fn describe(&self) -> String {
+                <Self as $($predicate)::+>::describe(self)
+            }
+        }
+    };
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct True;
 impl Predicate for True {
-    fn evaluate(&self, _: &Plan<impl Config>, _: &[String]) -> bool {
+    fn evaluate<C: Config>(&self, _: &Plan<C>, _: &[C::Name]) -> bool {
         true
     }
+    fn is_statically_false(&self) -> Option<bool> {
+        Some(true)
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct False;
 impl Predicate for False {
-    fn evaluate(&self, _: &Plan<impl Config>, _: &[String]) -> bool {
+    fn evaluate<C: Config>(&self, _: &Plan<C>, _: &[C::Name]) -> bool {
         false
     }
+    fn is_statically_false(&self) -> Option<bool> {
+        Some(false)
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct And<P>(pub Vec<P>);
 impl<P: Predicate> Predicate for And<P> {
-    fn evaluate(&self, plan: &Plan<impl Config>, src: &[String]) -> bool {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool {
         self.0.iter().all(|pred| pred.evaluate(plan, src))
     }
+    fn is_statically_false(&self) -> Option<bool> {
+        static_all(&self.0)
+    }
+    fn describe(&self) -> String {
+        describe_all("And", &self.0)
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Or<P>(pub Vec<P>);
 impl<P: Predicate> Predicate for Or<P> {
-    fn evaluate(&self, plan: &Plan<impl Config>, src: &[String]) -> bool {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool {
         self.0.iter().any(|pred| pred.evaluate(plan, src))
     }
+    fn is_statically_false(&self) -> Option<bool> {
+        static_any(&self.0)
+    }
+    fn describe(&self) -> String {
+        describe_all("Or", &self.0)
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Xor<P>(pub Vec<P>);
 impl<P: Predicate> Predicate for Xor<P> {
-    fn evaluate(&self, plan: &Plan<impl Config>, src: &[String]) -> bool {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool {
         0 != 1 & self.0.iter().filter(|x| x.evaluate(plan, src)).count()
     }
+    fn is_statically_false(&self) -> Option<bool> {
+        static_parity(&self.0)
+    }
+    fn describe(&self) -> String {
+        describe_all("Xor", &self.0)
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Not<P>(pub Box<P>);
 impl<P: Predicate> Predicate for Not<P> {
-    fn evaluate(&self, plan: &Plan<impl Config>, src: &[String]) -> bool {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool {
         !self.0.evaluate(plan, src)
     }
+    fn is_statically_false(&self) -> Option<bool> {
+        self.0.is_statically_false().map(|x| !x)
+    }
+    fn describe(&self) -> String {
+        format!("Not({})", self.0.describe())
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Nand<P>(pub Vec<P>);
 impl<P: Predicate> Predicate for Nand<P> {
-    fn evaluate(&self, plan: &Plan<impl Config>, src: &[String]) -> bool {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool {
         !self.0.iter().all(|pred| pred.evaluate(plan, src))
     }
+    fn is_statically_false(&self) -> Option<bool> {
+        static_all(&self.0).map(|x| !x)
+    }
+    fn describe(&self) -> String {
+        describe_all("Nand", &self.0)
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Nor<P>(pub Vec<P>);
 impl<P: Predicate> Predicate for Nor<P> {
-    fn evaluate(&self, plan: &Plan<impl Config>, src: &[String]) -> bool {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool {
         !self.0.iter().any(|pred| pred.evaluate(plan, src))
     }
+    fn is_statically_false(&self) -> Option<bool> {
+        static_any(&self.0).map(|x| !x)
+    }
+    fn describe(&self) -> String {
+        describe_all("Nor", &self.0)
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Xnor<P>(pub Vec<P>);
 impl<P: Predicate> Predicate for Xnor<P> {
-    fn evaluate(&self, plan: &Plan<impl Config>, src: &[String]) -> bool {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool {
         0 == 1 & self.0.iter().filter(|x| x.evaluate(plan, src)).count()
     }
+    fn is_statically_false(&self) -> Option<bool> {
+        static_parity(&self.0).map(|x| !x)
+    }
+    fn describe(&self) -> String {
+        describe_all("Xnor", &self.0)
+    }
 }
 
+/// True if all subplans succeed, or all `src` plans if given.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let mut plan = Plan::<config::Default>::new_stub("root", true);
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+/// plan.insert(Plan::new_stub("b", true));
+/// plan.insert(Plan::new(AnySuccessStatus.into(), "c", 1, true));
+/// let holds = |predicate: Predicates, src: &[&str]| {
+///     let src = src.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+///     predicate.evaluate(&plan, &src)
+/// };
+/// // all subplans unless `src` is given
+/// assert!(!holds(AllSuccess.into(), &[]));
+/// assert!(holds(AllSuccess.into(), &["a"]));
+/// assert!(holds(AnySuccess.into(), &[]));
+/// assert!(!holds(AllFailure.into(), &["a", "c"]));
+/// assert!(holds(AnyFailure.into(), &["b", "c"]));
+/// // in progress is neither success nor failure
+/// assert!(!holds(AnySuccess.into(), &["b"]));
+/// assert!(!holds(AnyFailure.into(), &["b"]));
+/// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AllSuccess;
 impl Predicate for AllSuccess {
-    fn evaluate(&self, plan: &Plan<impl Config>, src: &[String]) -> bool {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool {
         all_success(plan, src, false)
     }
 }
 
+/// True if any subplan succeeds, or any `src` plan if given.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AnySuccess;
 impl Predicate for AnySuccess {
-    fn evaluate(&self, plan: &Plan<impl Config>, src: &[String]) -> bool {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool {
         any_success(plan, src, false)
     }
 }
 
+/// True if all subplans fail, or all `src` plans if given.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AllFailure;
 impl Predicate for AllFailure {
-    fn evaluate(&self, plan: &Plan<impl Config>, src: &[String]) -> bool {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool {
         !any_success(plan, src, true)
     }
 }
 
+/// True if any subplan fails, or any `src` plan if given.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AnyFailure;
 impl Predicate for AnyFailure {
-    fn evaluate(&self, plan: &Plan<impl Config>, src: &[String]) -> bool {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool {
         !all_success(plan, src, true)
     }
 }
 
-fn all_success<C: Config>(plan: &Plan<C>, src: &[String], none_val: bool) -> bool {
+/// Name of type `T` with module paths stripped, including those of generic parameters.
+pub fn short_type_name<T: ?Sized>() -> String {
+    let mut name = String::new();
+    let mut path_start = 0;
+    let mut chars = std::any::type_name::<T>().chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            name.truncate(path_start);
+        } else {
+            name.push(c);
+            if !(c.is_alphanumeric() || c == '_') {
+                path_start = name.len();
+            }
+        }
+    }
+    name
+}
+
+/// Name of type `T` without module path or generic parameters.
+pub fn base_type_name<T: ?Sized>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+fn describe_all<P: Predicate>(name: &str, predicates: &[P]) -> String {
+    let inner = predicates.iter().map(P::describe).collect::<Vec<_>>();
+    format!("{name}({})", inner.join(", "))
+}
+
+/// Matches events posted to a plan. See [EventMatches].
+pub trait EventMatcher: 'static {
+    fn matches(&self, event: &impl EnumCast) -> bool;
+}
+
+/// Event matcher that succeeds when the event downcasts to type `E`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EventIs<E>(std::marker::PhantomData<E>);
+impl<E> Default for EventIs<E> {
+    fn default() -> Self {
+        Self(std::marker::PhantomData)
+    }
+}
+impl<E: 'static> EventMatcher for EventIs<E> {
+    fn matches(&self, event: &impl EnumCast) -> bool {
+        event.cast::<E>().is_some()
+    }
+}
+
+/// True if any event visible to the plan during the current tick satisfies the matcher.
+///
+/// ```
+/// use dynamic_plan_tree::{config::*, predicate::*};
+/// use dynamic_plan_tree::behaviour::Broadcast;
+///
+/// let mut plan = Plan::<config::Default>::new_stub("root", true);
+/// plan.post_event(DefaultEvent::Broadcast(Broadcast("go".into())));
+/// let any_broadcast = EventMatches(EventIs::<Broadcast>::default());
+/// let go = EventMatches(Broadcast("go".into()));
+/// let stop = EventMatches(Broadcast("stop".into()));
+/// assert!(!any_broadcast.evaluate(&plan, &[]));
+/// // visible during the next run
+/// plan.run();
+/// assert!(any_broadcast.evaluate(&plan, &[]));
+/// assert!(go.evaluate(&plan, &[]));
+/// assert!(!stop.evaluate(&plan, &[]));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EventMatches<M>(pub M);
+impl<M: EventMatcher> Predicate for EventMatches<M> {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, _: &[C::Name]) -> bool {
+        plan.events().iter().any(|event| self.0.matches(event))
+    }
+}
+
+/// True during the tick in which the transition with matching label fired within the plan.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let mut plan = Plan::<config::Default>::new_stub("root", true);
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+/// plan.insert(Plan::new_stub("b", false));
+/// let mut transition = transition!("a" => "b", AllSuccess);
+/// transition.label = Some("a done".into());
+/// plan.transitions.push(transition);
+/// let fired = TransitionFired { label: "a done".into() };
+/// assert!(!fired.evaluate(&plan, &[]));
+/// plan.run();
+/// assert!(fired.evaluate(&plan, &[]));
+/// plan.run();
+/// assert!(!fired.evaluate(&plan, &[]));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TransitionFired {
+    pub label: String,
+}
+impl Predicate for TransitionFired {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, _: &[C::Name]) -> bool {
+        plan.fired_transitions().contains(&self.label)
+    }
+}
+
+/// Evaluates the predicate of the transition with matching label within the plan, against its `src` plans.
+///
+/// False if no such transition exists. Must not refer to a transition that contains itself.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+///
+/// let mut plan = Plan::<config::Default>::new_stub("root", true);
+/// plan.insert(Plan::new_stub("a", true));
+/// plan.insert(Plan::new_stub("b", false));
+/// let mut transition = transition!("a" => "b", AllSuccess);
+/// transition.label = Some("a done".into());
+/// plan.transitions.push(transition);
+/// let would_fire = TransitionWouldFire { label: "a done".into() };
+/// assert!(!would_fire.evaluate(&plan, &[]));
+/// plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+/// assert!(would_fire.evaluate(&plan, &[]));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TransitionWouldFire {
+    pub label: String,
+}
+impl Predicate for TransitionWouldFire {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, _: &[C::Name]) -> bool {
+        plan.transitions
+            .iter()
+            .find(|t| t.label.as_ref() == Some(&self.label))
+            .map(|t| {
+                t.clauses.iter().all(|(src, p)| p.evaluate(plan, src))
+                    && match t.threshold {
+                        Some(threshold) => t.predicate.score(plan, &t.src) >= threshold,
+                        None => t.predicate.evaluate(plan, &t.src),
+                    }
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Scores the utility of subplan `b` minus that of subplan `a`, holding when positive.
+///
+/// Scores `0.0` if either subplan does not exist, including when `N` differs from the plan name type.
+///
+/// ```
+/// use dynamic_plan_tree::{behaviour::*, config::*, predicate::*};
+/// # use dynamic_plan_tree::config::fixtures::scored;
+///
+/// let mut plan = Plan::<config::Default>::new_stub("root", true);
+/// plan.insert(scored("a", true, 1.));
+/// plan.insert(scored("b", false, 1.5));
+/// let delta = UtilityDelta { a: "a".to_string(), b: "b".to_string() };
+/// assert_eq!(delta.score(&plan, &[]), 0.5);
+/// assert!(delta.evaluate(&plan, &[]));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UtilityDelta<N = DefaultName> {
+    pub a: N,
+    pub b: N,
+}
+impl<N: 'static> Predicate for UtilityDelta<N> {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool {
+        self.score(plan, src) > 0.
+    }
+    fn score<C: Config>(&self, plan: &Plan<C>, _: &[C::Name]) -> f64 {
+        let utility = |name: &N| {
+            let name = (name as &dyn std::any::Any).downcast_ref::<C::Name>()?;
+            Some(plan.get(name)?.utility())
+        };
+        match (utility(&self.a), utility(&self.b)) {
+            (Some(a), Some(b)) => b - a,
+            _ => 0.,
+        }
+    }
+}
+
+/// Scores the numeric value stored under `key` in the data of the plan, holding when positive.
+///
+/// Booleans score `1.0` or `0.0`. Scores `0.0` if missing or not numeric.
+///
+/// ```
+/// use dynamic_plan_tree::{config::*, predicate::*};
+/// use dynamic_plan_tree::serde_value::Value;
+///
+/// let mut plan = Plan::<config::Default>::new_stub("root", true);
+/// let ammo = DataValue { key: "ammo".into() };
+/// assert!(!ammo.evaluate(&plan, &[]));
+/// plan.set_data("ammo", Value::U32(3));
+/// assert_eq!(ammo.score(&plan, &[]), 3.);
+/// assert!(ammo.evaluate(&plan, &[]));
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DataValue {
+    pub key: String,
+}
+impl Predicate for DataValue {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, src: &[C::Name]) -> bool {
+        self.score(plan, src) > 0.
+    }
+    fn score<C: Config>(&self, plan: &Plan<C>, _: &[C::Name]) -> f64 {
+        use serde_value::Value;
+        match plan.data().get(&self.key) {
+            Some(Value::Bool(x)) => *x as u8 as f64,
+            Some(Value::U8(x)) => *x as f64,
+            Some(Value::U16(x)) => *x as f64,
+            Some(Value::U32(x)) => *x as f64,
+            Some(Value::U64(x)) => *x as f64,
+            Some(Value::I8(x)) => *x as f64,
+            Some(Value::I16(x)) => *x as f64,
+            Some(Value::I32(x)) => *x as f64,
+            Some(Value::I64(x)) => *x as f64,
+            Some(Value::F32(x)) => *x as f64,
+            Some(Value::F64(x)) => *x,
+            _ => 0.,
+        }
+    }
+}
+
+/// Folds the static values of `predicates` as in [And].
+fn static_all<P: Predicate>(predicates: &[P]) -> Option<bool> {
+    let values = predicates.iter().map(|x| x.is_statically_false());
+    match values.collect::<Vec<_>>() {
+        x if x.contains(&Some(false)) => Some(false),
+        x if x.contains(&None) => None,
+        _ => Some(true),
+    }
+}
+
+/// Folds the static values of `predicates` as in [Or].
+fn static_any<P: Predicate>(predicates: &[P]) -> Option<bool> {
+    let values = predicates.iter().map(|x| x.is_statically_false());
+    match values.collect::<Vec<_>>() {
+        x if x.contains(&Some(true)) => Some(true),
+        x if x.contains(&None) => None,
+        _ => Some(false),
+    }
+}
+
+/// Folds the static values of `predicates` as in [Xor], only known if all are known.
+fn static_parity<P: Predicate>(predicates: &[P]) -> Option<bool> {
+    predicates
+        .iter()
+        .try_fold(false, |parity, x| Some(parity ^ x.is_statically_false()?))
+}
+
+fn all_success<C: Config>(plan: &Plan<C>, src: &[C::Name], none_val: bool) -> bool {
     let f = |p: &Plan<C>| p.status().unwrap_or(none_val);
     if src.is_empty() {
         plan.plans.iter().all(f)
@@ -147,7 +617,7 @@ fn all_success<C: Config>(plan: &Plan<C>, src: &[String], none_val: bool) -> boo
     }
 }
 
-fn any_success<C: Config>(plan: &Plan<C>, src: &[String], none_val: bool) -> bool {
+fn any_success<C: Config>(plan: &Plan<C>, src: &[C::Name], none_val: bool) -> bool {
     let f = |p: &Plan<C>| p.status().unwrap_or(none_val);
     if src.is_empty() {
         plan.plans.iter().any(f)
@@ -175,6 +645,30 @@ mod tests {
     enum TestPredicate {
         True,
         False,
+        DamageEvent(EventMatches<EventIs<Damage>>),
+        OrderEvent(EventMatches<EventIs<Order>>),
+        HeavyDamage(EventMatches<DamageAbove>),
+    }
+
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Damage(pub u32);
+
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct Order(pub i32, pub i32);
+
+    #[derive(EnumCast)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub enum TestEvent {
+        Damage(Damage),
+        Order(Order),
+    }
+
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct DamageAbove(pub u32);
+    impl EventMatcher for DamageAbove {
+        fn matches(&self, event: &impl EnumCast) -> bool {
+            event.cast::<Damage>().is_some_and(|x| x.0 > self.0)
+        }
     }
 
     #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -182,6 +676,8 @@ mod tests {
     impl Config for TestConfig {
         type Predicate = TestPredicate;
         type Behaviour = SetStatusBehaviour;
+        type Event = TestEvent;
+        type Name = DefaultName;
     }
 
     #[test]
@@ -245,7 +741,7 @@ mod tests {
         assert!(Xnor::<TestPredicate>(vec![True.into(), True.into()]).evaluate(&p, &[]));
     }
 
-    fn make_plan(a: bool, b: bool, c: Option<bool>) -> Plan<impl Config> {
+    fn make_plan(a: bool, b: bool, c: Option<bool>) -> Plan<TestConfig> {
         let mut p = Plan::<TestConfig>::new_stub("", false);
         p.insert(Plan::<TestConfig>::new(
             SetStatusBehaviour(Some(a)),
@@ -268,6 +764,48 @@ mod tests {
         p
     }
 
+    #[test]
+    fn describe() {
+        type P = Predicates;
+        assert_eq!(P::from(True).describe(), "True");
+        let not = Not::<P>(Box::new(False.into()));
+        let and = And::<P>(vec![True.into(), not.into()]);
+        assert_eq!(and.describe(), "And(True, Not(False))");
+        assert_eq!(short_type_name::<Vec<Option<True>>>(), "Vec<Option<True>>");
+        assert_eq!(base_type_name::<Vec<Option<True>>>(), "Vec");
+    }
+
+    #[test]
+    fn is_statically_false() {
+        type P = TestPredicate;
+        let t = || P::from(True);
+        let f = || P::from(False);
+        let e = || P::from(EventMatches(EventIs::<Damage>::default()));
+        assert_eq!(t().is_statically_false(), Some(true));
+        assert_eq!(f().is_statically_false(), Some(false));
+        assert_eq!(e().is_statically_false(), None);
+
+        assert_eq!(And::<P>(vec![t(), f()]).is_statically_false(), Some(false));
+        assert_eq!(And::<P>(vec![e(), f()]).is_statically_false(), Some(false));
+        assert_eq!(And::<P>(vec![e(), t()]).is_statically_false(), None);
+        assert_eq!(And::<P>(vec![t(), t()]).is_statically_false(), Some(true));
+        assert_eq!(And::<P>(vec![]).is_statically_false(), Some(true));
+
+        assert_eq!(Or::<P>(vec![e(), t()]).is_statically_false(), Some(true));
+        assert_eq!(Or::<P>(vec![e(), f()]).is_statically_false(), None);
+        assert_eq!(Or::<P>(vec![f(), f()]).is_statically_false(), Some(false));
+
+        assert_eq!(Nand::<P>(vec![e(), f()]).is_statically_false(), Some(true));
+        assert_eq!(Nor::<P>(vec![e(), t()]).is_statically_false(), Some(false));
+        assert_eq!(Not::<P>(Box::new(f())).is_statically_false(), Some(true));
+        assert_eq!(Not::<P>(Box::new(e())).is_statically_false(), None);
+
+        assert_eq!(Xor::<P>(vec![t(), f()]).is_statically_false(), Some(true));
+        assert_eq!(Xor::<P>(vec![t(), t()]).is_statically_false(), Some(false));
+        assert_eq!(Xor::<P>(vec![t(), e()]).is_statically_false(), None);
+        assert_eq!(Xnor::<P>(vec![t(), t()]).is_statically_false(), Some(true));
+    }
+
     #[test]
     fn all_success() {
         let op = AllSuccess;
@@ -347,4 +885,130 @@ mod tests {
         assert!(op.evaluate(&make_plan(true, false, Some(true)), &src));
         assert!(!op.evaluate(&make_plan(true, true, Some(true)), &src));
     }
+
+    #[test]
+    fn event_matches() {
+        let mut p = Plan::<TestConfig>::new_stub("root", true);
+        for name in ["idle", "evade", "move", "retreat"] {
+            p.insert(Plan::new(SetStatusBehaviour(None), name, 1, name == "idle"));
+        }
+        let transition = |src: &str, dst: &str, predicate: TestPredicate| {
+            Transition::new(vec![src.into()], vec![dst.into()], predicate)
+        };
+        p.transitions = vec![
+            transition(
+                "idle",
+                "evade",
+                EventMatches(EventIs::<Damage>::default()).into(),
+            ),
+            transition(
+                "idle",
+                "move",
+                EventMatches(EventIs::<Order>::default()).into(),
+            ),
+            transition("evade", "retreat", EventMatches(DamageAbove(10)).into()),
+            transition(
+                "evade",
+                "idle",
+                EventMatches(EventIs::<Order>::default()).into(),
+            ),
+        ];
+        let active = |p: &Plan<TestConfig>| {
+            p.plans
+                .iter()
+                .filter(|x| x.active())
+                .map(|x| x.name().clone())
+                .collect::<Vec<_>>()
+        };
+        // no events, no transitions
+        p.run();
+        assert_eq!(active(&p), ["idle"]);
+        assert!(p.events().is_empty());
+        // order event moves
+        p.post_event(TestEvent::Order(Order(1, 2)));
+        p.run();
+        let order = p.events()[0].cast::<Order>().unwrap();
+        assert_eq!((order.0, order.1), (1, 2));
+        assert_eq!(active(&p), ["move"]);
+        // events are only visible for one tick
+        p.run();
+        assert!(p.events().is_empty());
+        // damage event evades instead
+        p.exit(false);
+        p.post_event(TestEvent::Damage(Damage(5)));
+        p.run();
+        assert_eq!(active(&p), ["evade"]);
+        // payload below threshold does not retreat
+        p.post_event(TestEvent::Damage(Damage(5)));
+        p.run();
+        assert_eq!(active(&p), ["evade"]);
+        // payload above threshold retreats
+        p.post_event(TestEvent::Damage(Damage(20)));
+        p.run();
+        assert_eq!(active(&p), ["retreat"]);
+        // pending events are discarded on exit
+        p.post_event(TestEvent::Order(Order(0, 0)));
+        p.exit(false);
+        p.run();
+        assert!(p.events().is_empty());
+        assert_eq!(active(&p), ["idle"]);
+    }
+
+    #[test]
+    fn scored_predicates() {
+        use serde_value::Value;
+
+        #[derive(EnumCast)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct SetUtilityBehaviour(pub f64);
+        impl<C: Config> Behaviour<C> for SetUtilityBehaviour {
+            fn status(&self, _: &Plan<C>) -> Option<bool> {
+                None
+            }
+            fn utility(&self, _: &Plan<C>) -> f64 {
+                self.0
+            }
+        }
+
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        struct UtilityConfig;
+        impl Config for UtilityConfig {
+            type Predicate = Predicates;
+            type Behaviour = SetUtilityBehaviour;
+            type Event = ();
+            type Name = DefaultName;
+        }
+
+        let mut p = Plan::<UtilityConfig>::new(SetUtilityBehaviour(0.), "root", 1, false);
+        p.insert(Plan::new(SetUtilityBehaviour(0.5), "a", 1, false));
+        p.insert(Plan::new(SetUtilityBehaviour(0.75), "b", 1, false));
+
+        // boolean predicates score 1.0 or 0.0
+        assert_eq!(True.score(&p, &[]), 1.);
+        assert_eq!(False.score(&p, &[]), 0.);
+
+        let delta = |a: &str, b: &str| UtilityDelta {
+            a: a.to_string(),
+            b: b.to_string(),
+        };
+        assert_eq!(delta("a", "b").score(&p, &[]), 0.25);
+        assert!(delta("a", "b").evaluate(&p, &[]));
+        assert_eq!(delta("b", "a").score(&p, &[]), -0.25);
+        assert!(!delta("b", "a").evaluate(&p, &[]));
+        assert_eq!(delta("a", "ghost").score(&p, &[]), 0.);
+        assert_eq!(UtilityDelta { a: 0u16, b: 1u16 }.score(&p, &[]), 0.);
+
+        let value = DataValue { key: "x".into() };
+        assert_eq!(value.score(&p, &[]), 0.);
+        p.set_data("x", Value::I32(-2));
+        assert_eq!(value.score(&p, &[]), -2.);
+        assert!(!value.evaluate(&p, &[]));
+        p.set_data("x", Value::F32(1.5));
+        assert_eq!(value.score(&p, &[]), 1.5);
+        assert!(value.evaluate(&p, &[]));
+        p.set_data("x", Value::Bool(true));
+        assert_eq!(value.score(&p, &[]), 1.);
+        p.set_data("x", Value::String("3".into()));
+        assert_eq!(value.score(&p, &[]), 0.);
+    }
 }
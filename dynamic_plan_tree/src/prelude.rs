@@ -0,0 +1,33 @@
+//! Common imports, so that `use dynamic_plan_tree::prelude::*;` is enough to follow the examples.
+//!
+//! The ready-made configs are named through their module, e.g. `Plan::<config::Default>`,
+//! since importing [config::Default] directly would shadow the standard `Default` trait.
+//!
+//! ```
+//! use dynamic_plan_tree::prelude::*;
+//!
+//! let sequence = SequenceBehaviour::<config::Default>::new();
+//! let mut root = Plan::<config::Default>::new(sequence.into(), "root", 1, true);
+//! root.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+//! root.insert(Plan::new(EvaluateStatus(False.into(), False.into()).into(), "b", 1, false));
+//! root.transitions.push(transition!("a" => "b", AllSuccess));
+//! root.run();
+//! assert!(root.get("b").unwrap().active());
+//! ```
+
+pub use crate::behaviour::{
+    AllSuccessStatus, AnySuccessStatus, Behaviour, Behaviours, Broadcast, DelayBehaviour,
+    EvaluateStatus, EventBroadcastBehaviour, FallbackBehaviour, MaxUtilBehaviour, ModifyStatus,
+    ModifyUtilityBehaviour, PreconditionBehaviour, RepeatBehaviour, SequenceBehaviour,
+    TimeoutBehaviour,
+};
+pub use crate::config::{self, DefaultEvent, Minimal};
+pub use crate::predicate::{
+    AllFailure, AllSuccess, And, AnyFailure, AnySuccess, EventMatches, False, Not, Or, Predicate,
+    Predicates, True,
+};
+pub use crate::{
+    enum_dispatch, plan_tree, transition, Config, DefaultName, EnumCast, Plan, Transition,
+};
+#[cfg(feature = "serde")]
+pub use crate::{Deserialize, Serialize};
@@ -0,0 +1,393 @@
+use crate::*;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Immutable state of a plan tree as of the end of a tick. See [Plan::shared_view].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PlanSnapshot<N = DefaultName> {
+    /// Number of ticks run when the snapshot was taken.
+    pub tick: u64,
+    /// State of every plan in the tree depth first, starting with the root.
+    pub plans: Vec<PlanState<N>>,
+}
+
+impl<N> Default for PlanSnapshot<N> {
+    fn default() -> Self {
+        Self {
+            tick: 0,
+            plans: Vec::new(),
+        }
+    }
+}
+
+/// State of a single plan within a [PlanSnapshot].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PlanState<N = DefaultName> {
+    pub name: N,
+    /// Index of the parent plan within [PlanSnapshot::plans], `None` for the root.
+    pub parent: Option<usize>,
+    pub active: bool,
+    pub status: Option<bool>,
+}
+
+/// Handle to the latest [PlanSnapshot] of a tree, readable from any thread. See [Plan::shared_view].
+pub struct PlanView<N = DefaultName> {
+    pub(crate) latest: Arc<Mutex<Arc<PlanSnapshot<N>>>>,
+}
+
+impl<N> Clone for PlanView<N> {
+    fn clone(&self) -> Self {
+        Self {
+            latest: self.latest.clone(),
+        }
+    }
+}
+
+impl<N> PlanView<N> {
+    /// Latest published snapshot, which stays unchanged for as long as it is held.
+    pub fn snapshot(&self) -> Arc<PlanSnapshot<N>> {
+        self.latest.lock().unwrap().clone()
+    }
+}
+
+/// Publishing side of a [PlanView], keeping the previously published snapshot to reuse its buffers.
+pub(crate) struct Publisher<N> {
+    pub(crate) view: PlanView<N>,
+    pub(crate) spare: Arc<PlanSnapshot<N>>,
+}
+
+/// Changes between two [PlanSnapshot]s, with plans identified by their path of names from the root.
+///
+/// Computed by [SnapshotDelta::between] and applied by [apply_delta]. See [DeltaStreamer].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "N: Deserialize<'de>")))]
+pub struct SnapshotDelta<N = DefaultName> {
+    /// Tick of the newer snapshot.
+    pub tick: u64,
+    /// Paths of plans no longer in the tree.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub removed: Vec<Vec<N>>,
+    /// Plans added to the tree with all fields, or existing plans with only the fields that changed.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub changed: Vec<PlanStateDelta<N>>,
+}
+
+/// Changed fields of a single plan within a [SnapshotDelta].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "N: Deserialize<'de>")))]
+pub struct PlanStateDelta<N = DefaultName> {
+    pub path: Vec<N>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub active: Option<bool>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "present"
+        )
+    )]
+    pub status: Option<Option<bool>>,
+}
+
+/// Deserialize a field that is present, even if null, as `Some`.
+#[cfg(feature = "serde")]
+fn present<'de, D: serde::Deserializer<'de>, T: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
+}
+
+impl<N: Ord + Clone> SnapshotDelta<N> {
+    /// Changes required to turn `previous` into `next`.
+    pub fn between(previous: &PlanSnapshot<N>, next: &PlanSnapshot<N>) -> Self {
+        let mut previous = by_path(previous);
+        let changed = by_path(next)
+            .into_iter()
+            .filter_map(|(path, state)| {
+                let (active, status) = match previous.remove(&path) {
+                    None => (Some(state.active), Some(state.status)),
+                    Some(old) => (
+                        (old.active != state.active).then_some(state.active),
+                        (old.status != state.status).then_some(state.status),
+                    ),
+                };
+                (active.is_some() || status.is_some()).then_some(PlanStateDelta {
+                    path,
+                    active,
+                    status,
+                })
+            })
+            .collect();
+        Self {
+            tick: next.tick,
+            // plans remaining from the previous snapshot are absent from the next
+            removed: previous.into_keys().collect(),
+            changed,
+        }
+    }
+}
+
+/// Apply changes from `delta` to `snapshot`, which must be the snapshot the delta was computed from.
+///
+/// Plans are kept depth first with subplans sorted by name, matching the order of snapshots of a tree.
+pub fn apply_delta<N: Ord + Clone>(snapshot: &mut PlanSnapshot<N>, delta: &SnapshotDelta<N>) {
+    let mut states = by_path(snapshot)
+        .into_iter()
+        .map(|(path, state)| (path, (state.active, state.status)))
+        .collect::<BTreeMap<_, _>>();
+    for path in &delta.removed {
+        states.remove(path);
+    }
+    for change in &delta.changed {
+        let state = states.entry(change.path.clone()).or_default();
+        if let Some(active) = change.active {
+            state.0 = active;
+        }
+        if let Some(status) = change.status {
+            state.1 = status;
+        }
+    }
+    // lexicographic order of paths is depth first with subplans sorted by name
+    let mut indices = BTreeMap::new();
+    snapshot.tick = delta.tick;
+    snapshot.plans.clear();
+    for (path, (active, status)) in states {
+        let Some((name, parent)) = path.split_last() else {
+            continue;
+        };
+        snapshot.plans.push(PlanState {
+            name: name.clone(),
+            parent: indices.get(parent).copied(),
+            active,
+            status,
+        });
+        indices.insert(path, snapshot.plans.len() - 1);
+    }
+}
+
+/// State of each plan in a snapshot by its path of names from the root.
+fn by_path<N: Ord + Clone>(snapshot: &PlanSnapshot<N>) -> BTreeMap<Vec<N>, &PlanState<N>> {
+    let mut paths = Vec::<Vec<N>>::with_capacity(snapshot.plans.len());
+    for state in &snapshot.plans {
+        let mut path = match state.parent {
+            Some(parent) => paths[parent].clone(),
+            None => Vec::new(),
+        };
+        path.push(state.name.clone());
+        paths.push(path);
+    }
+    paths.into_iter().zip(&snapshot.plans).collect()
+}
+
+/// Frame of a snapshot stream produced by [DeltaStreamer].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SnapshotFrame<N = DefaultName> {
+    /// Complete snapshot that replaces any previous one.
+    Keyframe(PlanSnapshot<N>),
+    /// Changes to apply to the previous snapshot.
+    Delta(SnapshotDelta<N>),
+}
+
+impl<N: Ord + Clone> SnapshotFrame<N> {
+    /// Update the snapshot reconstructed from previous frames, if any, with this frame.
+    ///
+    /// Deltas are ignored until the first keyframe.
+    pub fn apply(&self, snapshot: &mut Option<PlanSnapshot<N>>) {
+        match (self, snapshot) {
+            (SnapshotFrame::Keyframe(keyframe), snapshot) => *snapshot = Some(keyframe.clone()),
+            (SnapshotFrame::Delta(delta), Some(snapshot)) => apply_delta(snapshot, delta),
+            (SnapshotFrame::Delta(_), None) => {}
+        }
+    }
+}
+
+/// Encodes a sequence of snapshots as periodic keyframes with deltas in between.
+pub struct DeltaStreamer<N = DefaultName> {
+    /// Number of frames from one keyframe to the next, where every frame is a keyframe if at most 1.
+    pub keyframe_interval: u64,
+    previous: Option<PlanSnapshot<N>>,
+    frames: u64,
+}
+
+impl<N: Ord + Clone> DeltaStreamer<N> {
+    pub fn new(keyframe_interval: u64) -> Self {
+        Self {
+            keyframe_interval,
+            previous: None,
+            frames: 0,
+        }
+    }
+
+    /// Encode the next snapshot of the sequence, starting with a keyframe.
+    pub fn next_frame(&mut self, snapshot: &PlanSnapshot<N>) -> SnapshotFrame<N> {
+        let keyframe = self.frames.is_multiple_of(self.keyframe_interval.max(1));
+        self.frames += 1;
+        let frame = match (&self.previous, keyframe) {
+            (Some(previous), false) => {
+                SnapshotFrame::Delta(SnapshotDelta::between(previous, snapshot))
+            }
+            _ => SnapshotFrame::Keyframe(snapshot.clone()),
+        };
+        match &mut self.previous {
+            Some(previous) => previous.clone_from(snapshot),
+            None => self.previous = Some(snapshot.clone()),
+        }
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(EnumCast)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct CycleStatusBehaviour(u32);
+    impl<C: Config> Behaviour<C> for CycleStatusBehaviour {
+        fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+            match self.0 % 3 {
+                0 => None,
+                1 => Some(true),
+                _ => Some(false),
+            }
+        }
+        fn on_run(&mut self, _plan: &mut Plan<C>) {
+            self.0 += 1;
+        }
+    }
+
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    struct TestConfig;
+    impl Config for TestConfig {
+        type Predicate = predicate::Predicates;
+        type Behaviour = CycleStatusBehaviour;
+        type Event = ();
+        type Name = DefaultName;
+    }
+
+    fn new_plan(name: &str, run_interval: u32) -> Plan<TestConfig> {
+        Plan::new(CycleStatusBehaviour(0), name, run_interval, true)
+    }
+
+    #[test]
+    fn delta_stream() {
+        let mut root_plan = new_plan("root", 1);
+        root_plan.insert(new_plan("a", 2));
+        root_plan.insert(new_plan("b", 3));
+        let view = root_plan.shared_view();
+        let mut streamer = DeltaStreamer::new(4);
+        let mut reconstructed = None;
+        for tick in 1..=30 {
+            match tick {
+                10 => {
+                    let c = root_plan.insert(new_plan("c", 1));
+                    c.insert(new_plan("x", 2));
+                }
+                15 => {
+                    root_plan.exit_plan("b");
+                }
+                20 => {
+                    root_plan.remove("a");
+                }
+                _ => {}
+            }
+            root_plan.run();
+            let snapshot = view.snapshot();
+            let frame = streamer.next_frame(&snapshot);
+            assert_eq!(matches!(frame, SnapshotFrame::Keyframe(_)), tick % 4 == 1);
+            // frames survive the trip over the wire
+            #[cfg(feature = "serde")]
+            let frame =
+                serde_json::from_slice::<SnapshotFrame>(&serde_json::to_vec(&frame).unwrap())
+                    .unwrap();
+            frame.apply(&mut reconstructed);
+            let reconstructed = reconstructed.as_ref().unwrap();
+            assert_eq!(reconstructed, &*snapshot);
+            #[cfg(feature = "serde")]
+            assert_eq!(
+                serde_json::to_vec(reconstructed).unwrap(),
+                serde_json::to_vec(&*snapshot).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn delta_contents() {
+        let mut root_plan = new_plan("root", 1);
+        root_plan.insert(new_plan("a", 1));
+        root_plan.insert(new_plan("b", 1));
+        let view = root_plan.shared_view();
+        let previous = view.snapshot();
+        root_plan.remove("b");
+        root_plan.get_mut("a").unwrap().insert(new_plan("x", 1));
+        root_plan.run();
+        let delta = SnapshotDelta::between(&previous, &view.snapshot());
+        let path = |x: &[&str]| x.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+        assert_eq!(delta.removed, [path(&["root", "b"])]);
+        assert_eq!(
+            delta.changed,
+            [
+                PlanStateDelta {
+                    path: path(&["root"]),
+                    active: Some(true),
+                    status: Some(Some(true)),
+                },
+                PlanStateDelta {
+                    path: path(&["root", "a"]),
+                    active: Some(true),
+                    status: Some(Some(true)),
+                },
+                PlanStateDelta {
+                    path: path(&["root", "a", "x"]),
+                    active: Some(true),
+                    status: Some(Some(true)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn compact_delta() {
+        let mut root_plan = new_plan("root", 1);
+        let view = root_plan.shared_view();
+        root_plan.run();
+        let previous = view.snapshot();
+        // unchanged fields are skipped
+        root_plan.run();
+        let delta = SnapshotDelta::between(&previous, &view.snapshot());
+        let json = serde_json::to_string(&delta).unwrap();
+        assert_eq!(
+            json,
+            r#"{"tick":2,"changed":[{"path":["root"],"status":false}]}"#
+        );
+        // a status changing to in progress is distinct from an unchanged status
+        let previous = view.snapshot();
+        root_plan.run();
+        let delta = SnapshotDelta::between(&previous, &view.snapshot());
+        let json = serde_json::to_string(&delta).unwrap();
+        assert_eq!(
+            json,
+            r#"{"tick":3,"changed":[{"path":["root"],"status":null}]}"#
+        );
+        assert_eq!(serde_json::from_str::<SnapshotDelta>(&json).unwrap(), delta);
+        let previous = view.snapshot();
+        let delta = SnapshotDelta::between(&previous, &previous);
+        assert_eq!(serde_json::to_string(&delta).unwrap(), r#"{"tick":3}"#);
+    }
+}
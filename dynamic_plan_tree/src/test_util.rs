@@ -0,0 +1,406 @@
+//! Utilities for testing user defined plan trees.
+
+use crate::*;
+
+/// Assert that a plan tree produces the same active sets and statuses with sequential and parallel execution.
+///
+/// The tree returned by `builder` is run for `ticks` ticks twice, once on a single thread and once on
+/// multiple threads when the `rayon` feature is enabled. The state of every plan is compared after each tick,
+/// panicking with a diff at the first divergence. Without `rayon` both runs are sequential,
+/// which still surfaces behaviours that are not reproducible across runs.
+///
+/// Behaviours that fail this check typically depend on the order sibling plans run in, such as:
+/// - Sharing mutable state between siblings through interior mutability, e.g. a common counter or queue.
+/// - Claiming a limited external resource on a first come first served basis.
+/// - Reading clocks, thread identity, or unseeded randomness.
+pub fn assert_deterministic<C: Config>(builder: impl Fn() -> Plan<C> + Sync, ticks: usize) {
+    let sequential = record(&builder, ticks, 1);
+    let parallel = record(&builder, ticks, 4);
+    for (tick, (a, b)) in sequential.iter().zip(&parallel).enumerate() {
+        if a == b {
+            continue;
+        }
+        let diff = a
+            .iter()
+            .filter(|x| !b.contains(x))
+            .map(|x| format!("- {}", x))
+            .chain(
+                b.iter()
+                    .filter(|x| !a.contains(x))
+                    .map(|x| format!("+ {}", x)),
+            )
+            .collect::<Vec<_>>()
+            .join("\n");
+        panic!(
+            "sequential (-) and parallel (+) execution diverged at tick {}:\n{}",
+            tick, diff
+        );
+    }
+}
+
+/// Run the tree and record the state of every plan after each tick.
+fn record<C: Config>(
+    builder: &(impl Fn() -> Plan<C> + Sync),
+    ticks: usize,
+    _threads: usize,
+) -> Vec<Vec<String>> {
+    let run = || {
+        let mut plan = builder();
+        (0..ticks)
+            .map(|_| {
+                plan.run();
+                let mut states = Vec::new();
+                snapshot(&plan, "", &mut states);
+                states
+            })
+            .collect()
+    };
+    #[cfg(feature = "rayon")]
+    return rayon::ThreadPoolBuilder::new()
+        .num_threads(_threads)
+        .build()
+        .unwrap()
+        .install(run);
+    #[cfg(not(feature = "rayon"))]
+    run()
+}
+
+fn snapshot<C: Config>(plan: &Plan<C>, parent: &str, states: &mut Vec<String>) {
+    let path = format!("{}/{:?}", parent, plan.name());
+    states.push(format!(
+        "{} active={} status={:?}",
+        path,
+        plan.active(),
+        plan.status()
+    ));
+    plan.plans
+        .iter()
+        .for_each(|plan| snapshot(plan, &path, states));
+}
+
+/// Assert that randomly generated plan trees are unchanged by a round trip through JSON.
+///
+/// `count` trees are generated from `seed`, with random structure, names, transitions, and behaviour and
+/// predicate variants. Values are generated from the serde schema of the config types traced with
+/// `serde-reflection`, so every serialized field of every variant is exercised, including those of
+/// user defined behaviours and predicates. Each tree is serialized to JSON, deserialized, and serialized again,
+/// panicking if the two documents differ or if a value conforming to the schema fails to deserialize.
+///
+/// Typical mistakes caught are mismatched `serialize_with`/`deserialize_with` pairs, `skip_serializing_if`
+/// without a matching `default`, and fields lost by custom implementations.
+///
+/// Enums nested within behaviours or predicates are only generated with the variants found by tracing,
+/// which is the first variant unless the enum also appears directly in a config type.
+///
+/// ```
+/// use dynamic_plan_tree::{config, test_util::fuzz_roundtrip};
+///
+/// fuzz_roundtrip::<config::Default>(10, 0);
+/// ```
+#[cfg(feature = "serde")]
+pub fn fuzz_roundtrip<C: Config>(count: usize, seed: u64)
+where
+    Plan<C>: Serialize + serde::de::DeserializeOwned,
+{
+    let schema = fuzz::Schema::trace::<C>();
+    let mut rng = fuzz::Rng(seed);
+    for i in 0..count {
+        let plan = schema.plan::<C>(&mut rng, 0);
+        let json = serde_json::to_value(&plan).expect("failed to serialize plan");
+        let restored = serde_json::from_value::<Plan<C>>(json.clone()).unwrap_or_else(|e| {
+            panic!("plan {i} of seed {seed} failed to deserialize: {e}\n{json:#}");
+        });
+        let restored = serde_json::to_value(&restored).expect("failed to serialize plan");
+        if json != restored {
+            panic!(
+                "plan {i} of seed {seed} changed in a JSON round trip:\n{json:#}\nrestored as:\n{restored:#}"
+            );
+        }
+    }
+}
+
+/// Random generation of values from a traced serde schema. See [fuzz_roundtrip].
+#[cfg(feature = "serde")]
+mod fuzz {
+    use crate::*;
+    use serde::de::DeserializeOwned;
+    use serde_json::{json, Value};
+    use serde_reflection::{
+        ContainerFormat, Format, FormatHolder, Named, Registry, Tracer, TracerConfig, VariantFormat,
+    };
+    use std::collections::BTreeMap;
+
+    /// Depth of nested containers beyond which the shallowest variants are chosen.
+    const MAX_VALUE_DEPTH: usize = 4;
+    /// Depth of generated plan trees, with the root at depth 0.
+    const MAX_PLAN_DEPTH: usize = 2;
+
+    /// SplitMix64 generator, to keep generated trees reproducible without extra dependencies.
+    pub struct Rng(pub u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+
+        fn chance(&mut self, one_in: usize) -> bool {
+            self.below(one_in) == 0
+        }
+    }
+
+    pub struct Schema {
+        registry: Registry,
+        /// Minimum nesting depth of values of each container.
+        heights: BTreeMap<String, usize>,
+        behaviour: Format,
+        predicate: Format,
+        name: Format,
+        transition: Format,
+    }
+
+    impl Schema {
+        pub fn trace<C: Config>() -> Self {
+            let mut tracer = Tracer::new(TracerConfig::default());
+            let behaviour = trace::<C::Behaviour>(&mut tracer, "behaviour");
+            let predicate = trace::<C::Predicate>(&mut tracer, "predicate");
+            let name = trace::<C::Name>(&mut tracer, "name");
+            let transition = trace::<Transition<C::Predicate, C::Name>>(&mut tracer, "transition");
+            let mut schema = Self {
+                // enums nested in structs may be incomplete, leaving out their untraced variants
+                registry: tracer.registry_unchecked(),
+                heights: BTreeMap::new(),
+                behaviour,
+                predicate,
+                name,
+                transition,
+            };
+            // iterate to a fixed point, since heights only decrease
+            loop {
+                let heights = schema.registry.iter().filter_map(|(name, container)| {
+                    let height = schema.container_height(container)?;
+                    let lower = schema.heights.get(name).is_none_or(|x| height < *x);
+                    lower.then(|| (name.clone(), height))
+                });
+                let heights = heights.collect::<Vec<_>>();
+                if heights.is_empty() {
+                    break;
+                }
+                schema.heights.extend(heights);
+            }
+            schema
+        }
+
+        /// Random plan tree with subplans down to [MAX_PLAN_DEPTH].
+        pub fn plan<C: Config>(&self, rng: &mut Rng, depth: usize) -> Plan<C> {
+            let behaviour = self.generate(rng, &self.behaviour, "behaviour");
+            let name = self.generate::<C::Name>(rng, &self.name, "name");
+            let mut plan = Plan::<C>::new(behaviour, name, 1 + rng.below(3) as u32, rng.chance(2));
+            if rng.chance(4) {
+                plan.set_autostart_if(Some(self.generate(rng, &self.predicate, "predicate")));
+            }
+            if rng.chance(4) {
+                plan.seed = Some(rng.next());
+            }
+            if rng.chance(4) {
+                plan.tags_mut().push(format!("tag{}", rng.below(3)));
+            }
+            if rng.chance(4) {
+                let value = serde_value::Value::I64(rng.below(100) as i64);
+                plan.set_data(format!("key{}", rng.below(3)), value);
+            }
+            let children = if depth < MAX_PLAN_DEPTH {
+                rng.below(4)
+            } else {
+                0
+            };
+            for _ in 0..children {
+                plan.insert(self.plan(rng, depth + 1));
+            }
+            let names = plan
+                .plans
+                .iter()
+                .map(|x| x.name().clone())
+                .collect::<Vec<_>>();
+            let transitions = if names.is_empty() { 0 } else { rng.below(3) };
+            for _ in 0..transitions {
+                let mut transition: Transition<C::Predicate, C::Name> =
+                    self.generate(rng, &self.transition, "transition");
+                transition.src = vec![names[rng.below(names.len())].clone()];
+                transition.dst = vec![names[rng.below(names.len())].clone()];
+                plan.transitions.push(transition);
+            }
+            plan
+        }
+
+        fn generate<T: DeserializeOwned>(&self, rng: &mut Rng, format: &Format, what: &str) -> T {
+            let value = self.value(rng, format, 0);
+            serde_json::from_value(value.clone()).unwrap_or_else(|e| {
+                panic!("generated {what} does not deserialize: {e}\n{value:#}");
+            })
+        }
+
+        fn value(&self, rng: &mut Rng, format: &Format, depth: usize) -> Value {
+            let deep = depth >= MAX_VALUE_DEPTH;
+            match format {
+                Format::Variable(_) | Format::Unit => Value::Null,
+                Format::TypeName(name) => self.container(rng, name, depth + 1),
+                Format::Bool => json!(rng.chance(2)),
+                Format::I8 | Format::I16 | Format::I32 | Format::I64 | Format::I128 => {
+                    json!(rng.below(201) as i64 - 100)
+                }
+                Format::U8 | Format::U16 | Format::U32 | Format::U64 | Format::U128 => {
+                    json!(rng.below(101))
+                }
+                // exactly representable in both precisions
+                Format::F32 | Format::F64 => json!((rng.below(801) as f64 - 400.) / 4.),
+                Format::Char => json!(((b'a' + rng.below(26) as u8) as char).to_string()),
+                Format::Str => json!(format!("s{}", rng.below(100))),
+                Format::Bytes => json!((0..rng.below(4))
+                    .map(|_| rng.below(256))
+                    .collect::<Vec<_>>()),
+                Format::Option(format) => {
+                    if deep || rng.chance(2) {
+                        Value::Null
+                    } else {
+                        self.value(rng, format, depth)
+                    }
+                }
+                Format::Seq(format) => {
+                    let len = if deep { 0 } else { rng.below(3) };
+                    Value::Array((0..len).map(|_| self.value(rng, format, depth)).collect())
+                }
+                Format::Map { key, value } => {
+                    let len = if deep { 0 } else { rng.below(3) };
+                    let entries = (0..len).filter_map(|_| {
+                        // JSON keys are strings, which serde parses back into numbers as needed
+                        let key = match self.value(rng, key, depth) {
+                            Value::String(key) => key,
+                            Value::Number(key) => key.to_string(),
+                            _ => return None,
+                        };
+                        Some((key, self.value(rng, value, depth)))
+                    });
+                    Value::Object(entries.collect())
+                }
+                Format::Tuple(formats) => self.values(rng, formats.iter(), depth),
+                Format::TupleArray { content, size } => {
+                    self.values(rng, std::iter::repeat_n(&**content, *size), depth)
+                }
+            }
+        }
+
+        fn values<'a>(
+            &self,
+            rng: &mut Rng,
+            formats: impl Iterator<Item = &'a Format>,
+            depth: usize,
+        ) -> Value {
+            Value::Array(formats.map(|x| self.value(rng, x, depth)).collect())
+        }
+
+        fn fields(&self, rng: &mut Rng, fields: &[Named<Format>], depth: usize) -> Value {
+            let fields = fields
+                .iter()
+                .map(|x| (x.name.clone(), self.value(rng, &x.value, depth)));
+            Value::Object(fields.collect())
+        }
+
+        fn container(&self, rng: &mut Rng, name: &str, depth: usize) -> Value {
+            let Some(container) = self.registry.get(name) else {
+                return Value::Null;
+            };
+            match container {
+                ContainerFormat::UnitStruct => Value::Null,
+                ContainerFormat::NewTypeStruct(format) => self.value(rng, format, depth),
+                ContainerFormat::TupleStruct(formats) => self.values(rng, formats.iter(), depth),
+                ContainerFormat::Struct(fields) => self.fields(rng, fields, depth),
+                ContainerFormat::Enum(variants) => {
+                    // past the depth limit, only the shallowest variants to guarantee termination
+                    let limit = if depth >= MAX_VALUE_DEPTH {
+                        self.heights.get(name).copied()
+                    } else {
+                        Some(usize::MAX)
+                    };
+                    let variants = variants.values().filter(|x| {
+                        let height = self.variant_height(&x.value);
+                        height.is_some() && height <= limit
+                    });
+                    let variants = variants.collect::<Vec<_>>();
+                    if variants.is_empty() {
+                        return Value::Null;
+                    }
+                    let variant = variants[rng.below(variants.len())];
+                    let content = match &variant.value {
+                        VariantFormat::Variable(_) => Value::Null,
+                        VariantFormat::Unit => return json!(variant.name),
+                        VariantFormat::NewType(format) => self.value(rng, format, depth),
+                        VariantFormat::Tuple(formats) => self.values(rng, formats.iter(), depth),
+                        VariantFormat::Struct(fields) => self.fields(rng, fields, depth),
+                    };
+                    json!({ variant.name.clone(): content })
+                }
+            }
+        }
+
+        /// Minimum nesting depth of values of `format`, or `None` if no finite value is known.
+        fn height(&self, format: &Format) -> Option<usize> {
+            match format {
+                Format::Variable(_) => None,
+                Format::TypeName(name) => self.heights.get(name).map(|x| x + 1),
+                Format::Tuple(formats) => max_height(formats.iter().map(|x| self.height(x))),
+                Format::TupleArray { content, size } if *size > 0 => self.height(content),
+                // options, sequences, and maps may be empty
+                _ => Some(0),
+            }
+        }
+
+        fn variant_height(&self, variant: &VariantFormat) -> Option<usize> {
+            match variant {
+                VariantFormat::Variable(_) => None,
+                VariantFormat::Unit => Some(0),
+                VariantFormat::NewType(format) => self.height(format),
+                VariantFormat::Tuple(formats) => max_height(formats.iter().map(|x| self.height(x))),
+                VariantFormat::Struct(fields) => {
+                    max_height(fields.iter().map(|x| self.height(&x.value)))
+                }
+            }
+        }
+
+        fn container_height(&self, container: &ContainerFormat) -> Option<usize> {
+            match container {
+                ContainerFormat::UnitStruct => Some(0),
+                ContainerFormat::NewTypeStruct(format) => self.height(format),
+                ContainerFormat::TupleStruct(formats) => {
+                    max_height(formats.iter().map(|x| self.height(x)))
+                }
+                ContainerFormat::Struct(fields) => {
+                    max_height(fields.iter().map(|x| self.height(&x.value)))
+                }
+                ContainerFormat::Enum(variants) => variants
+                    .values()
+                    .filter_map(|x| self.variant_height(&x.value))
+                    .min(),
+            }
+        }
+    }
+
+    fn trace<T: DeserializeOwned>(tracer: &mut Tracer, what: &str) -> Format {
+        let (mut format, _) = tracer
+            .trace_simple_type::<T>()
+            .unwrap_or_else(|e| panic!("failed to trace {what} schema: {e}"));
+        format.reduce();
+        format
+    }
+
+    fn max_height(mut heights: impl Iterator<Item = Option<usize>>) -> Option<usize> {
+        heights.try_fold(0, |max, x| Some(max.max(x?)))
+    }
+}
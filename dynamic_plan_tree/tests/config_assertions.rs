@@ -0,0 +1,7 @@
+/// Expected errors are recorded without optional features, as the bounds checked depend on them.
+#[test]
+#[cfg(not(any(feature = "serde", feature = "rayon")))]
+fn config_assertion_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui_config/*.rs");
+}
@@ -0,0 +1,96 @@
+//! Downstream generic code written once against the `features` helpers,
+//! compiled under whichever feature combination the test run enables.
+
+use dynamic_plan_tree::behaviour::*;
+use dynamic_plan_tree::features::*;
+
+mod remote {
+    use dynamic_plan_tree::*;
+    behaviour_trait!();
+}
+
+/// Behaviour wrapper generic over the config, with one set of bounds for all features.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Counted<B> {
+    inner: B,
+    runs: u32,
+}
+
+impl<C: Config, B: Behaviour<C> + MaybeSend + MaybeSerde> Behaviour<C> for Counted<B> {
+    fn status(&self, plan: &Plan<C>) -> Option<bool> {
+        self.inner.status(plan)
+    }
+    fn on_run(&mut self, plan: &mut Plan<C>) {
+        self.runs += 1;
+        self.inner.on_run(plan);
+    }
+}
+
+#[enum_dispatch(Behaviour<C>)]
+#[derive(EnumCast)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum MatrixBehaviours<C: Config> {
+    AllSuccessStatus,
+    Counted(Counted<AllSuccessStatus>),
+    EvaluateStatus(EvaluateStatus<C>),
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct MatrixConfig;
+impl Config for MatrixConfig {
+    type Predicate = predicate::Predicates;
+    type Behaviour = MatrixBehaviours<Self>;
+    type Event = ();
+    type Name = DefaultName;
+}
+
+assert_config!(MatrixConfig);
+assert_behaviour_in_config!(MatrixConfig, Counted<AllSuccessStatus>);
+assert_predicate_in_config!(MatrixConfig, predicate::True);
+
+/// Bounds implied by `Config` under every feature combination.
+fn behaviour_bounds<C: Config>(behaviour: BehaviourOf<C>) -> impl MaybeSend + MaybeSerde {
+    behaviour
+}
+fn name_bounds<C: Config>(name: NameOf<C>) -> impl MaybeSend + MaybeSync + MaybeSerde {
+    name
+}
+
+fn names<C: Config>(plan: &Plan<C>) -> Vec<NameOf<C>> {
+    plan.plans.iter().map(|p| p.name().clone()).collect()
+}
+
+#[test]
+fn feature_flags() {
+    assert_eq!(features::SERDE, cfg!(feature = "serde"));
+    assert_eq!(features::RAYON, cfg!(feature = "rayon"));
+    assert_eq!(features::JOURNAL, cfg!(feature = "journal"));
+    assert_eq!(features::TIMING, cfg!(feature = "timing"));
+    assert_eq!(features::TEST_UTIL, cfg!(feature = "test-util"));
+}
+
+#[test]
+fn generic_bounds() {
+    let counted = Counted {
+        inner: AllSuccessStatus,
+        runs: 0,
+    };
+    let mut plan = Plan::<MatrixConfig>::new(counted.into(), "root", 1, true);
+    let _ = behaviour_bounds::<MatrixConfig>(AllSuccessStatus.into());
+    plan.insert(Plan::new(AllSuccessStatus.into(), "a", 1, true));
+    plan.run();
+    plan.run();
+    assert_eq!(names(&plan), ["a"]);
+    let _ = name_bounds::<MatrixConfig>(names(&plan).remove(0));
+    assert_eq!(plan.cast::<Counted<AllSuccessStatus>>().unwrap().runs, 2);
+    assert_eq!(plan.status(), Some(true));
+
+    if features::SERDE {
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&plan).unwrap();
+            let plan: Plan<MatrixConfig> = serde_json::from_str(&json).unwrap();
+            assert_eq!(plan.cast::<Counted<AllSuccessStatus>>().unwrap().runs, 2);
+        }
+    }
+}
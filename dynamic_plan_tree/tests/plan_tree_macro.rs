@@ -0,0 +1,5 @@
+#[test]
+fn plan_tree_syntax_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,54 @@
+use dynamic_plan_tree::prelude::*;
+
+#[test]
+fn default_config() {
+    let pending = || EvaluateStatus(False.into(), False.into());
+    let mut root = plan_tree!(config::Default; "root" autostart {
+        behaviour: SequenceBehaviour::new(),
+        children: {
+            "a" autostart { behaviour: AllSuccessStatus },
+            "b" { behaviour: pending() },
+        },
+        transitions: [["a"] -> ["b"] if AllSuccess],
+    });
+    root.run();
+    assert!(root.get("b").unwrap().active());
+    assert_eq!(root.status(), None);
+    assert!(root.cast::<SequenceBehaviour<config::Default>>().is_some());
+}
+
+#[test]
+fn minimal_config() {
+    let mut root = Plan::<Minimal>::new(FallbackBehaviour::new().into(), 0u16, 1, true);
+    root.insert(Plan::new(AnySuccessStatus.into(), 1u16, 1, true));
+    root.insert(Plan::new(AllSuccessStatus.into(), 2u16, 1, false));
+    root.transitions.push(transition!(1u16 => 2u16, AnyFailure));
+    root.run();
+    assert!(root.get(&2).unwrap().active());
+    assert_eq!(root.status(), Some(true));
+}
+
+#[test]
+fn custom_config() {
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    struct MyConfig;
+    impl Config for MyConfig {
+        type Predicate = Predicates;
+        type Behaviour = Behaviours<Self>;
+        type Event = ();
+        type Name = DefaultName;
+    }
+
+    let mut root = Plan::<MyConfig>::new(MaxUtilBehaviour::default().into(), "root", 1, true);
+    for (name, utility) in [("low", 1.), ("high", 2.)] {
+        let pending = EvaluateStatus(False.into(), False.into());
+        let behaviour = ModifyUtilityBehaviour::new(pending.into(), 0., utility);
+        root.insert(Plan::new(behaviour.into(), name, 1, false));
+    }
+    root.run();
+    let active = root.plans.iter().filter(|x| x.active());
+    assert_eq!(
+        active.map(|x| x.name().as_str()).collect::<Vec<_>>(),
+        ["high"]
+    );
+}
@@ -0,0 +1,16 @@
+use dynamic_plan_tree::*;
+
+struct TestConfig;
+impl Config for TestConfig {
+    type Predicate = predicate::Predicates;
+    type Behaviour = behaviour::Behaviours<Self>;
+    type Event = ();
+    type Name = DefaultName;
+}
+
+
+fn main() {
+    plan_tree!(TestConfig; "root" {
+        behavior: behaviour::AllSuccessStatus,
+    });
+}
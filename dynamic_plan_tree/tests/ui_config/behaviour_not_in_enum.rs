@@ -0,0 +1,20 @@
+use dynamic_plan_tree::*;
+
+struct TestConfig;
+impl Config for TestConfig {
+    type Predicate = predicate::Predicates;
+    type Behaviour = behaviour::Behaviours<Self>;
+    type Event = ();
+    type Name = DefaultName;
+}
+
+struct Wander;
+impl<C: Config> Behaviour<C> for Wander {
+    fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+        None
+    }
+}
+
+assert_behaviour_in_config!(TestConfig, Wander);
+
+fn main() {}
@@ -0,0 +1,15 @@
+use dynamic_plan_tree::*;
+
+struct TestConfig;
+impl Config for TestConfig {
+    type Predicate = predicate::Predicates;
+    type Behaviour = behaviour::Behaviours<Self>;
+    type Event = ();
+    type Name = DefaultName;
+}
+
+struct Wander;
+
+assert_behaviour_in_config!(TestConfig, Wander);
+
+fn main() {}
@@ -0,0 +1,19 @@
+use dynamic_plan_tree::behaviour::*;
+
+#[enum_dispatch(Behaviour<C>)]
+enum TestBehaviours<C: Config> {
+    AllSuccessStatus,
+    SequenceBehaviour(SequenceBehaviour<C>),
+}
+
+struct TestConfig;
+impl Config for TestConfig {
+    type Predicate = predicate::Predicates;
+    type Behaviour = TestBehaviours<Self>;
+    type Event = ();
+    type Name = DefaultName;
+}
+
+assert_config!(TestConfig);
+
+fn main() {}
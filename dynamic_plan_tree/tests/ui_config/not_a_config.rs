@@ -0,0 +1,7 @@
+use dynamic_plan_tree::*;
+
+struct TestConfig;
+
+assert_config!(TestConfig);
+
+fn main() {}
@@ -0,0 +1,20 @@
+use dynamic_plan_tree::*;
+
+struct TestConfig;
+impl Config for TestConfig {
+    type Predicate = predicate::Predicates;
+    type Behaviour = behaviour::Behaviours<Self>;
+    type Event = ();
+    type Name = DefaultName;
+}
+
+struct IsNight;
+impl Predicate for IsNight {
+    fn evaluate<C: Config>(&self, _plan: &Plan<C>, _src: &[C::Name]) -> bool {
+        false
+    }
+}
+
+assert_predicate_in_config!(TestConfig, IsNight);
+
+fn main() {}
@@ -37,3 +37,33 @@ pub trait IntoEnum {
 }
 
 impl<T> IntoEnum for T {}
+
+/// Unit type casts only to itself, useful as a placeholder for unused types.
+impl EnumCast for () {
+    fn cast<T: 'static>(&self) -> Option<&T> {
+        (self as &dyn std::any::Any).downcast_ref::<T>()
+    }
+    fn cast_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        (self as &mut dyn std::any::Any).downcast_mut::<T>()
+    }
+    fn from_any<T: 'static>(x: T) -> Option<Self> {
+        (&x as &dyn std::any::Any).downcast_ref::<()>().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_cast() {
+        let mut unit = ();
+        assert_eq!(unit.cast::<()>(), Some(&()));
+        assert_eq!(unit.cast::<u32>(), None);
+        assert_eq!(unit.cast_mut::<()>(), Some(&mut ()));
+        assert_eq!(unit.cast_mut::<u32>(), None);
+        assert_eq!(<()>::from_any(()), Some(()));
+        assert_eq!(<()>::from_any(1u32), None);
+        assert_eq!(().into_enum::<()>(), Some(()));
+    }
+}
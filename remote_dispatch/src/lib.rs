@@ -0,0 +1,126 @@
+//! Downstream crate redefining the `Behaviour` and `Predicate` traits for remote enum_dispatch,
+//! mixing the built-ins of `dynamic_plan_tree` with its own behaviours and predicates.
+
+use dynamic_plan_tree::behaviour::{AllSuccessStatus, EvaluateStatus, SequenceBehaviour};
+use dynamic_plan_tree::predicate::{AllSuccess, Not, True};
+// named by the signature of `inspect()` copied into the enum_dispatch implementations
+use dynamic_plan_tree::serde_value;
+use dynamic_plan_tree::{
+    enum_dispatch, forward_behaviour, forward_predicate, Config, DefaultName, EnumCast, EnumRef,
+    Hypothetical, Plan,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub mod remote {
+    use dynamic_plan_tree::*;
+    behaviour_trait!();
+    predicate_trait!();
+    impl_builtin_behaviours!(Behaviour);
+    impl_builtin_predicates!(Predicate);
+}
+use remote::{Behaviour, Predicate};
+
+/// Redefinition implemented by the built-ins for a single config only.
+pub mod concrete {
+    use dynamic_plan_tree::*;
+    behaviour_trait!();
+    impl_builtin_behaviours!(Behaviour, super::MixedConfig);
+}
+
+/// Succeeds once it has run the given number of times.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Countdown(pub u32);
+impl<C: Config> Behaviour<C> for Countdown {
+    fn status(&self, _plan: &Plan<C>) -> Option<bool> {
+        (self.0 == 0).then_some(true)
+    }
+    fn on_run(&mut self, _plan: &mut Plan<C>) {
+        self.0 = self.0.saturating_sub(1);
+    }
+}
+
+/// True if the data entry of the plan is set to `true`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Flag(pub String);
+impl Predicate for Flag {
+    fn evaluate<C: Config>(&self, plan: &Plan<C>, _: &[C::Name]) -> bool {
+        plan.data().get(&self.0) == Some(&serde_value::Value::Bool(true))
+    }
+}
+
+#[enum_dispatch(Behaviour<C>)]
+#[derive(EnumCast)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MixedBehaviours<C: Config> {
+    AllSuccessStatus,
+    EvaluateStatus(EvaluateStatus<C>),
+    SequenceBehaviour(SequenceBehaviour<C>),
+    Countdown,
+}
+
+#[enum_dispatch(Predicate)]
+#[derive(EnumCast)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MixedPredicates {
+    True,
+    AllSuccess,
+    Not(Not<Self>),
+    Flag,
+}
+
+forward_behaviour!(Behaviour, MixedBehaviours);
+forward_predicate!(Predicate, MixedPredicates);
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MixedConfig;
+impl Config for MixedConfig {
+    type Predicate = MixedPredicates;
+    type Behaviour = MixedBehaviours<Self>;
+    type Event = ();
+    type Name = DefaultName;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dynamic_plan_tree::transition;
+
+    #[test]
+    fn mixed_tree() {
+        let sequence = SequenceBehaviour::<MixedConfig>::new();
+        let mut transitions = sequence.generate_transitions(&["a".into(), "b".into()]);
+        let mut root_plan = Plan::<MixedConfig>::new(sequence.into(), "root", 1, true);
+        root_plan.insert(Plan::new(Countdown(2).into(), "a", 1, true));
+        root_plan.insert(Plan::new(AllSuccessStatus.into(), "b", 1, false));
+        // pending until flagged by its parent, then succeeds
+        let flagged = EvaluateStatus(Flag("go".into()).into(), Not(Box::new(True.into())).into());
+        root_plan.insert(Plan::new(flagged.into(), "c", 1, false));
+        transitions.push(transition!("b" => "c", AllSuccess));
+        root_plan.transitions = transitions;
+
+        let active = |plan: &Plan<MixedConfig>| {
+            let active = plan.plans.iter().filter(|x| x.active());
+            active.map(|x| x.name().clone()).collect::<Vec<_>>()
+        };
+        root_plan.run();
+        assert_eq!(active(&root_plan), ["a"]);
+        root_plan.run();
+        root_plan.run();
+        assert_eq!(active(&root_plan), ["b"]);
+        root_plan.run();
+        assert_eq!(active(&root_plan), ["c"]);
+        let c = root_plan.get_mut("c").unwrap();
+        assert_eq!(c.status(), None);
+        c.set_data("go", serde_value::Value::Bool(true));
+        assert_eq!(c.status(), Some(true));
+
+        // dispatched through the redefined traits to the built-ins
+        let behaviour = root_plan.behaviour.as_deref().unwrap();
+        assert_eq!(Behaviour::type_name(behaviour), "SequenceBehaviour");
+        assert!(Behaviour::exclusive_subplans(behaviour));
+        let not_flag = MixedPredicates::from(Not(Box::new(Flag("go".into()).into())));
+        assert_eq!(not_flag.describe(), "Not(Flag)");
+        assert_eq!(Predicate::is_statically_false(&True), Some(true));
+    }
+}